@@ -0,0 +1,134 @@
+use adw::{prelude::*, subclass::prelude::*};
+use futures_channel::oneshot;
+use gtk::{glib, glib::clone, CompositeTemplate};
+
+use crate::{components::Avatar, prelude::*, session::model::Session, session_list::SessionList};
+
+/// The size of the avatars of the accounts.
+const AVATAR_SIZE: i32 = 32;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/account_chooser_dialog.ui")]
+    pub struct AccountChooserDialog {
+        #[template_child]
+        pub accounts: TemplateChild<gtk::ListBox>,
+        /// The list of logged-in sessions.
+        pub session_list: glib::WeakRef<SessionList>,
+        /// The sender for the ID of the chosen session.
+        pub sender: RefCell<Option<oneshot::Sender<Option<String>>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AccountChooserDialog {
+        const NAME: &'static str = "AccountChooserDialog";
+        type Type = super::AccountChooserDialog;
+        type ParentType = adw::MessageDialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for AccountChooserDialog {}
+    impl WidgetImpl for AccountChooserDialog {}
+    impl WindowImpl for AccountChooserDialog {}
+
+    impl MessageDialogImpl for AccountChooserDialog {
+        fn response(&self, response: &str) {
+            if let Some(sender) = self.sender.take() {
+                let _ = sender.send(None);
+            }
+
+            self.parent_response(response)
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Dialog to choose an account among the ones that are logged in.
+    pub struct AccountChooserDialog(ObjectSubclass<imp::AccountChooserDialog>)
+        @extends gtk::Widget, gtk::Window, adw::MessageDialog, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl AccountChooserDialog {
+    pub fn new(parent_window: Option<&impl IsA<gtk::Window>>, session_list: &SessionList) -> Self {
+        let obj = glib::Object::builder::<Self>()
+            .property("transient-for", parent_window)
+            .build();
+
+        let imp = obj.imp();
+        imp.session_list.set(Some(session_list));
+        imp.accounts.bind_model(Some(session_list), |item| {
+            account_row(
+                item.downcast_ref::<Session>()
+                    .expect("SessionList must contain only `Session`"),
+            )
+        });
+
+        obj
+    }
+
+    /// Open this dialog to choose an account.
+    ///
+    /// Returns the ID of the session of the chosen account, or `None` if the
+    /// dialog was cancelled.
+    pub async fn choose_account(&self) -> Option<String> {
+        let (sender, receiver) = oneshot::channel();
+        self.imp().sender.replace(Some(sender));
+
+        self.present();
+
+        receiver.await.ok().flatten()
+    }
+
+    /// Select the given row.
+    #[template_callback]
+    fn select_row(&self, row: &gtk::ListBoxRow) {
+        let imp = self.imp();
+
+        let session_id = imp
+            .session_list
+            .upgrade()
+            .and_then(|session_list| session_list.item(row.index() as u32))
+            .and_downcast::<Session>()
+            .map(|session| session.session_id().to_owned());
+
+        if let Some(sender) = imp.sender.take() {
+            let _ = sender.send(session_id);
+        }
+
+        self.close();
+    }
+}
+
+/// Construct a row presenting the account of the given session.
+fn account_row(session: &Session) -> gtk::Widget {
+    let row = adw::ActionRow::builder().activatable(true).build();
+
+    if let Some(user) = session.user() {
+        let avatar = Avatar::new();
+        avatar.set_size(AVATAR_SIZE);
+        avatar.set_data(Some(user.avatar_data().clone()));
+        row.add_prefix(&avatar);
+
+        user.bind_property("display-name", &row, "title")
+            .sync_create()
+            .build();
+        row.set_subtitle(user.user_id().as_str());
+    }
+
+    row.upcast()
+}
@@ -5,9 +5,9 @@ use gio::{ApplicationFlags, Settings};
 use glib::{clone, WeakRef};
 use gtk::{gio, glib, prelude::*, subclass::prelude::*};
 use ruma::{OwnedRoomId, RoomId};
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
-use crate::{config, Window};
+use crate::{config, utils::matrix::MatrixIdUri, Window};
 
 mod imp {
     use adw::subclass::prelude::AdwApplicationImpl;
@@ -70,6 +70,22 @@ mod imp {
             app.main_window().present();
         }
 
+        fn open(&self, files: &[gio::File], _hint: &str) {
+            debug!("GtkApplication<Application>::open");
+            let app = self.obj();
+
+            app.activate();
+            let window = app.main_window();
+
+            for file in files {
+                let uri = file.uri();
+                match MatrixIdUri::parse(&uri) {
+                    Ok(matrix_uri) => window.show_matrix_uri(matrix_uri),
+                    Err(error) => warn!("Could not parse Matrix URI {uri}: {error}"),
+                }
+            }
+        }
+
         fn startup(&self) {
             debug!("GtkApplication<Application>::startup");
             self.parent_startup();
@@ -89,7 +105,7 @@ impl Application {
     pub fn new() -> Self {
         glib::Object::builder()
             .property("application-id", Some(config::APP_ID))
-            .property("flags", ApplicationFlags::default())
+            .property("flags", ApplicationFlags::HANDLES_OPEN)
             .property("resource-base-path", Some("/org/gnome/Fractal/"))
             .build()
     }
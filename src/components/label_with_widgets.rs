@@ -1,4 +1,4 @@
-use gtk::{glib, pango, prelude::*, subclass::prelude::*};
+use gtk::{glib, glib::clone, pango, prelude::*, subclass::prelude::*};
 
 pub const DEFAULT_PLACEHOLDER: &str = "<widget>";
 const OBJECT_REPLACEMENT_CHARACTER: &str = "\u{FFFC}";
@@ -209,6 +209,18 @@ impl LabelWithWidgets {
         self.imp().placeholder.borrow().to_owned()
     }
 
+    /// Connect to the signal emitted when a link is activated in the label.
+    pub fn connect_activate_link<F: Fn(&Self, &str) -> glib::Propagation + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.imp().label.connect_activate_link(
+            clone!(@weak self as obj => @default-return glib::Propagation::Proceed, move |_, uri| {
+                f(&obj, uri)
+            }),
+        )
+    }
+
     fn invalidate_child_widgets(&self) {
         self.imp().widgets_sizes.borrow_mut().clear();
         self.allocate_shapes();
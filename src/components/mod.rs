@@ -23,6 +23,7 @@ mod spinner_button;
 mod toastable_window;
 mod video_player;
 mod video_player_renderer;
+mod waveform;
 
 pub use self::{
     action_button::{ActionButton, ActionState},
@@ -50,4 +51,5 @@ pub use self::{
     toastable_window::{ToastableWindow, ToastableWindowExt, ToastableWindowImpl},
     video_player::VideoPlayer,
     video_player_renderer::VideoPlayerRenderer,
+    waveform::Waveform,
 };
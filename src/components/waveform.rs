@@ -0,0 +1,266 @@
+use gtk::{
+    glib,
+    glib::{clone, closure_local},
+    graphene,
+    prelude::*,
+    subclass::prelude::*,
+};
+
+/// The width of a bar of the waveform.
+const BAR_WIDTH: f32 = 2.0;
+/// The space between two bars of the waveform.
+const BAR_SPACING: f32 = 2.0;
+/// The minimum height of a bar, so silences are still visible.
+const BAR_MIN_HEIGHT: f32 = 2.0;
+/// The opacity of the bars that were not played yet.
+const UNPLAYED_OPACITY: f32 = 0.4;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{signal::SignalHandlerId, subclass::Signal};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct Waveform {
+        /// The amplitudes of the waveform, between `0.0` and `1.0`.
+        pub samples: RefCell<Vec<f32>>,
+        /// The position of the playback, between `0.0` and `1.0`.
+        pub position: Cell<f64>,
+        /// The media file that is played, with the handler of its timestamp.
+        pub media_file: RefCell<Option<(gtk::MediaFile, SignalHandlerId)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Waveform {
+        const NAME: &'static str = "ComponentsWaveform";
+        type Type = super::Waveform;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.set_css_name("waveform");
+            klass.set_accessible_role(gtk::AccessibleRole::Img);
+        }
+    }
+
+    impl ObjectImpl for Waveform {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("seek")
+                    .param_types([f64::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecDouble::builder("position")
+                    .minimum(0.0)
+                    .maximum(1.0)
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "position" => self.obj().set_position(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "position" => self.obj().position().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let click = gtk::GestureClick::new();
+            click.connect_released(clone!(@weak obj => move |_, _, x, _| {
+                let width = obj.width();
+                if width > 0 {
+                    let position = (x / width as f64).clamp(0.0, 1.0);
+                    obj.emit_by_name::<()>("seek", &[&position]);
+
+                    if let Some((media_file, _)) = &*obj.imp().media_file.borrow() {
+                        let timestamp = position * media_file.duration() as f64;
+                        media_file.seek(timestamp as i64);
+                    }
+                }
+            }));
+            obj.add_controller(click);
+        }
+
+        fn dispose(&self) {
+            if let Some((media_file, handler)) = self.media_file.take() {
+                media_file.disconnect(handler);
+            }
+        }
+    }
+
+    impl WidgetImpl for Waveform {
+        fn measure(&self, orientation: gtk::Orientation, _for_size: i32) -> (i32, i32, i32, i32) {
+            match orientation {
+                gtk::Orientation::Horizontal => (40, 200, -1, -1),
+                _ => (24, 24, -1, -1),
+            }
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let obj = self.obj();
+            let width = obj.width() as f32;
+            let height = obj.height() as f32;
+            if width <= 0.0 || height <= 0.0 {
+                return;
+            }
+
+            let n_bars = ((width + BAR_SPACING) / (BAR_WIDTH + BAR_SPACING)).floor() as usize;
+            let bars = resample(&self.samples.borrow(), n_bars);
+            let played_bars = (self.position.get() * n_bars as f64).round() as usize;
+
+            let color = obj.color();
+            let mut unplayed_color = color;
+            unplayed_color.set_alpha(color.alpha() * UNPLAYED_OPACITY);
+
+            for (i, amplitude) in bars.into_iter().enumerate() {
+                let bar_height = (amplitude * height).max(BAR_MIN_HEIGHT).min(height);
+                let rect = graphene::Rect::new(
+                    i as f32 * (BAR_WIDTH + BAR_SPACING),
+                    (height - bar_height) / 2.0,
+                    BAR_WIDTH,
+                    bar_height,
+                );
+
+                let color = if i < played_bars {
+                    &color
+                } else {
+                    &unplayed_color
+                };
+                snapshot.append_color(color, &rect);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A widget displaying the waveform of an audio file.
+    pub struct Waveform(ObjectSubclass<imp::Waveform>)
+        @extends gtk::Widget, @implements gtk::Accessible;
+}
+
+impl Waveform {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The amplitudes of the waveform, between `0.0` and `1.0`.
+    pub fn samples(&self) -> Vec<f32> {
+        self.imp().samples.borrow().clone()
+    }
+
+    /// Set the amplitudes of the waveform, between `0.0` and `1.0`.
+    pub fn set_samples(&self, samples: Vec<f32>) {
+        self.imp().samples.replace(samples);
+        self.queue_draw();
+    }
+
+    /// The position of the playback, between `0.0` and `1.0`.
+    pub fn position(&self) -> f64 {
+        self.imp().position.get()
+    }
+
+    /// Set the position of the playback, between `0.0` and `1.0`.
+    pub fn set_position(&self, position: f64) {
+        let position = position.clamp(0.0, 1.0);
+        if self.position() == position {
+            return;
+        }
+
+        self.imp().position.set(position);
+        self.queue_draw();
+        self.notify("position");
+    }
+
+    /// Set the media file that is played.
+    ///
+    /// The position follows the playback of the file, and clicking on the
+    /// waveform seeks in the file.
+    pub fn set_media_file(&self, media_file: Option<&gtk::MediaFile>) {
+        let imp = self.imp();
+
+        if let Some((media_file, handler)) = imp.media_file.take() {
+            media_file.disconnect(handler);
+        }
+
+        let Some(media_file) = media_file else {
+            self.set_position(0.0);
+            return;
+        };
+
+        let handler =
+            media_file.connect_timestamp_notify(clone!(@weak self as obj => move |media_file| {
+                obj.update_position(media_file);
+            }));
+        self.update_position(media_file);
+        imp.media_file.replace(Some((media_file.clone(), handler)));
+    }
+
+    /// Update the position from the timestamp of the given media file.
+    fn update_position(&self, media_file: &gtk::MediaFile) {
+        let duration = media_file.duration();
+        let position = if duration > 0 {
+            media_file.timestamp() as f64 / duration as f64
+        } else {
+            0.0
+        };
+        self.set_position(position);
+    }
+
+    /// Connect to the signal emitted when the user clicks on the waveform to
+    /// change the position of the playback.
+    pub fn connect_seek<F: Fn(&Self, f64) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "seek",
+            true,
+            closure_local!(|obj: Self, position: f64| {
+                f(&obj, position);
+            }),
+        )
+    }
+}
+
+impl Default for Waveform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Resample the given amplitudes to the given number of values.
+///
+/// The maximum amplitude of each chunk is kept, so peaks are not lost.
+fn resample(samples: &[f32], len: usize) -> Vec<f32> {
+    if samples.is_empty() || len == 0 {
+        return vec![0.0; len];
+    }
+
+    (0..len)
+        .map(|i| {
+            let start = i * samples.len() / len;
+            let end = ((i + 1) * samples.len() / len).max(start + 1);
+            samples[start..end.min(samples.len())]
+                .iter()
+                .copied()
+                .fold(0.0, f32::max)
+        })
+        .collect()
+}
@@ -0,0 +1,288 @@
+use std::collections::{BTreeSet, HashSet};
+
+use futures_util::{future, StreamExt};
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{OwnedUserId, UserId};
+use tracing::error;
+
+use super::Session;
+use crate::{spawn, spawn_tokio};
+
+/// An update of the encryption data of a user.
+#[derive(Debug)]
+enum CryptoUpdate {
+    /// The identity of the user was received or changed.
+    Identity {
+        user_id: OwnedUserId,
+        is_verified: bool,
+    },
+    /// Some devices of the user were received or changed.
+    Devices(OwnedUserId),
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{object::WeakRef, subclass::Signal};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct CryptoIdentities {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The users whose identity was seen as verified.
+        pub verified_users: RefCell<BTreeSet<OwnedUserId>>,
+        /// The verified users whose identity changed.
+        pub changed_identities: RefCell<BTreeSet<OwnedUserId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for CryptoIdentities {
+        const NAME: &'static str = "CryptoIdentities";
+        type Type = super::CryptoIdentities;
+    }
+
+    impl ObjectImpl for CryptoIdentities {
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![Signal::builder("user-updated")
+                    .param_types([String::static_type()])
+                    .build()]
+            });
+            SIGNALS.as_ref()
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.obj().set_session(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "session" => self.obj().session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The trust state of the encryption identities and devices of the users
+    /// known by a `Session`.
+    pub struct CryptoIdentities(ObjectSubclass<imp::CryptoIdentities>);
+}
+
+impl CryptoIdentities {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<&Session>) {
+        if self.session().as_ref() == session {
+            return;
+        }
+
+        self.imp().session.set(session);
+        self.notify("session");
+    }
+
+    /// Listen to the changes of the identities and devices of the users.
+    pub fn setup_update_handlers(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        // Restore the identities that were seen as verified in previous runs,
+        // to detect the changes that happened while we were offline.
+        let imp = self.imp();
+        let settings = session.settings();
+        imp.verified_users.replace(settings.verified_users());
+        imp.changed_identities
+            .replace(settings.changed_identities());
+
+        let encryption = session.client().encryption();
+
+        let (sender, mut receiver) = futures_channel::mpsc::channel(16);
+        spawn_tokio!(async move {
+            let identities = match encryption.user_identities_stream().await {
+                Ok(stream) => stream,
+                Err(error) => {
+                    error!("Could not listen to user identities updates: {error}");
+                    return;
+                }
+            };
+            let devices = match encryption.devices_stream().await {
+                Ok(stream) => stream,
+                Err(error) => {
+                    error!("Could not listen to devices updates: {error}");
+                    return;
+                }
+            };
+
+            let mut identities_sender = sender.clone();
+            let identities_fut = identities.for_each(move |updates| {
+                for (user_id, identity) in updates.new.into_iter().chain(updates.changed) {
+                    let update = CryptoUpdate::Identity {
+                        user_id,
+                        is_verified: identity.is_verified(),
+                    };
+
+                    if let Err(error) = identities_sender.try_send(update) {
+                        error!("Error sending user identity update: {error}");
+                    }
+                }
+
+                async {}
+            });
+
+            let mut devices_sender = sender;
+            let devices_fut = devices.for_each(move |updates| {
+                let user_ids = updates
+                    .new
+                    .into_keys()
+                    .chain(updates.changed.into_keys())
+                    .collect::<HashSet<_>>();
+
+                for user_id in user_ids {
+                    if let Err(error) = devices_sender.try_send(CryptoUpdate::Devices(user_id)) {
+                        error!("Error sending devices update: {error}");
+                    }
+                }
+
+                async {}
+            });
+
+            future::join(identities_fut, devices_fut).await;
+        });
+
+        spawn!(clone!(@weak self as obj => async move {
+            while let Some(update) = receiver.next().await {
+                obj.handle_update(update);
+            }
+        }));
+    }
+
+    fn handle_update(&self, update: CryptoUpdate) {
+        let imp = self.imp();
+
+        let user_id = match update {
+            CryptoUpdate::Identity {
+                user_id,
+                is_verified,
+            } => {
+                if is_verified {
+                    imp.verified_users.borrow_mut().insert(user_id.clone());
+                    imp.changed_identities.borrow_mut().remove(&user_id);
+                } else if imp.verified_users.borrow_mut().remove(&user_id) {
+                    imp.changed_identities.borrow_mut().insert(user_id.clone());
+                }
+
+                self.save();
+                user_id
+            }
+            CryptoUpdate::Devices(user_id) => user_id,
+        };
+
+        self.emit_by_name::<()>("user-updated", &[&user_id.as_str()]);
+    }
+
+    /// Remember whether the identity of the user with the given ID is
+    /// verified.
+    ///
+    /// This is used to detect when the identity of a verified user changes.
+    pub fn set_user_verified(&self, user_id: &UserId, is_verified: bool) {
+        let imp = self.imp();
+
+        if is_verified {
+            imp.verified_users.borrow_mut().insert(user_id.to_owned());
+            let was_changed = imp.changed_identities.borrow_mut().remove(user_id);
+            self.save();
+
+            if was_changed {
+                self.emit_by_name::<()>("user-updated", &[&user_id.as_str()]);
+            }
+        }
+    }
+
+    /// Whether the identity of the user with the given ID changed since it
+    /// was verified.
+    pub fn has_identity_changed(&self, user_id: &UserId) -> bool {
+        self.imp().changed_identities.borrow().contains(user_id)
+    }
+
+    /// The users whose identity changed since it was verified.
+    pub fn changed_identities(&self) -> Vec<OwnedUserId> {
+        self.imp()
+            .changed_identities
+            .borrow()
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Stop warning about the identity change of the user with the given ID.
+    pub fn dismiss_identity_change(&self, user_id: &UserId) {
+        if self.imp().changed_identities.borrow_mut().remove(user_id) {
+            self.save();
+            self.emit_by_name::<()>("user-updated", &[&user_id.as_str()]);
+        }
+    }
+
+    /// Persist the verified users and the identity changes in the settings of
+    /// the session.
+    fn save(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let imp = self.imp();
+
+        session.settings().set_crypto_identities(
+            imp.verified_users.borrow().clone(),
+            imp.changed_identities.borrow().clone(),
+        );
+    }
+
+    /// Connect to the signal emitted when the identity or the devices of a
+    /// user were updated.
+    pub fn connect_user_updated<F: Fn(&Self, &UserId) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("user-updated", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            let user_id = values[1].get::<&str>().unwrap();
+
+            if let Ok(user_id) = UserId::parse(user_id) {
+                f(&obj, &user_id);
+            }
+
+            None
+        })
+    }
+}
+
+impl Default for CryptoIdentities {
+    fn default() -> Self {
+        Self::new()
+    }
+}
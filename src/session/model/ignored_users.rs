@@ -0,0 +1,258 @@
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use indexmap::IndexMap;
+use matrix_sdk::Result as MatrixResult;
+use ruma::{
+    events::{ignored_user_list::IgnoredUserListEventContent, GlobalAccountDataEvent},
+    OwnedUserId, UserId,
+};
+use tracing::error;
+
+use super::{Session, User, UserExt};
+use crate::spawn_tokio;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::object::WeakRef;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct IgnoredUsers {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The ignored users, by user ID.
+        pub list: RefCell<IndexMap<OwnedUserId, User>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for IgnoredUsers {
+        const NAME: &'static str = "IgnoredUsers";
+        type Type = super::IgnoredUsers;
+        type Interfaces = (gio::ListModel,);
+    }
+
+    impl ObjectImpl for IgnoredUsers {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.obj().set_session(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "session" => self.obj().session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl ListModelImpl for IgnoredUsers {
+        fn item_type(&self) -> glib::Type {
+            User::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.list.borrow().len() as u32
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            self.list
+                .borrow()
+                .get_index(position as usize)
+                .map(|(_, user)| user.clone().upcast())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The list of users ignored by the user of a `Session`.
+    ///
+    /// This is backed by the `m.ignored_user_list` account data.
+    pub struct IgnoredUsers(ObjectSubclass<imp::IgnoredUsers>)
+        @implements gio::ListModel;
+}
+
+impl IgnoredUsers {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<&Session>) {
+        if self.session().as_ref() == session {
+            return;
+        }
+
+        self.imp().session.set(session);
+        self.notify("session");
+    }
+
+    /// Whether the user with the given ID is ignored.
+    pub fn contains(&self, user_id: &UserId) -> bool {
+        self.imp().list.borrow().contains_key(user_id)
+    }
+
+    /// Load the list of ignored users from the account data.
+    pub async fn load(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let account = session.client().account();
+
+        let handle =
+            spawn_tokio!(
+                async move { account.account_data::<IgnoredUserListEventContent>().await }
+            );
+
+        let content = match handle.await.unwrap() {
+            Ok(Some(raw)) => match raw.deserialize() {
+                Ok(content) => content,
+                Err(error) => {
+                    error!("Failed to deserialize ignored user list: {error}");
+                    return;
+                }
+            },
+            Ok(None) => return,
+            Err(error) => {
+                error!("Failed to load ignored user list: {error}");
+                return;
+            }
+        };
+
+        self.update(content.ignored_users.into_keys().collect());
+    }
+
+    /// Listen to changes of the list of ignored users.
+    pub fn setup_event_handler(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let obj_weak = glib::SendWeakRef::from(self.downgrade());
+        session.client().add_event_handler(
+            move |event: GlobalAccountDataEvent<IgnoredUserListEventContent>| {
+                let obj_weak = obj_weak.clone();
+                async move {
+                    let ctx = glib::MainContext::default();
+                    ctx.spawn(async move {
+                        if let Some(obj) = obj_weak.upgrade() {
+                            obj.update(event.content.ignored_users.into_keys().collect());
+                        }
+                    });
+                }
+            },
+        );
+    }
+
+    /// Replace the list of ignored users with the given user IDs.
+    fn update(&self, user_ids: Vec<OwnedUserId>) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let (removed, added) = {
+            let mut list = self.imp().list.borrow_mut();
+
+            if list.len() == user_ids.len() && user_ids.iter().all(|id| list.contains_key(id)) {
+                return;
+            }
+
+            let removed = list.len() as u32;
+            let old_list = std::mem::take(&mut *list);
+
+            for user_id in user_ids {
+                let user = old_list.get(&user_id).cloned().unwrap_or_else(|| {
+                    let user = User::new(&session, &user_id);
+                    user.load_profile();
+                    user
+                });
+                list.insert(user_id, user);
+            }
+
+            (removed, list.len() as u32)
+        };
+
+        self.items_changed(0, removed, added);
+    }
+
+    /// Ignore the user with the given ID.
+    pub async fn add(&self, user_id: OwnedUserId) -> MatrixResult<()> {
+        if self.contains(&user_id) {
+            return Ok(());
+        }
+
+        let mut user_ids = self.user_ids();
+        user_ids.push(user_id);
+        self.send(user_ids).await
+    }
+
+    /// Stop ignoring the user with the given ID.
+    pub async fn remove(&self, user_id: &UserId) -> MatrixResult<()> {
+        if !self.contains(user_id) {
+            return Ok(());
+        }
+
+        let mut user_ids = self.user_ids();
+        user_ids.retain(|id| id != user_id);
+        self.send(user_ids).await
+    }
+
+    /// The IDs of the ignored users.
+    fn user_ids(&self) -> Vec<OwnedUserId> {
+        self.imp().list.borrow().keys().cloned().collect()
+    }
+
+    /// Send the given list of ignored users to the homeserver.
+    async fn send(&self, user_ids: Vec<OwnedUserId>) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let account = session.client().account();
+
+        let content = IgnoredUserListEventContent::users(user_ids.clone());
+        let handle = spawn_tokio!(async move { account.set_account_data(content).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => {
+                // Update the list right away, the sync will confirm it.
+                self.update(user_ids);
+                Ok(())
+            }
+            Err(error) => {
+                error!("Failed to update ignored user list: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Connect to the signal emitted when the list of ignored users changed.
+    pub fn connect_changed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_items_changed(move |obj, _, _, _| {
+            f(obj);
+        })
+    }
+}
+
+impl Default for IgnoredUsers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
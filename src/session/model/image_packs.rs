@@ -0,0 +1,316 @@
+//! Image packs, as defined in [MSC2545].
+//!
+//! [MSC2545]: https://github.com/matrix-org/matrix-spec-proposals/pull/2545
+
+use std::collections::{BTreeMap, HashSet};
+
+use gtk::{gdk, glib, prelude::*};
+use matrix_sdk::{
+    deserialized_responses::SyncOrStrippedState,
+    media::{MediaFormat, MediaRequest, MediaThumbnailSize},
+};
+use ruma::{
+    api::client::media::get_content_thumbnail::v3::Method,
+    events::{
+        macros::EventContent,
+        room::{ImageInfo, MediaSource},
+        SyncStateEvent,
+    },
+    OwnedMxcUri, OwnedRoomId,
+};
+use serde::{Deserialize, Serialize};
+use tracing::error;
+
+use super::{Room, Session};
+use crate::{components::ImagePaintable, spawn_tokio};
+
+/// The `im.ponies.user_emotes` account data, the image pack of the user.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "im.ponies.user_emotes", kind = GlobalAccountData)]
+pub struct UserImagePackEventContent {
+    /// The images in the pack, by shortcode.
+    #[serde(default)]
+    pub images: BTreeMap<String, PackImage>,
+
+    /// The metadata of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackInfo>,
+}
+
+/// The `im.ponies.room_emotes` state event, an image pack of a room.
+///
+/// A room can have several packs, identified by their state key.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "im.ponies.room_emotes", kind = State, state_key_type = String)]
+pub struct RoomImagePackEventContent {
+    /// The images in the pack, by shortcode.
+    #[serde(default)]
+    pub images: BTreeMap<String, PackImage>,
+
+    /// The metadata of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pack: Option<PackInfo>,
+}
+
+/// The `im.ponies.emote_rooms` account data, the room image packs that the
+/// user enabled everywhere.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, EventContent)]
+#[ruma_event(type = "im.ponies.emote_rooms", kind = GlobalAccountData)]
+pub struct EnabledImagePacksEventContent {
+    /// The state keys of the enabled packs, by room ID.
+    #[serde(default)]
+    pub rooms: BTreeMap<OwnedRoomId, BTreeMap<String, serde_json::Value>>,
+}
+
+/// An image in a pack.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PackImage {
+    /// The URI of the image.
+    pub url: OwnedMxcUri,
+
+    /// The description of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+
+    /// The metadata of the image.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub info: Option<ImageInfo>,
+
+    /// How the image can be used, overrides the usage of the pack.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<PackUsage>,
+}
+
+/// The metadata of a pack.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct PackInfo {
+    /// The name of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+
+    /// The URI of the avatar of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_url: Option<OwnedMxcUri>,
+
+    /// How the images of the pack can be used.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub usage: Vec<PackUsage>,
+
+    /// The attribution of the pack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribution: Option<String>,
+}
+
+/// How an image can be used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackUsage {
+    /// The image can be used as a custom emoji.
+    Emoticon,
+    /// The image can be sent as a sticker.
+    Sticker,
+    /// An unknown usage.
+    #[serde(other)]
+    Unknown,
+}
+
+/// An image of a pack, with its shortcode.
+#[derive(Clone, Debug)]
+pub struct ImagePackItem {
+    /// The unique shortcode of the image in the pack.
+    pub shortcode: String,
+    /// The image.
+    pub image: PackImage,
+}
+
+impl ImagePackItem {
+    /// The description of the image, or its shortcode.
+    pub fn body(&self) -> &str {
+        self.image
+            .body
+            .as_deref()
+            .filter(|body| !body.is_empty())
+            .unwrap_or(&self.shortcode)
+    }
+
+    /// Load a thumbnail of the image with the given size.
+    pub async fn load_thumbnail(&self, session: &Session, size: u32) -> Option<gdk::Paintable> {
+        let client = session.client();
+        let request = MediaRequest {
+            source: MediaSource::Plain(self.image.url.clone()),
+            format: MediaFormat::Thumbnail(MediaThumbnailSize {
+                width: size.into(),
+                height: size.into(),
+                method: Method::Scale,
+            }),
+        };
+        let handle =
+            spawn_tokio!(async move { client.media().get_media_content(&request, true).await });
+
+        match handle.await.unwrap() {
+            Ok(data) => ImagePaintable::from_bytes(&glib::Bytes::from(&data), None)
+                .ok()
+                .map(|paintable| paintable.upcast()),
+            Err(error) => {
+                error!("Could not fetch image of pack: {error}");
+                None
+            }
+        }
+    }
+}
+
+/// An image pack available in a room.
+#[derive(Clone, Debug)]
+pub struct ImagePack {
+    /// The name of the pack.
+    pub display_name: String,
+    /// The stickers of the pack.
+    pub stickers: Vec<ImagePackItem>,
+    /// The custom emoji of the pack.
+    pub emoticons: Vec<ImagePackItem>,
+}
+
+impl ImagePack {
+    /// Construct an `ImagePack` with the given images and metadata.
+    ///
+    /// Returns `None` if the pack doesn't contain any image.
+    fn new(
+        images: BTreeMap<String, PackImage>,
+        info: Option<PackInfo>,
+        default_name: String,
+    ) -> Option<Self> {
+        let info = info.unwrap_or_default();
+        let mut stickers = Vec::new();
+        let mut emoticons = Vec::new();
+
+        for (shortcode, image) in images {
+            let usage = if image.usage.is_empty() {
+                &info.usage
+            } else {
+                &image.usage
+            };
+            // Images without usage can be used both ways.
+            let is_sticker = usage.is_empty() || usage.contains(&PackUsage::Sticker);
+            let is_emoticon = usage.is_empty() || usage.contains(&PackUsage::Emoticon);
+
+            let item = ImagePackItem { shortcode, image };
+            if is_sticker {
+                stickers.push(item.clone());
+            }
+            if is_emoticon {
+                emoticons.push(item);
+            }
+        }
+
+        if stickers.is_empty() && emoticons.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            display_name: info
+                .display_name
+                .filter(|name| !name.is_empty())
+                .unwrap_or(default_name),
+            stickers,
+            emoticons,
+        })
+    }
+
+    /// Load the image packs that can be used in the given room.
+    ///
+    /// These are the pack of the user, the packs of the room and the room
+    /// packs that the user enabled everywhere.
+    pub async fn load_for_room(room: &Room) -> Vec<Self> {
+        let client = room.session().client();
+        let matrix_room = room.matrix_room();
+        let room_name = room.display_name();
+
+        let handle = spawn_tokio!(async move {
+            let account = client.account();
+            let mut packs = Vec::new();
+
+            match account.account_data::<UserImagePackEventContent>().await {
+                Ok(Some(raw)) => match raw.deserialize() {
+                    Ok(content) => {
+                        packs.extend(Self::new(content.images, content.pack, String::new()))
+                    }
+                    Err(error) => error!("Could not deserialize user image pack: {error}"),
+                },
+                Ok(None) => {}
+                Err(error) => error!("Could not load user image pack: {error}"),
+            }
+
+            // The packs of the current room.
+            let mut loaded_packs = HashSet::new();
+            match matrix_room
+                .get_state_events_static::<RoomImagePackEventContent>()
+                .await
+            {
+                Ok(events) => {
+                    for event in events.into_iter().filter_map(|raw| raw.deserialize().ok()) {
+                        if let SyncOrStrippedState::Sync(SyncStateEvent::Original(event)) = event {
+                            loaded_packs.insert(event.state_key.clone());
+                            packs.extend(Self::new(
+                                event.content.images,
+                                event.content.pack,
+                                room_name.clone(),
+                            ));
+                        }
+                    }
+                }
+                Err(error) => error!("Could not load room image packs: {error}"),
+            }
+
+            // The packs enabled everywhere.
+            let enabled = match account
+                .account_data::<EnabledImagePacksEventContent>()
+                .await
+            {
+                Ok(Some(raw)) => raw.deserialize().map(|c| c.rooms).unwrap_or_default(),
+                Ok(None) => Default::default(),
+                Err(error) => {
+                    error!("Could not load enabled image packs: {error}");
+                    Default::default()
+                }
+            };
+
+            for (room_id, state_keys) in enabled {
+                let Some(pack_room) = client.get_room(&room_id) else {
+                    continue;
+                };
+                let is_current_room = pack_room.room_id() == matrix_room.room_id();
+                let pack_room_name = pack_room
+                    .display_name()
+                    .await
+                    .map(|name| name.to_string())
+                    .unwrap_or_else(|_| room_id.to_string());
+
+                for state_key in state_keys.into_keys() {
+                    if is_current_room && loaded_packs.contains(&state_key) {
+                        continue;
+                    }
+
+                    let event = pack_room
+                        .get_state_event_static_for_key::<RoomImagePackEventContent, _>(&state_key)
+                        .await
+                        .ok()
+                        .flatten()
+                        .and_then(|raw| raw.deserialize().ok());
+
+                    if let Some(SyncOrStrippedState::Sync(SyncStateEvent::Original(event))) = event
+                    {
+                        packs.extend(Self::new(
+                            event.content.images,
+                            event.content.pack,
+                            pack_room_name.clone(),
+                        ));
+                    }
+                }
+            }
+
+            packs
+        });
+
+        handle.await.unwrap()
+    }
+}
@@ -0,0 +1,470 @@
+use std::fmt;
+
+use futures_util::StreamExt;
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use matrix_sdk::encryption::{
+    backups::BackupState as SdkBackupState,
+    recovery::{EnableProgress, Recovery, RecoveryError, RecoveryState as SdkRecoveryState},
+};
+use tracing::error;
+
+use super::Session;
+use crate::{spawn, spawn_tokio};
+
+/// The state of the recovery of the secrets of the account.
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "RecoveryState")]
+pub enum RecoveryState {
+    /// The state is not known yet.
+    #[default]
+    Unknown,
+    /// Recovery is not set up for the account.
+    Disabled,
+    /// Recovery is set up and all the secrets are available in this session.
+    Enabled,
+    /// Recovery is set up but some secrets are missing in this session.
+    Incomplete,
+}
+
+impl From<SdkRecoveryState> for RecoveryState {
+    fn from(value: SdkRecoveryState) -> Self {
+        match value {
+            SdkRecoveryState::Unknown => Self::Unknown,
+            SdkRecoveryState::Disabled => Self::Disabled,
+            SdkRecoveryState::Enabled => Self::Enabled,
+            SdkRecoveryState::Incomplete => Self::Incomplete,
+        }
+    }
+}
+
+/// The state of the backup of the room keys in this session.
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "KeyBackupState")]
+pub enum KeyBackupState {
+    /// There is no active backup in this session.
+    #[default]
+    Disabled,
+    /// A backup is being created or enabled.
+    Enabling,
+    /// The room keys are backed up.
+    Enabled,
+    /// The room keys are being downloaded from the backup.
+    Downloading,
+    /// The backup is being disabled.
+    Disabling,
+}
+
+impl From<SdkBackupState> for KeyBackupState {
+    fn from(value: SdkBackupState) -> Self {
+        match value {
+            SdkBackupState::Unknown => Self::Disabled,
+            SdkBackupState::Creating | SdkBackupState::Enabling | SdkBackupState::Resuming => {
+                Self::Enabling
+            }
+            SdkBackupState::Enabled => Self::Enabled,
+            SdkBackupState::Downloading => Self::Downloading,
+            SdkBackupState::Disabling => Self::Disabling,
+        }
+    }
+}
+
+/// An error that occurred when setting up the recovery of the account.
+#[derive(Debug)]
+pub enum EnableRecoveryError {
+    /// The session was dropped.
+    NoSession,
+    /// The recovery could not be set up.
+    Failed(RecoveryError),
+}
+
+impl fmt::Display for EnableRecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSession => write!(f, "the session was dropped"),
+            Self::Failed(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+/// An error that occurred when resetting the recovery of the account.
+#[derive(Debug)]
+pub enum ResetRecoveryError {
+    /// The session was dropped.
+    NoSession,
+    /// The recovery could not be reset, nothing changed.
+    Failed(RecoveryError),
+    /// The previous backup was deleted, but a new one could not be created.
+    ///
+    /// The room keys that were not available in this session are lost, and
+    /// the backup needs to be set up again.
+    BackupDeleted(RecoveryError),
+}
+
+impl fmt::Display for ResetRecoveryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSession => write!(f, "the session was dropped"),
+            Self::Failed(error) => write!(f, "{error}"),
+            Self::BackupDeleted(error) => {
+                write!(
+                    f,
+                    "backup was deleted but a new one could not be created: {error}"
+                )
+            }
+        }
+    }
+}
+
+/// An error that occurred when verifying this session with the recovery of
+/// the account.
+#[derive(Debug)]
+pub enum RecoverError {
+    /// The session was dropped.
+    NoSession,
+    /// The secrets could not be recovered.
+    Recovery(RecoveryError),
+    /// This session could not be signed with the recovered cross-signing
+    /// keys.
+    Verification(String),
+}
+
+impl fmt::Display for RecoverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoSession => write!(f, "the session was dropped"),
+            Self::Recovery(error) => write!(f, "{error}"),
+            Self::Verification(error) => write!(f, "could not sign this session: {error}"),
+        }
+    }
+}
+
+mod imp {
+    use std::cell::Cell;
+
+    use glib::object::WeakRef;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct KeyBackup {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The state of the recovery of the account.
+        pub recovery_state: Cell<RecoveryState>,
+        /// The state of the backup of the room keys.
+        pub backup_state: Cell<KeyBackupState>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KeyBackup {
+        const NAME: &'static str = "KeyBackup";
+        type Type = super::KeyBackup;
+    }
+
+    impl ObjectImpl for KeyBackup {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Session>("session")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<RecoveryState>("recovery-state")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<KeyBackupState>("backup-state")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.obj().set_session(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                "recovery-state" => obj.recovery_state().to_value(),
+                "backup-state" => obj.backup_state().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The server-side backup of the room keys of a `Session`, and the
+    /// recovery of the secrets of the account.
+    pub struct KeyBackup(ObjectSubclass<imp::KeyBackup>);
+}
+
+impl KeyBackup {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<&Session>) {
+        if self.session().as_ref() == session {
+            return;
+        }
+
+        self.imp().session.set(session);
+        self.notify("session");
+    }
+
+    /// The state of the recovery of the account.
+    pub fn recovery_state(&self) -> RecoveryState {
+        self.imp().recovery_state.get()
+    }
+
+    /// Set the state of the recovery of the account.
+    fn set_recovery_state(&self, state: RecoveryState) {
+        if self.recovery_state() == state {
+            return;
+        }
+
+        self.imp().recovery_state.set(state);
+        self.notify("recovery-state");
+    }
+
+    /// The state of the backup of the room keys.
+    pub fn backup_state(&self) -> KeyBackupState {
+        self.imp().backup_state.get()
+    }
+
+    /// Set the state of the backup of the room keys.
+    fn set_backup_state(&self, state: KeyBackupState) {
+        if self.backup_state() == state {
+            return;
+        }
+
+        self.imp().backup_state.set(state);
+        self.notify("backup-state");
+    }
+
+    /// Listen to changes of the recovery and backup states.
+    pub fn setup_state_handlers(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let encryption = session.client().encryption();
+
+        let recovery = encryption.recovery();
+        self.set_recovery_state(recovery.state().into());
+
+        let (mut sender, mut receiver) = futures_channel::mpsc::channel(8);
+        let fut = recovery.state_stream().for_each(move |state| {
+            if let Err(error) = sender.try_send(state) {
+                error!("Error sending recovery state: {error}");
+            }
+
+            async {}
+        });
+        spawn_tokio!(fut);
+
+        spawn!(clone!(@weak self as obj => async move {
+            while let Some(state) = receiver.next().await {
+                obj.set_recovery_state(state.into());
+            }
+        }));
+
+        let backups = encryption.backups();
+        self.set_backup_state(backups.state().into());
+
+        let (mut sender, mut receiver) = futures_channel::mpsc::channel(8);
+        let fut = backups.state_stream().for_each(move |state| {
+            match state {
+                Ok(state) => {
+                    if let Err(error) = sender.try_send(state) {
+                        error!("Error sending key backup state: {error}");
+                    }
+                }
+                Err(error) => {
+                    error!("Could not receive key backup state: {error}");
+                }
+            }
+
+            async {}
+        });
+        spawn_tokio!(fut);
+
+        spawn!(clone!(@weak self as obj => async move {
+            while let Some(state) = receiver.next().await {
+                obj.set_backup_state(state.into());
+            }
+        }));
+    }
+
+    /// Set up the key backup and the recovery of the account.
+    ///
+    /// The secrets are protected with a new recovery key and, if it is
+    /// provided, with the given passphrase. `progress` is called with the
+    /// fraction of the room keys that were uploaded to the backup.
+    ///
+    /// Returns the new recovery key.
+    pub async fn enable(
+        &self,
+        passphrase: Option<String>,
+        progress: impl Fn(f64) + 'static,
+    ) -> Result<String, EnableRecoveryError> {
+        let Some(session) = self.session() else {
+            return Err(EnableRecoveryError::NoSession);
+        };
+        let recovery = session.client().encryption().recovery();
+
+        enable_recovery(recovery, passphrase, progress)
+            .await
+            .map_err(EnableRecoveryError::Failed)
+    }
+
+    /// Restore the secrets of the account and the room keys of the backup
+    /// with the given recovery key or passphrase.
+    ///
+    /// This session is then signed with the recovered cross-signing keys, so
+    /// it becomes verified.
+    pub async fn recover(&self, recovery_key: String) -> Result<(), RecoverError> {
+        let Some(session) = self.session() else {
+            return Err(RecoverError::NoSession);
+        };
+        let encryption = session.client().encryption();
+
+        spawn_tokio!(async move {
+            encryption
+                .recovery()
+                .recover(&recovery_key)
+                .await
+                .map_err(RecoverError::Recovery)?;
+
+            let device = match encryption.get_own_device().await {
+                Ok(Some(device)) => device,
+                Ok(None) => {
+                    return Err(RecoverError::Verification(
+                        "the device of this session was not found".to_owned(),
+                    ));
+                }
+                Err(error) => return Err(RecoverError::Verification(error.to_string())),
+            };
+
+            if !device.is_cross_signed_by_owner() {
+                device
+                    .verify()
+                    .await
+                    .map_err(|error| RecoverError::Verification(error.to_string()))?;
+            }
+
+            Ok(())
+        })
+        .await
+        .unwrap()
+    }
+
+    /// Reset the recovery of the account, when the recovery key was lost.
+    ///
+    /// If the secrets are still available in this session, only the recovery
+    /// key is replaced. Otherwise the current backup and secret storage are
+    /// deleted and new ones are created, so the room keys that are not
+    /// available in this session are lost.
+    ///
+    /// Returns the new recovery key.
+    pub async fn reset(
+        &self,
+        passphrase: Option<String>,
+        progress: impl Fn(f64) + 'static,
+    ) -> Result<String, ResetRecoveryError> {
+        let Some(session) = self.session() else {
+            return Err(ResetRecoveryError::NoSession);
+        };
+        let recovery = session.client().encryption().recovery();
+
+        if self.recovery_state() == RecoveryState::Enabled {
+            let handle = spawn_tokio!(async move {
+                let mut reset = recovery.reset_key();
+                if let Some(passphrase) = &passphrase {
+                    reset = reset.with_passphrase(passphrase);
+                }
+
+                reset.await
+            });
+
+            return handle.await.unwrap().map_err(ResetRecoveryError::Failed);
+        }
+
+        let disable_recovery = recovery.clone();
+        spawn_tokio!(async move { disable_recovery.disable().await })
+            .await
+            .unwrap()
+            .map_err(ResetRecoveryError::Failed)?;
+
+        enable_recovery(recovery, passphrase, progress)
+            .await
+            .map_err(ResetRecoveryError::BackupDeleted)
+    }
+}
+
+impl Default for KeyBackup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Set up the key backup and the recovery of the account with the given
+/// recovery API.
+///
+/// Returns the new recovery key.
+async fn enable_recovery(
+    recovery: Recovery,
+    passphrase: Option<String>,
+    progress: impl Fn(f64) + 'static,
+) -> Result<String, RecoveryError> {
+    let (mut sender, mut receiver) = futures_channel::mpsc::channel(8);
+    let handle = spawn_tokio!(async move {
+        let mut enable = recovery.enable().wait_for_backups_to_upload();
+        if let Some(passphrase) = &passphrase {
+            enable = enable.with_passphrase(passphrase);
+        }
+
+        let fut = enable.subscribe_to_progress().for_each(move |state| {
+            if let Ok(EnableProgress::BackingUp(counts)) = state {
+                let fraction = if counts.total > 0 {
+                    counts.backed_up as f64 / counts.total as f64
+                } else {
+                    1.0
+                };
+
+                if let Err(error) = sender.try_send(fraction) {
+                    error!("Error sending key backup progress: {error}");
+                }
+            }
+
+            async {}
+        });
+        tokio::spawn(fut);
+
+        enable.await
+    });
+
+    spawn!(async move {
+        while let Some(fraction) = receiver.next().await {
+            progress(fraction);
+        }
+    });
+
+    handle.await.unwrap()
+}
@@ -1,7 +1,13 @@
 mod avatar;
+mod crypto_identities;
+mod ignored_users;
+mod image_packs;
+mod key_backup;
 mod notifications;
+mod notifications_settings;
 mod room;
 mod room_list;
+mod search;
 mod session;
 mod settings;
 mod sidebar;
@@ -10,15 +16,30 @@ mod verification;
 
 pub use self::{
     avatar::{AvatarData, AvatarImage, AvatarUriSource},
+    crypto_identities::CryptoIdentities,
+    ignored_users::IgnoredUsers,
+    image_packs::{
+        EnabledImagePacksEventContent, ImagePack, ImagePackItem, PackImage, PackInfo, PackUsage,
+        RoomImagePackEventContent, UserImagePackEventContent,
+    },
+    key_backup::{
+        EnableRecoveryError, KeyBackup, KeyBackupState, RecoverError, RecoveryState,
+        ResetRecoveryError,
+    },
     notifications::Notifications,
+    notifications_settings::{
+        NotificationsRoomSetting, NotificationsSettings, PredefinedRules, PredefinedRulesState,
+    },
     room::{
-        Event, EventKey, HighlightFlags, Member, MemberList, MemberRole, Membership, PowerLevel,
-        ReactionGroup, ReactionList, Room, RoomType, Timeline, TimelineItem, TimelineItemExt,
-        TimelineState, TypingList, VirtualItem, VirtualItemKind, POWER_LEVEL_MAX, POWER_LEVEL_MIN,
+        Event, EventKey, HighlightFlags, Member, MemberList, MemberRole, Membership,
+        PendingAttachment, PowerLevel, ReactionGroup, ReactionList, Room, RoomType, SendState,
+        Thread, Timeline, TimelineContext, TimelineItem, TimelineItemExt, TimelineState,
+        TypingList, VirtualItem, VirtualItemKind, POWER_LEVEL_MAX, POWER_LEVEL_MIN,
     },
     room_list::RoomList,
+    search::{MessageSearch, MessageSearchState, SearchIndex, SearchResult},
     session::{Session, SessionState},
-    settings::SessionSettings,
+    settings::{ComposerDraft, DraftChunk, DraftRelation, SessionSettings},
     sidebar::{
         Category, CategoryType, Entry, EntryType, ItemList, Selection, SidebarItem, SidebarItemExt,
         SidebarItemImpl, SidebarListModel,
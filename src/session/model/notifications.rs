@@ -139,8 +139,14 @@ impl Notifications {
             }
         };
 
-        let matrix_room = room.matrix_room();
         let sender_id = event.sender();
+
+        // Don't show notifications for events from ignored users.
+        if session.ignored_users().contains(sender_id) {
+            return;
+        }
+
+        let matrix_room = room.matrix_room();
         let owned_sender_id = sender_id.to_owned();
         let handle =
             spawn_tokio!(async move { matrix_room.get_member_no_sync(&owned_sender_id).await });
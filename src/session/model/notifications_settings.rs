@@ -0,0 +1,792 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+use matrix_sdk::Result as MatrixResult;
+use ruma::{
+    api::client::push::{
+        delete_pushrule, set_pushrule, set_pushrule_actions, set_pushrule_enabled, RuleKind,
+        RuleScope,
+    },
+    events::push_rules::{PushRulesEvent, PushRulesEventContent},
+    push::{
+        Action, NewConditionalPushRule, NewPatternedPushRule, NewPushRule, NewSimplePushRule,
+        PredefinedOverrideRuleId, PushCondition, Ruleset, Tweak,
+    },
+    RoomId,
+};
+use tracing::{error, warn};
+
+use super::{Room, Session};
+use crate::spawn_tokio;
+
+/// The notifications setting of a room.
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "NotificationsRoomSetting")]
+pub enum NotificationsRoomSetting {
+    /// Be notified of all messages.
+    #[default]
+    #[enum_value(nick = "all")]
+    All,
+    /// Only be notified of mentions and keywords.
+    #[enum_value(nick = "mentions-only")]
+    MentionsOnly,
+    /// Never be notified.
+    #[enum_value(nick = "mute")]
+    Mute,
+}
+
+impl NotificationsRoomSetting {
+    /// The string representation of this setting.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::MentionsOnly => "mentions-only",
+            Self::Mute => "mute",
+        }
+    }
+}
+
+impl TryFrom<&str> for NotificationsRoomSetting {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "all" => Ok(Self::All),
+            "mentions-only" => Ok(Self::MentionsOnly),
+            "mute" => Ok(Self::Mute),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A group of predefined push rules that are presented as a single setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PredefinedRules {
+    /// Messages that mention the user.
+    Mentions,
+    /// Messages that mention the whole room.
+    RoomMentions,
+    /// Messages in direct chats.
+    DirectMessages,
+    /// Messages in group chats.
+    GroupMessages,
+    /// Invites to rooms.
+    Invites,
+    /// Calls.
+    Calls,
+}
+
+impl PredefinedRules {
+    /// All the groups of predefined rules, in the order they are presented.
+    pub const ALL: [Self; 6] = [
+        Self::Mentions,
+        Self::RoomMentions,
+        Self::DirectMessages,
+        Self::GroupMessages,
+        Self::Invites,
+        Self::Calls,
+    ];
+
+    /// The kinds and IDs of the push rules in this group.
+    fn rules(&self) -> &'static [(RuleKind, &'static str)] {
+        match self {
+            Self::Mentions => &[
+                (RuleKind::Override, ".m.rule.is_user_mention"),
+                (RuleKind::Override, ".m.rule.contains_display_name"),
+                (RuleKind::Content, ".m.rule.contains_user_name"),
+            ],
+            Self::RoomMentions => &[
+                (RuleKind::Override, ".m.rule.is_room_mention"),
+                (RuleKind::Override, ".m.rule.roomnotif"),
+            ],
+            Self::DirectMessages => &[
+                (RuleKind::Underride, ".m.rule.room_one_to_one"),
+                (RuleKind::Underride, ".m.rule.encrypted_room_one_to_one"),
+            ],
+            Self::GroupMessages => &[
+                (RuleKind::Underride, ".m.rule.message"),
+                (RuleKind::Underride, ".m.rule.encrypted"),
+            ],
+            Self::Invites => &[(RuleKind::Override, ".m.rule.invite_for_me")],
+            Self::Calls => &[(RuleKind::Underride, ".m.rule.call")],
+        }
+    }
+}
+
+/// The state of a group of predefined push rules.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PredefinedRulesState {
+    /// Whether the rules notify.
+    pub enabled: bool,
+    /// Whether the rules play a sound.
+    pub sound: bool,
+    /// Whether the rules highlight the events.
+    pub highlight: bool,
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{object::WeakRef, subclass::Signal};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct NotificationsSettings {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The push rules of the account.
+        pub ruleset: RefCell<Option<Ruleset>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for NotificationsSettings {
+        const NAME: &'static str = "NotificationsSettings";
+        type Type = super::NotificationsSettings;
+    }
+
+    impl ObjectImpl for NotificationsSettings {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn signals() -> &'static [Signal] {
+            static SIGNALS: Lazy<Vec<Signal>> =
+                Lazy::new(|| vec![Signal::builder("changed").build()]);
+            SIGNALS.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.obj().set_session(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "session" => self.obj().session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The notifications settings of a `Session`.
+    ///
+    /// This is backed by the push rules of the account.
+    pub struct NotificationsSettings(ObjectSubclass<imp::NotificationsSettings>);
+}
+
+impl NotificationsSettings {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<&Session>) {
+        if self.session().as_ref() == session {
+            return;
+        }
+
+        self.imp().session.set(session);
+        self.notify("session");
+    }
+
+    /// Load the push rules from the account data.
+    pub async fn load(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let account = session.client().account();
+
+        let handle =
+            spawn_tokio!(async move { account.account_data::<PushRulesEventContent>().await });
+
+        let ruleset = match handle.await.unwrap() {
+            Ok(Some(raw)) => match raw.deserialize() {
+                Ok(content) => content.global,
+                Err(error) => {
+                    error!("Could not deserialize push rules: {error}");
+                    return;
+                }
+            },
+            Ok(None) => {
+                warn!("Could not find push rules, using the default ruleset instead.");
+                let user_id = session.user().unwrap().user_id();
+                Ruleset::server_default(&user_id)
+            }
+            Err(error) => {
+                error!("Could not get push rules: {error}");
+                return;
+            }
+        };
+
+        self.update(ruleset);
+    }
+
+    /// Listen to changes of the push rules.
+    pub fn setup_event_handler(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let obj_weak = glib::SendWeakRef::from(self.downgrade());
+        session
+            .client()
+            .add_event_handler(move |event: PushRulesEvent| {
+                let obj_weak = obj_weak.clone();
+                async move {
+                    let ctx = glib::MainContext::default();
+                    ctx.spawn(async move {
+                        if let Some(obj) = obj_weak.upgrade() {
+                            obj.update(event.content.global);
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Replace the push rules with the given ruleset.
+    fn update(&self, ruleset: Ruleset) {
+        self.imp().ruleset.replace(Some(ruleset));
+        self.emit_by_name::<()>("changed", &[]);
+
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        for room in session.room_list().iter::<Room>() {
+            let Ok(room) = room else {
+                break;
+            };
+
+            room.set_notifications_setting(self.room_setting(room.room_id()));
+        }
+    }
+
+    /// The notifications setting of the room with the given ID.
+    pub fn room_setting(&self, room_id: &RoomId) -> NotificationsRoomSetting {
+        let ruleset = self.imp().ruleset.borrow();
+        let Some(ruleset) = ruleset.as_ref() else {
+            return NotificationsRoomSetting::default();
+        };
+
+        if ruleset
+            .override_
+            .iter()
+            .any(|rule| rule.enabled && rule.rule_id == room_id.as_str())
+        {
+            return NotificationsRoomSetting::Mute;
+        }
+
+        if let Some(rule) = ruleset
+            .room
+            .iter()
+            .find(|rule| rule.enabled && rule.rule_id == room_id)
+        {
+            if !rule.actions.iter().any(|action| action.should_notify()) {
+                return NotificationsRoomSetting::MentionsOnly;
+            }
+        }
+
+        NotificationsRoomSetting::All
+    }
+
+    /// Set the notifications setting of the room with the given ID.
+    ///
+    /// This changes the push rules of the room on the homeserver.
+    pub async fn set_room_setting(
+        &self,
+        room_id: &RoomId,
+        setting: NotificationsRoomSetting,
+    ) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        let (has_override_rule, has_room_rule) = {
+            let ruleset = self.imp().ruleset.borrow();
+            ruleset.as_ref().map_or((false, false), |ruleset| {
+                (
+                    ruleset
+                        .override_
+                        .iter()
+                        .any(|rule| rule.rule_id == room_id.as_str()),
+                    ruleset.room.iter().any(|rule| rule.rule_id == room_id),
+                )
+            })
+        };
+
+        let mut delete_requests = Vec::new();
+        if has_override_rule {
+            delete_requests.push(delete_pushrule::v3::Request::new(
+                RuleScope::Global,
+                RuleKind::Override,
+                room_id.to_string(),
+            ));
+        }
+
+        let new_rule = match setting {
+            NotificationsRoomSetting::All => {
+                if has_room_rule {
+                    delete_requests.push(delete_pushrule::v3::Request::new(
+                        RuleScope::Global,
+                        RuleKind::Room,
+                        room_id.to_string(),
+                    ));
+                }
+
+                None
+            }
+            NotificationsRoomSetting::MentionsOnly => Some(NewPushRule::Room(
+                NewSimplePushRule::new(room_id.to_owned(), vec![]),
+            )),
+            NotificationsRoomSetting::Mute => {
+                if has_room_rule {
+                    delete_requests.push(delete_pushrule::v3::Request::new(
+                        RuleScope::Global,
+                        RuleKind::Room,
+                        room_id.to_string(),
+                    ));
+                }
+
+                Some(NewPushRule::Override(mute_rule(room_id)))
+            }
+        };
+
+        let handle = spawn_tokio!(async move {
+            for request in delete_requests {
+                client.send(request, None).await?;
+            }
+
+            if let Some(rule) = new_rule {
+                let request = set_pushrule::v3::Request::new(RuleScope::Global, rule);
+                client.send(request, None).await?;
+            }
+
+            MatrixResult::Ok(())
+        });
+
+        match handle.await.unwrap() {
+            Ok(()) => {
+                // Update the room right away, the sync will confirm it.
+                if let Some(room) = session.room_list().get(room_id) {
+                    room.set_notifications_setting(setting);
+                }
+
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not change notifications setting of room {room_id}: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Connect to the signal emitted when the push rules changed.
+    pub fn connect_changed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("changed", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+            None
+        })
+    }
+
+    /// Whether the push rules were loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.imp().ruleset.borrow().is_some()
+    }
+
+    /// Apply the given changes to the local push rules right away, the sync
+    /// will confirm them.
+    fn edit_ruleset(&self, f: impl FnOnce(&mut Ruleset)) {
+        if let Some(ruleset) = self.imp().ruleset.borrow_mut().as_mut() {
+            f(ruleset);
+        }
+        self.emit_by_name::<()>("changed", &[]);
+    }
+
+    /// Whether notifications are enabled for this account.
+    pub fn account_enabled(&self) -> bool {
+        let ruleset = self.imp().ruleset.borrow();
+        let Some(ruleset) = ruleset.as_ref() else {
+            return true;
+        };
+
+        if let Some(rule) = ruleset
+            .override_
+            .iter()
+            .find(|r| r.rule_id == PredefinedOverrideRuleId::Master.as_str())
+        {
+            !rule.enabled
+        } else {
+            warn!("Could not find `.m.rule.master` push rule, using the default rule instead.");
+            true
+        }
+    }
+
+    /// Set whether notifications are enabled for this account.
+    ///
+    /// This changes the `.m.rule.master` push rule on the homeserver.
+    pub async fn set_account_enabled(&self, enabled: bool) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        let request = set_pushrule_enabled::v3::Request::new(
+            RuleScope::Global,
+            RuleKind::Override,
+            PredefinedOverrideRuleId::Master.to_string(),
+            !enabled,
+        );
+        let handle = spawn_tokio!(async move { client.send(request, None).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => {
+                self.edit_ruleset(|ruleset| {
+                    let _ = ruleset.set_enabled(
+                        RuleKind::Override,
+                        PredefinedOverrideRuleId::Master.as_str(),
+                        !enabled,
+                    );
+                });
+                Ok(())
+            }
+            Err(error) => {
+                error!(
+                    "Could not update `{}` push rule: {error}",
+                    PredefinedOverrideRuleId::Master
+                );
+                Err(error.into())
+            }
+        }
+    }
+
+    /// The current state of the given group of predefined push rules.
+    ///
+    /// Returns `None` if none of the rules of the group exist.
+    pub fn predefined_rules_state(&self, rules: PredefinedRules) -> Option<PredefinedRulesState> {
+        let ruleset = self.imp().ruleset.borrow();
+        let ruleset = ruleset.as_ref()?;
+
+        let mut is_available = false;
+        let mut state = PredefinedRulesState::default();
+
+        for (kind, rule_id) in rules.rules() {
+            let Some(rule) = ruleset.get(kind.clone(), rule_id) else {
+                continue;
+            };
+            is_available = true;
+
+            if !rule.enabled() {
+                continue;
+            }
+
+            for action in rule.actions() {
+                match action {
+                    Action::Notify => state.enabled = true,
+                    Action::SetTweak(Tweak::Sound(_)) => state.sound = true,
+                    Action::SetTweak(Tweak::Highlight(true)) => state.highlight = true,
+                    _ => {}
+                }
+            }
+        }
+
+        is_available.then_some(state)
+    }
+
+    /// Enable or disable the given group of predefined push rules.
+    ///
+    /// When enabling rules that do not notify, they are set to notify with
+    /// the given tweaks.
+    pub async fn set_predefined_rules_enabled(
+        &self,
+        rules: PredefinedRules,
+        enabled: bool,
+        sound: bool,
+        highlight: bool,
+    ) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        // The rules to change, with the new actions if they need to notify.
+        let changes = {
+            let ruleset = self.imp().ruleset.borrow();
+            let Some(ruleset) = ruleset.as_ref() else {
+                return Ok(());
+            };
+
+            rules
+                .rules()
+                .iter()
+                .filter_map(|(kind, rule_id)| {
+                    let rule = ruleset.get(kind.clone(), rule_id)?;
+                    let notifies = rule.actions().iter().any(|a| a.should_notify());
+                    let actions = (enabled && !notifies)
+                        .then(|| notify_actions(rule.actions(), sound, highlight));
+
+                    Some((kind.clone(), rule_id.to_string(), actions))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let local_changes = changes.clone();
+        let handle = spawn_tokio!(async move {
+            for (kind, rule_id, actions) in changes {
+                let request = set_pushrule_enabled::v3::Request::new(
+                    RuleScope::Global,
+                    kind.clone(),
+                    rule_id.clone(),
+                    enabled,
+                );
+                client.send(request, None).await?;
+
+                if let Some(actions) = actions {
+                    let request = set_pushrule_actions::v3::Request::new(
+                        RuleScope::Global,
+                        kind,
+                        rule_id,
+                        actions,
+                    );
+                    client.send(request, None).await?;
+                }
+            }
+
+            MatrixResult::Ok(())
+        });
+
+        match handle.await.unwrap() {
+            Ok(()) => {
+                self.edit_ruleset(|ruleset| {
+                    for (kind, rule_id, actions) in local_changes {
+                        let _ = ruleset.set_enabled(kind.clone(), &rule_id, enabled);
+                        if let Some(actions) = actions {
+                            let _ = ruleset.set_actions(kind, &rule_id, actions);
+                        }
+                    }
+                });
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not update push rules: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Set the tweaks of the given group of predefined push rules.
+    ///
+    /// Only the rules that currently notify are changed.
+    pub async fn set_predefined_rules_tweaks(
+        &self,
+        rules: PredefinedRules,
+        sound: bool,
+        highlight: bool,
+    ) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        let changes = {
+            let ruleset = self.imp().ruleset.borrow();
+            let Some(ruleset) = ruleset.as_ref() else {
+                return Ok(());
+            };
+
+            rules
+                .rules()
+                .iter()
+                .filter_map(|(kind, rule_id)| {
+                    let rule = ruleset.get(kind.clone(), rule_id)?;
+                    if !rule.actions().iter().any(|a| a.should_notify()) {
+                        return None;
+                    }
+
+                    let actions = notify_actions(rule.actions(), sound, highlight);
+                    Some((kind.clone(), rule_id.to_string(), actions))
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let local_changes = changes.clone();
+        let handle = spawn_tokio!(async move {
+            for (kind, rule_id, actions) in changes {
+                let request = set_pushrule_actions::v3::Request::new(
+                    RuleScope::Global,
+                    kind,
+                    rule_id,
+                    actions,
+                );
+                client.send(request, None).await?;
+            }
+
+            MatrixResult::Ok(())
+        });
+
+        match handle.await.unwrap() {
+            Ok(()) => {
+                self.edit_ruleset(|ruleset| {
+                    for (kind, rule_id, actions) in local_changes {
+                        let _ = ruleset.set_actions(kind, &rule_id, actions);
+                    }
+                });
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not update push rules actions: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// The keywords of the push rules.
+    ///
+    /// They are the content push rules that are not predefined.
+    pub fn keywords(&self) -> Vec<String> {
+        self.imp()
+            .ruleset
+            .borrow()
+            .as_ref()
+            .map(|ruleset| {
+                ruleset
+                    .content
+                    .iter()
+                    .filter(|rule| !rule.default)
+                    .map(|rule| rule.pattern.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add a push rule for the given keyword.
+    pub async fn add_keyword(&self, keyword: String) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        let rule = NewPushRule::Content(NewPatternedPushRule::new(
+            keyword.clone(),
+            keyword,
+            notify_actions(&[], true, true),
+        ));
+        let local_rule = rule.clone();
+        let handle = spawn_tokio!(async move {
+            let request = set_pushrule::v3::Request::new(RuleScope::Global, rule);
+            client.send(request, None).await
+        });
+
+        match handle.await.unwrap() {
+            Ok(_) => {
+                self.edit_ruleset(|ruleset| {
+                    let _ = ruleset.insert(local_rule, None, None);
+                });
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not add keyword push rule: {error}");
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Remove the push rule for the given keyword.
+    pub async fn remove_keyword(&self, keyword: &str) -> MatrixResult<()> {
+        let Some(session) = self.session() else {
+            return Ok(());
+        };
+        let client = session.client();
+
+        // The rule ID is the keyword for the rules we create, but not
+        // necessarily for the rules created by other clients.
+        let rule_id = self.imp().ruleset.borrow().as_ref().and_then(|ruleset| {
+            ruleset
+                .content
+                .iter()
+                .find(|rule| !rule.default && rule.pattern == keyword)
+                .map(|rule| rule.rule_id.clone())
+        });
+        let Some(rule_id) = rule_id else {
+            return Ok(());
+        };
+
+        let local_rule_id = rule_id.clone();
+        let handle = spawn_tokio!(async move {
+            let request =
+                delete_pushrule::v3::Request::new(RuleScope::Global, RuleKind::Content, rule_id);
+            client.send(request, None).await
+        });
+
+        match handle.await.unwrap() {
+            Ok(_) => {
+                self.edit_ruleset(|ruleset| {
+                    let _ = ruleset.remove(RuleKind::Content, &local_rule_id);
+                });
+                Ok(())
+            }
+            Err(error) => {
+                error!("Could not remove keyword push rule: {error}");
+                Err(error.into())
+            }
+        }
+    }
+}
+
+impl Default for NotificationsSettings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The override push rule to mute the room with the given ID.
+fn mute_rule(room_id: &RoomId) -> NewConditionalPushRule {
+    NewConditionalPushRule::new(
+        room_id.to_string(),
+        vec![PushCondition::EventMatch {
+            key: "room_id".to_owned(),
+            pattern: room_id.to_string(),
+        }],
+        vec![],
+    )
+}
+
+/// The actions of a push rule that notifies, with the given tweaks.
+///
+/// The sound of the given current actions is kept if the sound stays enabled.
+fn notify_actions(current_actions: &[Action], sound: bool, highlight: bool) -> Vec<Action> {
+    let mut actions = vec![Action::Notify];
+
+    if sound {
+        let sound = current_actions
+            .iter()
+            .find_map(|action| match action {
+                Action::SetTweak(Tweak::Sound(sound)) => Some(sound.clone()),
+                _ => None,
+            })
+            .unwrap_or_else(|| "default".to_owned());
+        actions.push(Action::SetTweak(Tweak::Sound(sound)));
+    }
+    if highlight {
+        actions.push(Action::SetTweak(Tweak::Highlight(true)));
+    }
+
+    actions
+}
@@ -3,11 +3,20 @@ use std::fmt;
 use gtk::{glib, prelude::*, subclass::prelude::*};
 use indexmap::IndexMap;
 use matrix_sdk_ui::timeline::{
-    AnyOtherFullStateEventContent, Error as TimelineError, EventTimelineItem, RepliedToEvent,
-    TimelineDetails, TimelineItemContent,
+    AnyOtherFullStateEventContent, Error as TimelineError, EventSendState, EventTimelineItem,
+    RepliedToEvent, TimelineDetails, TimelineItemContent,
 };
 use ruma::{
-    events::{receipt::Receipt, room::message::MessageType, AnySyncTimelineEvent},
+    events::{
+        receipt::Receipt,
+        room::{
+            message::{
+                MessageType, OriginalSyncRoomMessageEvent, Relation, RoomMessageEventContent,
+            },
+            MediaSource,
+        },
+        AnyMessageLikeEventContent, AnySyncTimelineEvent,
+    },
     serde::Raw,
     MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedTransactionId, OwnedUserId,
 };
@@ -20,7 +29,7 @@ use super::{
     timeline::{TimelineItem, TimelineItemImpl},
     Member, Room,
 };
-use crate::{spawn_tokio, utils::matrix::get_media_content};
+use crate::{session::model::PackImage, spawn_tokio, utils::matrix::get_media_content};
 
 /// The unique key to identify an event in a room.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -41,12 +50,26 @@ impl fmt::Display for EventKey {
     }
 }
 
+/// The state of the sending of an event.
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "RoomEventSendState")]
+pub enum SendState {
+    /// The event was received from the server.
+    #[default]
+    Sent,
+    /// The event is a local echo that is being sent.
+    Sending,
+    /// The event is a local echo that could not be sent.
+    Failed,
+}
+
 #[derive(Clone, Debug, glib::Boxed)]
 #[boxed_type(name = "BoxedEventTimelineItem")]
 pub struct BoxedEventTimelineItem(EventTimelineItem);
 
 mod imp {
-    use std::cell::RefCell;
+    use std::cell::{Cell, RefCell};
 
     use glib::object::WeakRef;
     use once_cell::sync::Lazy;
@@ -66,6 +89,16 @@ mod imp {
 
         /// The read receipts on this event.
         pub read_receipts: gtk::StringList,
+
+        /// The ID of the root of the thread this event is in, if any.
+        pub thread_root_id: RefCell<Option<OwnedEventId>>,
+
+        /// The number of loaded replies in the thread this event is the root
+        /// of.
+        pub loaded_thread_replies_count: Cell<u32>,
+
+        /// The latest loaded reply in the thread this event is the root of.
+        pub thread_latest_reply: WeakRef<super::Event>,
     }
 
     #[glib::object_subclass]
@@ -99,6 +132,18 @@ mod imp {
                     glib::ParamSpecBoolean::builder("has-read-receipts")
                         .read_only()
                         .build(),
+                    glib::ParamSpecUInt::builder("thread-replies-count")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("has-thread-replies")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecObject::builder::<super::Event>("thread-latest-reply")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<SendState>("send-state")
+                        .read_only()
+                        .build(),
                 ]
             });
 
@@ -131,6 +176,10 @@ mod imp {
                 "is-edited" => obj.is_edited().to_value(),
                 "is-highlighted" => obj.is_highlighted().to_value(),
                 "has-read-receipts" => obj.has_read_receipts().to_value(),
+                "thread-replies-count" => obj.thread_replies_count().to_value(),
+                "has-thread-replies" => obj.has_thread_replies().to_value(),
+                "thread-latest-reply" => obj.thread_latest_reply().to_value(),
+                "send-state" => obj.send_state().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -230,10 +279,14 @@ impl Event {
     pub fn set_item(&self, item: EventTimelineItem) {
         let was_edited = self.is_edited();
         let was_highlighted = self.is_highlighted();
+        let prev_send_state = self.send_state();
+        let prev_thread_replies_count = self.thread_replies_count();
         let imp = self.imp();
 
         imp.reactions.update(item.reactions().clone());
         self.update_read_receipts(item.read_receipts());
+        imp.thread_root_id
+            .replace(item.original_json().and_then(thread_root_id));
         imp.item.replace(Some(item));
 
         self.notify("source");
@@ -243,6 +296,10 @@ impl Event {
         if self.is_highlighted() != was_highlighted {
             self.notify("is-highlighted");
         }
+        if self.send_state() != prev_send_state {
+            self.notify("send-state");
+        }
+        self.notify_thread_replies_count(prev_thread_replies_count);
     }
 
     /// The raw JSON source for this `Event`, if it has been echoed back
@@ -302,6 +359,22 @@ impl Event {
         }
     }
 
+    /// The state of the sending of this `Event`.
+    pub fn send_state(&self) -> SendState {
+        let item_ref = self.imp().item.borrow();
+        let Some(item) = item_ref.as_ref() else {
+            return SendState::default();
+        };
+
+        match item.send_state() {
+            Some(EventSendState::NotSentYet) => SendState::Sending,
+            Some(EventSendState::SendingFailed { .. } | EventSendState::Cancelled) => {
+                SendState::Failed
+            }
+            Some(EventSendState::Sent { .. }) | None => SendState::Sent,
+        }
+    }
+
     /// The user ID of the sender of this `Event`.
     pub fn sender_id(&self) -> OwnedUserId {
         self.imp()
@@ -482,6 +555,80 @@ impl Event {
         }
     }
 
+    /// The ID of the root of the thread this `Event` is in, if any.
+    pub fn thread_root_id(&self) -> Option<OwnedEventId> {
+        self.imp().thread_root_id.borrow().clone()
+    }
+
+    /// Whether this `Event` is a reply in a thread.
+    pub fn is_thread_reply(&self) -> bool {
+        self.imp().thread_root_id.borrow().is_some()
+    }
+
+    /// The number of replies in the thread this `Event` is the root of.
+    ///
+    /// This uses the summary sent by the server if it knows more replies than
+    /// the ones that were loaded.
+    pub fn thread_replies_count(&self) -> u32 {
+        let imp = self.imp();
+        let loaded_count = imp.loaded_thread_replies_count.get();
+        let bundled_count = imp
+            .item
+            .borrow()
+            .as_ref()
+            .and_then(|item| item.original_json())
+            .map(bundled_thread_replies_count)
+            .unwrap_or_default();
+
+        loaded_count.max(bundled_count)
+    }
+
+    /// Whether this `Event` is the root of a thread with replies.
+    pub fn has_thread_replies(&self) -> bool {
+        self.thread_replies_count() > 0
+    }
+
+    /// The latest loaded reply in the thread this `Event` is the root of, if
+    /// any.
+    pub fn thread_latest_reply(&self) -> Option<Event> {
+        self.imp().thread_latest_reply.upgrade()
+    }
+
+    /// Update the summary of the thread this `Event` is the root of with the
+    /// given loaded replies.
+    pub fn update_thread_summary(&self, replies: &[Event]) {
+        let imp = self.imp();
+        let prev_thread_replies_count = self.thread_replies_count();
+
+        imp.loaded_thread_replies_count.set(replies.len() as u32);
+
+        let latest_reply = replies
+            .iter()
+            .max_by_key(|event| event.origin_server_ts())
+            .cloned();
+        if self.thread_latest_reply() != latest_reply {
+            imp.thread_latest_reply.set(latest_reply.as_ref());
+            self.notify("thread-latest-reply");
+        }
+
+        self.notify_thread_replies_count(prev_thread_replies_count);
+    }
+
+    /// Notify the changes of the number of thread replies, if it is different
+    /// than `prev_count`.
+    fn notify_thread_replies_count(&self, prev_count: u32) {
+        let count = self.thread_replies_count();
+
+        if count == prev_count {
+            return;
+        }
+
+        self.notify("thread-replies-count");
+        if (count > 0) != (prev_count > 0) {
+            self.notify("has-thread-replies");
+        }
+    }
+
     /// Fetch missing details for this event.
     ///
     /// This is a no-op if called for a local event.
@@ -518,11 +665,74 @@ impl Event {
         get_media_content(client, message.msgtype().clone()).await
     }
 
+    /// Whether this `Event` contains a media that can be fetched with
+    /// [`Event::get_media_content()`].
+    pub fn has_media_content(&self) -> bool {
+        matches!(
+            self.message(),
+            Some(
+                MessageType::File(_)
+                    | MessageType::Image(_)
+                    | MessageType::Video(_)
+                    | MessageType::Audio(_)
+            )
+        )
+    }
+
+    /// The content to send to forward this `Event` to a room, if it can be
+    /// forwarded.
+    ///
+    /// The relations of this event are not kept, and the media are not
+    /// uploaded again.
+    pub fn forward_content(&self) -> Option<AnyMessageLikeEventContent> {
+        let msgtype = self.message()?;
+        Some(RoomMessageEventContent::new(msgtype).into())
+    }
+
+    /// The image of this `Event` that can be added to an image pack, if any.
+    ///
+    /// Only unencrypted images and stickers can be added to a pack.
+    pub fn pack_image(&self) -> Option<PackImage> {
+        let (url, body, info) = match self.content() {
+            TimelineItemContent::Message(message) => {
+                let MessageType::Image(image) = message.msgtype() else {
+                    return None;
+                };
+                let MediaSource::Plain(url) = &image.source else {
+                    return None;
+                };
+                (
+                    url.clone(),
+                    image.body.clone(),
+                    image.info.as_deref().cloned(),
+                )
+            }
+            TimelineItemContent::Sticker(sticker) => {
+                let content = sticker.content();
+                (
+                    content.url.clone(),
+                    content.body.clone(),
+                    Some(content.info.clone()),
+                )
+            }
+            _ => return None,
+        };
+
+        Some(PackImage {
+            url,
+            body: Some(body),
+            info,
+            usage: Vec::new(),
+        })
+    }
+
     /// Whether this `Event` is considered a message.
     pub fn is_message(&self) -> bool {
         matches!(
             self.content(),
-            TimelineItemContent::Message(_) | TimelineItemContent::Sticker(_)
+            TimelineItemContent::Message(_)
+                | TimelineItemContent::Sticker(_)
+                | TimelineItemContent::Poll(_)
         )
     }
 
@@ -555,7 +765,7 @@ pub fn count_as_unread(content: &TimelineItemContent) -> bool {
         TimelineItemContent::Message(message) => {
             !matches!(message.msgtype(), MessageType::Notice(_))
         }
-        TimelineItemContent::Sticker(_) => true,
+        TimelineItemContent::Sticker(_) | TimelineItemContent::Poll(_) => true,
         TimelineItemContent::OtherState(state) => matches!(
             state.content(),
             AnyOtherFullStateEventContent::RoomTombstone(_)
@@ -563,3 +773,29 @@ pub fn count_as_unread(content: &TimelineItemContent) -> bool {
         _ => false,
     }
 }
+
+/// Get the ID of the root of the thread the given event is in, if any.
+fn thread_root_id(raw: &Raw<AnySyncTimelineEvent>) -> Option<OwnedEventId> {
+    let event = raw.deserialize_as::<OriginalSyncRoomMessageEvent>().ok()?;
+
+    match event.content.relates_to? {
+        Relation::Thread(thread) => Some(thread.event_id),
+        _ => None,
+    }
+}
+
+/// Get the number of replies in the thread summary bundled by the server with
+/// the given event.
+///
+/// Returns `0` if the event has no bundled thread summary.
+fn bundled_thread_replies_count(raw: &Raw<AnySyncTimelineEvent>) -> u32 {
+    raw.get_field::<serde_json::Value>("unsigned")
+        .ok()
+        .flatten()
+        .and_then(|unsigned| {
+            unsigned
+                .pointer("/m.relations/m.thread/count")
+                .and_then(|count| count.as_u64())
+        })
+        .unwrap_or_default() as u32
+}
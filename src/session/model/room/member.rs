@@ -7,7 +7,7 @@ use matrix_sdk::{
             room::member::{MembershipState, RoomMemberEventContent},
             OriginalSyncStateEvent, StrippedStateEvent,
         },
-        OwnedMxcUri, UserId,
+        OwnedMxcUri, OwnedUserId, UserId,
     },
 };
 use tracing::error;
@@ -66,8 +66,9 @@ impl From<MembershipState> for Membership {
 }
 
 mod imp {
-    use std::cell::Cell;
+    use std::cell::{Cell, RefCell};
 
+    use glib::object::WeakRef;
     use once_cell::sync::Lazy;
 
     use super::*;
@@ -78,6 +79,13 @@ mod imp {
         pub membership: Cell<Membership>,
         /// The timestamp of the latest activity of this member.
         pub latest_activity: Cell<u64>,
+        /// The room of this member.
+        pub room: WeakRef<Room>,
+        /// The reason of the latest change of membership of this member.
+        pub membership_reason: RefCell<Option<String>>,
+        /// The ID of the user who made the latest change of membership of
+        /// this member.
+        pub membership_sender_id: RefCell<Option<OwnedUserId>>,
     }
 
     #[glib::object_subclass]
@@ -102,6 +110,9 @@ mod imp {
                     glib::ParamSpecUInt64::builder("latest-activity")
                         .explicit_notify()
                         .build(),
+                    glib::ParamSpecString::builder("membership-reason")
+                        .read_only()
+                        .build(),
                 ]
             });
 
@@ -122,6 +133,7 @@ mod imp {
                 "power-level" => obj.power_level().to_value(),
                 "membership" => obj.membership().to_value(),
                 "latest-activity" => obj.latest_activity().to_value(),
+                "membership-reason" => obj.membership_reason().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -136,10 +148,17 @@ glib::wrapper! {
 impl Member {
     pub fn new(room: &Room, user_id: &UserId) -> Self {
         let session = room.session();
-        glib::Object::builder()
+        let obj: Self = glib::Object::builder()
             .property("session", &session)
             .property("user-id", user_id.as_str())
-            .build()
+            .build();
+        obj.imp().room.set(Some(room));
+        obj
+    }
+
+    /// The room of this member.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
     }
 
     /// The power level of the member.
@@ -148,7 +167,7 @@ impl Member {
     }
 
     /// Set the power level of the member.
-    fn set_power_level(&self, power_level: PowerLevel) {
+    pub(super) fn set_power_level(&self, power_level: PowerLevel) {
         if self.power_level() == power_level {
             return;
         }
@@ -188,6 +207,37 @@ impl Member {
         self.notify("membership");
     }
 
+    /// The reason of the latest change of membership of this member, if any.
+    pub fn membership_reason(&self) -> Option<String> {
+        self.imp().membership_reason.borrow().clone()
+    }
+
+    /// The ID of the user who made the latest change of membership of this
+    /// member, if any.
+    pub fn membership_sender_id(&self) -> Option<OwnedUserId> {
+        self.imp().membership_sender_id.borrow().clone()
+    }
+
+    /// The user who made the latest change of membership of this member, if
+    /// any.
+    pub fn membership_sender(&self) -> Option<Member> {
+        let sender_id = self.membership_sender_id()?;
+        let room = self.room()?;
+        Some(room.get_or_create_members().get_or_create(sender_id))
+    }
+
+    /// Set the details of the latest change of membership of this member.
+    fn set_membership_change(&self, sender_id: Option<OwnedUserId>, reason: Option<String>) {
+        let imp = self.imp();
+        imp.membership_sender_id.replace(sender_id);
+
+        if self.membership_reason() == reason {
+            return;
+        }
+        imp.membership_reason.replace(reason);
+        self.notify("membership-reason");
+    }
+
     /// The timestamp of the latest activity of this member.
     pub fn latest_activity(&self) -> u64 {
         self.imp().latest_activity.get()
@@ -215,6 +265,12 @@ impl Member {
             .image()
             .set_uri(member.avatar_url().map(std::borrow::ToOwned::to_owned));
         self.set_power_level(member.power_level());
+
+        let event = member.event();
+        self.set_membership_change(
+            Some(event.sender().to_owned()),
+            event.original_content().and_then(|c| c.reason.clone()),
+        );
         self.set_membership(member.membership().into());
     }
 
@@ -227,6 +283,10 @@ impl Member {
 
         self.set_display_name(event.display_name());
         self.avatar_data().image().set_uri(event.avatar_url());
+        self.set_membership_change(
+            Some(event.sender().to_owned()),
+            event.content().reason.clone(),
+        );
         self.set_membership((&event.content().membership).into());
 
         let session = self.session();
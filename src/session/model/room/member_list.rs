@@ -14,7 +14,7 @@ use matrix_sdk::{
 };
 use tracing::error;
 
-use super::{Event, Member, Membership, Room};
+use super::{Event, Member, Membership, PowerLevels, Room};
 use crate::{spawn, spawn_tokio};
 
 mod imp {
@@ -287,6 +287,13 @@ impl MemberList {
             .update_from_member_event(event);
     }
 
+    /// Updates the power levels of the room members.
+    pub(super) fn update_power_levels(&self, power_levels: &PowerLevels) {
+        for member in self.imp().members.borrow().values() {
+            member.set_power_level(power_levels.user_power_level(&member.user_id()));
+        }
+    }
+
     /// Returns the Membership of a given UserId.
     ///
     /// If the user has no Membership, Membership::Leave will be returned
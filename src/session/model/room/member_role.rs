@@ -30,6 +30,18 @@ impl MemberRole {
     pub fn is_peasant(&self) -> bool {
         matches!(*self, Self::Peasant)
     }
+
+    /// The power level to give to a member to get this role.
+    ///
+    /// For `Peasant`, the default power level of the room should be used
+    /// instead, if it is lower.
+    pub fn power_level(&self) -> PowerLevel {
+        match *self {
+            Self::Admin => 100,
+            Self::Mod => 50,
+            Self::Peasant => 0,
+        }
+    }
 }
 
 impl From<PowerLevel> for MemberRole {
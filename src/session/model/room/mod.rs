@@ -8,29 +8,38 @@ mod room_type;
 mod timeline;
 mod typing_list;
 
-use std::{cell::RefCell, io::Cursor};
+use std::{cell::RefCell, collections::HashSet, time::Duration};
 
 use gettextrs::gettext;
 use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
-    attachment::{generate_image_thumbnail, AttachmentConfig, AttachmentInfo, Thumbnail},
+    attachment::AttachmentInfo,
     deserialized_responses::{MemberEvent, SyncOrStrippedState, SyncTimelineEvent},
     room::Room as MatrixRoom,
     sync::{JoinedRoom, LeftRoom},
-    DisplayName, Result as MatrixResult, RoomMemberships, RoomState,
+    DisplayName, HttpError, Result as MatrixResult, RoomMemberships, RoomState,
 };
+use matrix_sdk_ui::timeline::TimelineItemContent;
 use ruma::{
+    api::client::membership::unban_user,
     events::{
         reaction::ReactionEventContent,
         receipt::{ReceiptEventContent, ReceiptType},
         relation::Annotation,
-        room::power_levels::{PowerLevelAction, RoomPowerLevelsEventContent},
+        room::{
+            guest_access::{GuestAccess, RoomGuestAccessEventContent},
+            history_visibility::{HistoryVisibility, RoomHistoryVisibilityEventContent},
+            join_rules::{JoinRule, RoomJoinRulesEventContent},
+            power_levels::{PowerLevelAction, RoomPowerLevelsEventContent},
+        },
+        space::child::SpaceChildEventContent,
         tag::{TagInfo, TagName},
         typing::TypingEventContent,
         AnyMessageLikeEventContent, AnyRoomAccountDataEvent, AnySyncStateEvent,
-        AnySyncTimelineEvent, SyncEphemeralRoomEvent, SyncStateEvent,
+        AnySyncTimelineEvent, EmptyStateKey, StateEventContent, SyncEphemeralRoomEvent,
+        SyncStateEvent,
     },
-    OwnedEventId, OwnedRoomId, OwnedUserId, RoomId,
+    Int, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId, UserId,
 };
 use tracing::{debug, error, warn};
 
@@ -46,11 +55,20 @@ pub use self::{
     typing_list::TypingList,
 };
 use super::{
-    AvatarData, AvatarImage, AvatarUriSource, IdentityVerification, Session, SidebarItem,
-    SidebarItemImpl, User,
+    AvatarData, AvatarImage, AvatarUriSource, ComposerDraft, IdentityVerification,
+    NotificationsRoomSetting, Session, SidebarItem, SidebarItemImpl, User,
 };
 use crate::{components::Pill, gettext_f, prelude::*, spawn, spawn_tokio};
 
+/// The maximum number of attempts to send a request that failed.
+const MAX_SEND_ATTEMPTS: u32 = 5;
+
+/// The delay before sending a request again, after the given number of
+/// failed attempts.
+fn send_retry_delay(attempts: u32) -> Duration {
+    Duration::from_secs(2u64.pow(attempts.min(5)))
+}
+
 mod imp {
     use std::cell::Cell;
 
@@ -80,6 +98,9 @@ mod imp {
         ///
         /// If it is not known, it will return `0`.
         pub latest_activity: Cell<u64>,
+        /// The ID of the latest event of this room received in a sync
+        /// response.
+        pub latest_event_id: RefCell<Option<OwnedEventId>>,
         /// Whether all messages of this room are read.
         pub is_read: Cell<bool>,
         /// The highlight state of the room,
@@ -96,6 +117,15 @@ mod imp {
         pub is_encrypted: Cell<bool>,
         /// The list of members currently typing in this room.
         pub typing_list: TypingList,
+        /// The IDs of the children of this room, if it is a space.
+        pub space_children: RefCell<HashSet<OwnedRoomId>>,
+        /// The notifications setting of this room.
+        pub notifications_setting: Cell<NotificationsRoomSetting>,
+        /// The draft of the composer, if this room is encrypted.
+        ///
+        /// Drafts of encrypted rooms are only kept in memory, to avoid
+        /// storing the content of encrypted messages in the settings.
+        pub draft: RefCell<Option<ComposerDraft>>,
     }
 
     #[glib::object_subclass]
@@ -168,6 +198,14 @@ mod imp {
                     glib::ParamSpecObject::builder::<TypingList>("typing-list")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("has-draft")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<NotificationsRoomSetting>(
+                        "notifications-setting",
+                    )
+                    .read_only()
+                    .build(),
                 ]
             });
 
@@ -214,13 +252,20 @@ mod imp {
                 "verification" => obj.verification().to_value(),
                 "encrypted" => obj.is_encrypted().to_value(),
                 "typing-list" => obj.typing_list().to_value(),
+                "has-draft" => obj.has_draft().to_value(),
+                "notifications-setting" => obj.notifications_setting().to_value(),
                 _ => unimplemented!(),
             }
         }
 
         fn signals() -> &'static [Signal] {
-            static SIGNALS: Lazy<Vec<Signal>> =
-                Lazy::new(|| vec![Signal::builder("room-forgotten").build()]);
+            static SIGNALS: Lazy<Vec<Signal>> = Lazy::new(|| {
+                vec![
+                    Signal::builder("room-forgotten").build(),
+                    Signal::builder("space-children-changed").build(),
+                    Signal::builder("access-changed").build(),
+                ]
+            });
             SIGNALS.as_ref()
         }
 
@@ -231,6 +276,12 @@ mod imp {
             obj.set_matrix_room(obj.session().client().get_room(obj.room_id()).unwrap());
             self.timeline.set(Timeline::new(&obj)).unwrap();
 
+            let notifications_setting = obj
+                .session()
+                .notifications_settings()
+                .room_setting(obj.room_id());
+            self.notifications_setting.set(notifications_setting);
+
             self.timeline
                 .get()
                 .unwrap()
@@ -252,6 +303,7 @@ mod imp {
             }));
 
             obj.load_power_levels();
+            obj.load_space_children();
 
             spawn!(clone!(@strong obj => async move {
                 obj.setup_is_encrypted().await;
@@ -792,10 +844,23 @@ impl Room {
             .unwrap()
             .unread_notification_counts();
 
-        if counts.highlight_count > 0 {
-            highlight = HighlightFlags::all();
-        } else if counts.notification_count > 0 || !self.is_read() {
-            highlight = HighlightFlags::BOLD;
+        match self.notifications_setting() {
+            NotificationsRoomSetting::All => {
+                if counts.highlight_count > 0 {
+                    highlight = HighlightFlags::all();
+                } else if counts.notification_count > 0 || !self.is_read() {
+                    highlight = HighlightFlags::BOLD;
+                }
+            }
+            NotificationsRoomSetting::MentionsOnly => {
+                if counts.highlight_count > 0 {
+                    highlight = HighlightFlags::all();
+                } else if !self.is_read() {
+                    highlight = HighlightFlags::BOLD;
+                }
+            }
+            // Muted rooms are never highlighted.
+            NotificationsRoomSetting::Mute => {}
         }
 
         self.set_highlight(highlight);
@@ -893,13 +958,50 @@ impl Room {
     }
 
     /// The number of unread notifications of this room.
+    ///
+    /// Only the mentions are counted if the room is set to notify only for
+    /// mentions and keywords, and nothing is counted if the room is muted.
     pub fn notification_count(&self) -> u64 {
         let matrix_room = self.imp().matrix_room.borrow();
-        matrix_room
-            .as_ref()
-            .unwrap()
-            .unread_notification_counts()
-            .notification_count
+        let counts = matrix_room.as_ref().unwrap().unread_notification_counts();
+
+        match self.notifications_setting() {
+            NotificationsRoomSetting::All => counts.notification_count,
+            NotificationsRoomSetting::MentionsOnly => counts.highlight_count,
+            NotificationsRoomSetting::Mute => 0,
+        }
+    }
+
+    /// The notifications setting of this room.
+    pub fn notifications_setting(&self) -> NotificationsRoomSetting {
+        self.imp().notifications_setting.get()
+    }
+
+    /// Set the notifications setting of this room.
+    ///
+    /// This only sets the property locally, use
+    /// [`Room::sync_notifications_setting()`] to change it on the homeserver.
+    pub fn set_notifications_setting(&self, setting: NotificationsRoomSetting) {
+        if self.notifications_setting() == setting {
+            return;
+        }
+
+        self.imp().notifications_setting.set(setting);
+        self.notify("notifications-setting");
+
+        self.notify_notification_count();
+        self.update_highlight();
+    }
+
+    /// Change the notifications setting of this room on the homeserver.
+    pub async fn sync_notifications_setting(
+        &self,
+        setting: NotificationsRoomSetting,
+    ) -> MatrixResult<()> {
+        self.session()
+            .notifications_settings()
+            .set_room_setting(self.room_id(), setting)
+            .await
     }
 
     /// The Avatar of this room.
@@ -955,6 +1057,15 @@ impl Room {
             MemberEvent::Stripped(event) => event.sender.clone(),
         };
 
+        // Reject invites from ignored users right away.
+        if self.session().ignored_users().contains(&inviter_id) {
+            debug!("Rejecting invite from ignored user {inviter_id}");
+            if let Err(error) = self.decline_invite().await {
+                error!("Failed to reject invite from ignored user {inviter_id}: {error}");
+            }
+            return;
+        }
+
         let inviter_id_clone = inviter_id.clone();
         let handle =
             spawn_tokio!(async move { matrix_room.get_member_no_sync(&inviter_id_clone).await });
@@ -981,6 +1092,16 @@ impl Room {
         // FIXME: notify only when the count has changed
         self.notify_notification_count();
 
+        if let Some(event_id) = batch.iter().rev().find_map(|event| {
+            event
+                .event
+                .get_field::<OwnedEventId>("event_id")
+                .ok()
+                .flatten()
+        }) {
+            self.imp().latest_event_id.replace(Some(event_id));
+        }
+
         let events: Vec<_> = batch
             .iter()
             .flat_map(|e| e.event.deserialize().ok())
@@ -1015,10 +1136,22 @@ impl Room {
                     }
                     AnySyncStateEvent::RoomPowerLevels(SyncStateEvent::Original(event)) => {
                         self.power_levels().update_from_event(event.clone());
+
+                        if let Some(members) = self.members() {
+                            members.update_power_levels(&self.power_levels());
+                        }
                     }
                     AnySyncStateEvent::RoomTombstone(_) => {
                         self.load_tombstone();
                     }
+                    AnySyncStateEvent::SpaceChild(_) => {
+                        self.load_space_children();
+                    }
+                    AnySyncStateEvent::RoomJoinRules(_)
+                    | AnySyncStateEvent::RoomHistoryVisibility(_)
+                    | AnySyncStateEvent::RoomGuestAccess(_) => {
+                        self.emit_by_name::<()>("access-changed", &[]);
+                    }
                     _ => {}
                 }
             }
@@ -1038,6 +1171,12 @@ impl Room {
         self.imp().latest_activity.get()
     }
 
+    /// The ID of the latest event of this room received in a sync response,
+    /// if any.
+    pub fn latest_event_id(&self) -> Option<OwnedEventId> {
+        self.imp().latest_event_id.borrow().clone()
+    }
+
     /// Set the timestamp of the room's latest possibly unread event.
     fn set_latest_activity(&self, latest_activity: u64) {
         if latest_activity == self.latest_activity() {
@@ -1080,6 +1219,355 @@ impl Room {
         );
     }
 
+    /// Whether this room is a space.
+    pub fn is_space(&self) -> bool {
+        self.matrix_room().is_space()
+    }
+
+    /// The IDs of the children of this room, if it is a space.
+    pub fn space_children(&self) -> HashSet<OwnedRoomId> {
+        self.imp().space_children.borrow().clone()
+    }
+
+    /// Set the IDs of the children of this room.
+    fn set_space_children(&self, children: HashSet<OwnedRoomId>) {
+        if *self.imp().space_children.borrow() == children {
+            return;
+        }
+
+        self.imp().space_children.replace(children);
+        self.emit_by_name::<()>("space-children-changed", &[]);
+    }
+
+    /// Load the children of this room from the `m.space.child` state events,
+    /// if it is a space.
+    fn load_space_children(&self) {
+        if !self.is_space() {
+            return;
+        }
+
+        let matrix_room = self.matrix_room();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .get_state_events_static::<SpaceChildEventContent>()
+                .await
+        });
+
+        spawn!(
+            glib::Priority::DEFAULT_IDLE,
+            clone!(@weak self as obj => async move {
+                let events = match handle.await.unwrap() {
+                    Ok(events) => events,
+                    Err(error) => {
+                        error!("Failed to load the children of the space: {error}");
+                        return;
+                    }
+                };
+
+                // A child event with an empty `via` means that the room was removed from
+                // the space.
+                let children = events
+                    .into_iter()
+                    .filter_map(|raw| raw.deserialize().ok())
+                    .filter_map(|event| match event {
+                        SyncOrStrippedState::Sync(SyncStateEvent::Original(event))
+                            if !event.content.via.is_empty() =>
+                        {
+                            Some(event.state_key)
+                        }
+                        _ => None,
+                    })
+                    .collect();
+
+                obj.set_space_children(children);
+            })
+        );
+    }
+
+    /// Add the room with the given ID to the children of this space.
+    pub async fn add_space_child(&self, room_id: OwnedRoomId) -> MatrixResult<()> {
+        let via = vec![self
+            .session()
+            .user()
+            .unwrap()
+            .user_id()
+            .server_name()
+            .to_owned()];
+        self.send_space_child_event(room_id, SpaceChildEventContent::new(via))
+            .await
+    }
+
+    /// Remove the room with the given ID from the children of this space.
+    pub async fn remove_space_child(&self, room_id: OwnedRoomId) -> MatrixResult<()> {
+        self.send_space_child_event(room_id, SpaceChildEventContent::new(vec![]))
+            .await
+    }
+
+    async fn send_space_child_event(
+        &self,
+        room_id: OwnedRoomId,
+        content: SpaceChildEventContent,
+    ) -> MatrixResult<()> {
+        let matrix_room = self.matrix_room();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .send_state_event_for_key(&room_id, content)
+                .await
+        });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Failed to update the children of the space: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Connect to the signal emitted when the children of this space changed.
+    pub fn connect_space_children_changed<F: Fn(&Self) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_local("space-children-changed", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+            None
+        })
+    }
+
+    /// The spaces that contain this room.
+    pub fn parent_spaces(&self) -> Vec<Room> {
+        let room_id = self.room_id();
+
+        self.session()
+            .room_list()
+            .iter::<Room>()
+            .filter_map(Result::ok)
+            .filter(|room| room.is_space() && room.space_children().contains(room_id))
+            .collect()
+    }
+
+    /// The rule to join this room.
+    pub fn join_rule(&self) -> JoinRule {
+        self.matrix_room().join_rule()
+    }
+
+    /// Set the rule to join this room.
+    pub async fn set_join_rule(&self, join_rule: JoinRule) -> MatrixResult<()> {
+        self.send_room_state_event(RoomJoinRulesEventContent::new(join_rule))
+            .await
+    }
+
+    /// Who can read the history of this room.
+    pub fn history_visibility(&self) -> HistoryVisibility {
+        self.matrix_room().history_visibility()
+    }
+
+    /// Set who can read the history of this room.
+    pub async fn set_history_visibility(
+        &self,
+        history_visibility: HistoryVisibility,
+    ) -> MatrixResult<()> {
+        self.send_room_state_event(RoomHistoryVisibilityEventContent::new(history_visibility))
+            .await
+    }
+
+    /// Whether guests can join this room.
+    pub fn guest_access(&self) -> GuestAccess {
+        self.matrix_room().guest_access()
+    }
+
+    /// Set whether guests can join this room.
+    pub async fn set_guest_access(&self, guest_access: GuestAccess) -> MatrixResult<()> {
+        self.send_room_state_event(RoomGuestAccessEventContent::new(guest_access))
+            .await
+    }
+
+    async fn send_room_state_event<C>(&self, content: C) -> MatrixResult<()>
+    where
+        C: StateEventContent<StateKey = EmptyStateKey> + Send + 'static,
+    {
+        let matrix_room = self.matrix_room();
+        let event_type = content.event_type();
+        let handle = spawn_tokio!(async move { matrix_room.send_state_event(content).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Failed to send {event_type} state event: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Set the power levels of this room.
+    pub async fn set_power_levels(&self, content: RoomPowerLevelsEventContent) -> MatrixResult<()> {
+        self.send_room_state_event(content).await
+    }
+
+    /// Set the power level of the member with the given user ID.
+    pub async fn set_member_power_level(
+        &self,
+        user_id: OwnedUserId,
+        power_level: PowerLevel,
+    ) -> MatrixResult<()> {
+        let mut content = self.power_levels().content();
+        let power_level = Int::new_saturating(power_level);
+
+        if power_level == content.users_default {
+            content.users.remove(&user_id);
+        } else {
+            content.users.insert(user_id, power_level);
+        }
+
+        self.set_power_levels(content).await
+    }
+
+    /// Kick the user with the given ID from this room.
+    pub async fn kick_user(
+        &self,
+        user_id: OwnedUserId,
+        reason: Option<String>,
+    ) -> MatrixResult<()> {
+        let matrix_room = self.matrix_room();
+        let handle =
+            spawn_tokio!(async move { matrix_room.kick_user(&user_id, reason.as_deref()).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Failed to kick user: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Ban the user with the given ID from this room.
+    pub async fn ban_user(&self, user_id: OwnedUserId, reason: Option<String>) -> MatrixResult<()> {
+        let matrix_room = self.matrix_room();
+        let handle =
+            spawn_tokio!(async move { matrix_room.ban_user(&user_id, reason.as_deref()).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Failed to ban user: {error}");
+                Err(error)
+            }
+        }
+    }
+
+    /// Unban the user with the given ID from this room.
+    pub async fn unban_user(
+        &self,
+        user_id: OwnedUserId,
+        reason: Option<String>,
+    ) -> MatrixResult<()> {
+        let client = self.session().client();
+        let mut request = unban_user::v3::Request::new(self.room_id().to_owned(), user_id);
+        request.reason = reason;
+
+        let handle = spawn_tokio!(async move { client.send(request, None).await });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Failed to unban user: {error}");
+                Err(error.into())
+            }
+        }
+    }
+
+    /// Redact the messages of the user with the given ID that are loaded in
+    /// the timeline of this room.
+    ///
+    /// Returns the number of events that will be redacted. Nothing is redacted
+    /// if our own user is not allowed to redact the events of other users.
+    pub fn redact_loaded_events_of(&self, user_id: &UserId, reason: Option<String>) -> usize {
+        let Some(own_user_id) = self.session().user().map(|user| user.user_id()) else {
+            return 0;
+        };
+        if user_id != own_user_id.as_ref()
+            && !self
+                .power_levels()
+                .member_is_allowed_to(&own_user_id, PowerLevelAction::Redact)
+        {
+            return 0;
+        }
+
+        let items = self.timeline().sdk_items();
+        let mut count = 0;
+
+        for pos in 0..items.n_items() {
+            let Some(event) = items.item(pos).and_downcast::<Event>() else {
+                continue;
+            };
+
+            if event.sender_id() != user_id
+                || !matches!(
+                    event.content(),
+                    TimelineItemContent::Message(_)
+                        | TimelineItemContent::Sticker(_)
+                        | TimelineItemContent::Poll(_)
+                        | TimelineItemContent::UnableToDecrypt(_)
+                )
+            {
+                continue;
+            }
+
+            if let Some(event_id) = event.event_id() {
+                self.redact(event_id, reason.clone());
+                count += 1;
+            }
+        }
+
+        count
+    }
+
+    /// Connect to the signal emitted when the join rule, the history
+    /// visibility or the guest access of this room changed.
+    pub fn connect_access_changed<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("access-changed", true, move |values| {
+            let obj = values[0].get::<Self>().unwrap();
+            f(&obj);
+            None
+        })
+    }
+
+    /// The draft of the composer for this room, if any.
+    pub fn draft(&self) -> Option<ComposerDraft> {
+        if self.is_encrypted() {
+            return self.imp().draft.borrow().clone();
+        }
+
+        self.session().settings().draft(self.room_id())
+    }
+
+    /// Set the draft of the composer for this room.
+    ///
+    /// Drafts of encrypted rooms are not persisted.
+    pub fn set_draft(&self, draft: Option<ComposerDraft>) {
+        let had_draft = self.has_draft();
+
+        if self.is_encrypted() {
+            self.imp()
+                .draft
+                .replace(draft.filter(|draft| !draft.is_empty()));
+        } else {
+            self.session().settings().set_draft(self.room_id(), draft);
+        }
+
+        if self.has_draft() != had_draft {
+            self.notify("has-draft");
+        }
+    }
+
+    /// Whether this room has a draft in the composer.
+    pub fn has_draft(&self) -> bool {
+        self.draft().is_some()
+    }
+
     /// Send a message with the given `content` in this room.
     pub fn send_room_message_event(&self, content: impl Into<AnyMessageLikeEventContent>) {
         let timeline = self.timeline().matrix_timeline();
@@ -1101,30 +1589,75 @@ impl Room {
     }
 
     /// Redact `redacted_event_id` in this room because of `reason`.
+    ///
+    /// The request is kept while the session is offline, and sent again if it
+    /// fails because of a connection error.
     pub fn redact(&self, redacted_event_id: OwnedEventId, reason: Option<String>) {
         let matrix_room = self.matrix_room();
         if matrix_room.state() != RoomState::Joined {
             return;
         };
 
-        let handle = spawn_tokio!(async move {
-            matrix_room
-                .redact(&redacted_event_id, reason.as_deref(), None)
-                .await
-        });
-
         spawn!(
             glib::Priority::DEFAULT_IDLE,
             clone!(@weak self as obj => async move {
-                // FIXME: We should retry the request if it fails
-                match handle.await.unwrap() {
-                    Ok(_) => {},
-                    Err(error) => error!("Couldn’t redact event: {error}"),
-                };
+                let mut attempts = 0;
+
+                loop {
+                    obj.wait_until_online().await;
+
+                    let matrix_room = matrix_room.clone();
+                    let redacted_event_id = redacted_event_id.clone();
+                    let reason = reason.clone();
+                    let handle = spawn_tokio!(async move {
+                        matrix_room
+                            .redact(&redacted_event_id, reason.as_deref(), None)
+                            .await
+                    });
+
+                    match handle.await.unwrap() {
+                        Ok(_) => break,
+                        Err(error) => {
+                            attempts += 1;
+
+                            // Only retry if the request did not reach the homeserver.
+                            if !matches!(error, HttpError::Reqwest(_))
+                                || attempts >= MAX_SEND_ATTEMPTS
+                            {
+                                error!("Couldn’t redact event: {error}");
+                                break;
+                            }
+
+                            warn!("Couldn’t redact event, retrying: {error}");
+                            glib::timeout_future(send_retry_delay(attempts)).await;
+                        }
+                    }
+                }
             })
         );
     }
 
+    /// Wait until the session of this room is online.
+    async fn wait_until_online(&self) {
+        let session = self.session();
+        if !session.is_offline() {
+            return;
+        }
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let sender = RefCell::new(Some(sender));
+        let handler_id = session.connect_notify_local(Some("offline"), move |session, _| {
+            if !session.is_offline() {
+                if let Some(sender) = sender.take() {
+                    let _ = sender.send(());
+                }
+            }
+        });
+
+        let _ = receiver.await;
+        session.disconnect(handler_id);
+    }
+
     pub fn send_typing_notification(&self, is_typing: bool) {
         let matrix_room = self.matrix_room();
         if matrix_room.state() != RoomState::Joined {
@@ -1361,6 +1894,9 @@ impl Room {
         false
     }
 
+    /// Send an attachment with the given data in this room.
+    ///
+    /// The attachment is presented in the timeline until it is sent.
     pub fn send_attachment(
         &self,
         bytes: Vec<u8>,
@@ -1368,43 +1904,15 @@ impl Room {
         body: &str,
         info: AttachmentInfo,
     ) {
-        let matrix_room = self.matrix_room();
-        if matrix_room.state() != RoomState::Joined {
+        if self.matrix_room().state() != RoomState::Joined {
             return;
         };
 
-        let body = body.to_string();
-        spawn_tokio!(async move {
-            // Needed to hold the thumbnail data until it is sent.
-            let data_slot;
-
-            // The method will filter compatible mime types so we don't need to
-            // since we ignore errors.
-            let thumbnail = match generate_image_thumbnail(&mime, Cursor::new(&bytes), None) {
-                Ok((data, info)) => {
-                    data_slot = data;
-                    Some(Thumbnail {
-                        data: data_slot,
-                        content_type: mime::IMAGE_JPEG,
-                        info: Some(info),
-                    })
-                }
-                _ => None,
-            };
-
-            let config = if let Some(thumbnail) = thumbnail {
-                AttachmentConfig::with_thumbnail(thumbnail)
-            } else {
-                AttachmentConfig::new()
-            }
-            .info(info);
-
-            matrix_room
-                // TODO This should be added to pending messages instead of
-                // sending it directly.
-                .send_attachment(&body, &mime, bytes, config)
-                .await
-                .unwrap();
+        self.timeline().send_attachment(AttachmentData {
+            bytes: bytes.into(),
+            mime,
+            body: body.to_owned(),
+            info,
         });
     }
 
@@ -1481,6 +1989,9 @@ impl Room {
     }
 
     /// Set whether this room is encrypted.
+    ///
+    /// This only updates the state from the one known by the SDK, use
+    /// `enable_encryption()` to enable encryption in the room.
     pub fn set_is_encrypted(&self, is_encrypted: bool) {
         let was_encrypted = self.is_encrypted();
         if was_encrypted == is_encrypted {
@@ -1492,15 +2003,27 @@ impl Room {
             return;
         }
 
-        // if self.matrix_room().is_encrypted() != is_encrypted {
-        // TODO: enable encryption if it isn't enabled yet
-        // }
-
         spawn!(clone!(@strong self as obj => async move {
             obj.setup_is_encrypted().await;
         }));
     }
 
+    /// Enable encryption in this room.
+    ///
+    /// Does nothing if encryption is already enabled.
+    pub async fn enable_encryption(&self) -> MatrixResult<()> {
+        let matrix_room = self.matrix_room();
+        let handle = spawn_tokio!(async move { matrix_room.enable_encryption().await });
+
+        if let Err(error) = handle.await.unwrap() {
+            error!("Failed to enable encryption: {error}");
+            return Err(error);
+        }
+
+        self.setup_is_encrypted().await;
+        Ok(())
+    }
+
     async fn setup_is_encrypted(&self) {
         let matrix_room = self.matrix_room();
         let handle = spawn_tokio!(async move { matrix_room.is_encrypted().await });
@@ -1515,7 +2038,15 @@ impl Room {
             return;
         }
 
-        self.imp().is_encrypted.set(true);
+        let imp = self.imp();
+
+        // Move the draft saved before the room was encrypted out of the
+        // settings.
+        let settings = self.session().settings();
+        imp.draft.replace(settings.draft(self.room_id()));
+        settings.set_draft(self.room_id(), None);
+
+        imp.is_encrypted.set(true);
         self.notify("encrypted");
     }
 
@@ -7,6 +7,8 @@ use ruma::{
     OwnedUserId, UserId,
 };
 
+use super::MemberRole;
+
 #[derive(Clone, Debug, Default, glib::Boxed)]
 #[boxed_type(name = "BoxedPowerLevelsEventContent")]
 pub struct BoxedPowerLevelsEventContent(RoomPowerLevelsEventContent);
@@ -73,6 +75,37 @@ impl PowerLevels {
         self.imp().content.borrow().clone()
     }
 
+    /// The content of the power levels event.
+    pub fn content(&self) -> RoomPowerLevelsEventContent {
+        self.imp().content.borrow().0.clone()
+    }
+
+    /// The power level of the member with the given user ID.
+    pub fn user_power_level(&self, user_id: &UserId) -> PowerLevel {
+        let content = self.imp().content.borrow().0.clone();
+        RoomPowerLevels::from(content).for_user(user_id).into()
+    }
+
+    /// Whether the member with the given user ID is the only admin of the
+    /// room.
+    ///
+    /// If this member loses their power level, nobody will be able to get it
+    /// back.
+    pub fn is_last_admin(&self, user_id: &UserId) -> bool {
+        let content = self.imp().content.borrow().0.clone();
+        let power_levels = RoomPowerLevels::from(content);
+        let power_level = power_levels.for_user(user_id);
+
+        if !MemberRole::from(PowerLevel::from(power_level)).is_admin() {
+            return false;
+        }
+
+        !power_levels
+            .users
+            .iter()
+            .any(|(other_id, other_level)| other_id != user_id && *other_level >= power_level)
+    }
+
     /// Returns whether the member with the given user ID is allowed to do the
     /// given action.
     pub fn member_is_allowed_to(&self, user_id: &UserId, room_action: PowerLevelAction) -> bool {
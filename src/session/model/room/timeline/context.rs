@@ -0,0 +1,331 @@
+use gtk::{gio, glib, prelude::*, subclass::prelude::*};
+use matrix_sdk::{room::MessagesOptions, Error as MatrixError};
+use ruma::{
+    api::{client::context::get_context, Direction},
+    assign,
+    events::{room::message::Relation, AnyTimelineEvent},
+    serde::Raw,
+    OwnedEventId, UInt,
+};
+use tracing::error;
+
+use super::{decrypt_events, original_room_message, Timeline, TimelineState};
+use crate::{session::model::SearchResult, spawn_tokio};
+
+/// The maximum number of events to request at once.
+const MAX_BATCH_SIZE: u32 = 20;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::object::WeakRef;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct TimelineContext {
+        /// The timeline containing the focused event.
+        pub timeline: WeakRef<Timeline>,
+        /// The ID of the focused event.
+        pub event_id: OnceCell<OwnedEventId>,
+        /// The events of this context, sorted chronologically.
+        pub items: gio::ListStore,
+        /// The token to load older events from the homeserver.
+        pub start_token: RefCell<Option<String>>,
+        /// The token to load newer events from the homeserver.
+        pub end_token: RefCell<Option<String>>,
+        /// The number of events presented before the focused event.
+        pub focus_position: Cell<u32>,
+        pub state: Cell<TimelineState>,
+    }
+
+    impl Default for TimelineContext {
+        fn default() -> Self {
+            Self {
+                timeline: Default::default(),
+                event_id: Default::default(),
+                items: gio::ListStore::new::<SearchResult>(),
+                start_token: Default::default(),
+                end_token: Default::default(),
+                focus_position: Default::default(),
+                state: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for TimelineContext {
+        const NAME: &'static str = "TimelineContext";
+        type Type = super::TimelineContext;
+    }
+
+    impl ObjectImpl for TimelineContext {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<gio::ListModel>("items")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<TimelineState>("state")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "items" => obj.items().to_value(),
+                "state" => obj.state().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The events around an event that is not loaded in the live timeline.
+    ///
+    /// The events are fetched from the homeserver with `/context`, then
+    /// `/messages` to load more events in both directions. They are presented
+    /// as `SearchResult`s.
+    pub struct TimelineContext(ObjectSubclass<imp::TimelineContext>);
+}
+
+impl TimelineContext {
+    /// Create the context of the event with the given ID in the given
+    /// timeline.
+    ///
+    /// The context is empty until `load_context()` is called.
+    fn new(timeline: &Timeline, event_id: OwnedEventId) -> Self {
+        let obj = glib::Object::new::<Self>();
+        let imp = obj.imp();
+
+        imp.timeline.set(Some(timeline));
+        imp.event_id.set(event_id).unwrap();
+
+        obj
+    }
+
+    /// Fetch the context of the event with the given ID in the given timeline.
+    pub(super) async fn fetch(
+        timeline: &Timeline,
+        event_id: OwnedEventId,
+    ) -> Result<Self, MatrixError> {
+        let obj = Self::new(timeline, event_id);
+        obj.load_context().await?;
+        Ok(obj)
+    }
+
+    /// The timeline containing the focused event.
+    pub fn timeline(&self) -> Option<Timeline> {
+        self.imp().timeline.upgrade()
+    }
+
+    /// The ID of the focused event.
+    pub fn event_id(&self) -> &OwnedEventId {
+        self.imp().event_id.get().unwrap()
+    }
+
+    /// The `GListModel` containing the events of this context, sorted
+    /// chronologically.
+    pub fn items(&self) -> &gio::ListModel {
+        self.imp().items.upcast_ref()
+    }
+
+    /// The state of the loading of the events of this context.
+    pub fn state(&self) -> TimelineState {
+        self.imp().state.get()
+    }
+
+    fn set_state(&self, state: TimelineState) {
+        if self.state() == state {
+            return;
+        }
+
+        self.imp().state.set(state);
+        self.notify("state");
+    }
+
+    /// Whether it's possible to load more events in the given direction with
+    /// the current state of the context.
+    pub fn can_load(&self, direction: Direction) -> bool {
+        let imp = self.imp();
+
+        let has_token = match direction {
+            Direction::Backward => imp.start_token.borrow().is_some(),
+            Direction::Forward => imp.end_token.borrow().is_some(),
+        };
+
+        has_token && matches!(self.state(), TimelineState::Ready | TimelineState::Error)
+    }
+
+    /// Fetch the focused event and the events around it with `/context`.
+    async fn load_context(&self) -> Result<(), MatrixError> {
+        let Some(timeline) = self.timeline() else {
+            return Ok(());
+        };
+        let imp = self.imp();
+
+        self.set_state(TimelineState::Loading);
+
+        let matrix_room = timeline.room().matrix_room();
+        let event_id = self.event_id().clone();
+
+        let handle = spawn_tokio!(async move {
+            let mut request =
+                get_context::v3::Request::new(matrix_room.room_id().to_owned(), event_id);
+            request.limit = UInt::from(MAX_BATCH_SIZE);
+
+            let response = matrix_room.client().send(request, None).await?;
+
+            // The events before are in reverse-chronological order.
+            let events_before =
+                decrypt_events(&matrix_room, response.events_before.into_iter().rev()).await;
+            let events_after = decrypt_events(
+                &matrix_room,
+                response.event.into_iter().chain(response.events_after),
+            )
+            .await;
+
+            Ok::<_, MatrixError>((events_before, events_after, response.start, response.end))
+        });
+
+        match handle.await.unwrap() {
+            Ok((events_before, events_after, start, end)) => {
+                let mut results = self.search_results(events_before);
+                imp.focus_position.set(results.len() as u32);
+                results.extend(self.search_results(events_after));
+                imp.items.splice(0, imp.items.n_items(), &results);
+
+                imp.start_token.replace(start);
+                imp.end_token.replace(end);
+
+                self.set_state(TimelineState::Ready);
+                Ok(())
+            }
+            Err(error) => {
+                self.set_state(TimelineState::Error);
+                Err(error)
+            }
+        }
+    }
+
+    /// Load the next batch of events in the given direction from the
+    /// homeserver.
+    pub async fn load(&self, direction: Direction) {
+        if !self.can_load(direction) {
+            return;
+        }
+        let Some(timeline) = self.timeline() else {
+            return;
+        };
+        let imp = self.imp();
+
+        self.set_state(TimelineState::Loading);
+
+        let matrix_room = timeline.room().matrix_room();
+        let token = match direction {
+            Direction::Backward => imp.start_token.borrow().clone(),
+            Direction::Forward => imp.end_token.borrow().clone(),
+        };
+
+        let handle = spawn_tokio!(async move {
+            let options = match direction {
+                Direction::Backward => MessagesOptions::backward(),
+                Direction::Forward => MessagesOptions::forward(),
+            };
+            let options = assign!(options.from(token.as_deref()), {
+                limit: UInt::from(MAX_BATCH_SIZE),
+            });
+
+            matrix_room.messages(options).await
+        });
+
+        match handle.await.unwrap() {
+            Ok(messages) => {
+                // The SDK already decrypted the events.
+                let is_empty = messages.chunk.is_empty();
+                let events = messages.chunk.into_iter().map(|event| event.event);
+
+                match direction {
+                    Direction::Backward => {
+                        // The events are in reverse-chronological order.
+                        let results = self.search_results(events.rev());
+                        imp.items.splice(0, 0, &results);
+                        imp.focus_position
+                            .set(imp.focus_position.get() + results.len() as u32);
+                        imp.start_token.replace(messages.end);
+                    }
+                    Direction::Forward => {
+                        let results = self.search_results(events);
+                        imp.items.extend_from_slice(&results);
+
+                        // The homeserver might keep returning a token when we
+                        // reached the latest event.
+                        let end = messages.end.filter(|_| !is_empty);
+                        imp.end_token.replace(end);
+                    }
+                }
+
+                self.set_state(TimelineState::Ready);
+            }
+            Err(error) => {
+                error!("Failed to load context of event: {error}");
+                self.set_state(TimelineState::Error);
+            }
+        }
+    }
+
+    /// The position of the focused event in this context.
+    ///
+    /// If the focused event is not presented, this is the position of the
+    /// closest event that is presented.
+    pub fn focus_position(&self) -> u32 {
+        self.imp()
+            .focus_position
+            .get()
+            .min(self.items().n_items().saturating_sub(1))
+    }
+
+    /// Convert the given events to the `SearchResult`s that should be
+    /// presented.
+    fn search_results(
+        &self,
+        events: impl IntoIterator<Item = Raw<AnyTimelineEvent>>,
+    ) -> Vec<SearchResult> {
+        let Some(timeline) = self.timeline() else {
+            return Vec::new();
+        };
+        let room = timeline.room();
+        let session = room.session();
+        let ignored_users = session.ignored_users();
+
+        events
+            .into_iter()
+            .filter_map(|raw| original_room_message(&raw))
+            .filter(|event| {
+                // Edits and replies in threads are not presented in the main
+                // timeline.
+                !matches!(
+                    event.content.relates_to,
+                    Some(Relation::Replacement(_) | Relation::Thread(_))
+                ) && !ignored_users.contains(&event.sender)
+            })
+            .map(|event| {
+                SearchResult::new(
+                    &room,
+                    event.event_id,
+                    event.sender,
+                    event.content.msgtype.body().to_owned(),
+                    event.origin_server_ts,
+                )
+            })
+            .collect()
+    }
+}
@@ -1,31 +1,77 @@
+mod context;
+mod pending_attachment;
+mod thread;
 mod timeline_item;
 mod virtual_item;
 
-use std::{collections::HashMap, sync::Arc};
+use std::{cell::Cell, collections::HashMap, fmt, io::Cursor, sync::Arc};
 
 use eyeball_im::VectorDiff;
 use futures_util::StreamExt;
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
-use matrix_sdk::Error as MatrixError;
+use matrix_sdk::{
+    attachment::{generate_image_thumbnail, AttachmentConfig, Thumbnail},
+    room::{MessagesOptions, Receipts, Room as MatrixRoom},
+    Error as MatrixError,
+};
 use matrix_sdk_ui::timeline::{
-    BackPaginationStatus, PaginationOptions, RoomExt, Timeline as SdkTimeline,
-    TimelineItem as SdkTimelineItem,
+    BackPaginationStatus, Error as TimelineError, PaginationOptions, RoomExt,
+    Timeline as SdkTimeline, TimelineItem as SdkTimelineItem,
 };
 use ruma::{
+    api::{client::room::get_event_by_timestamp, Direction},
+    assign,
     events::{
-        room::message::{MessageType, Relation},
-        AnySyncMessageLikeEvent, AnySyncStateEvent, AnySyncTimelineEvent, SyncMessageLikeEvent,
+        fully_read::FullyReadEventContent,
+        room::{
+            encrypted::OriginalSyncRoomEncryptedEvent,
+            message::{MessageType, OriginalRoomMessageEvent, Relation},
+        },
+        AnyMessageLikeEvent, AnySyncMessageLikeEvent, AnySyncStateEvent, AnySyncTimelineEvent,
+        AnyTimelineEvent, MessageLikeEvent, MessageLikeEventType, SyncMessageLikeEvent,
     },
-    OwnedEventId,
+    serde::Raw,
+    uint, EventId, MilliSecondsSinceUnixEpoch, OwnedEventId,
 };
 use tracing::{error, warn};
 
 pub use self::{
+    context::TimelineContext,
+    pending_attachment::{AttachmentData, PendingAttachment},
+    thread::Thread,
     timeline_item::{TimelineItem, TimelineItemExt, TimelineItemImpl},
     virtual_item::{VirtualItem, VirtualItemKind},
 };
-use super::{Event, EventKey, Room};
-use crate::{prelude::*, spawn, spawn_tokio};
+use super::{send_retry_delay, Event, EventKey, Room, SendState, MAX_SEND_ATTEMPTS};
+use crate::{
+    prelude::*,
+    session::model::{IgnoredUsers, Session},
+    spawn, spawn_tokio,
+};
+
+/// An error that occurred when marking a `Timeline` as read.
+#[derive(Debug)]
+pub enum MarkAsReadError {
+    /// The latest event of the room is not known.
+    NoLatestEvent,
+    /// A request failed.
+    Request(MatrixError),
+}
+
+impl fmt::Display for MarkAsReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoLatestEvent => write!(f, "the latest event of the room is not known"),
+            Self::Request(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<MatrixError> for MarkAsReadError {
+    fn from(value: MatrixError) -> Self {
+        Self::Request(value)
+    }
+}
 
 #[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
 #[repr(u32)]
@@ -54,7 +100,7 @@ const MAX_BATCH_SIZE: u16 = 20;
 mod imp {
     use std::cell::{Cell, RefCell};
 
-    use glib::object::WeakRef;
+    use glib::{object::WeakRef, signal::SignalHandlerId};
     use once_cell::{sync::Lazy, unsync::OnceCell};
 
     use super::*;
@@ -68,38 +114,77 @@ mod imp {
         pub start_items: gio::ListStore,
         /// Items provided by the SDK timeline.
         pub sdk_items: gio::ListStore,
+        /// The attachments that were not sent yet.
+        pub pending_attachments: gio::ListStore,
         /// Items added at the end of the timeline.
         pub end_items: gio::ListStore,
         /// The `GListModel` containing all the timeline items.
-        pub items: gtk::FlattenListModel,
+        pub all_items: gtk::FlattenListModel,
+        /// The `GListModel` containing the items of the main timeline.
+        ///
+        /// This excludes the replies in threads and the events of ignored
+        /// users.
+        pub items: gtk::FilterListModel,
         /// A Hashmap linking `EventKey` to corresponding `Event`
         pub event_map: RefCell<HashMap<EventKey, Event>>,
+        /// The loaded replies of the threads in this timeline, by thread root
+        /// ID.
+        pub threads: RefCell<HashMap<OwnedEventId, Vec<Event>>>,
         pub state: Cell<TimelineState>,
+        /// The context of the event that is focused, if any.
+        ///
+        /// It is presented instead of the live timeline.
+        pub context: RefCell<Option<TimelineContext>>,
         /// Whether this timeline has a typing row.
         pub has_typing: Cell<bool>,
+        /// The number of times we tried to send the failed events again.
+        pub send_retry_attempts: Cell<u32>,
+        /// The source of the timeout to send the failed events again.
+        pub send_retry_timeout: RefCell<Option<glib::SourceId>>,
+        /// The list of ignored users of the session, with the handler for its
+        /// changes.
+        pub ignored_users_handler: RefCell<Option<(WeakRef<IgnoredUsers>, SignalHandlerId)>>,
+        /// The session, with the handler for the changes of its offline
+        /// state.
+        pub offline_handler: RefCell<Option<(WeakRef<Session>, SignalHandlerId)>>,
     }
 
     impl Default for Timeline {
         fn default() -> Self {
             let start_items = gio::ListStore::new::<TimelineItem>();
             let sdk_items = gio::ListStore::new::<TimelineItem>();
+            let pending_attachments = gio::ListStore::new::<PendingAttachment>();
             let end_items = gio::ListStore::new::<TimelineItem>();
 
             let model_list = gio::ListStore::new::<gio::ListModel>();
             model_list.append(&start_items);
             model_list.append(&sdk_items);
+            model_list.append(&pending_attachments);
             model_list.append(&end_items);
 
+            let all_items = gtk::FlattenListModel::new(Some(model_list));
+
+            // The filter is set when the room is set.
+            let items = gtk::FilterListModel::new(Some(all_items.clone()), None::<gtk::Filter>);
+
             Self {
                 room: Default::default(),
                 timeline: Default::default(),
                 start_items,
                 sdk_items,
+                pending_attachments,
                 end_items,
-                items: gtk::FlattenListModel::new(Some(model_list)),
+                all_items,
+                items,
                 event_map: Default::default(),
+                threads: Default::default(),
                 state: Default::default(),
+                context: Default::default(),
                 has_typing: Default::default(),
+                send_retry_attempts: Default::default(),
+                send_retry_timeout: Default::default(),
+                ignored_users_handler: Default::default(),
+                offline_handler: Default::default(),
             }
         }
     }
@@ -124,6 +209,9 @@ mod imp {
                     glib::ParamSpecEnum::builder::<TimelineState>("state")
                         .read_only()
                         .build(),
+                    glib::ParamSpecObject::builder::<TimelineContext>("context")
+                        .read_only()
+                        .build(),
                 ]
             });
 
@@ -145,9 +233,24 @@ mod imp {
                 "items" => obj.items().to_value(),
                 "empty" => obj.is_empty().to_value(),
                 "state" => obj.state().to_value(),
+                "context" => obj.context().to_value(),
                 _ => unimplemented!(),
             }
         }
+
+        fn dispose(&self) {
+            if let Some((ignored_users, handler_id)) = self.ignored_users_handler.take() {
+                if let Some(ignored_users) = ignored_users.upgrade() {
+                    ignored_users.disconnect(handler_id);
+                }
+            }
+
+            if let Some((session, handler_id)) = self.offline_handler.take() {
+                if let Some(session) = session.upgrade() {
+                    session.disconnect(handler_id);
+                }
+            }
+        }
     }
 }
 
@@ -165,7 +268,10 @@ impl Timeline {
         glib::Object::builder().property("room", room).build()
     }
 
-    /// The `GListModel` containing the timeline items.
+    /// The `GListModel` containing the items of the main timeline.
+    ///
+    /// The replies in threads are not part of it, use `thread()` to get
+    /// them. The events of ignored users are not part of it either.
     pub fn items(&self) -> &gio::ListModel {
         self.imp().items.upcast_ref()
     }
@@ -175,6 +281,14 @@ impl Timeline {
         self.imp().sdk_items.upcast_ref()
     }
 
+    /// The thread with the given root ID in this `Timeline`.
+    ///
+    /// The replies that are not loaded in this `Timeline` can be fetched with
+    /// `Thread::load()`.
+    pub fn thread(&self, root_id: OwnedEventId) -> Thread {
+        Thread::new(self, root_id)
+    }
+
     /// Update this `Timeline` with the given diff.
     fn update(&self, diff: VectorDiff<Arc<SdkTimelineItem>>) {
         let imp = self.imp();
@@ -254,6 +368,9 @@ impl Timeline {
             VectorDiff::Set { index, value } => {
                 let pos = index as u32;
                 let prev_item = sdk_items.item(pos).and_downcast::<TimelineItem>().unwrap();
+                let prev_thread_root_id = prev_item
+                    .downcast_ref::<Event>()
+                    .and_then(|event| event.thread_root_id());
 
                 let item = if !prev_item.try_update_with(&value) {
                     self.remove_item(&prev_item);
@@ -263,6 +380,25 @@ impl Timeline {
 
                     item
                 } else {
+                    if let Some(event) = prev_item.downcast_ref::<Event>() {
+                        // The event might have been decrypted, edited or redacted.
+                        self.index_event(event);
+
+                        if event.thread_root_id() != prev_thread_root_id {
+                            if let Some(root_id) = prev_thread_root_id {
+                                self.remove_thread_reply(&root_id, event);
+                            }
+                            self.add_thread_reply(event);
+
+                            // Let the filtered lists know that the item changed.
+                            sdk_items.items_changed(pos, 1, 1);
+                        }
+
+                        if event.send_state() == SendState::Failed {
+                            self.queue_send_retry();
+                        }
+                    }
+
                     prev_item
                 };
 
@@ -339,7 +475,14 @@ impl Timeline {
 
             let current_sender = current.event_sender_id();
 
-            if !current.can_hide_header() {
+            if current
+                .downcast_ref::<Event>()
+                .is_some_and(|event| event.is_thread_reply())
+            {
+                // Thread replies are not displayed next to the other items of the
+                // main timeline.
+                current.set_show_header(true);
+            } else if !current.can_hide_header() {
                 current.set_show_header(false);
                 previous_sender = None;
             } else if current_sender != previous_sender {
@@ -362,6 +505,15 @@ impl Timeline {
                 .borrow_mut()
                 .insert(event.key(), event.clone());
 
+            self.index_event(event);
+            self.add_thread_reply(event);
+            if let Some(replies) = event
+                .event_id()
+                .and_then(|event_id| self.imp().threads.borrow().get(&event_id).cloned())
+            {
+                event.update_thread_summary(&replies);
+            }
+
             // Keep track of the activity of the sender.
             if event.counts_as_unread() {
                 if let Some(members) = self.room().members() {
@@ -374,10 +526,70 @@ impl Timeline {
         item
     }
 
+    /// Add the given event to the local search index, if its room is
+    /// encrypted.
+    fn index_event(&self, event: &Event) {
+        let room = self.room();
+
+        if room.is_encrypted() {
+            room.session().search_index().index_event(event);
+        }
+    }
+
     /// Remove the given item from this `Timeline`.
     fn remove_item(&self, item: &TimelineItem) {
         if let Some(event) = item.downcast_ref::<Event>() {
             self.imp().event_map.borrow_mut().remove(&event.key());
+
+            if let Some(root_id) = event.thread_root_id() {
+                self.remove_thread_reply(&root_id, event);
+            }
+        }
+    }
+
+    /// Add the given event to the replies of its thread, if it is in a thread.
+    fn add_thread_reply(&self, event: &Event) {
+        let Some(root_id) = event.thread_root_id() else {
+            return;
+        };
+
+        let replies = {
+            let mut threads = self.imp().threads.borrow_mut();
+            let replies = threads.entry(root_id.clone()).or_default();
+            replies.push(event.clone());
+            replies.clone()
+        };
+
+        self.update_thread_summary(&root_id, &replies);
+    }
+
+    /// Remove the given event from the replies of the thread with the given
+    /// root ID.
+    fn remove_thread_reply(&self, root_id: &EventId, event: &Event) {
+        let replies = {
+            let mut threads = self.imp().threads.borrow_mut();
+            let Some(replies) = threads.get_mut(root_id) else {
+                return;
+            };
+
+            replies.retain(|reply| reply != event);
+            let replies = replies.clone();
+
+            if replies.is_empty() {
+                threads.remove(root_id);
+            }
+
+            replies
+        };
+
+        self.update_thread_summary(root_id, &replies);
+    }
+
+    /// Update the summary of the root of the thread with the given ID, if it is
+    /// loaded.
+    fn update_thread_summary(&self, root_id: &EventId, replies: &[Event]) {
+        if let Some(root) = self.event_by_key(&EventKey::EventId(root_id.to_owned())) {
+            root.update_thread_summary(replies);
         }
     }
 
@@ -395,6 +607,11 @@ impl Timeline {
 
     /// Load events at the start of the timeline.
     pub async fn load(&self) {
+        self.paginate_backwards(MAX_BATCH_SIZE).await;
+    }
+
+    /// Request at most `event_limit` events at the start of the timeline.
+    async fn paginate_backwards(&self, event_limit: u16) {
         if !self.can_load() {
             return;
         }
@@ -405,7 +622,7 @@ impl Timeline {
         let handle = spawn_tokio!(async move {
             matrix_timeline
                 .paginate_backwards(PaginationOptions::until_num_items(
-                    MAX_BATCH_SIZE,
+                    event_limit,
                     MAX_BATCH_SIZE,
                 ))
                 .await
@@ -417,11 +634,278 @@ impl Timeline {
         }
     }
 
+    /// Wait for the current batch of events to be loaded, if any.
+    pub async fn wait_for_loading(&self) {
+        if self.state() != TimelineState::Loading {
+            return;
+        }
+
+        let (sender, receiver) = futures_channel::oneshot::channel();
+        let sender = Cell::new(Some(sender));
+        let handler = self.connect_notify_local(Some("state"), move |obj, _| {
+            if obj.state() != TimelineState::Loading {
+                if let Some(sender) = sender.take() {
+                    let _ = sender.send(());
+                }
+            }
+        });
+
+        let _ = receiver.await;
+        self.disconnect(handler);
+    }
+
+    /// Fetch the context of the event with the given ID from the homeserver.
+    ///
+    /// Use `set_context()` to present it instead of the live timeline.
+    pub async fn fetch_context(
+        &self,
+        event_id: OwnedEventId,
+    ) -> Result<TimelineContext, MatrixError> {
+        TimelineContext::fetch(self, event_id).await
+    }
+
+    /// The context of the event that is focused, if any.
+    ///
+    /// It is presented instead of the live timeline.
+    pub fn context(&self) -> Option<TimelineContext> {
+        self.imp().context.borrow().clone()
+    }
+
+    /// Set the context of the event that is focused.
+    ///
+    /// Set it to `None` to go back to the live timeline.
+    pub fn set_context(&self, context: Option<TimelineContext>) {
+        if self.context() == context {
+            return;
+        }
+
+        self.imp().context.replace(context);
+        self.notify("context");
+    }
+
+    /// Fetch the ID of the first event sent after the given date in this
+    /// `Timeline`, with `/timestamp_to_event`.
+    pub async fn fetch_event_id_for_date(
+        &self,
+        timestamp: MilliSecondsSinceUnixEpoch,
+    ) -> Result<OwnedEventId, MatrixError> {
+        let matrix_room = self.room().matrix_room();
+        let handle = spawn_tokio!(async move {
+            let request = get_event_by_timestamp::v1::Request::new(
+                matrix_room.room_id().to_owned(),
+                timestamp,
+                Direction::Forward,
+            );
+            matrix_room.client().send(request, None).await
+        });
+
+        let response = handle.await.unwrap()?;
+        Ok(response.event_id)
+    }
+
     fn clear(&self) {
         let imp = self.imp();
 
         imp.sdk_items.remove_all();
         imp.event_map.take();
+        imp.threads.take();
+    }
+
+    /// The local echoes in this `Timeline` that could not be sent.
+    pub fn failed_events(&self) -> Vec<Event> {
+        self.imp()
+            .event_map
+            .borrow()
+            .values()
+            .filter(|event| event.send_state() == SendState::Failed)
+            .cloned()
+            .collect()
+    }
+
+    /// Queue a retry to send the failed events of this `Timeline`.
+    ///
+    /// The events are kept while the session is offline, and the delay between
+    /// retries increases with the number of attempts.
+    fn queue_send_retry(&self) {
+        let imp = self.imp();
+
+        if imp.send_retry_timeout.borrow().is_some() || self.room().session().is_offline() {
+            return;
+        }
+
+        let attempts = imp.send_retry_attempts.get();
+        if attempts >= MAX_SEND_ATTEMPTS {
+            return;
+        }
+
+        let source_id = glib::timeout_add_local_once(
+            send_retry_delay(attempts),
+            clone!(@weak self as obj => move || {
+                obj.imp().send_retry_timeout.take();
+                obj.retry_failed_events();
+            }),
+        );
+        imp.send_retry_timeout.replace(Some(source_id));
+    }
+
+    /// The attachments in this `Timeline` that could not be sent.
+    fn failed_attachments(&self) -> Vec<PendingAttachment> {
+        self.imp()
+            .pending_attachments
+            .iter::<PendingAttachment>()
+            .filter_map(Result::ok)
+            .filter(|attachment| attachment.send_state() == SendState::Failed)
+            .collect()
+    }
+
+    /// Try to send all the failed events of this `Timeline` again.
+    fn retry_failed_events(&self) {
+        let imp = self.imp();
+        let failed_events = self.failed_events();
+        let failed_attachments = self.failed_attachments();
+
+        if failed_events.is_empty() && failed_attachments.is_empty() {
+            imp.send_retry_attempts.set(0);
+            return;
+        }
+
+        imp.send_retry_attempts
+            .set(imp.send_retry_attempts.get() + 1);
+
+        for event in failed_events {
+            spawn!(clone!(@weak self as obj => async move {
+                if let Err(error) = obj.retry_send(&event).await {
+                    warn!("Failed to send event again: {error}");
+                }
+            }));
+        }
+
+        for attachment in failed_attachments {
+            spawn!(clone!(@weak self as obj => async move {
+                obj.send_pending_attachment(&attachment).await;
+            }));
+        }
+    }
+
+    /// Send an attachment with the given data in this `Timeline`.
+    ///
+    /// The attachment is presented in the timeline until it is sent. If it
+    /// fails, it can be sent again or cancelled, like the local echo of a
+    /// message.
+    pub fn send_attachment(&self, data: AttachmentData) {
+        let attachment = PendingAttachment::new(data);
+        self.imp().pending_attachments.append(&attachment);
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.send_pending_attachment(&attachment).await;
+        }));
+    }
+
+    /// Try to send the given pending attachment.
+    ///
+    /// It is removed from the timeline if it is sent successfully.
+    pub async fn send_pending_attachment(&self, attachment: &PendingAttachment) {
+        attachment.set_send_state(SendState::Sending);
+
+        let matrix_room = self.room().matrix_room();
+        let AttachmentData {
+            bytes,
+            mime,
+            body,
+            info,
+        } = attachment.data().duplicate();
+
+        let handle = spawn_tokio!(async move {
+            // Needed to hold the thumbnail data until it is sent.
+            let data_slot;
+
+            // The method will filter compatible mime types so we don't need to
+            // since we ignore errors.
+            let thumbnail = match generate_image_thumbnail(&mime, Cursor::new(&*bytes), None) {
+                Ok((data, info)) => {
+                    data_slot = data;
+                    Some(Thumbnail {
+                        data: data_slot,
+                        content_type: mime::IMAGE_JPEG,
+                        info: Some(info),
+                    })
+                }
+                _ => None,
+            };
+
+            let config = if let Some(thumbnail) = thumbnail {
+                AttachmentConfig::with_thumbnail(thumbnail)
+            } else {
+                AttachmentConfig::new()
+            }
+            .info(info);
+
+            matrix_room
+                .send_attachment(&body, &mime, bytes.to_vec(), config)
+                .await
+        });
+
+        match handle.await.unwrap() {
+            Ok(_) => {
+                self.remove_pending_attachment(attachment);
+            }
+            Err(error) => {
+                warn!("Couldn’t send attachment: {error}");
+                attachment.set_send_state(SendState::Failed);
+                self.queue_send_retry();
+            }
+        }
+    }
+
+    /// Cancel the sending of the given pending attachment.
+    ///
+    /// Returns `true` if the attachment was removed from the timeline.
+    pub fn cancel_pending_attachment(&self, attachment: &PendingAttachment) -> bool {
+        if attachment.send_state() != SendState::Failed {
+            return false;
+        }
+
+        self.remove_pending_attachment(attachment)
+    }
+
+    /// Remove the given pending attachment from this `Timeline`.
+    ///
+    /// Returns `true` if the attachment was found.
+    fn remove_pending_attachment(&self, attachment: &PendingAttachment) -> bool {
+        let pending_attachments = &self.imp().pending_attachments;
+
+        if let Some(pos) = pending_attachments.find(attachment) {
+            pending_attachments.remove(pos);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Try to send the given failed event again.
+    pub async fn retry_send(&self, event: &Event) -> Result<(), TimelineError> {
+        let Some(txn_id) = event.transaction_id() else {
+            return Ok(());
+        };
+        let matrix_timeline = self.matrix_timeline();
+
+        spawn_tokio!(async move { matrix_timeline.retry_send(&txn_id).await })
+            .await
+            .unwrap()
+    }
+
+    /// Cancel the sending of the given local echo.
+    ///
+    /// Returns `true` if the event was removed from the timeline.
+    pub async fn cancel_send(&self, event: &Event) -> bool {
+        let Some(txn_id) = event.transaction_id() else {
+            return false;
+        };
+        let matrix_timeline = self.matrix_timeline();
+
+        spawn_tokio!(async move { matrix_timeline.cancel_send(&txn_id).await })
+            .await
+            .unwrap()
     }
 
     /// Get the event with the given key from this `Timeline`.
@@ -449,6 +933,94 @@ impl Timeline {
         None
     }
 
+    /// Get the position of the read marker in this `Timeline`, if it is loaded.
+    pub fn find_read_marker_position(&self) -> Option<usize> {
+        self.items()
+            .iter::<glib::Object>()
+            .map_while(Result::ok)
+            .position(|item| {
+                item.downcast_ref::<VirtualItem>()
+                    .is_some_and(|item| item.kind() == VirtualItemKind::NewMessages)
+            })
+    }
+
+    /// The ID of the latest event of the room.
+    ///
+    /// This is the latest event received in a sync response. If none was
+    /// received since the session was opened, it is fetched from the
+    /// homeserver.
+    async fn latest_event_id(&self) -> Result<Option<OwnedEventId>, MatrixError> {
+        let room = self.room();
+
+        if let Some(event_id) = room.latest_event_id() {
+            return Ok(Some(event_id));
+        }
+
+        let matrix_room = room.matrix_room();
+        let handle = spawn_tokio!(async move {
+            let options = assign!(MessagesOptions::backward(), {
+                limit: uint!(1),
+            });
+            matrix_room.messages(options).await
+        });
+
+        let messages = handle.await.unwrap()?;
+        Ok(messages.chunk.into_iter().find_map(|event| {
+            event
+                .event
+                .get_field::<OwnedEventId>("event_id")
+                .ok()
+                .flatten()
+        }))
+    }
+
+    /// Fetch the ID of the event that was fully read by the user, from the
+    /// `m.fully_read` account data of the room.
+    pub async fn fetch_fully_read_event_id(&self) -> Option<OwnedEventId> {
+        let matrix_room = self.room().matrix_room();
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .account_data_static::<FullyReadEventContent>()
+                .await
+        });
+
+        match handle.await.unwrap() {
+            Ok(raw) => raw
+                .and_then(|raw| raw.deserialize().ok())
+                .map(|event| event.content.event_id),
+            Err(error) => {
+                warn!("Could not fetch fully-read marker: {error}");
+                None
+            }
+        }
+    }
+
+    /// Mark all the events of this `Timeline` as read.
+    ///
+    /// This sends a read receipt and moves the fully-read marker to the latest
+    /// event of the room, even if it is not loaded in this `Timeline`.
+    pub async fn mark_as_read(&self) -> Result<(), MarkAsReadError> {
+        let Some(event_id) = self.latest_event_id().await? else {
+            return Err(MarkAsReadError::NoLatestEvent);
+        };
+        let matrix_room = self.room().matrix_room();
+
+        // Send the receipts with the room rather than the SDK timeline,
+        // because the latest event might not be loaded in the timeline.
+        let handle = spawn_tokio!(async move {
+            matrix_room
+                .send_multiple_receipts(
+                    Receipts::new()
+                        .fully_read_marker(event_id.clone())
+                        .public_read_receipt(event_id),
+                )
+                .await
+        });
+
+        handle.await.unwrap()?;
+        Ok(())
+    }
+
     /// Fetch the event with the given id.
     ///
     /// If the event can't be found locally, a request will be made to the
@@ -484,6 +1056,38 @@ impl Timeline {
         self.imp().room.set(room.as_ref());
 
         if let Some(room) = room {
+            let ignored_users = room.session().ignored_users().clone();
+            let main_timeline_filter = gtk::CustomFilter::new(
+                clone!(@weak ignored_users => @default-return true, move |obj| {
+                    obj.downcast_ref::<Event>().map_or(true, |event| {
+                        !event.is_thread_reply() && !ignored_users.contains(&event.sender_id())
+                    })
+                }),
+            );
+            let ignored_users_handler =
+                ignored_users.connect_changed(clone!(@weak main_timeline_filter => move |_| {
+                    main_timeline_filter.changed(gtk::FilterChange::Different);
+                }));
+            self.imp()
+                .ignored_users_handler
+                .replace(Some((ignored_users.downgrade(), ignored_users_handler)));
+            self.imp().items.set_filter(Some(&main_timeline_filter));
+
+            let session = room.session();
+            let offline_handler = session.connect_notify_local(
+                Some("offline"),
+                clone!(@weak self as obj => move |session, _| {
+                    if !session.is_offline() {
+                        // Start over with the failed events.
+                        obj.imp().send_retry_attempts.set(0);
+                        obj.queue_send_retry();
+                    }
+                }),
+            );
+            self.imp()
+                .offline_handler
+                .replace(Some((session.downgrade(), offline_handler)));
+
             room.typing_list().connect_items_changed(
                 clone!(@weak self as obj => move |list, _, _, _| {
                     if !list.is_empty() {
@@ -537,6 +1141,9 @@ impl Timeline {
                                 )
                             }
                             AnySyncMessageLikeEvent::Sticker(SyncMessageLikeEvent::Original(_))
+                            | AnySyncMessageLikeEvent::UnstablePollStart(
+                                SyncMessageLikeEvent::Original(_),
+                            )
                             | AnySyncMessageLikeEvent::RoomEncrypted(
                                 SyncMessageLikeEvent::Original(_),
                             ) => true,
@@ -552,6 +1159,7 @@ impl Timeline {
                         ),
                     })
                     .add_failed_to_parse(false)
+                    .track_read_marker_and_receipts()
                     .build()
                     .await,
             )
@@ -716,3 +1324,46 @@ impl Timeline {
         None
     }
 }
+
+/// Decrypt the given events fetched from the homeserver, if needed.
+///
+/// The events that could not be decrypted are dropped.
+async fn decrypt_events(
+    matrix_room: &MatrixRoom,
+    events: impl IntoIterator<Item = Raw<AnyTimelineEvent>>,
+) -> Vec<Raw<AnyTimelineEvent>> {
+    let mut decrypted_events = Vec::new();
+
+    for raw in events {
+        let is_encrypted = raw
+            .get_field::<MessageLikeEventType>("type")
+            .ok()
+            .flatten()
+            .is_some_and(|event_type| event_type == MessageLikeEventType::RoomEncrypted);
+
+        if is_encrypted {
+            match matrix_room
+                .decrypt_event(raw.cast_ref::<OriginalSyncRoomEncryptedEvent>())
+                .await
+            {
+                Ok(event) => decrypted_events.push(event.event),
+                Err(error) => error!("Could not decrypt event: {error}"),
+            }
+        } else {
+            decrypted_events.push(raw);
+        }
+    }
+
+    decrypted_events
+}
+
+/// The given event fetched from the homeserver, if it is an original room
+/// message.
+fn original_room_message(raw: &Raw<AnyTimelineEvent>) -> Option<OriginalRoomMessageEvent> {
+    match raw.deserialize().ok()? {
+        AnyTimelineEvent::MessageLike(AnyMessageLikeEvent::RoomMessage(
+            MessageLikeEvent::Original(event),
+        )) => Some(event),
+        _ => None,
+    }
+}
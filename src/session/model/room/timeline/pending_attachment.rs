@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use gtk::{glib, prelude::*, subclass::prelude::*};
+use matrix_sdk::attachment::AttachmentInfo;
+use ruma::{OwnedTransactionId, TransactionId};
+
+use super::{TimelineItem, TimelineItemImpl};
+use crate::session::model::SendState;
+
+/// The data of an attachment that is not sent yet.
+#[derive(Debug)]
+pub struct AttachmentData {
+    /// The content of the file.
+    ///
+    /// It is shared between the attempts to send the attachment.
+    pub bytes: Arc<[u8]>,
+    /// The MIME type of the file.
+    pub mime: mime::Mime,
+    /// The body of the message, usually the filename.
+    pub body: String,
+    /// The metadata of the file.
+    pub info: AttachmentInfo,
+}
+
+impl AttachmentData {
+    /// Copy this data, to be able to send it again if sending it fails.
+    ///
+    /// The content of the file is not copied.
+    pub fn duplicate(&self) -> Self {
+        let info = match &self.info {
+            AttachmentInfo::Image(info) => AttachmentInfo::Image(info.clone()),
+            AttachmentInfo::Video(info) => AttachmentInfo::Video(info.clone()),
+            AttachmentInfo::Audio(info) => AttachmentInfo::Audio(info.clone()),
+            AttachmentInfo::File(info) => AttachmentInfo::File(info.clone()),
+            AttachmentInfo::Voice {
+                audio_info,
+                waveform,
+            } => AttachmentInfo::Voice {
+                audio_info: audio_info.clone(),
+                waveform: waveform.clone(),
+            },
+        };
+
+        Self {
+            bytes: self.bytes.clone(),
+            mime: self.mime.clone(),
+            body: self.body.clone(),
+            info,
+        }
+    }
+}
+
+mod imp {
+    use std::cell::Cell;
+
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct PendingAttachment {
+        /// The local ID of this attachment.
+        pub txn_id: OnceCell<OwnedTransactionId>,
+        /// The data of this attachment.
+        pub data: OnceCell<AttachmentData>,
+        /// The state of the sending of this attachment.
+        pub send_state: Cell<SendState>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PendingAttachment {
+        const NAME: &'static str = "TimelinePendingAttachment";
+        type Type = super::PendingAttachment;
+        type ParentType = TimelineItem;
+    }
+
+    impl ObjectImpl for PendingAttachment {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecString::builder("body").read_only().build(),
+                    glib::ParamSpecEnum::builder::<SendState>("send-state")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "body" => obj.body().to_value(),
+                "send-state" => obj.send_state().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl TimelineItemImpl for PendingAttachment {
+        fn id(&self) -> String {
+            format!("PendingAttachment::{}", self.obj().txn_id())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// An attachment that was not sent yet.
+    ///
+    /// It is presented in the timeline until it is sent, like the local echo
+    /// of a message.
+    pub struct PendingAttachment(ObjectSubclass<imp::PendingAttachment>) @extends TimelineItem;
+}
+
+impl PendingAttachment {
+    /// Create a new `PendingAttachment` with the given data.
+    pub fn new(data: AttachmentData) -> Self {
+        let obj = glib::Object::new::<Self>();
+        let imp = obj.imp();
+
+        imp.txn_id.set(TransactionId::new()).unwrap();
+        imp.data.set(data).unwrap();
+        imp.send_state.set(SendState::Sending);
+
+        obj
+    }
+
+    /// The local ID of this attachment.
+    pub fn txn_id(&self) -> &TransactionId {
+        self.imp().txn_id.get().unwrap()
+    }
+
+    /// The data of this attachment.
+    pub fn data(&self) -> &AttachmentData {
+        self.imp().data.get().unwrap()
+    }
+
+    /// The body of the message of this attachment.
+    pub fn body(&self) -> &str {
+        &self.data().body
+    }
+
+    /// The state of the sending of this attachment.
+    pub fn send_state(&self) -> SendState {
+        self.imp().send_state.get()
+    }
+
+    /// Set the state of the sending of this attachment.
+    pub fn set_send_state(&self, send_state: SendState) {
+        if self.send_state() == send_state {
+            return;
+        }
+
+        self.imp().send_state.set(send_state);
+        self.notify("send-state");
+    }
+}
@@ -0,0 +1,306 @@
+use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{
+    api::client::relations::get_relating_events_with_rel_type,
+    events::{relation::RelationType, AnyTimelineEvent},
+    serde::Raw,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, UInt,
+};
+use tracing::error;
+
+use super::{decrypt_events, original_room_message, Timeline, TimelineState};
+use crate::{
+    session::model::{Event, EventKey, IgnoredUsers, SearchResult},
+    spawn_tokio,
+};
+
+/// The maximum number of replies to request at once.
+const MAX_BATCH_SIZE: u32 = 20;
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{object::WeakRef, signal::SignalHandlerId};
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug)]
+    pub struct Thread {
+        /// The timeline containing the root of this thread.
+        pub timeline: WeakRef<Timeline>,
+        /// The ID of the root of this thread.
+        pub root_id: OnceCell<OwnedEventId>,
+        /// The root and the replies of this thread that are loaded in the
+        /// timeline.
+        pub loaded_items: gtk::FilterListModel,
+        /// The replies of this thread that were fetched from the homeserver
+        /// but are not loaded in the timeline.
+        pub remote_replies: gio::ListStore,
+        /// The `GListModel` containing all the items of this thread, sorted
+        /// chronologically.
+        pub items: gtk::SortListModel,
+        /// The token to load the next batch of replies from the homeserver.
+        pub next_batch: RefCell<Option<String>>,
+        pub state: Cell<TimelineState>,
+        /// The list of ignored users of the session, with the handler for its
+        /// changes.
+        pub ignored_users_handler: RefCell<Option<(WeakRef<IgnoredUsers>, SignalHandlerId)>>,
+    }
+
+    impl Default for Thread {
+        fn default() -> Self {
+            // The filter is set when the root ID is set.
+            let loaded_items =
+                gtk::FilterListModel::new(None::<gio::ListModel>, None::<gtk::Filter>);
+            let remote_replies = gio::ListStore::new::<SearchResult>();
+
+            let model_list = gio::ListStore::new::<gio::ListModel>();
+            model_list.append(&remote_replies);
+            model_list.append(&loaded_items);
+
+            let sorter =
+                gtk::CustomSorter::new(|a, b| item_timestamp(a).cmp(&item_timestamp(b)).into());
+            let items = gtk::SortListModel::new(
+                Some(gtk::FlattenListModel::new(Some(model_list))),
+                Some(sorter),
+            );
+
+            Self {
+                timeline: Default::default(),
+                root_id: Default::default(),
+                loaded_items,
+                remote_replies,
+                items,
+                next_batch: Default::default(),
+                state: Default::default(),
+                ignored_users_handler: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Thread {
+        const NAME: &'static str = "TimelineThread";
+        type Type = super::Thread;
+    }
+
+    impl ObjectImpl for Thread {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<gio::ListModel>("items")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<TimelineState>("state")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "items" => obj.items().to_value(),
+                "state" => obj.state().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some((ignored_users, handler_id)) = self.ignored_users_handler.take() {
+                if let Some(ignored_users) = ignored_users.upgrade() {
+                    ignored_users.disconnect(handler_id);
+                }
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The root and the replies of a thread.
+    ///
+    /// The replies that are loaded in the timeline are presented as `Event`s.
+    /// The other replies are fetched from the homeserver with `/relations` and
+    /// are presented as `SearchResult`s.
+    pub struct Thread(ObjectSubclass<imp::Thread>);
+}
+
+impl Thread {
+    /// Create the thread with the given root ID in the given timeline.
+    pub fn new(timeline: &Timeline, root_id: OwnedEventId) -> Self {
+        let obj = glib::Object::new::<Self>();
+        let imp = obj.imp();
+
+        imp.timeline.set(Some(timeline));
+
+        let ignored_users = timeline.room().session().ignored_users().clone();
+        let filter = gtk::CustomFilter::new(
+            clone!(@strong root_id, @weak ignored_users => @default-return false, move |obj| {
+                obj.downcast_ref::<Event>().is_some_and(|event| {
+                    (event.thread_root_id().as_ref() == Some(&root_id)
+                        || event.event_id().as_ref() == Some(&root_id))
+                        && !ignored_users.contains(&event.sender_id())
+                })
+            }),
+        );
+        let ignored_users_handler =
+            ignored_users.connect_changed(clone!(@weak filter => move |_| {
+                filter.changed(gtk::FilterChange::Different);
+            }));
+        imp.ignored_users_handler
+            .replace(Some((ignored_users.downgrade(), ignored_users_handler)));
+        imp.loaded_items.set_filter(Some(&filter));
+        imp.loaded_items.set_model(Some(timeline.sdk_items()));
+
+        imp.root_id.set(root_id).unwrap();
+        imp.state.set(TimelineState::Ready);
+
+        obj
+    }
+
+    /// The timeline containing the root of this thread.
+    pub fn timeline(&self) -> Option<Timeline> {
+        self.imp().timeline.upgrade()
+    }
+
+    /// The ID of the root of this thread.
+    pub fn root_id(&self) -> &OwnedEventId {
+        self.imp().root_id.get().unwrap()
+    }
+
+    /// The `GListModel` containing the root and the replies of this thread,
+    /// sorted chronologically.
+    pub fn items(&self) -> &gio::ListModel {
+        self.imp().items.upcast_ref()
+    }
+
+    /// The state of the loading of the replies of this thread.
+    pub fn state(&self) -> TimelineState {
+        self.imp().state.get()
+    }
+
+    fn set_state(&self, state: TimelineState) {
+        if self.state() == state {
+            return;
+        }
+
+        self.imp().state.set(state);
+        self.notify("state");
+    }
+
+    /// Whether it's possible to load more replies with the current state of
+    /// the thread.
+    pub fn can_load(&self) -> bool {
+        matches!(self.state(), TimelineState::Ready | TimelineState::Error)
+    }
+
+    /// Load the next batch of older replies from the homeserver.
+    pub async fn load(&self) {
+        if !self.can_load() {
+            return;
+        }
+        let Some(timeline) = self.timeline() else {
+            return;
+        };
+        let imp = self.imp();
+
+        self.set_state(TimelineState::Loading);
+
+        let room = timeline.room();
+        let matrix_room = room.matrix_room();
+        let root_id = self.root_id().clone();
+        let next_batch = imp.next_batch.borrow().clone();
+
+        let handle = spawn_tokio!(async move {
+            let mut request = get_relating_events_with_rel_type::v1::Request::new(
+                matrix_room.room_id().to_owned(),
+                root_id,
+                RelationType::Thread,
+            );
+            request.from = next_batch;
+            request.limit = Some(UInt::from(MAX_BATCH_SIZE));
+
+            let response = matrix_room.client().send(request, None).await?;
+
+            // Decrypt the replies in encrypted rooms.
+            let events = decrypt_events(
+                &matrix_room,
+                response
+                    .chunk
+                    .into_iter()
+                    .map(Raw::cast::<AnyTimelineEvent>),
+            )
+            .await;
+
+            Ok::<_, matrix_sdk::Error>((events, response.next_batch))
+        });
+
+        match handle.await.unwrap() {
+            Ok((events, next_batch)) => {
+                self.add_remote_replies(events);
+
+                let has_more = next_batch.is_some();
+                imp.next_batch.replace(next_batch);
+
+                if has_more {
+                    self.set_state(TimelineState::Ready);
+                } else {
+                    self.set_state(TimelineState::Complete);
+                }
+            }
+            Err(error) => {
+                error!("Failed to load thread replies: {error}");
+                self.set_state(TimelineState::Error);
+            }
+        }
+    }
+
+    /// Add the given replies fetched from the homeserver, if they are not
+    /// loaded in the timeline.
+    fn add_remote_replies(&self, events: Vec<Raw<AnyTimelineEvent>>) {
+        let Some(timeline) = self.timeline() else {
+            return;
+        };
+        let room = timeline.room();
+        let session = room.session();
+        let ignored_users = session.ignored_users();
+
+        let replies = events
+            .iter()
+            .filter_map(original_room_message)
+            .filter_map(|event| {
+                if timeline
+                    .event_by_key(&EventKey::EventId(event.event_id.clone()))
+                    .is_some()
+                    || ignored_users.contains(&event.sender)
+                {
+                    return None;
+                }
+
+                Some(SearchResult::new(
+                    &room,
+                    event.event_id,
+                    event.sender,
+                    event.content.msgtype.body().to_owned(),
+                    event.origin_server_ts,
+                ))
+            })
+            .collect::<Vec<_>>();
+
+        self.imp().remote_replies.extend_from_slice(&replies);
+    }
+}
+
+/// The timestamp of the given item of a thread.
+fn item_timestamp(item: &glib::Object) -> Option<MilliSecondsSinceUnixEpoch> {
+    if let Some(event) = item.downcast_ref::<Event>() {
+        Some(event.origin_server_ts())
+    } else {
+        item.downcast_ref::<SearchResult>()
+            .map(|result| result.origin_server_ts())
+    }
+}
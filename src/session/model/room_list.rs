@@ -6,10 +6,13 @@ use std::{
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use indexmap::map::IndexMap;
 use matrix_sdk::{
-    ruma::{OwnedRoomId, OwnedRoomOrAliasId, OwnedServerName, RoomAliasId, RoomId, RoomOrAliasId},
+    ruma::{
+        api::client::knock::knock_room, OwnedRoomId, OwnedRoomOrAliasId, OwnedServerName,
+        RoomAliasId, RoomId, RoomOrAliasId,
+    },
     sync::Rooms as ResponseRooms,
 };
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::{
     gettext_f,
@@ -353,6 +356,40 @@ impl RoomList {
         }
     }
 
+    /// Knock on the room with the given identifier, to request an invite.
+    pub async fn knock_by_id_or_alias(
+        &self,
+        identifier: OwnedRoomOrAliasId,
+        reason: Option<String>,
+        via: Vec<OwnedServerName>,
+    ) -> Result<(), String> {
+        let client = self.session().client();
+        let identifier_clone = identifier.clone();
+
+        let handle = spawn_tokio!(async move {
+            let mut request = knock_room::v3::Request::new(identifier_clone);
+            request.reason = reason;
+            request.server_name = via;
+            client.send(request, None).await
+        });
+
+        match handle.await.unwrap() {
+            Ok(_) => Ok(()),
+            Err(error) => {
+                error!("Knocking on room {identifier} failed: {error}");
+
+                let error = gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this is a
+                    // variable name.
+                    "Failed to request an invite to room {room_name}. Try again later.",
+                    &[("room_name", identifier.as_str())],
+                );
+
+                Err(error)
+            }
+        }
+    }
+
     pub fn connect_pending_rooms_changed<F: Fn(&Self) + 'static>(
         &self,
         f: F,
@@ -372,6 +409,32 @@ impl RoomList {
             .filter(|room| room.is_joined())
     }
 
+    /// Get the joined room with the given identifier, if any.
+    ///
+    /// If the identifier is an alias that doesn't match the canonical alias of
+    /// a joined room, it is resolved by the homeserver.
+    pub async fn resolve_joined_room(&self, identifier: &RoomOrAliasId) -> Option<Room> {
+        if let Some(room) = self.joined_room(identifier.into()) {
+            return Some(room);
+        }
+
+        let RoomIdentifier::Alias(room_alias) = identifier.into() else {
+            return None;
+        };
+
+        let client = self.session().client();
+        let room_alias = room_alias.to_owned();
+        let handle = spawn_tokio!(async move { client.resolve_room_alias(&room_alias).await });
+
+        match handle.await.unwrap() {
+            Ok(response) => self.joined_room((&*response.room_id).into()),
+            Err(error) => {
+                warn!("Could not resolve room alias: {error}");
+                None
+            }
+        }
+    }
+
     /// Add a room that was tombstoned but for which we haven't joined the
     /// successor yet.
     pub fn add_tombstoned_room(&self, room_id: OwnedRoomId) {
@@ -0,0 +1,379 @@
+mod search_index;
+mod search_result;
+
+use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::{
+    api::client::{filter::RoomEventFilter, search::search_events::v3},
+    assign,
+    events::{AnyMessageLikeEvent, AnyTimelineEvent, MessageLikeEvent},
+    OwnedRoomId,
+};
+use tracing::error;
+
+pub use self::{search_index::SearchIndex, search_result::SearchResult};
+use super::{Room, Session};
+use crate::{spawn, spawn_tokio};
+
+/// The state of a message search.
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "MessageSearchState")]
+pub enum MessageSearchState {
+    /// No search was made.
+    #[default]
+    Initial,
+    /// Results are being loaded.
+    Loading,
+    /// More results can be loaded.
+    Ready,
+    /// An error occurred while loading the results.
+    Error,
+    /// All the results were loaded.
+    Complete,
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::object::WeakRef;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct MessageSearch {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The room to search in.
+        ///
+        /// If this is `None`, all the joined rooms are searched.
+        pub room: WeakRef<Room>,
+        /// The results of the current search, from the most recent to the
+        /// oldest.
+        pub list: RefCell<Vec<SearchResult>>,
+        /// The current search term.
+        pub term: RefCell<String>,
+        /// The rooms that are searched on the homeserver.
+        pub server_rooms: RefCell<Vec<OwnedRoomId>>,
+        /// The token to load the next batch of results from the homeserver.
+        pub next_batch: RefCell<Option<String>>,
+        /// The number of the current search.
+        ///
+        /// Used to ignore responses to outdated requests.
+        pub generation: Cell<u32>,
+        pub state: Cell<MessageSearchState>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageSearch {
+        const NAME: &'static str = "MessageSearch";
+        type Type = super::MessageSearch;
+        type Interfaces = (gio::ListModel,);
+    }
+
+    impl ObjectImpl for MessageSearch {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Session>("session")
+                        .construct_only()
+                        .build(),
+                    glib::ParamSpecObject::builder::<Room>("room")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecEnum::builder::<MessageSearchState>("state")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => self.session.set(value.get().ok().as_ref()),
+                "room" => obj.set_room(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                "room" => obj.room().to_value(),
+                "state" => obj.state().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl ListModelImpl for MessageSearch {
+        fn item_type(&self) -> glib::Type {
+            SearchResult::static_type()
+        }
+
+        fn n_items(&self) -> u32 {
+            self.list.borrow().len() as u32
+        }
+
+        fn item(&self, position: u32) -> Option<glib::Object> {
+            self.list
+                .borrow()
+                .get(position as usize)
+                .map(|o| o.clone().upcast())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// The results of a full-text search of messages.
+    ///
+    /// Unencrypted rooms are searched on the homeserver, while encrypted rooms
+    /// are searched in the local [`SearchIndex`] of the session.
+    pub struct MessageSearch(ObjectSubclass<imp::MessageSearch>)
+        @implements gio::ListModel;
+}
+
+impl MessageSearch {
+    pub fn new(session: &Session) -> Self {
+        glib::Object::builder().property("session", session).build()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// The room to search in.
+    ///
+    /// If this is `None`, all the joined rooms are searched.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// Set the room to search in.
+    pub fn set_room(&self, room: Option<Room>) {
+        if self.room() == room {
+            return;
+        }
+
+        self.imp().room.set(room.as_ref());
+        self.notify("room");
+
+        // Restart the current search in the new scope.
+        let term = self.imp().term.borrow().clone();
+        self.search(term);
+    }
+
+    /// The state of the search.
+    pub fn state(&self) -> MessageSearchState {
+        self.imp().state.get()
+    }
+
+    /// Set the state of the search.
+    fn set_state(&self, state: MessageSearchState) {
+        if self.state() == state {
+            return;
+        }
+
+        self.imp().state.set(state);
+        self.notify("state");
+    }
+
+    /// Whether more results can be loaded.
+    pub fn can_load_more(&self) -> bool {
+        self.state() == MessageSearchState::Ready
+    }
+
+    /// Search the messages matching the given term.
+    ///
+    /// This replaces the results of the previous search.
+    pub fn search(&self, term: String) {
+        let imp = self.imp();
+
+        imp.generation.set(imp.generation.get().wrapping_add(1));
+        imp.next_batch.take();
+        imp.server_rooms.take();
+
+        let removed = {
+            let mut list = imp.list.borrow_mut();
+            let len = list.len();
+            list.clear();
+            len
+        };
+        if removed > 0 {
+            self.items_changed(0, removed as u32, 0);
+        }
+
+        imp.term.replace(term.clone());
+
+        let Some(session) = self.session() else {
+            return;
+        };
+        if term.trim().is_empty() {
+            self.set_state(MessageSearchState::Initial);
+            return;
+        }
+
+        let rooms = if let Some(room) = self.room() {
+            vec![room]
+        } else {
+            session
+                .room_list()
+                .iter::<Room>()
+                .filter_map(Result::ok)
+                .filter(|room| room.is_joined())
+                .collect()
+        };
+        let (encrypted_rooms, server_rooms): (Vec<_>, Vec<_>) =
+            rooms.into_iter().partition(|room| room.is_encrypted());
+
+        // Encrypted rooms are searched locally.
+        let encrypted_room_ids = encrypted_rooms
+            .iter()
+            .map(|room| room.room_id().to_owned())
+            .collect::<Vec<_>>();
+        let local_results = session
+            .search_index()
+            .search(&encrypted_room_ids, &term)
+            .into_iter()
+            .filter_map(|message| {
+                let room = encrypted_rooms
+                    .iter()
+                    .find(|room| *room.room_id() == *message.room_id)?;
+                Some(SearchResult::new(
+                    room,
+                    message.event_id,
+                    message.sender_id,
+                    message.body,
+                    message.origin_server_ts,
+                ))
+            })
+            .collect::<Vec<_>>();
+        self.insert_results(local_results);
+
+        // Unencrypted rooms are searched on the homeserver.
+        if server_rooms.is_empty() {
+            self.set_state(MessageSearchState::Complete);
+        } else {
+            imp.server_rooms.replace(
+                server_rooms
+                    .iter()
+                    .map(|room| room.room_id().to_owned())
+                    .collect(),
+            );
+            self.set_state(MessageSearchState::Ready);
+            self.load_more();
+        }
+    }
+
+    /// Load the next batch of results from the homeserver.
+    pub fn load_more(&self) {
+        if !self.can_load_more() {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.load_more_inner().await;
+        }));
+    }
+
+    async fn load_more_inner(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let imp = self.imp();
+
+        self.set_state(MessageSearchState::Loading);
+
+        let generation = imp.generation.get();
+        let term = imp.term.borrow().clone();
+        let next_batch = imp.next_batch.borrow().clone();
+        let room_event_filter = assign!(RoomEventFilter::default(), {
+            rooms: Some(imp.server_rooms.borrow().clone()),
+        });
+        let criteria = assign!(v3::Criteria::new(term), {
+            filter: room_event_filter,
+            order_by: Some(v3::OrderBy::Recent),
+        });
+        let request = assign!(v3::Request::new(assign!(v3::Categories::new(), {
+            room_events: Some(criteria),
+        })), {
+            next_batch,
+        });
+
+        let client = session.client();
+        let handle = spawn_tokio!(async move { client.send(request, None).await });
+        let result = handle.await.unwrap();
+
+        if imp.generation.get() != generation {
+            // A new search was started in the meantime.
+            return;
+        }
+
+        match result {
+            Ok(response) => {
+                let room_events = response.search_categories.room_events;
+                let room_list = session.room_list();
+
+                let results = room_events
+                    .results
+                    .into_iter()
+                    .filter_map(|result| result.result?.deserialize().ok())
+                    .filter_map(|event| {
+                        let AnyTimelineEvent::MessageLike(AnyMessageLikeEvent::RoomMessage(
+                            MessageLikeEvent::Original(event),
+                        )) = event
+                        else {
+                            return None;
+                        };
+                        let room = room_list.get(&event.room_id)?;
+
+                        Some(SearchResult::new(
+                            &room,
+                            event.event_id,
+                            event.sender,
+                            event.content.msgtype.body().to_owned(),
+                            event.origin_server_ts,
+                        ))
+                    })
+                    .collect::<Vec<_>>();
+                self.insert_results(results);
+
+                let has_more = room_events.next_batch.is_some();
+                imp.next_batch.replace(room_events.next_batch);
+
+                if has_more {
+                    self.set_state(MessageSearchState::Ready);
+                } else {
+                    self.set_state(MessageSearchState::Complete);
+                }
+            }
+            Err(error) => {
+                error!("Failed to search messages: {error}");
+                self.set_state(MessageSearchState::Error);
+            }
+        }
+    }
+
+    /// Insert the given results in the list, keeping it sorted from the most
+    /// recent to the oldest.
+    fn insert_results(&self, results: Vec<SearchResult>) {
+        for result in results {
+            let pos = {
+                let mut list = self.imp().list.borrow_mut();
+                let ts = result.origin_server_ts();
+                let pos = list.partition_point(|other| other.origin_server_ts() >= ts);
+                list.insert(pos, result);
+                pos
+            };
+
+            self.items_changed(pos as u32, 0, 1);
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use indexmap::IndexMap;
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId, OwnedUserId, RoomId};
+
+use crate::session::model::Event;
+
+/// A message stored in the [`SearchIndex`].
+#[derive(Debug, Clone)]
+pub struct IndexedMessage {
+    /// The ID of the room of the message.
+    pub room_id: OwnedRoomId,
+    /// The ID of the event of the message.
+    pub event_id: OwnedEventId,
+    /// The ID of the sender of the message.
+    pub sender_id: OwnedUserId,
+    /// The body of the message.
+    pub body: String,
+    /// The lowercase body of the message, used for matching.
+    normalized_body: String,
+    /// The timestamp of the message.
+    pub origin_server_ts: MilliSecondsSinceUnixEpoch,
+}
+
+/// A local full-text index of the messages of encrypted rooms.
+///
+/// The homeserver cannot search the content of encrypted events, so the
+/// messages are indexed here after they have been decrypted by the timeline.
+///
+/// The index only lives in memory, so it only contains the messages that were
+/// loaded during this run of the application.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// The indexed messages, by room ID and event ID.
+    rooms: RefCell<HashMap<OwnedRoomId, IndexMap<OwnedEventId, IndexedMessage>>>,
+}
+
+impl SearchIndex {
+    /// Add the given event to the index or update it.
+    ///
+    /// If the event is not a message anymore, for example because it was
+    /// redacted, it is removed from the index.
+    pub fn index_event(&self, event: &Event) {
+        let Some(event_id) = event.event_id() else {
+            // Local echoes will be indexed when they are received from the server.
+            return;
+        };
+        let room_id = event.room().room_id().to_owned();
+
+        let Some(message) = event.message() else {
+            self.remove_event(&room_id, &event_id);
+            return;
+        };
+
+        let body = message.body().to_owned();
+        let indexed_message = IndexedMessage {
+            room_id: room_id.clone(),
+            event_id: event_id.clone(),
+            sender_id: event.sender_id(),
+            normalized_body: body.to_lowercase(),
+            body,
+            origin_server_ts: event.origin_server_ts(),
+        };
+
+        self.rooms
+            .borrow_mut()
+            .entry(room_id)
+            .or_default()
+            .insert(event_id, indexed_message);
+    }
+
+    /// Remove the event with the given ID from the index.
+    fn remove_event(&self, room_id: &RoomId, event_id: &OwnedEventId) {
+        if let Some(messages) = self.rooms.borrow_mut().get_mut(room_id) {
+            messages.shift_remove(event_id);
+        }
+    }
+
+    /// Search the messages matching the given term in the rooms with the given
+    /// IDs.
+    ///
+    /// A message matches if its body contains all the words of the term,
+    /// ignoring case. The results are sorted from the most recent to the
+    /// oldest.
+    pub fn search(&self, room_ids: &[OwnedRoomId], term: &str) -> Vec<IndexedMessage> {
+        let words = term
+            .split_whitespace()
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>();
+
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let rooms = self.rooms.borrow();
+        let mut results = room_ids
+            .iter()
+            .filter_map(|room_id| rooms.get(room_id))
+            .flat_map(|messages| messages.values())
+            .filter(|message| {
+                words
+                    .iter()
+                    .all(|word| message.normalized_body.contains(word.as_str()))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+
+        results.sort_by(|a, b| b.origin_server_ts.cmp(&a.origin_server_ts));
+        results
+    }
+}
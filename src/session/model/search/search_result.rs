@@ -0,0 +1,125 @@
+use gtk::{glib, prelude::*, subclass::prelude::*};
+use ruma::{MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedUserId};
+
+use crate::session::model::{EventKey, Member, Room};
+
+mod imp {
+    use glib::object::WeakRef;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct SearchResult {
+        /// The room containing the message.
+        pub room: WeakRef<Room>,
+        /// The ID of the event of the message.
+        pub event_id: OnceCell<OwnedEventId>,
+        /// The ID of the sender of the message.
+        pub sender_id: OnceCell<OwnedUserId>,
+        /// The body of the message.
+        pub body: OnceCell<String>,
+        /// The timestamp of the message.
+        pub origin_server_ts: OnceCell<MilliSecondsSinceUnixEpoch>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SearchResult {
+        const NAME: &'static str = "SearchResult";
+        type Type = super::SearchResult;
+    }
+
+    impl ObjectImpl for SearchResult {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Room>("room")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecString::builder("body").read_only().build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "room" => obj.room().to_value(),
+                "body" => obj.body().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A message matching a search term.
+    pub struct SearchResult(ObjectSubclass<imp::SearchResult>);
+}
+
+impl SearchResult {
+    pub fn new(
+        room: &Room,
+        event_id: OwnedEventId,
+        sender_id: OwnedUserId,
+        body: String,
+        origin_server_ts: MilliSecondsSinceUnixEpoch,
+    ) -> Self {
+        let obj = glib::Object::new::<Self>();
+        let imp = obj.imp();
+
+        imp.room.set(Some(room));
+        imp.event_id.set(event_id).unwrap();
+        imp.sender_id.set(sender_id).unwrap();
+        imp.body.set(body).unwrap();
+        imp.origin_server_ts.set(origin_server_ts).unwrap();
+
+        obj
+    }
+
+    /// The room containing the message.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// The ID of the event of the message.
+    pub fn event_id(&self) -> &OwnedEventId {
+        self.imp().event_id.get().unwrap()
+    }
+
+    /// The key of the event of the message.
+    pub fn event_key(&self) -> EventKey {
+        EventKey::EventId(self.event_id().clone())
+    }
+
+    /// The sender of the message.
+    pub fn sender(&self) -> Option<Member> {
+        let sender_id = self.imp().sender_id.get().unwrap();
+        self.room().map(|room| {
+            room.get_or_create_members()
+                .get_or_create(sender_id.clone())
+        })
+    }
+
+    /// The body of the message.
+    pub fn body(&self) -> &str {
+        self.imp().body.get().unwrap()
+    }
+
+    /// The timestamp of the message.
+    pub fn origin_server_ts(&self) -> MilliSecondsSinceUnixEpoch {
+        *self.imp().origin_server_ts.get().unwrap()
+    }
+
+    /// The local date and time of the message.
+    pub fn timestamp(&self) -> glib::DateTime {
+        let ts = self.origin_server_ts();
+
+        glib::DateTime::from_unix_utc(ts.as_secs().into())
+            .and_then(|t| t.to_local())
+            .unwrap()
+    }
+}
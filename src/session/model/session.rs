@@ -28,7 +28,8 @@ use tracing::{debug, error};
 use url::Url;
 
 use super::{
-    ItemList, Notifications, RoomList, SessionSettings, SidebarListModel, User, VerificationList,
+    CryptoIdentities, IgnoredUsers, ItemList, KeyBackup, Notifications, NotificationsSettings,
+    RoomList, SearchIndex, SessionSettings, SidebarListModel, User, VerificationList,
 };
 use crate::{
     prelude::*,
@@ -76,6 +77,16 @@ mod imp {
         pub offline: Cell<bool>,
         pub settings: OnceCell<SessionSettings>,
         pub notifications: Notifications,
+        /// The users ignored by our user.
+        pub ignored_users: IgnoredUsers,
+        /// The notifications settings of our user.
+        pub notifications_settings: NotificationsSettings,
+        /// The backup of the room keys of our user.
+        pub key_backup: KeyBackup,
+        /// The trust state of the encryption identities of the known users.
+        pub crypto_identities: CryptoIdentities,
+        /// The local index of the messages of encrypted rooms.
+        pub search_index: SearchIndex,
     }
 
     #[glib::object_subclass]
@@ -145,6 +156,10 @@ mod imp {
                 .unwrap();
 
             self.notifications.set_session(Some(&obj));
+            self.ignored_users.set_session(Some(&obj));
+            self.notifications_settings.set_session(Some(&obj));
+            self.key_backup.set_session(Some(&obj));
+            self.crypto_identities.set_session(Some(&obj));
 
             let monitor = gio::NetworkMonitor::default();
             let handler_id = monitor.connect_network_changed(clone!(@weak obj => move |_, _| {
@@ -236,6 +251,17 @@ impl Session {
         self.update_user_profile();
         self.update_offline().await;
 
+        // The ignored users must be known before loading invites.
+        self.ignored_users().load().await;
+        self.ignored_users().setup_event_handler();
+
+        // The push rules must be known before loading the rooms.
+        self.notifications_settings().load().await;
+        self.notifications_settings().setup_event_handler();
+
+        self.key_backup().setup_state_handlers();
+        self.crypto_identities().setup_update_handlers();
+
         self.room_list().load();
         self.setup_direct_room_handler();
         self.setup_room_encrypted_changes();
@@ -576,4 +602,29 @@ impl Session {
     pub fn notifications(&self) -> &Notifications {
         &self.imp().notifications
     }
+
+    /// The users ignored by our user.
+    pub fn ignored_users(&self) -> &IgnoredUsers {
+        &self.imp().ignored_users
+    }
+
+    /// The notifications settings of our user.
+    pub fn notifications_settings(&self) -> &NotificationsSettings {
+        &self.imp().notifications_settings
+    }
+
+    /// The backup of the room keys of our user.
+    pub fn key_backup(&self) -> &KeyBackup {
+        &self.imp().key_backup
+    }
+
+    /// The trust state of the encryption identities of the known users.
+    pub fn crypto_identities(&self) -> &CryptoIdentities {
+        &self.imp().crypto_identities
+    }
+
+    /// The local index of the messages of encrypted rooms.
+    pub fn search_index(&self) -> &SearchIndex {
+        &self.imp().search_index
+    }
 }
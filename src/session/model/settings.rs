@@ -1,5 +1,8 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use gtk::{glib, prelude::*, subclass::prelude::*};
 use indexmap::IndexMap;
+use ruma::{OwnedEventId, OwnedRoomId, OwnedUserId, RoomId};
 use serde::{Deserialize, Serialize};
 use tracing::error;
 
@@ -17,6 +20,19 @@ pub struct StoredSessionSettings {
         skip_serializing_if = "ruma::serde::is_true"
     )]
     notifications_enabled: bool,
+
+    /// The drafts of the composer, by room ID.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    drafts: BTreeMap<OwnedRoomId, ComposerDraft>,
+
+    /// The users whose identity was seen as verified.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    verified_users: BTreeSet<OwnedUserId>,
+
+    /// The verified users whose identity changed, and whose change was not
+    /// dismissed.
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    changed_identities: BTreeSet<OwnedUserId>,
 }
 
 impl Default for StoredSessionSettings {
@@ -24,10 +40,56 @@ impl Default for StoredSessionSettings {
         Self {
             explore_custom_servers: Default::default(),
             notifications_enabled: true,
+            drafts: Default::default(),
+            verified_users: Default::default(),
+            changed_identities: Default::default(),
         }
     }
 }
 
+/// A draft of a message in the composer of a room.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComposerDraft {
+    /// The content of the composer, split by mentions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunks: Vec<DraftChunk>,
+
+    /// The event the draft is related to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relation: Option<DraftRelation>,
+}
+
+impl ComposerDraft {
+    /// Whether this draft is empty.
+    pub fn is_empty(&self) -> bool {
+        self.relation.is_none()
+            && self.chunks.iter().all(|chunk| match chunk {
+                DraftChunk::Text(text) => text.trim().is_empty(),
+                DraftChunk::Mention { .. } => false,
+            })
+    }
+}
+
+/// A chunk of the content of a `ComposerDraft`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftChunk {
+    /// Some text.
+    Text(String),
+    /// A mention of a user or a room.
+    Mention { name: String, uri: String },
+}
+
+/// The relation of a `ComposerDraft` to another event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DraftRelation {
+    /// The draft is a reply to the event with the given ID.
+    Reply(OwnedEventId),
+    /// The draft is an edit of the event with the given ID.
+    Edit(OwnedEventId),
+}
+
 mod imp {
     use std::cell::RefCell;
 
@@ -196,6 +258,74 @@ impl SessionSettings {
         self.save();
         self.notify("notifications-enabled");
     }
+
+    /// The draft of the composer for the room with the given ID, if any.
+    pub fn draft(&self, room_id: &RoomId) -> Option<ComposerDraft> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .drafts
+            .get(room_id)
+            .cloned()
+    }
+
+    /// Set the draft of the composer for the room with the given ID.
+    ///
+    /// Empty drafts are removed.
+    pub fn set_draft(&self, room_id: &RoomId, draft: Option<ComposerDraft>) {
+        let draft = draft.filter(|draft| !draft.is_empty());
+
+        if self.draft(room_id) == draft {
+            return;
+        }
+
+        {
+            let drafts = &mut self.imp().stored_settings.borrow_mut().drafts;
+            if let Some(draft) = draft {
+                drafts.insert(room_id.to_owned(), draft);
+            } else {
+                drafts.remove(room_id);
+            }
+        }
+        self.save();
+    }
+
+    /// The users whose identity was seen as verified.
+    pub fn verified_users(&self) -> BTreeSet<OwnedUserId> {
+        self.imp().stored_settings.borrow().verified_users.clone()
+    }
+
+    /// The verified users whose identity changed, and whose change was not
+    /// dismissed.
+    pub fn changed_identities(&self) -> BTreeSet<OwnedUserId> {
+        self.imp()
+            .stored_settings
+            .borrow()
+            .changed_identities
+            .clone()
+    }
+
+    /// Set the users whose identity was seen as verified, and the ones whose
+    /// identity changed since then.
+    pub fn set_crypto_identities(
+        &self,
+        verified_users: BTreeSet<OwnedUserId>,
+        changed_identities: BTreeSet<OwnedUserId>,
+    ) {
+        {
+            let mut stored_settings = self.imp().stored_settings.borrow_mut();
+
+            if stored_settings.verified_users == verified_users
+                && stored_settings.changed_identities == changed_identities
+            {
+                return;
+            }
+
+            stored_settings.verified_users = verified_users;
+            stored_settings.changed_identities = changed_identities;
+        }
+        self.save();
+    }
 }
 
 /// Get map of session stored in the GSettings.
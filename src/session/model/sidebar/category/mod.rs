@@ -12,7 +12,7 @@ use self::category_filter::CategoryFilter;
 pub use self::category_type::CategoryType;
 use super::{SidebarItem, SidebarItemExt, SidebarItemImpl};
 use crate::{
-    session::model::{Room, RoomList, RoomType},
+    session::model::{Room, RoomType},
     utils::ExpressionListModel,
 };
 
@@ -157,7 +157,7 @@ impl Category {
         let type_ = self.type_();
 
         // Special case room lists so that they are sorted and in the right category
-        let model = if model.is::<RoomList>() {
+        let model = if model.item_type().is_a(Room::static_type()) {
             let room_category_type = Room::this_expression("category")
                 .chain_closure::<CategoryType>(closure!(
                     |_: Option<glib::Object>, room_type: RoomType| {
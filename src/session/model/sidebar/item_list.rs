@@ -1,7 +1,7 @@
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 
-use super::{Category, CategoryType, Entry, EntryType, SidebarItem, SidebarItemExt};
-use crate::session::model::{RoomList, VerificationList};
+use super::{Category, CategoryType, Entry, EntryType, SidebarItem, SidebarItemExt, SpaceFilter};
+use crate::session::model::{Room, RoomList, VerificationList};
 
 mod imp {
     use std::cell::Cell;
@@ -19,6 +19,10 @@ mod imp {
         ///
         /// Uses `RoomType::can_change_to` to find compatible categories.
         pub show_all_for_category: Cell<CategoryType>,
+        /// The filter of the rooms by space.
+        pub space_filter: SpaceFilter,
+        /// The list of spaces.
+        pub spaces: OnceCell<Category>,
     }
 
     #[glib::object_subclass]
@@ -41,6 +45,9 @@ mod imp {
                     glib::ParamSpecEnum::builder::<CategoryType>("show-all-for-category")
                         .explicit_notify()
                         .build(),
+                    glib::ParamSpecObject::builder::<Room>("space")
+                        .explicit_notify()
+                        .build(),
                 ]
             });
 
@@ -54,6 +61,7 @@ mod imp {
                 "room-list" => obj.set_room_list(value.get().unwrap()),
                 "verification-list" => obj.set_verification_list(value.get().unwrap()),
                 "show-all-for-category" => obj.set_show_all_for_category(value.get().unwrap()),
+                "space" => obj.set_space(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -65,6 +73,7 @@ mod imp {
                 "room-list" => obj.room_list().to_value(),
                 "verification-list" => obj.verification_list().to_value(),
                 "show-all-for-category" => obj.show_all_for_category().to_value(),
+                "space" => obj.space().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -76,18 +85,32 @@ mod imp {
             let room_list = obj.room_list();
             let verification_list = obj.verification_list();
 
+            // The rooms displayed in the categories, filtered by the current space.
+            let rooms =
+                gtk::FilterListModel::new(Some(room_list.clone()), Some(self.space_filter.clone()));
+
             let list: [SidebarItem; 9] = [
                 Entry::new(EntryType::Explore).upcast(),
                 Category::new(CategoryType::VerificationRequest, verification_list).upcast(),
-                Category::new(CategoryType::Invited, room_list).upcast(),
-                Category::new(CategoryType::Favorite, room_list).upcast(),
-                Category::new(CategoryType::Direct, room_list).upcast(),
-                Category::new(CategoryType::Normal, room_list).upcast(),
-                Category::new(CategoryType::LowPriority, room_list).upcast(),
-                Category::new(CategoryType::Left, room_list).upcast(),
+                Category::new(CategoryType::Invited, &rooms).upcast(),
+                Category::new(CategoryType::Favorite, &rooms).upcast(),
+                Category::new(CategoryType::Direct, &rooms).upcast(),
+                Category::new(CategoryType::Normal, &rooms).upcast(),
+                Category::new(CategoryType::LowPriority, &rooms).upcast(),
+                Category::new(CategoryType::Left, &rooms).upcast(),
                 Entry::new(EntryType::Forget).upcast(),
             ];
 
+            self.spaces
+                .set(Category::new(CategoryType::Space, room_list))
+                .unwrap();
+            self.space_filter.connect_notify_local(
+                Some("space"),
+                clone!(@weak obj => move |_, _| {
+                    obj.notify("space");
+                }),
+            );
+
             self.list.set(list.clone()).unwrap();
 
             for item in list.iter() {
@@ -172,6 +195,23 @@ impl ItemList {
         self.notify("show-all-for-category");
     }
 
+    /// The space to show the rooms of.
+    ///
+    /// If this is `None`, all the rooms are shown.
+    pub fn space(&self) -> Option<Room> {
+        self.imp().space_filter.space()
+    }
+
+    /// Set the space to show the rooms of.
+    pub fn set_space(&self, space: Option<Room>) {
+        self.imp().space_filter.set_space(space);
+    }
+
+    /// The list of joined spaces.
+    pub fn spaces(&self) -> &Category {
+        self.imp().spaces.get().unwrap()
+    }
+
     /// Set the list of rooms.
     fn set_room_list(&self, room_list: RoomList) {
         self.imp().room_list.set(room_list).unwrap();
@@ -4,6 +4,7 @@ mod item;
 mod item_list;
 mod list_model;
 mod selection;
+mod space_filter;
 
 pub use self::{
     category::{Category, CategoryType},
@@ -12,4 +13,5 @@ pub use self::{
     item_list::ItemList,
     list_model::SidebarListModel,
     selection::Selection,
+    space_filter::SpaceFilter,
 };
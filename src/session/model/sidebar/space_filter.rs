@@ -0,0 +1,174 @@
+use std::collections::HashSet;
+
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::OwnedRoomId;
+
+use crate::session::model::Room;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{object::WeakRef, signal::SignalHandlerId};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct SpaceFilter {
+        /// The space to filter.
+        pub space: WeakRef<Room>,
+        /// The IDs of the rooms in the space, including the rooms in its
+        /// subspaces.
+        pub room_ids: RefCell<HashSet<OwnedRoomId>>,
+        /// The spaces we are watching, with the handler of their
+        /// `space-children-changed` signal.
+        pub watched_spaces: RefCell<Vec<(WeakRef<Room>, SignalHandlerId)>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceFilter {
+        const NAME: &'static str = "SpaceFilter";
+        type Type = super::SpaceFilter;
+        type ParentType = gtk::Filter;
+    }
+
+    impl ObjectImpl for SpaceFilter {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("space")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "space" => self.obj().set_space(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "space" => self.obj().space().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.obj().disconnect_spaces();
+        }
+    }
+
+    impl FilterImpl for SpaceFilter {
+        fn strictness(&self) -> gtk::FilterMatch {
+            if self.space.upgrade().is_none() {
+                gtk::FilterMatch::All
+            } else {
+                gtk::FilterMatch::Some
+            }
+        }
+
+        fn match_(&self, item: &glib::Object) -> bool {
+            if self.space.upgrade().is_none() {
+                return true;
+            }
+
+            let Some(room) = item.downcast_ref::<Room>() else {
+                return true;
+            };
+
+            self.room_ids.borrow().contains(room.room_id())
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A filter keeping only the rooms that are in a space.
+    ///
+    /// The rooms in the subspaces of the space also match.
+    pub struct SpaceFilter(ObjectSubclass<imp::SpaceFilter>)
+        @extends gtk::Filter;
+}
+
+impl SpaceFilter {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The space to filter.
+    pub fn space(&self) -> Option<Room> {
+        self.imp().space.upgrade()
+    }
+
+    /// Set the space to filter.
+    ///
+    /// If this is `None`, all the rooms match.
+    pub fn set_space(&self, space: Option<Room>) {
+        if self.space() == space {
+            return;
+        }
+
+        self.imp().space.set(space.as_ref());
+        self.update_room_ids();
+        self.notify("space");
+    }
+
+    /// Update the list of rooms in the space.
+    fn update_room_ids(&self) {
+        self.disconnect_spaces();
+
+        let mut room_ids = HashSet::new();
+        let mut watched_spaces = Vec::new();
+
+        if let Some(space) = self.space() {
+            let room_list = space.session().room_list().clone();
+            let mut spaces_to_visit = vec![space];
+            let mut visited_spaces = HashSet::new();
+
+            while let Some(space) = spaces_to_visit.pop() {
+                if !visited_spaces.insert(space.room_id().to_owned()) {
+                    // Spaces can contain each other, avoid infinite loops.
+                    continue;
+                }
+
+                let handler_id =
+                    space.connect_space_children_changed(clone!(@weak self as obj => move |_| {
+                        obj.update_room_ids();
+                    }));
+                watched_spaces.push((space.downgrade(), handler_id));
+
+                for child_id in space.space_children() {
+                    if let Some(child) = room_list.get(&child_id).filter(|room| room.is_space()) {
+                        spaces_to_visit.push(child);
+                    }
+
+                    room_ids.insert(child_id);
+                }
+            }
+        }
+
+        let imp = self.imp();
+        imp.watched_spaces.replace(watched_spaces);
+        imp.room_ids.replace(room_ids);
+
+        self.changed(gtk::FilterChange::Different);
+    }
+
+    /// Disconnect the handlers from the watched spaces.
+    fn disconnect_spaces(&self) {
+        for (space, handler_id) in self.imp().watched_spaces.take() {
+            if let Some(space) = space.upgrade() {
+                space.disconnect(handler_id);
+            }
+        }
+    }
+}
+
+impl Default for SpaceFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
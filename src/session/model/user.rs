@@ -1,14 +1,20 @@
 use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
-    encryption::identities::UserIdentity,
-    ruma::{OwnedMxcUri, OwnedUserId, UserId},
+    encryption::identities::{Device as CryptoDevice, UserIdentity},
+    ruma::{
+        api::client::room::create_room,
+        assign,
+        events::{room::encryption::RoomEncryptionEventContent, InitialStateEvent},
+        OwnedMxcUri, OwnedUserId, UserId,
+    },
 };
 use tracing::error;
 
 use crate::{
     components::Pill,
     session::model::{
-        AvatarData, AvatarImage, AvatarUriSource, IdentityVerification, Session, VerificationState,
+        AvatarData, AvatarImage, AvatarUriSource, IdentityVerification, Room, Session,
+        VerificationState,
     },
     spawn, spawn_tokio,
 };
@@ -27,6 +33,7 @@ impl Default for UserActions {
 mod imp {
     use std::cell::{Cell, RefCell};
 
+    use glib::SignalHandlerId;
     use once_cell::{sync::Lazy, unsync::OnceCell};
 
     use super::*;
@@ -38,6 +45,7 @@ mod imp {
         pub session: OnceCell<Session>,
         pub avatar_data: OnceCell<AvatarData>,
         pub is_verified: Cell<bool>,
+        pub identity_handler: RefCell<Option<SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -65,6 +73,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("verified")
                         .read_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("identity-changed")
+                        .read_only()
+                        .build(),
                     glib::ParamSpecFlags::builder::<UserActions>("allowed-actions")
                         .read_only()
                         .build(),
@@ -104,6 +115,7 @@ mod imp {
                 "session" => obj.session().to_value(),
                 "avatar-data" => obj.avatar_data().to_value(),
                 "verified" => obj.is_verified().to_value(),
+                "identity-changed" => obj.identity_changed().to_value(),
                 "allowed-actions" => obj.allowed_actions().to_value(),
                 _ => unimplemented!(),
             }
@@ -121,7 +133,26 @@ mod imp {
                 .sync_create()
                 .build();
 
-            obj.init_is_verified();
+            let user_id = obj.user_id();
+            let identity_handler = obj.session().crypto_identities().connect_user_updated(
+                clone!(@weak obj => move |_, updated_user_id| {
+                    if updated_user_id == &*user_id {
+                        obj.update_is_verified();
+                        obj.notify("identity-changed");
+                    }
+                }),
+            );
+            self.identity_handler.replace(Some(identity_handler));
+
+            obj.update_is_verified();
+        }
+
+        fn dispose(&self) {
+            if let Some(handler) = self.identity_handler.take() {
+                if let Some(session) = self.session.get() {
+                    session.crypto_identities().disconnect(handler);
+                }
+            }
         }
     }
 }
@@ -153,15 +184,31 @@ impl User {
         }
     }
 
+    /// The encryption devices of this user.
+    pub async fn crypto_devices(&self) -> Vec<CryptoDevice> {
+        let encryption = self.session().client().encryption();
+        let user_id = self.user_id();
+        let handle = spawn_tokio!(async move { encryption.get_user_devices(&user_id).await });
+
+        match handle.await.unwrap() {
+            Ok(devices) => devices.devices().collect(),
+            Err(error) => {
+                error!("Failed to get the devices of the user: {error}");
+                Vec::new()
+            }
+        }
+    }
+
     pub async fn verify_identity(&self) -> IdentityVerification {
         let request = IdentityVerification::create(self.session(), Some(self)).await;
         self.session().verification_list().add(request.clone());
-        // FIXME: actually listen to room events to get updates for verification state
+        // Later changes of the identity are received from the crypto identities
+        // updates, but update the state as soon as possible.
         request.connect_notify_local(
             Some("state"),
             clone!(@weak self as obj => move |request,_| {
                 if request.state() == VerificationState::Completed {
-                    obj.init_is_verified();
+                    obj.update_is_verified();
                 }
             }),
         );
@@ -173,9 +220,55 @@ impl User {
         self.imp().is_verified.get()
     }
 
-    fn init_is_verified(&self) {
+    /// Whether the identity of this user changed since it was verified.
+    pub fn identity_changed(&self) -> bool {
+        self.session()
+            .crypto_identities()
+            .has_identity_changed(&self.user_id())
+    }
+
+    /// The existing direct chat with this user, if any.
+    pub fn direct_chat(&self) -> Option<Room> {
+        let session = self.session();
+        let matrix_room = session.client().get_dm_room(&self.user_id())?;
+        session.room_list().get(matrix_room.room_id())
+    }
+
+    /// Create a new direct chat with this user.
+    pub async fn create_direct_chat(&self) -> Result<Room, matrix_sdk::Error> {
+        let session = self.session();
+        let client = session.client();
+        let other_user = self.user_id();
+
+        let handle = spawn_tokio!(async move {
+            let request = assign!(create_room::v3::Request::new(),
+            {
+                is_direct: true,
+                invite: vec![other_user],
+                preset: Some(create_room::v3::RoomPreset::TrustedPrivateChat),
+                initial_state: vec![
+                   InitialStateEvent::new(RoomEncryptionEventContent::with_recommended_defaults()).to_raw_any(),
+                ],
+            });
+
+            client.create_room(request).await
+        });
+
+        let matrix_room = handle.await.unwrap()?;
+        let room = session
+            .room_list()
+            .get_wait(matrix_room.room_id())
+            .await
+            .expect("The newly created room was not found");
+        Ok(room)
+    }
+
+    fn update_is_verified(&self) {
         spawn!(clone!(@weak self as obj => async move {
             let is_verified = obj.crypto_identity().await.map_or(false, |i| i.is_verified());
+            obj.session()
+                .crypto_identities()
+                .set_user_verified(&obj.user_id(), is_verified);
 
             if is_verified == obj.is_verified() {
                 return;
@@ -3,7 +3,7 @@ use std::time::Duration;
 use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
     encryption::{
-        identities::RequestVerificationError,
+        identities::{Device as CryptoDevice, RequestVerificationError},
         verification::{
             CancelInfo, Emoji, QrVerificationData, SasVerification, Verification,
             VerificationRequest,
@@ -358,16 +358,7 @@ impl IdentityVerification {
 
             match handle.await.unwrap() {
                 Ok(request) => {
-                    let obj = glib::Object::builder()
-                        .property("state", State::RequestSend)
-                        .property("supported-methods", supported_methods)
-                        .property("flow-id", request.flow_id())
-                        .property("session", session)
-                        .property("user", user)
-                        .property("start-time", &glib::DateTime::now_local().unwrap())
-                        .build();
-
-                    return obj;
+                    return Self::for_sent_request(session, user, supported_methods, &request);
                 }
                 Err(error) => {
                     error!("Starting a verification failed: {error}");
@@ -380,6 +371,49 @@ impl IdentityVerification {
         Self::for_error(session, user, &glib::DateTime::now_local().unwrap())
     }
 
+    /// Creates and send a new verification request to the given device of our
+    /// own user.
+    pub async fn create_for_device(session: &Session, device: CryptoDevice) -> Self {
+        let user = session.user().unwrap();
+
+        let has_camera =
+            spawn_tokio!(async move { Camera::default().has_camera().await.unwrap_or_default() })
+                .await
+                .unwrap();
+        let supported_methods = SupportedMethods::with_camera(has_camera);
+
+        let handle = spawn_tokio!(async move {
+            device
+                .request_verification_with_methods(supported_methods.into())
+                .await
+        });
+
+        match handle.await.unwrap() {
+            Ok(request) => Self::for_sent_request(session, user, supported_methods, &request),
+            Err(error) => {
+                error!("Starting a device verification failed: {error}");
+                Self::for_error(session, user, &glib::DateTime::now_local().unwrap())
+            }
+        }
+    }
+
+    /// Create a new object tracking a verification request that we just sent.
+    fn for_sent_request(
+        session: &Session,
+        user: &User,
+        supported_methods: SupportedMethods,
+        request: &VerificationRequest,
+    ) -> Self {
+        glib::Object::builder()
+            .property("state", State::RequestSend)
+            .property("supported-methods", supported_methods)
+            .property("flow-id", request.flow_id())
+            .property("session", session)
+            .property("user", user)
+            .property("start-time", &glib::DateTime::now_local().unwrap())
+            .build()
+    }
+
     fn start_handler(&self) {
         let imp = self.imp();
 
@@ -9,7 +9,7 @@ use matrix_sdk::{
 
 use crate::{
     components::{AuthDialog, AuthError},
-    session::model::Session,
+    session::model::{IdentityVerification, Session},
 };
 
 mod imp {
@@ -178,4 +178,24 @@ impl Device {
             .get()
             .map_or(false, |device| device.is_verified())
     }
+
+    /// Whether this device can be verified.
+    ///
+    /// Only devices that support end-to-end encryption can be verified.
+    pub fn can_be_verified(&self) -> bool {
+        self.imp().crypto_device.get().is_some()
+    }
+
+    /// Start an interactive verification of this device.
+    ///
+    /// Returns `None` if this device cannot be verified.
+    pub async fn verify(&self) -> Option<IdentityVerification> {
+        let crypto_device = self.imp().crypto_device.get()?.clone();
+        let session = self.session();
+
+        let request = IdentityVerification::create_for_device(&session, crypto_device).await;
+        session.verification_list().add(request.clone());
+
+        Some(request)
+    }
 }
@@ -2,12 +2,20 @@ use gettextrs::gettext;
 use gtk::{gio, glib, glib::clone, prelude::*, subclass::prelude::*};
 use matrix_sdk::{
     encryption::identities::UserDevices as CryptoDevices,
-    ruma::api::client::device::Device as MatrixDevice, Error,
+    ruma::{
+        api::client::device::{delete_devices, Device as MatrixDevice},
+        assign,
+    },
+    Error,
 };
 use tracing::error;
 
-use super::{Device, DeviceItem};
-use crate::{session::model::Session, spawn, spawn_tokio};
+use super::{device_item::ItemType, Device, DeviceItem};
+use crate::{
+    components::{AuthDialog, AuthError},
+    session::model::Session,
+    spawn, spawn_tokio,
+};
 
 mod imp {
     use std::cell::{Cell, RefCell};
@@ -223,4 +231,45 @@ impl DeviceList {
             obj.finish_loading(handle.await.unwrap());
         }));
     }
+
+    /// The other devices of the user that are not verified.
+    pub fn unverified_devices(&self) -> Vec<Device> {
+        self.imp()
+            .list
+            .borrow()
+            .iter()
+            .filter_map(|item| match item.type_() {
+                ItemType::Device(device) if !device.is_verified() => Some(device.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Delete the given devices and reload the list.
+    pub async fn delete_devices(
+        &self,
+        devices: &[Device],
+        transient_for: Option<&impl IsA<gtk::Window>>,
+    ) -> Result<(), AuthError> {
+        let session = self.session();
+        let device_ids = devices
+            .iter()
+            .map(|device| device.device_id().to_owned())
+            .collect::<Vec<_>>();
+
+        let dialog = AuthDialog::new(transient_for, &session);
+
+        dialog
+            .authenticate(move |client, auth| {
+                let device_ids = device_ids.clone();
+                async move {
+                    let request = assign!(delete_devices::v3::Request::new(device_ids), { auth });
+                    client.send(request, None).await.map_err(Into::into)
+                }
+            })
+            .await?;
+
+        self.load_devices();
+        Ok(())
+    }
 }
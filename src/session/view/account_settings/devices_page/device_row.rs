@@ -3,7 +3,7 @@ use gettextrs::gettext;
 use gtk::{glib, glib::clone, subclass::prelude::*, CompositeTemplate};
 use tracing::error;
 
-use super::Device;
+use super::{Device, DevicesPage};
 use crate::{
     components::{AuthError, SpinnerButton},
     gettext_f, spawn, toast,
@@ -107,6 +107,7 @@ mod imp {
                         .add_css_class("destructive-action");
                 }
             }
+            obj.update_verify_button();
 
             self.delete_logout_button
                 .connect_clicked(clone!(@weak obj => move |_| {
@@ -118,7 +119,7 @@ mod imp {
 
             self.verify_button
                 .connect_clicked(clone!(@weak obj => move |_| {
-                    todo!("Not implemented");
+                    obj.verify();
                 }));
         }
     }
@@ -157,8 +158,6 @@ impl DeviceRow {
             self.set_tooltip_text(Some(device.device_id().as_str()));
 
             imp.verified_icon.set_visible(device.is_verified());
-            // TODO: Implement verification
-            // imp.verify_button.set_visible(!device.is_verified());
 
             let last_seen_ip_visible = if let Some(last_seen_ip) = device.last_seen_ip() {
                 imp.last_seen_ip.set_label(last_seen_ip);
@@ -179,9 +178,21 @@ impl DeviceRow {
         }
 
         imp.device.replace(device);
+        self.update_verify_button();
         self.notify("device");
     }
 
+    /// Update the visibility of the verify button.
+    ///
+    /// The current session is verified with the session verification instead.
+    fn update_verify_button(&self) {
+        let visible = !self.is_current_device()
+            && self
+                .device()
+                .is_some_and(|device| !device.is_verified() && device.can_be_verified());
+        self.imp().verify_button.set_visible(visible);
+    }
+
     /// Set whether this is the device of the current session.
     fn set_current_device(&self, input_bool: bool) {
         let imp = self.imp();
@@ -197,6 +208,26 @@ impl DeviceRow {
         self.imp().is_current_device.get()
     }
 
+    /// Start the verification of the device.
+    fn verify(&self) {
+        let Some(device) = self.device() else {
+            return;
+        };
+        let Some(page) = self
+            .ancestor(DevicesPage::static_type())
+            .and_downcast::<DevicesPage>()
+        else {
+            return;
+        };
+
+        spawn!(clone!(@weak self as obj => async move {
+            let verify_button = &obj.imp().verify_button;
+            verify_button.set_loading(true);
+            page.verify_device(&device).await;
+            verify_button.set_loading(false);
+        }));
+    }
+
     fn delete(&self) {
         self.imp().delete_logout_button.set_loading(true);
 
@@ -1,5 +1,7 @@
-use adw::subclass::prelude::*;
-use gtk::{glib, glib::clone, prelude::*, CompositeTemplate};
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use tracing::error;
 
 mod device;
 use self::device::Device;
@@ -9,7 +11,15 @@ mod device_item;
 use self::device_item::Item as DeviceItem;
 mod device_list;
 use self::device_list::DeviceList;
-use crate::{components::LoadingListBoxRow, prelude::*, session::model::User};
+use crate::{
+    components::{AuthError, LoadingListBoxRow},
+    prelude::*,
+    session::{
+        model::{User, VerificationState},
+        view::content::verification::IdentityVerificationWidget,
+    },
+    spawn, toast,
+};
 
 mod imp {
     use std::cell::RefCell;
@@ -24,9 +34,13 @@ mod imp {
     )]
     pub struct DevicesPage {
         pub user: RefCell<Option<User>>,
+        /// The list of devices of the user.
+        pub device_list: RefCell<Option<DeviceList>>,
         #[template_child]
         pub other_sessions_group: TemplateChild<adw::PreferencesGroup>,
         #[template_child]
+        pub sign_out_unverified_button: TemplateChild<gtk::Button>,
+        #[template_child]
         pub other_sessions: TemplateChild<gtk::ListBox>,
         #[template_child]
         pub current_session: TemplateChild<gtk::ListBox>,
@@ -40,6 +54,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -81,6 +96,7 @@ glib::wrapper! {
         @extends gtk::Widget, gtk::Window, adw::Window, adw::PreferencesWindow, @implements gtk::Accessible;
 }
 
+#[gtk::template_callbacks]
 impl DevicesPage {
     pub fn new(user: &User) -> Self {
         glib::Object::builder().property("user", user).build()
@@ -123,7 +139,8 @@ impl DevicesPage {
 
             device_list.connect_items_changed(
                 clone!(@weak self as obj => move |device_list, _, _, _| {
-                    obj.set_other_sessions_visibility(device_list.n_items() > 0)
+                    obj.set_other_sessions_visibility(device_list.n_items() > 0);
+                    obj.update_sign_out_unverified_button();
                 }),
             );
 
@@ -137,18 +154,135 @@ impl DevicesPage {
             );
 
             self.set_current_device(&device_list);
+            imp.device_list.replace(Some(device_list));
         } else {
             imp.other_sessions.unbind_model();
 
             if let Some(child) = imp.current_session.first_child() {
                 imp.current_session.remove(&child);
             }
+            imp.device_list.take();
         }
 
         imp.user.replace(user);
+        self.update_sign_out_unverified_button();
         self.notify("user");
     }
 
+    /// The list of devices of the user.
+    fn device_list(&self) -> Option<DeviceList> {
+        self.imp().device_list.borrow().clone()
+    }
+
+    /// Update the visibility of the button to sign out unverified sessions.
+    fn update_sign_out_unverified_button(&self) {
+        let visible = self
+            .device_list()
+            .is_some_and(|device_list| !device_list.unverified_devices().is_empty());
+        self.imp().sign_out_unverified_button.set_visible(visible);
+    }
+
+    /// Start the verification of the given device and show it in a subpage.
+    pub async fn verify_device(&self, device: &Device) {
+        let Some(request) = device.verify().await else {
+            return;
+        };
+
+        if request.state() == VerificationState::Error {
+            toast!(
+                self,
+                gettext("Could not start the verification of the session")
+            );
+            return;
+        }
+
+        // Refresh the trust state of the devices when the verification is done.
+        request.connect_notify_local(
+            Some("state"),
+            clone!(@weak self as obj => move |request, _| {
+                if request.state() == VerificationState::Completed {
+                    if let Some(device_list) = obj.device_list() {
+                        device_list.load_devices();
+                    }
+                }
+            }),
+        );
+
+        let Some(window) = self.root().and_downcast::<adw::PreferencesWindow>() else {
+            return;
+        };
+
+        let toolbar_view = adw::ToolbarView::new();
+        toolbar_view.add_top_bar(&adw::HeaderBar::new());
+        toolbar_view.set_content(Some(&IdentityVerificationWidget::new(&request)));
+
+        let subpage = adw::NavigationPage::builder()
+            .title(gettext("Verify Session"))
+            .child(&toolbar_view)
+            .build();
+        // Cancel the verification if the subpage is closed before it is done.
+        subpage.connect_hidden(clone!(@weak request => move |_| {
+            if !request.is_finished() {
+                request.cancel(true);
+            }
+        }));
+
+        window.push_subpage(&subpage);
+    }
+
+    /// Sign out all the other sessions that are not verified.
+    #[template_callback]
+    fn sign_out_unverified_sessions(&self) {
+        spawn!(clone!(@weak self as obj => async move {
+            obj.sign_out_unverified_sessions_inner().await;
+        }));
+    }
+
+    async fn sign_out_unverified_sessions_inner(&self) {
+        let Some(device_list) = self.device_list() else {
+            return;
+        };
+        let devices = device_list.unverified_devices();
+        if devices.is_empty() {
+            return;
+        }
+
+        let window = self.root().and_downcast::<gtk::Window>();
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Sign Out Unverified Sessions?"))
+            .body(gettext(
+                "All the sessions that were not verified will be disconnected. They will not be able to read your encrypted messages anymore.",
+            ))
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(window.as_ref());
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("sign-out", &gettext("_Sign Out")),
+        ]);
+        dialog.set_response_appearance("sign-out", adw::ResponseAppearance::Destructive);
+
+        if dialog.choose_future().await != "sign-out" {
+            return;
+        }
+
+        let button = &self.imp().sign_out_unverified_button;
+        button.set_sensitive(false);
+
+        match device_list.delete_devices(&devices, window.as_ref()).await {
+            Ok(()) | Err(AuthError::UserCancelled) => {}
+            Err(error) => {
+                error!("Could not sign out unverified sessions: {error:?}");
+                toast!(self, gettext("Could not sign out the unverified sessions"));
+            }
+        }
+
+        button.set_sensitive(true);
+    }
+
     fn set_other_sessions_visibility(&self, visible: bool) {
         self.imp().other_sessions_group.set_visible(visible);
     }
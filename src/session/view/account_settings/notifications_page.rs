@@ -1,15 +1,50 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{glib, glib::clone, CompositeTemplate};
-use matrix_sdk::event_handler::EventHandlerDropGuard;
-use ruma::{
-    api::client::push::{set_pushrule_enabled, RuleKind, RuleScope},
-    events::push_rules::{PushRulesEvent, PushRulesEventContent},
-    push::{PredefinedOverrideRuleId, Ruleset},
+
+use crate::{
+    prelude::*,
+    session::model::{PredefinedRules, Session},
+    spawn, toast,
 };
-use tracing::{error, warn};
 
-use crate::{prelude::*, session::model::Session, spawn, spawn_tokio, toast};
+/// The title of the setting for the given group of predefined push rules.
+fn rules_title(rules: PredefinedRules) -> String {
+    match rules {
+        PredefinedRules::Mentions => gettext("Mentions"),
+        PredefinedRules::RoomMentions => gettext("Room Pings"),
+        PredefinedRules::DirectMessages => gettext("Direct Messages"),
+        PredefinedRules::GroupMessages => gettext("Group Messages"),
+        PredefinedRules::Invites => gettext("Invites"),
+        PredefinedRules::Calls => gettext("Calls"),
+    }
+}
+
+/// The description of the setting for the given group of predefined push
+/// rules.
+fn rules_subtitle(rules: PredefinedRules) -> String {
+    match rules {
+        PredefinedRules::Mentions => gettext("Messages that mention your name or username"),
+        PredefinedRules::RoomMentions => gettext("Messages that mention the whole room with @room"),
+        PredefinedRules::DirectMessages => gettext("Messages in one-to-one rooms"),
+        PredefinedRules::GroupMessages => gettext("Messages in rooms with more than two members"),
+        PredefinedRules::Invites => gettext("Invites to join a room"),
+        PredefinedRules::Calls => gettext("Incoming calls"),
+    }
+}
+
+/// The widgets presenting a group of predefined push rules.
+#[derive(Debug)]
+pub struct PredefinedRulesRow {
+    /// The group of rules.
+    rules: PredefinedRules,
+    /// The row, with a switch to enable the rules.
+    row: adw::ExpanderRow,
+    /// The switch to play a sound for the rules.
+    sound: adw::SwitchRow,
+    /// The switch to highlight the events matching the rules.
+    highlight: adw::SwitchRow,
+}
 
 mod imp {
     use std::cell::{Cell, RefCell};
@@ -27,14 +62,26 @@ mod imp {
         pub session: WeakRef<Session>,
         /// Binding to the session settings `notifications-enabled` property.
         pub settings_binding: RefCell<Option<glib::Binding>>,
-        /// The guard of the event handler for push rules changes.
-        pub event_handler_guard: RefCell<Option<EventHandlerDropGuard>>,
+        /// The handler for the changes of the push rules.
+        pub changed_handler: RefCell<Option<glib::SignalHandlerId>>,
         /// Whether notifications are enabled for this account.
         pub account_enabled: Cell<bool>,
         /// Whether an account notifications change is being processed.
         pub account_loading: Cell<bool>,
         /// Whether notifications are enabled for this session.
         pub session_enabled: Cell<bool>,
+        /// Whether the page is being updated for the current push rules.
+        pub updating: Cell<bool>,
+        /// The rows of the groups of predefined push rules.
+        pub rules_rows: RefCell<Vec<PredefinedRulesRow>>,
+        /// The rows of the keywords.
+        pub keyword_rows: RefCell<Vec<adw::ActionRow>>,
+        #[template_child]
+        pub rules_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub keywords_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub keyword_entry: TemplateChild<adw::EntryRow>,
     }
 
     #[glib::object_subclass]
@@ -45,6 +92,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -97,6 +145,19 @@ mod imp {
                 _ => unimplemented!(),
             }
         }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.obj().init_rules_rows();
+        }
+
+        fn dispose(&self) {
+            if let Some(session) = self.session.upgrade() {
+                if let Some(handler) = self.changed_handler.take() {
+                    session.notifications_settings().disconnect(handler);
+                }
+            }
+        }
     }
 
     impl WidgetImpl for NotificationsPage {}
@@ -109,6 +170,7 @@ glib::wrapper! {
         @extends gtk::Widget, adw::PreferencesPage, @implements gtk::Accessible;
 }
 
+#[gtk::template_callbacks]
 impl NotificationsPage {
     pub fn new(session: &Session) -> Self {
         glib::Object::builder().property("session", session).build()
@@ -130,7 +192,11 @@ impl NotificationsPage {
         if let Some(binding) = imp.settings_binding.take() {
             binding.unbind();
         }
-        imp.event_handler_guard.take();
+        if let Some(prev_session) = prev_session {
+            if let Some(handler) = imp.changed_handler.take() {
+                prev_session.notifications_settings().disconnect(handler);
+            }
+        }
 
         if let Some(session) = &session {
             let binding = session
@@ -140,88 +206,265 @@ impl NotificationsPage {
                 .bidirectional()
                 .build();
             imp.settings_binding.replace(Some(binding));
+
+            let changed_handler = session.notifications_settings().connect_changed(
+                clone!(@weak self as obj => move |_| {
+                    obj.update_page();
+                }),
+            );
+            imp.changed_handler.replace(Some(changed_handler));
         }
 
         imp.session.set(session.as_ref());
         self.notify("session");
 
-        spawn!(
-            glib::Priority::DEFAULT_IDLE,
-            clone!(@weak self as obj => async move {
-                obj.init_page().await;
-            })
-        );
+        self.update_page();
     }
 
-    /// Initialize the page.
-    async fn init_page(&self) {
-        let session = match self.session() {
-            Some(session) => session,
-            None => return,
+    /// Update the page for the current push rules.
+    fn update_page(&self) {
+        let Some(session) = self.session() else {
+            return;
         };
+        let settings = session.notifications_settings();
 
-        let client = session.client();
-        let account = client.account();
-        let handle =
-            spawn_tokio!(async move { account.account_data::<PushRulesEventContent>().await });
+        if !settings.is_loaded() {
+            return;
+        }
 
-        match handle.await.unwrap() {
-            Ok(Some(pushrules)) => match pushrules.deserialize() {
-                Ok(pushrules) => {
-                    self.update_page(pushrules.global);
+        self.set_account_enabled(settings.account_enabled());
+        self.update_rules_rows();
+        self.update_keywords();
+    }
+
+    /// Create the rows for the groups of predefined push rules.
+    fn init_rules_rows(&self) {
+        let imp = self.imp();
+        let mut rows = Vec::with_capacity(PredefinedRules::ALL.len());
+
+        for rules in PredefinedRules::ALL {
+            let row = adw::ExpanderRow::builder()
+                .title(rules_title(rules))
+                .subtitle(rules_subtitle(rules))
+                .show_enable_switch(true)
+                .build();
+            let sound = adw::SwitchRow::builder()
+                .title(gettext("Play a Sound"))
+                .build();
+            let highlight = adw::SwitchRow::builder()
+                .title(gettext("Highlight"))
+                .build();
+            row.add_row(&sound);
+            row.add_row(&highlight);
+
+            row.connect_enable_expansion_notify(clone!(@weak self as obj => move |row| {
+                if obj.imp().updating.get() {
+                    return;
                 }
-                Err(error) => {
-                    error!("Could not deserialize push rules: {error}");
-                    toast!(
-                        self,
-                        gettext("Could not load notifications settings. Try again later")
-                    );
+
+                let enabled = row.enables_expansion();
+                spawn!(clone!(@weak obj => async move {
+                    obj.set_rules_enabled(rules, enabled).await;
+                }));
+            }));
+            sound.connect_active_notify(clone!(@weak self as obj => move |_| {
+                if obj.imp().updating.get() {
+                    return;
                 }
-            },
-            Ok(None) => {
-                warn!("Could not find push rules, using the default ruleset instead.");
-                let user_id = session.user().unwrap().user_id();
-                self.update_page(Ruleset::server_default(&user_id));
-            }
-            Err(error) => {
-                error!("Could not get push rules: {error}");
-                toast!(
-                    self,
-                    gettext("Could not load notifications settings. Try again later")
-                );
-            }
+
+                spawn!(clone!(@weak obj => async move {
+                    obj.set_rules_tweaks(rules).await;
+                }));
+            }));
+            highlight.connect_active_notify(clone!(@weak self as obj => move |_| {
+                if obj.imp().updating.get() {
+                    return;
+                }
+
+                spawn!(clone!(@weak obj => async move {
+                    obj.set_rules_tweaks(rules).await;
+                }));
+            }));
+
+            imp.rules_group.add(&row);
+            rows.push(PredefinedRulesRow {
+                rules,
+                row,
+                sound,
+                highlight,
+            });
         }
 
-        let obj_weak = glib::SendWeakRef::from(self.downgrade());
-        let handler = client.add_event_handler(move |event: PushRulesEvent| {
-            let obj_weak = obj_weak.clone();
-            async move {
-                let ctx = glib::MainContext::default();
-                ctx.spawn(async move {
-                    if let Some(obj) = obj_weak.upgrade() {
-                        obj.update_page(event.content.global)
-                    }
-                });
-            }
-        });
-        self.imp()
-            .event_handler_guard
-            .replace(Some(client.event_handler_drop_guard(handler)));
+        imp.rules_rows.replace(rows);
+    }
+
+    /// Update the rows of the predefined push rules for the current push
+    /// rules.
+    fn update_rules_rows(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let settings = session.notifications_settings();
+        let imp = self.imp();
+
+        imp.updating.set(true);
+
+        for rules_row in imp.rules_rows.borrow().iter() {
+            let state = settings.predefined_rules_state(rules_row.rules);
+
+            rules_row.row.set_visible(state.is_some());
+
+            let state = state.unwrap_or_default();
+            rules_row.row.set_enable_expansion(state.enabled);
+            rules_row.sound.set_active(state.sound);
+            rules_row.highlight.set_active(state.highlight);
+        }
+
+        imp.updating.set(false);
     }
 
-    /// Update the page for the given ruleset.
-    fn update_page(&self, rules: Ruleset) {
-        let account_enabled = if let Some(rule) = rules
-            .override_
+    /// The sound and highlight tweaks currently selected for the given group
+    /// of predefined push rules.
+    fn rules_tweaks(&self, rules: PredefinedRules) -> (bool, bool) {
+        self.imp()
+            .rules_rows
+            .borrow()
             .iter()
-            .find(|r| r.rule_id == PredefinedOverrideRuleId::Master.as_str())
+            .find(|row| row.rules == rules)
+            .map_or((true, false), |row| {
+                (row.sound.is_active(), row.highlight.is_active())
+            })
+    }
+
+    /// Enable or disable the given group of predefined push rules.
+    async fn set_rules_enabled(&self, rules: PredefinedRules, enabled: bool) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let (sound, highlight) = self.rules_tweaks(rules);
+
+        if session
+            .notifications_settings()
+            .set_predefined_rules_enabled(rules, enabled, sound, highlight)
+            .await
+            .is_err()
         {
-            !rule.enabled
-        } else {
-            warn!("Could not find `.m.rule.master` push rule, using the default rule instead.");
-            true
+            toast!(self, gettext("Could not change the notifications setting"));
+        }
+
+        self.update_rules_rows();
+    }
+
+    /// Send the tweaks currently selected for the given group of predefined
+    /// push rules.
+    async fn set_rules_tweaks(&self, rules: PredefinedRules) {
+        let Some(session) = self.session() else {
+            return;
         };
-        self.set_account_enabled(account_enabled);
+        let (sound, highlight) = self.rules_tweaks(rules);
+
+        if session
+            .notifications_settings()
+            .set_predefined_rules_tweaks(rules, sound, highlight)
+            .await
+            .is_err()
+        {
+            toast!(self, gettext("Could not change the notifications setting"));
+        }
+
+        self.update_rules_rows();
+    }
+
+    /// Update the rows of the keywords for the current push rules.
+    fn update_keywords(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let imp = self.imp();
+
+        for row in imp.keyword_rows.take() {
+            imp.keywords_group.remove(&row);
+        }
+
+        let mut rows = Vec::new();
+        for keyword in session.notifications_settings().keywords() {
+            let row = adw::ActionRow::builder()
+                .title(glib::markup_escape_text(&keyword))
+                .build();
+
+            let button = gtk::Button::builder()
+                .icon_name("user-trash-symbolic")
+                .tooltip_text(gettext("Remove Keyword"))
+                .valign(gtk::Align::Center)
+                .build();
+            button.add_css_class("flat");
+            button.connect_clicked(clone!(@weak self as obj => move |button| {
+                button.set_sensitive(false);
+
+                let keyword = keyword.clone();
+                spawn!(clone!(@weak obj, @weak button => async move {
+                    if !obj.remove_keyword(&keyword).await {
+                        button.set_sensitive(true);
+                    }
+                }));
+            }));
+            row.add_suffix(&button);
+
+            imp.keywords_group.add(&row);
+            rows.push(row);
+        }
+
+        imp.keyword_rows.replace(rows);
+    }
+
+    /// Add the keyword in the entry.
+    #[template_callback]
+    fn add_keyword(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+        let settings = session.notifications_settings().clone();
+        let imp = self.imp();
+        let keyword = imp.keyword_entry.text().trim().to_owned();
+
+        if keyword.is_empty() || settings.keywords().contains(&keyword) {
+            return;
+        }
+
+        imp.keyword_entry.set_sensitive(false);
+
+        spawn!(clone!(@weak self as obj, @weak settings => async move {
+            let imp = obj.imp();
+
+            if settings.add_keyword(keyword).await.is_ok() {
+                imp.keyword_entry.set_text("");
+            } else {
+                toast!(obj, gettext("Could not add the keyword"));
+            }
+
+            imp.keyword_entry.set_sensitive(true);
+        }));
+    }
+
+    /// Remove the given keyword.
+    ///
+    /// Returns `true` if the keyword was removed.
+    async fn remove_keyword(&self, keyword: &str) -> bool {
+        let Some(session) = self.session() else {
+            return false;
+        };
+
+        if session
+            .notifications_settings()
+            .remove_keyword(keyword)
+            .await
+            .is_err()
+        {
+            toast!(self, gettext("Could not remove the keyword"));
+            return false;
+        }
+
+        true
     }
 
     /// Whether notifications are enabled for this account.
@@ -265,38 +508,25 @@ impl NotificationsPage {
     ///
     /// This only changes the setting on the homeserver.
     async fn send_account_enabled(&self, enabled: bool) {
-        let client = match self.session() {
-            Some(session) => session.client(),
-            None => return,
+        let Some(session) = self.session() else {
+            return;
         };
 
-        let request = set_pushrule_enabled::v3::Request::new(
-            RuleScope::Global,
-            RuleKind::Override,
-            PredefinedOverrideRuleId::Master.to_string(),
-            !enabled,
-        );
-
-        let handle = spawn_tokio!(async move { client.send(request, None).await });
-
-        match handle.await.unwrap() {
-            Ok(_) => {}
-            Err(error) => {
-                error!(
-                    "Could not update `{}` push rule: {error}",
-                    PredefinedOverrideRuleId::Master
-                );
-
-                let msg = if enabled {
-                    gettext("Could not enable account notifications")
-                } else {
-                    gettext("Could not disable account notifications")
-                };
-                toast!(self, msg);
-
-                // Revert the local change.
-                self.set_account_enabled(!enabled);
-            }
+        if session
+            .notifications_settings()
+            .set_account_enabled(enabled)
+            .await
+            .is_err()
+        {
+            let msg = if enabled {
+                gettext("Could not enable account notifications")
+            } else {
+                gettext("Could not disable account notifications")
+            };
+            toast!(self, msg);
+
+            // Revert the local change.
+            self.set_account_enabled(!enabled);
         }
 
         self.set_account_loading(false);
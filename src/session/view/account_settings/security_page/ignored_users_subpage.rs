@@ -0,0 +1,173 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+
+use crate::{
+    components::Avatar,
+    session::model::{Session, User, UserExt},
+    spawn, toast,
+};
+
+mod imp {
+    use glib::{subclass::InitializingObject, WeakRef};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/account_settings/security_page/ignored_users_subpage.ui"
+    )]
+    pub struct IgnoredUsersSubpage {
+        pub session: WeakRef<Session>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub empty_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub list_page: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub list: TemplateChild<gtk::ListBox>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for IgnoredUsersSubpage {
+        const NAME: &'static str = "IgnoredUsersSubpage";
+        type Type = super::IgnoredUsersSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for IgnoredUsersSubpage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            use once_cell::sync::Lazy;
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Session>("session")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.obj().set_session(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "session" => self.obj().session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for IgnoredUsersSubpage {}
+    impl NavigationPageImpl for IgnoredUsersSubpage {}
+}
+
+glib::wrapper! {
+    /// Subpage to manage the users ignored by the current user.
+    pub struct IgnoredUsersSubpage(ObjectSubclass<imp::IgnoredUsersSubpage>)
+        @extends gtk::Widget, adw::NavigationPage;
+}
+
+impl IgnoredUsersSubpage {
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<Session>) {
+        let imp = self.imp();
+
+        if self.session() == session {
+            return;
+        }
+
+        if let Some(session) = &session {
+            let ignored_users = session.ignored_users();
+            imp.list.bind_model(
+                Some(ignored_users),
+                clone!(@weak self as obj => @default-panic, move |item| {
+                    obj.create_row(item.downcast_ref::<User>().unwrap()).upcast()
+                }),
+            );
+
+            ignored_users.connect_changed(clone!(@weak self as obj => move |_| {
+                obj.update_visible_page();
+            }));
+        } else {
+            imp.list.unbind_model();
+        }
+
+        imp.session.set(session.as_ref());
+        self.update_visible_page();
+        self.notify("session");
+    }
+
+    /// Show the empty page or the list, depending on the number of ignored
+    /// users.
+    fn update_visible_page(&self) {
+        let imp = self.imp();
+        let is_empty = self
+            .session()
+            .map_or(true, |session| session.ignored_users().n_items() == 0);
+
+        if is_empty {
+            imp.stack.set_visible_child(&*imp.empty_page);
+        } else {
+            imp.stack.set_visible_child(&*imp.list_page);
+        }
+    }
+
+    /// Create a row for the given ignored user.
+    fn create_row(&self, user: &User) -> adw::ActionRow {
+        let row = adw::ActionRow::builder()
+            .title(glib::markup_escape_text(&user.display_name()))
+            .subtitle(user.user_id().as_str())
+            .build();
+        user.bind_property("display-name", &row, "title")
+            .transform_to(|_, name: String| Some(glib::markup_escape_text(&name)))
+            .build();
+
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_data(Some(user.avatar_data().clone()));
+        row.add_prefix(&avatar);
+
+        let button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(gettext("Stop Ignoring"))
+            .valign(gtk::Align::Center)
+            .build();
+        button.add_css_class("flat");
+        button.connect_clicked(clone!(@weak self as obj, @weak user => move |button| {
+            button.set_sensitive(false);
+
+            spawn!(clone!(@weak obj, @weak button, @weak user => async move {
+                let Some(session) = obj.session() else {
+                    return;
+                };
+
+                if session.ignored_users().remove(&user.user_id()).await.is_err() {
+                    toast!(obj, gettext("Could not stop ignoring the user"));
+                    button.set_sensitive(true);
+                }
+            }));
+        }));
+        row.add_suffix(&button);
+
+        row
+    }
+}
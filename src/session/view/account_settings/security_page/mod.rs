@@ -2,13 +2,23 @@ use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{glib, glib::clone, CompositeTemplate};
 
-use crate::{components::ButtonRow, session::model::Session, spawn, spawn_tokio};
+use crate::{
+    components::ButtonRow,
+    session::model::{KeyBackupState, RecoveryState, Session},
+    spawn, spawn_tokio,
+};
 
+mod ignored_users_subpage;
 mod import_export_keys_subpage;
+mod recovery_subpage;
+use ignored_users_subpage::IgnoredUsersSubpage;
 use import_export_keys_subpage::{ImportExportKeysSubpage, KeysSubpageMode};
+use recovery_subpage::{RecoverySubpage, RecoverySubpageMode};
 
 mod imp {
-    use glib::{subclass::InitializingObject, WeakRef};
+    use std::cell::RefCell;
+
+    use glib::{subclass::InitializingObject, SignalHandlerId, WeakRef};
 
     use super::*;
 
@@ -18,9 +28,23 @@ mod imp {
     )]
     pub struct SecurityPage {
         pub session: WeakRef<Session>,
+        /// The handlers of the notifications of the key backup.
+        pub key_backup_handlers: RefCell<Vec<SignalHandlerId>>,
         #[template_child]
         pub import_export_keys_subpage: TemplateChild<ImportExportKeysSubpage>,
         #[template_child]
+        pub recovery_subpage: TemplateChild<RecoverySubpage>,
+        #[template_child]
+        pub backup_status: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub setup_backup_row: TemplateChild<ButtonRow>,
+        #[template_child]
+        pub recover_row: TemplateChild<ButtonRow>,
+        #[template_child]
+        pub reset_recovery_key_row: TemplateChild<ButtonRow>,
+        #[template_child]
+        pub ignored_users_subpage: TemplateChild<IgnoredUsersSubpage>,
+        #[template_child]
         pub master_key_status: TemplateChild<gtk::Label>,
         #[template_child]
         pub self_signing_key_status: TemplateChild<gtk::Label>,
@@ -95,11 +119,39 @@ impl SecurityPage {
 
     /// Set the current session.
     pub fn set_session(&self, session: Option<Session>) {
-        if self.session() == session {
+        let prev_session = self.session();
+        if prev_session == session {
             return;
         }
 
-        self.imp().session.set(session.as_ref());
+        let imp = self.imp();
+        let handlers = imp.key_backup_handlers.take();
+        if let Some(prev_session) = prev_session {
+            for handler in handlers {
+                prev_session.key_backup().disconnect(handler);
+            }
+        }
+
+        if let Some(session) = &session {
+            let key_backup = session.key_backup();
+            let recovery_state_handler = key_backup.connect_notify_local(
+                Some("recovery-state"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_backup();
+                }),
+            );
+            let backup_state_handler = key_backup.connect_notify_local(
+                Some("backup-state"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_backup();
+                }),
+            );
+            imp.key_backup_handlers
+                .replace(vec![recovery_state_handler, backup_state_handler]);
+        }
+
+        imp.session.set(session.as_ref());
+        self.update_backup();
         self.notify("session");
 
         spawn!(clone!(@weak self as obj => async move {
@@ -127,6 +179,80 @@ impl SecurityPage {
             .push_subpage(subpage);
     }
 
+    /// Update the section about the online backup.
+    fn update_backup(&self) {
+        let imp = self.imp();
+        let Some(session) = self.session() else {
+            return;
+        };
+        let key_backup = session.key_backup();
+        let recovery_state = key_backup.recovery_state();
+
+        let label = &imp.backup_status;
+        let status = match key_backup.backup_state() {
+            KeyBackupState::Enabled => Some(gettext("Enabled")),
+            KeyBackupState::Enabling => Some(gettext("Uploading")),
+            KeyBackupState::Downloading => Some(gettext("Downloading")),
+            KeyBackupState::Disabling => Some(gettext("Disabling")),
+            KeyBackupState::Disabled => None,
+        };
+        if let Some(status) = status {
+            label.add_css_class("success");
+            label.remove_css_class("error");
+            label.set_text(&status);
+        } else {
+            label.add_css_class("error");
+            label.remove_css_class("success");
+            if recovery_state == RecoveryState::Incomplete {
+                label.set_text(&gettext("Not restored"));
+            } else {
+                label.set_text(&gettext("Disabled"));
+            }
+        }
+
+        imp.setup_backup_row
+            .set_visible(recovery_state == RecoveryState::Disabled);
+        imp.recover_row
+            .set_visible(recovery_state == RecoveryState::Incomplete);
+        imp.reset_recovery_key_row.set_visible(matches!(
+            recovery_state,
+            RecoveryState::Enabled | RecoveryState::Incomplete
+        ));
+    }
+
+    /// Show the recovery subpage with the given mode.
+    fn show_recovery_page(&self, mode: RecoverySubpageMode) {
+        let subpage = &*self.imp().recovery_subpage;
+        subpage.set_mode(mode);
+        self.root()
+            .and_downcast_ref::<adw::PreferencesWindow>()
+            .unwrap()
+            .push_subpage(subpage);
+    }
+
+    #[template_callback]
+    fn handle_setup_backup(&self) {
+        self.show_recovery_page(RecoverySubpageMode::Setup);
+    }
+
+    #[template_callback]
+    fn handle_recover(&self) {
+        self.show_recovery_page(RecoverySubpageMode::Recover);
+    }
+
+    #[template_callback]
+    fn handle_reset_recovery_key(&self) {
+        self.show_recovery_page(RecoverySubpageMode::Reset);
+    }
+
+    #[template_callback]
+    fn show_ignored_users_page(&self) {
+        self.root()
+            .and_downcast_ref::<adw::PreferencesWindow>()
+            .unwrap()
+            .push_subpage(&*self.imp().ignored_users_subpage);
+    }
+
     async fn load_cross_signing_status(&self) {
         let Some(session) = self.session() else {
             return;
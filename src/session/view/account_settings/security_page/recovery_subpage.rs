@@ -0,0 +1,440 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{
+    glib::{self, clone},
+    CompositeTemplate,
+};
+use tracing::error;
+
+use crate::{
+    components::SpinnerButton,
+    session::model::{RecoverError, RecoveryState, ResetRecoveryError, Session},
+    spawn, toast,
+};
+
+#[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
+#[repr(u32)]
+#[enum_type(name = "RecoverySubpageMode")]
+pub enum RecoverySubpageMode {
+    /// Set up the key backup and the recovery of the account.
+    #[default]
+    Setup = 0,
+    /// Restore the secrets and the room keys from the backup.
+    Recover = 1,
+    /// Replace a lost recovery key.
+    Reset = 2,
+}
+
+mod imp {
+    use std::cell::Cell;
+
+    use glib::{subclass::InitializingObject, WeakRef};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/account_settings/security_page/recovery_subpage.ui"
+    )]
+    pub struct RecoverySubpage {
+        pub session: WeakRef<Session>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub description: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub instructions: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub passphrase: TemplateChild<adw::PasswordEntryRow>,
+        #[template_child]
+        pub confirm_passphrase_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub confirm_passphrase: TemplateChild<adw::PasswordEntryRow>,
+        #[template_child]
+        pub confirm_passphrase_error_revealer: TemplateChild<gtk::Revealer>,
+        #[template_child]
+        pub progress_bar: TemplateChild<gtk::ProgressBar>,
+        #[template_child]
+        pub proceed_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub recovery_key: TemplateChild<gtk::Label>,
+        pub mode: Cell<RecoverySubpageMode>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for RecoverySubpage {
+        const NAME: &'static str = "RecoverySubpage";
+        type Type = super::RecoverySubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            klass.bind_template();
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for RecoverySubpage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            use once_cell::sync::Lazy;
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Session>("session").build(),
+                    glib::ParamSpecEnum::builder::<RecoverySubpageMode>("mode")
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.set_session(value.get().unwrap()),
+                "mode" => obj.set_mode(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                "mode" => obj.mode().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            self.passphrase
+                .connect_changed(clone!(@weak obj => move|_| {
+                    obj.validate_passphrase_confirmation();
+                }));
+
+            self.confirm_passphrase
+                .connect_changed(clone!(@weak obj => move|_| {
+                    obj.validate_passphrase_confirmation();
+                }));
+
+            obj.update_for_mode();
+        }
+    }
+
+    impl WidgetImpl for RecoverySubpage {}
+    impl NavigationPageImpl for RecoverySubpage {}
+}
+
+glib::wrapper! {
+    /// Subpage to set up, use or reset the server-side backup of the room
+    /// encryption keys.
+    pub struct RecoverySubpage(ObjectSubclass<imp::RecoverySubpage>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl RecoverySubpage {
+    pub fn new(session: &Session) -> Self {
+        glib::Object::builder().property("session", session).build()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the current session.
+    pub fn set_session(&self, session: Option<Session>) {
+        self.imp().session.set(session.as_ref());
+    }
+
+    /// The mode of the subpage.
+    pub fn mode(&self) -> RecoverySubpageMode {
+        self.imp().mode.get()
+    }
+
+    /// Set the mode of the subpage.
+    pub fn set_mode(&self, mode: RecoverySubpageMode) {
+        if self.mode() == mode {
+            return;
+        }
+
+        self.imp().mode.set(mode);
+        self.update_for_mode();
+        self.notify("mode");
+    }
+
+    fn clear(&self) {
+        let imp = self.imp();
+
+        imp.passphrase.set_text("");
+        imp.confirm_passphrase.set_text("");
+        imp.recovery_key.set_label("");
+        imp.progress_bar.set_visible(false);
+        imp.progress_bar.set_fraction(0.0);
+        imp.stack.set_visible_child_name("form");
+    }
+
+    fn update_for_mode(&self) {
+        let imp = self.imp();
+        self.clear();
+
+        match self.mode() {
+            RecoverySubpageMode::Setup => {
+                self.set_title(&gettext("Set Up Online Backup"));
+                imp.description.set_label(&gettext(
+                    "An online backup stores your room encryption keys on your homeserver, so you can read your encrypted messages on any new session.",
+                ));
+                imp.instructions.set_label(&gettext(
+                    "The backup is protected by a recovery key that will be generated for you. You can also choose a passphrase that is easier to remember.",
+                ));
+                imp.passphrase.set_title(&gettext("Passphrase (Optional)"));
+                imp.confirm_passphrase_box.set_visible(true);
+                imp.proceed_button.set_label(&gettext("Set Up Backup"));
+                imp.proceed_button.remove_css_class("destructive-action");
+                imp.proceed_button.add_css_class("suggested-action");
+            }
+            RecoverySubpageMode::Recover => {
+                self.set_title(&gettext("Restore Online Backup"));
+                imp.description.set_label(&gettext(
+                    "Restoring the online backup allows this session to read your encrypted messages and to verify your other sessions.",
+                ));
+                imp.instructions.set_label(&gettext(
+                    "Enter the recovery key or the passphrase provided when the backup was set up.",
+                ));
+                imp.passphrase
+                    .set_title(&gettext("Recovery Key or Passphrase"));
+                imp.confirm_passphrase_box.set_visible(false);
+                imp.proceed_button.set_label(&gettext("Restore Backup"));
+                imp.proceed_button.remove_css_class("destructive-action");
+                imp.proceed_button.add_css_class("suggested-action");
+            }
+            RecoverySubpageMode::Reset => {
+                self.set_title(&gettext("Reset Recovery Key"));
+                imp.description.set_label(&gettext(
+                    "If you lost your recovery key and your passphrase, you can replace them with a new recovery key.",
+                ));
+                imp.instructions.set_label(&gettext(
+                    "If this session cannot access the current backup, the backup will be deleted and a new one will be created. Encrypted messages that cannot be read in this session will be lost.",
+                ));
+                imp.passphrase
+                    .set_title(&gettext("New Passphrase (Optional)"));
+                imp.confirm_passphrase_box.set_visible(true);
+                imp.proceed_button.set_label(&gettext("Reset Recovery Key"));
+                imp.proceed_button.remove_css_class("suggested-action");
+                imp.proceed_button.add_css_class("destructive-action");
+            }
+        }
+
+        self.update_button();
+    }
+
+    fn validate_passphrase_confirmation(&self) {
+        let imp = self.imp();
+        let entry = &imp.confirm_passphrase;
+        let revealer = &imp.confirm_passphrase_error_revealer;
+        let passphrase = imp.passphrase.text();
+        let confirmation = entry.text();
+
+        if confirmation.is_empty() {
+            revealer.set_reveal_child(false);
+            entry.remove_css_class("success");
+            entry.remove_css_class("warning");
+        } else if passphrase == confirmation {
+            revealer.set_reveal_child(false);
+            entry.add_css_class("success");
+            entry.remove_css_class("warning");
+        } else {
+            revealer.set_reveal_child(true);
+            entry.remove_css_class("success");
+            entry.add_css_class("warning");
+        }
+
+        self.update_button();
+    }
+
+    fn update_button(&self) {
+        self.imp().proceed_button.set_sensitive(self.can_proceed());
+    }
+
+    fn can_proceed(&self) -> bool {
+        let imp = self.imp();
+        let passphrase = imp.passphrase.text();
+
+        if self.mode() == RecoverySubpageMode::Recover {
+            !passphrase.is_empty()
+        } else {
+            passphrase == imp.confirm_passphrase.text()
+        }
+    }
+
+    /// Set whether the form is sensitive.
+    fn set_form_sensitive(&self, sensitive: bool) {
+        let imp = self.imp();
+
+        imp.proceed_button.set_loading(!sensitive);
+        imp.passphrase.set_sensitive(sensitive);
+        imp.confirm_passphrase.set_sensitive(sensitive);
+    }
+
+    #[template_callback]
+    fn handle_proceed(&self) {
+        spawn!(clone!(@weak self as obj => async move {
+            obj.proceed().await;
+        }));
+    }
+
+    async fn proceed(&self) {
+        if !self.can_proceed() {
+            return;
+        }
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let imp = self.imp();
+        let key_backup = session.key_backup();
+        let passphrase = imp.passphrase.text().to_string();
+        let mode = self.mode();
+
+        self.set_form_sensitive(false);
+
+        if mode == RecoverySubpageMode::Recover {
+            match key_backup.recover(passphrase).await {
+                Ok(()) => {
+                    toast!(self, gettext("Online backup restored successfully"));
+                    self.clear();
+                    self.activate_action("win.close-subpage", None).unwrap();
+                }
+                Err(RecoverError::Recovery(error)) => {
+                    error!("Could not restore online backup: {error}");
+                    toast!(
+                        self,
+                        gettext(
+                            "Could not restore the backup. Check the recovery key or passphrase."
+                        )
+                    );
+                }
+                Err(error) => {
+                    error!("Could not verify this session after restoring the backup: {error}");
+                    toast!(
+                        self,
+                        gettext("Online backup restored but could not verify this session")
+                    );
+                }
+            }
+
+            self.set_form_sensitive(true);
+            return;
+        }
+
+        if mode == RecoverySubpageMode::Reset
+            && !self
+                .confirm_reset(key_backup.recovery_state() != RecoveryState::Enabled)
+                .await
+        {
+            self.set_form_sensitive(true);
+            return;
+        }
+
+        let passphrase = Some(passphrase).filter(|passphrase| !passphrase.is_empty());
+        let progress = clone!(@weak self as obj => move |fraction| {
+            let progress_bar = &obj.imp().progress_bar;
+            progress_bar.set_visible(true);
+            progress_bar.set_fraction(fraction);
+        });
+
+        let result = if mode == RecoverySubpageMode::Setup {
+            key_backup
+                .enable(passphrase, progress)
+                .await
+                .map_err(|error| {
+                    error!("Could not set up online backup: {error}");
+                    toast!(self, gettext("Could not set up the online backup"));
+                })
+        } else {
+            key_backup
+                .reset(passphrase, progress)
+                .await
+                .map_err(|error| {
+                    error!("Could not reset recovery key: {error}");
+
+                    if let ResetRecoveryError::BackupDeleted(_) = error {
+                        self.show_backup_deleted_error();
+                    } else {
+                        toast!(self, gettext("Could not reset the recovery key"));
+                    }
+                })
+        };
+
+        if let Ok(recovery_key) = result {
+            imp.recovery_key.set_label(&recovery_key);
+            imp.stack.set_visible_child_name("recovery-key");
+        }
+
+        imp.progress_bar.set_visible(false);
+        self.set_form_sensitive(true);
+    }
+
+    /// Ask the user to confirm the reset of the recovery key.
+    ///
+    /// `deletes_backup` is whether the current backup will be deleted.
+    ///
+    /// Returns `true` if the user confirmed.
+    async fn confirm_reset(&self, deletes_backup: bool) -> bool {
+        let body = if deletes_backup {
+            gettext("This session cannot access the current backup, so it will be deleted. Encrypted messages that cannot be read in this session will be lost forever.")
+        } else {
+            gettext("The current recovery key and passphrase will not work anymore.")
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Reset Recovery Key?"))
+            .body(body)
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("reset", &gettext("_Reset")),
+        ]);
+        dialog.set_response_appearance("reset", adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future().await == "reset"
+    }
+
+    /// Show that the backup was deleted but that a new one could not be
+    /// created.
+    fn show_backup_deleted_error(&self) {
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Backup Deleted"))
+            .body(gettext("The previous backup was deleted but a new one could not be created. The encryption keys that were only in the previous backup are lost. Set up the online backup again to protect the keys of this session."))
+            .default_response("close")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_response("close", &gettext("_Close"));
+        dialog.present();
+
+        // The backup needs to be set up from scratch now.
+        self.set_mode(RecoverySubpageMode::Setup);
+    }
+
+    #[template_callback]
+    fn copy_recovery_key(&self) {
+        let recovery_key = self.imp().recovery_key.label();
+        self.clipboard().set_text(&recovery_key);
+        toast!(self, gettext("Recovery key copied to clipboard"));
+    }
+}
@@ -181,4 +181,10 @@ impl PublicRoom {
     pub fn matrix_public_room(&self) -> Option<&PublicRoomsChunk> {
         self.imp().matrix_public_room.get()
     }
+
+    /// Whether users need to request an invite to join this room.
+    pub fn can_knock(&self) -> bool {
+        self.matrix_public_room()
+            .is_some_and(|room| matches!(room.join_rule.as_str(), "knock" | "knock_restricted"))
+    }
 }
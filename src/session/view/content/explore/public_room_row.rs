@@ -5,6 +5,7 @@ use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTemplate
 use super::PublicRoom;
 use crate::{
     components::{Avatar, Spinner, SpinnerButton},
+    session::view::KnockDialog,
     spawn, toast, Window,
 };
 
@@ -206,6 +207,8 @@ impl PublicRoomRow {
         let button = &self.imp().button;
         if public_room.room().is_some() {
             button.set_label(&gettext("View"));
+        } else if public_room.can_knock() {
+            button.set_label(&gettext("Request Invite"));
         } else {
             button.set_label(&gettext("Join"));
         }
@@ -214,6 +217,8 @@ impl PublicRoomRow {
     }
 
     /// Join or view the public room.
+    ///
+    /// If the room requires an invite, a request to join it is sent instead.
     pub fn join_or_view(&self) {
         let Some(public_room) = self.public_room() else {
             return;
@@ -228,6 +233,22 @@ impl PublicRoomRow {
         } else if let Some(matrix_public_room) = public_room.matrix_public_room() {
             let room_id = matrix_public_room.room_id.clone();
 
+            if public_room.can_knock() {
+                let room_name = matrix_public_room
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| room_id.to_string());
+                let dialog = KnockDialog::new(
+                    self.root().and_downcast_ref::<gtk::Window>(),
+                    room_list,
+                    room_id.into(),
+                    vec![],
+                    &room_name,
+                );
+                dialog.present();
+                return;
+            }
+
             spawn!(clone!(@weak self as obj, @weak room_list => async move {
                 if let Err(error) = room_list.join_by_id_or_alias(room_id.into(), vec![]).await {
                     toast!(obj, error);
@@ -12,7 +12,7 @@ use self::{
     verification::IdentityVerificationWidget,
 };
 use crate::session::model::{
-    Entry, EntryType, IdentityVerification, Room, RoomType, Session, VerificationMode,
+    Entry, EntryType, EventKey, IdentityVerification, Room, RoomType, Session, VerificationMode,
 };
 
 mod imp {
@@ -144,6 +144,11 @@ impl Content {
         }
     }
 
+    /// Scroll to the event with the given key in the room history.
+    pub fn jump_to_event(&self, key: EventKey) {
+        self.imp().room_history.jump_to_event(key);
+    }
+
     /// The current session.
     pub fn session(&self) -> Option<Session> {
         self.imp().session.upgrade()
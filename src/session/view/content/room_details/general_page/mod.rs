@@ -20,7 +20,9 @@ use tracing::error;
 
 use crate::{
     components::{CustomEntry, EditableAvatar, SpinnerButton},
-    session::model::{AvatarData, AvatarImage, MemberList, Room},
+    session::model::{
+        AvatarData, AvatarImage, MemberList, NotificationsRoomSetting, Room, RoomType,
+    },
     spawn, spawn_tokio, toast,
     utils::{
         and_expr,
@@ -61,6 +63,16 @@ mod imp {
         pub save_details_btn: TemplateChild<SpinnerButton>,
         #[template_child]
         pub members_count: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub notifications_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub notifications_all_button: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub notifications_mentions_only_button: TemplateChild<gtk::CheckButton>,
+        #[template_child]
+        pub notifications_mute_button: TemplateChild<gtk::CheckButton>,
+        /// Whether the notifications setting is being changed.
+        pub changing_notifications_setting: Cell<bool>,
         /// Whether edit mode is enabled.
         pub edit_mode_enabled: Cell<bool>,
         pub changing_avatar: RefCell<Option<OngoingAsyncAction<OwnedMxcUri>>>,
@@ -173,9 +185,22 @@ impl GeneralPage {
                 obj.topic_changed(room.topic());
             }),
         );
+        room.connect_notify_local(
+            Some("notifications-setting"),
+            clone!(@weak self as obj => move |room, _| {
+                obj.update_notifications_setting(room);
+            }),
+        );
+        room.connect_notify_local(
+            Some("category"),
+            clone!(@weak self as obj => move |room, _| {
+                obj.update_notifications_setting(room);
+            }),
+        );
 
         self.init_avatar(room);
         self.init_edit_mode(room);
+        self.update_notifications_setting(room);
 
         let members = room.get_or_create_members();
         members.connect_items_changed(clone!(@weak self as obj => move |members, _, _, _| {
@@ -564,6 +589,63 @@ impl GeneralPage {
         }
     }
 
+    /// Update the notifications section for the setting of the given room.
+    fn update_notifications_setting(&self, room: &Room) {
+        let imp = self.imp();
+
+        imp.notifications_group.set_visible(!matches!(
+            room.category(),
+            RoomType::Invited | RoomType::Left | RoomType::Outdated
+        ));
+
+        let button = match room.notifications_setting() {
+            NotificationsRoomSetting::All => &imp.notifications_all_button,
+            NotificationsRoomSetting::MentionsOnly => &imp.notifications_mentions_only_button,
+            NotificationsRoomSetting::Mute => &imp.notifications_mute_button,
+        };
+
+        // Do not send the setting back to the homeserver.
+        imp.changing_notifications_setting.set(true);
+        button.set_active(true);
+        imp.changing_notifications_setting.set(false);
+    }
+
+    /// Handle when a notifications setting button was toggled.
+    #[template_callback]
+    fn notifications_setting_toggled(&self, button: &gtk::CheckButton) {
+        let imp = self.imp();
+
+        if !button.is_active() || imp.changing_notifications_setting.get() {
+            return;
+        }
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let setting = if *button == *imp.notifications_all_button {
+            NotificationsRoomSetting::All
+        } else if *button == *imp.notifications_mentions_only_button {
+            NotificationsRoomSetting::MentionsOnly
+        } else {
+            NotificationsRoomSetting::Mute
+        };
+
+        if room.notifications_setting() == setting {
+            return;
+        }
+
+        imp.notifications_group.set_sensitive(false);
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if room.sync_notifications_setting(setting).await.is_err() {
+                toast!(obj, gettext("Could not change the notifications setting"));
+                obj.update_notifications_setting(&room);
+            }
+
+            obj.imp().notifications_group.set_sensitive(true);
+        }));
+    }
+
     fn member_count_changed(&self, n: u32) {
         self.imp().members_count.set_text(&format!("{n}"));
     }
@@ -9,7 +9,9 @@ use matrix_sdk::ruma::events::{
 use tracing::warn;
 
 use super::HistoryViewerEvent;
-use crate::{session::model::Session, spawn, spawn_tokio};
+use crate::{
+    components::Waveform, session::model::Session, spawn, spawn_tokio, utils::media::audio_waveform,
+};
 
 mod imp {
     use std::cell::RefCell;
@@ -31,6 +33,8 @@ mod imp {
         #[template_child]
         pub title_label: TemplateChild<gtk::Label>,
         #[template_child]
+        pub waveform: TemplateChild<Waveform>,
+        #[template_child]
         pub duration_label: TemplateChild<gtk::Label>,
     }
 
@@ -104,6 +108,10 @@ impl AudioRow {
                 if let MessageType::Audio(audio) = content.msgtype {
                     imp.title_label.set_label(&audio.body);
 
+                    let waveform = audio_waveform(&audio);
+                    imp.waveform.set_visible(!waveform.is_empty());
+                    imp.waveform.set_samples(waveform);
+
                     if let Some(duration) = audio.info.as_ref().and_then(|i| i.duration) {
                         let duration_secs = duration.as_secs();
                         let secs = duration_secs % 60;
@@ -180,7 +188,9 @@ impl AudioRow {
             }
         }));
 
-        self.imp().media_file.replace(Some(media_file));
+        let imp = self.imp();
+        imp.waveform.set_media_file(Some(&media_file));
+        imp.media_file.replace(Some(media_file));
     }
 
     fn toggle_play(&self) {
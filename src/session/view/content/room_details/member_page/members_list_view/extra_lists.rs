@@ -11,8 +11,14 @@ mod imp {
 
     #[derive(Debug, Default)]
     pub struct ExtraLists {
+        pub knocked: OnceCell<MembershipSubpageItem>,
         pub invited: OnceCell<MembershipSubpageItem>,
         pub banned: OnceCell<MembershipSubpageItem>,
+        /// Whether our own member is allowed to handle requests to join.
+        pub can_handle_knocks: Cell<bool>,
+        /// Whether the knocked item is hidden, because it is empty or our own
+        /// member cannot handle requests to join.
+        pub knocked_is_hidden: Cell<bool>,
         pub invited_is_empty: Cell<bool>,
         pub banned_is_empty: Cell<bool>,
     }
@@ -28,12 +34,18 @@ mod imp {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
                 vec![
+                    glib::ParamSpecObject::builder::<MembershipSubpageItem>("knocked")
+                        .construct_only()
+                        .build(),
                     glib::ParamSpecObject::builder::<MembershipSubpageItem>("invited")
                         .construct_only()
                         .build(),
                     glib::ParamSpecObject::builder::<MembershipSubpageItem>("banned")
                         .construct_only()
                         .build(),
+                    glib::ParamSpecBoolean::builder("can-handle-knocks")
+                        .explicit_notify()
+                        .build(),
                 ]
             });
 
@@ -44,8 +56,10 @@ mod imp {
             let obj = self.obj();
 
             match pspec.name() {
+                "knocked" => obj.set_knocked(value.get().unwrap()),
                 "invited" => obj.set_invited(value.get().unwrap()),
                 "banned" => obj.set_banned(value.get().unwrap()),
+                "can-handle-knocks" => obj.set_can_handle_knocks(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -54,8 +68,10 @@ mod imp {
             let obj = self.obj();
 
             match pspec.name() {
+                "knocked" => obj.knocked().to_value(),
                 "invited" => obj.invited().to_value(),
                 "banned" => obj.banned().to_value(),
+                "can-handle-knocks" => obj.can_handle_knocks().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -64,9 +80,14 @@ mod imp {
             self.parent_constructed();
             let obj = self.obj();
 
+            let knocked_members = obj.knocked().model();
             let invited_members = obj.invited().model();
             let banned_members = obj.banned().model();
 
+            knocked_members.connect_items_changed(clone!(@weak obj => move |_, _, _, _| {
+                obj.update_knocked();
+            }));
+
             invited_members.connect_items_changed(clone!(@weak obj => move |_, _, _, _| {
                 obj.update_invited();
             }));
@@ -75,6 +96,9 @@ mod imp {
                 obj.update_banned();
             }));
 
+            // Our own member cannot handle requests to join until we know
+            // otherwise.
+            self.knocked_is_hidden.set(true);
             self.invited_is_empty.set(invited_members.n_items() == 0);
             self.banned_is_empty.set(banned_members.n_items() == 0);
         }
@@ -86,33 +110,27 @@ mod imp {
         }
 
         fn n_items(&self) -> u32 {
-            let mut len = 0;
-
-            if !self.invited_is_empty.get() {
-                len += 1;
-            }
-            if !self.banned_is_empty.get() {
-                len += 1;
-            }
-
-            len
+            self.visible_items().count() as u32
         }
 
         fn item(&self, position: u32) -> Option<glib::Object> {
-            let has_invited = !self.invited_is_empty.get();
-            let has_banned = !self.banned_is_empty.get();
-
-            if position == 0 && has_invited {
-                let invited = self.invited.get().unwrap();
-                return Some(invited.clone().upcast());
-            }
-
-            if has_banned && ((position == 0 && !has_invited) || (position == 1 && has_invited)) {
-                let banned = self.banned.get().unwrap();
-                return Some(banned.clone().upcast());
-            }
+            self.visible_items()
+                .nth(position as usize)
+                .map(|item| item.clone().upcast())
+        }
+    }
 
-            None
+    impl ExtraLists {
+        /// The items that are not hidden, in the order they are presented.
+        fn visible_items(&self) -> impl Iterator<Item = &MembershipSubpageItem> {
+            [
+                (&self.knocked, &self.knocked_is_hidden),
+                (&self.invited, &self.invited_is_empty),
+                (&self.banned, &self.banned_is_empty),
+            ]
+            .into_iter()
+            .filter(|(_, is_hidden)| !is_hidden.get())
+            .filter_map(|(item, _)| item.get())
         }
     }
 }
@@ -123,13 +141,28 @@ glib::wrapper! {
 }
 
 impl ExtraLists {
-    pub fn new(invited: &MembershipSubpageItem, banned: &MembershipSubpageItem) -> Self {
+    pub fn new(
+        knocked: &MembershipSubpageItem,
+        invited: &MembershipSubpageItem,
+        banned: &MembershipSubpageItem,
+    ) -> Self {
         glib::Object::builder()
+            .property("knocked", knocked)
             .property("invited", invited)
             .property("banned", banned)
             .build()
     }
 
+    /// The subpage item for members who requested an invite.
+    pub fn knocked(&self) -> &MembershipSubpageItem {
+        self.imp().knocked.get().unwrap()
+    }
+
+    /// Set the subpage item for members who requested an invite.
+    fn set_knocked(&self, item: MembershipSubpageItem) {
+        self.imp().knocked.set(item).unwrap();
+    }
+
     /// The subpage item for invited members.
     pub fn invited(&self) -> &MembershipSubpageItem {
         self.imp().invited.get().unwrap()
@@ -150,6 +183,42 @@ impl ExtraLists {
         self.imp().banned.set(item).unwrap();
     }
 
+    /// Whether our own member is allowed to handle requests to join.
+    pub fn can_handle_knocks(&self) -> bool {
+        self.imp().can_handle_knocks.get()
+    }
+
+    /// Set whether our own member is allowed to handle requests to join.
+    pub fn set_can_handle_knocks(&self, can_handle_knocks: bool) {
+        if self.can_handle_knocks() == can_handle_knocks {
+            return;
+        }
+
+        self.imp().can_handle_knocks.set(can_handle_knocks);
+        self.update_knocked();
+        self.notify("can-handle-knocks");
+    }
+
+    fn update_knocked(&self) {
+        let imp = self.imp();
+
+        let was_hidden = imp.knocked_is_hidden.get();
+        let is_hidden = self.knocked().model().n_items() == 0 || !self.can_handle_knocks();
+
+        if was_hidden == is_hidden {
+            // Nothing changed so don't do anything
+            return;
+        }
+
+        imp.knocked_is_hidden.set(is_hidden);
+
+        let added = if was_hidden { 1 } else { 0 };
+        // If it is not added, it is removed.
+        let removed = 1 - added;
+
+        self.items_changed(0, removed, added);
+    }
+
     fn update_invited(&self) {
         let imp = self.imp();
 
@@ -163,11 +232,13 @@ impl ExtraLists {
 
         imp.invited_is_empty.set(is_empty);
 
+        let position = if imp.knocked_is_hidden.get() { 0 } else { 1 };
+
         let added = if was_empty { 1 } else { 0 };
         // If it is not added, it is removed.
         let removed = 1 - added;
 
-        self.items_changed(0, removed, added);
+        self.items_changed(position, removed, added);
     }
 
     fn update_banned(&self) {
@@ -183,7 +254,10 @@ impl ExtraLists {
 
         imp.banned_is_empty.set(is_empty);
 
-        let position = if imp.invited_is_empty.get() { 0 } else { 1 };
+        let position = [&imp.knocked_is_hidden, &imp.invited_is_empty]
+            .into_iter()
+            .filter(|is_hidden| !is_hidden.get())
+            .count() as u32;
 
         let added = if was_empty { 1 } else { 0 };
         // If it is not added, it is removed.
@@ -1,10 +1,12 @@
 use adw::subclass::prelude::BinImpl;
+use gettextrs::gettext;
 use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTemplate};
 
 use super::super::{MemberMenu, MemberPage};
 use crate::{
     components::{Avatar, Badge},
-    session::model::Member,
+    gettext_f,
+    session::model::{Member, Membership},
 };
 
 mod imp {
@@ -21,8 +23,12 @@ mod imp {
     )]
     pub struct MemberRow {
         pub member: RefCell<Option<Member>>,
+        /// The signal handlers connected to the member.
+        pub member_handlers: RefCell<Vec<glib::SignalHandlerId>>,
         #[template_child]
         pub menu_btn: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub membership_info: TemplateChild<gtk::Label>,
     }
 
     #[glib::object_subclass]
@@ -117,10 +123,77 @@ impl MemberRow {
             }
         }
 
+        if let Some(member) = imp.member.take() {
+            for handler in imp.member_handlers.take() {
+                member.disconnect(handler);
+            }
+        }
+
+        if let Some(member) = &member {
+            let membership_handler = member.connect_notify_local(
+                Some("membership"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_membership_info();
+                }),
+            );
+            let reason_handler = member.connect_notify_local(
+                Some("membership-reason"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_membership_info();
+                }),
+            );
+            imp.member_handlers
+                .replace(vec![membership_handler, reason_handler]);
+        }
+
         imp.member.replace(member);
+        self.update_membership_info();
         self.notify("member");
     }
 
+    /// Update the information about the membership of the member.
+    ///
+    /// For now, only the details of bans are shown.
+    fn update_membership_info(&self) {
+        let label = &*self.imp().membership_info;
+        let Some(member) = self.member().filter(|m| m.membership() == Membership::Ban) else {
+            label.set_visible(false);
+            return;
+        };
+
+        let sender_name = member
+            .membership_sender()
+            .map(|sender| sender.display_name());
+        let reason = member
+            .membership_reason()
+            .filter(|reason| !reason.is_empty());
+
+        let text = match (sender_name, reason) {
+            (Some(sender_name), Some(reason)) => gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', these
+                // are variable names.
+                "Banned by {user}: {reason}",
+                &[("user", &sender_name), ("reason", &reason)],
+            ),
+            (Some(sender_name), None) => gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "Banned by {user}",
+                &[("user", &sender_name)],
+            ),
+            (None, Some(reason)) => gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "Reason: {reason}",
+                &[("reason", &reason)],
+            ),
+            (None, None) => gettext("Banned"),
+        };
+
+        label.set_label(&text);
+        label.set_visible(true);
+    }
+
     fn member_menu(&self) -> Option<MemberMenu> {
         let member_page = self
             .ancestor(MemberPage::static_type())
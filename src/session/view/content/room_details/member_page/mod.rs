@@ -11,15 +11,33 @@ mod member_menu;
 mod members_list_view;
 
 use members_list_view::{ExtraLists, MembersListView, MembershipSubpageItem};
-use ruma::events::room::power_levels::PowerLevelAction;
+use ruma::events::{room::power_levels::PowerLevelAction, StateEventType};
 
 use self::member_menu::MemberMenu;
 use crate::{
+    gettext_f, ngettext_f,
     prelude::*,
-    session::model::{Member, Membership, PowerLevel, Room, User, UserActions},
-    spawn,
+    session::{
+        model::{Member, MemberRole, Membership, PowerLevel, Room, User, UserActions},
+        view::UserProfileDialog,
+    },
+    spawn, toast,
+    utils::or_expr,
 };
 
+/// A moderation action on a room member.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModerationAction {
+    /// Remove the member from the room.
+    Kick,
+    /// Remove the member from the room and prevent them from joining again.
+    Ban,
+    /// Allow a banned member to join the room again.
+    Unban,
+    /// Deny the request of the member to join the room.
+    DenyKnock,
+}
+
 mod imp {
     use std::{
         cell::{Cell, RefCell},
@@ -47,6 +65,8 @@ mod imp {
         pub list_stack_children: RefCell<HashMap<Membership, glib::WeakRef<MembersListView>>>,
         pub state: Cell<Membership>,
         pub invite_action_watch: RefCell<Option<gtk::ExpressionWatch>>,
+        pub knocks_watch: RefCell<Option<gtk::ExpressionWatch>>,
+        pub ignored_users_handler: RefCell<Option<glib::SignalHandlerId>>,
     }
 
     #[glib::object_subclass]
@@ -58,6 +78,12 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
 
+            klass.install_action("member.view-profile", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.view_member_profile(member);
+                }
+            });
+
             klass.install_action("member.verify", None, move |widget, _, _| {
                 if let Some(member) = widget.member_menu().member() {
                     widget.verify_member(member);
@@ -66,6 +92,72 @@ mod imp {
                 }
             });
 
+            klass.install_action("member.make-mod", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.change_member_power_level(member, MemberRole::Mod.power_level());
+                }
+            });
+
+            klass.install_action("member.make-admin", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.change_member_power_level(member, MemberRole::Admin.power_level());
+                }
+            });
+
+            klass.install_action("member.make-normal", None, move |widget, _, _| {
+                let Some(member) = widget.member_menu().member() else {
+                    return;
+                };
+                let Some(room) = widget.room() else {
+                    return;
+                };
+
+                let users_default = room.power_levels().content().users_default;
+                widget.change_member_power_level(member, users_default.into());
+            });
+
+            klass.install_action("member.kick", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.moderate_member(member, ModerationAction::Kick);
+                }
+            });
+
+            klass.install_action("member.ban", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.moderate_member(member, ModerationAction::Ban);
+                }
+            });
+
+            klass.install_action("member.unban", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.moderate_member(member, ModerationAction::Unban);
+                }
+            });
+
+            klass.install_action("member.accept-knock", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.accept_knock(member);
+                }
+            });
+
+            klass.install_action("member.deny-knock", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.deny_knock(member);
+                }
+            });
+
+            klass.install_action("member.ignore", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.set_member_ignored(member, true);
+                }
+            });
+
+            klass.install_action("member.unignore", None, move |widget, _, _| {
+                if let Some(member) = widget.member_menu().member() {
+                    widget.set_member_ignored(member, false);
+                }
+            });
+
             klass.install_action("members.subpage", Some("u"), move |widget, _, param| {
                 let state = param.and_then(|variant| variant.get::<Membership>());
 
@@ -135,6 +227,15 @@ mod imp {
             if let Some(invite_action) = self.invite_action_watch.take() {
                 invite_action.unwatch();
             }
+            if let Some(watch) = self.knocks_watch.take() {
+                watch.unwatch();
+            }
+
+            if let Some(room) = self.room.upgrade() {
+                if let Some(handler) = self.ignored_users_handler.take() {
+                    room.session().ignored_users().disconnect(handler);
+                }
+            }
         }
     }
 
@@ -164,11 +265,24 @@ impl MemberPage {
         if let Some(invite_action) = imp.invite_action_watch.take() {
             invite_action.unwatch();
         }
+        if let Some(watch) = imp.knocks_watch.take() {
+            watch.unwatch();
+        }
 
         self.init_members_list(room);
         self.init_invite_button(room);
         self.set_state(Membership::Join);
 
+        let ignored_users_handler =
+            room.session()
+                .ignored_users()
+                .connect_changed(clone!(@weak self as obj => move |_| {
+                    let allowed_actions = obj.member_menu().allowed_actions();
+                    obj.update_actions(allowed_actions);
+                }));
+        imp.ignored_users_handler
+            .replace(Some(ignored_users_handler));
+
         imp.room.set(Some(room));
         self.notify("room");
     }
@@ -194,13 +308,24 @@ impl MemberPage {
         let members = gtk::SortListModel::new(Some(room.get_or_create_members()), Some(sorter));
 
         let joined_members = self.build_filtered_list(members.clone(), Membership::Join);
+        let knocked_members = self.build_filtered_list(members.clone(), Membership::Knock);
         let invited_members = self.build_filtered_list(members.clone(), Membership::Invite);
         let banned_members = self.build_filtered_list(members, Membership::Ban);
 
         let extra_list = ExtraLists::new(
+            &MembershipSubpageItem::new(Membership::Knock, &knocked_members),
             &MembershipSubpageItem::new(Membership::Invite, &invited_members),
             &MembershipSubpageItem::new(Membership::Ban, &banned_members),
         );
+        // Requests to join can only be handled by members who can invite or
+        // kick.
+        let can_handle_knocks = or_expr(
+            room.own_user_is_allowed_to_expr(PowerLevelAction::Invite),
+            room.own_user_is_allowed_to_expr(PowerLevelAction::Kick),
+        );
+        let knocks_watch =
+            can_handle_knocks.bind(&extra_list, "can-handle-knocks", glib::Object::NONE);
+        imp.knocks_watch.replace(Some(knocks_watch));
         let model_list = gio::ListStore::builder()
             .item_type(gio::ListModel::static_type())
             .build();
@@ -213,6 +338,9 @@ impl MemberPage {
         let joined_view = MembersListView::new(&main_list);
         imp.list_stack.add_child(&joined_view);
         list_stack_children.insert(Membership::Join, joined_view.downgrade());
+        let knocked_view = MembersListView::new(&knocked_members);
+        imp.list_stack.add_child(&knocked_view);
+        list_stack_children.insert(Membership::Knock, knocked_view.downgrade());
         let invited_view = MembersListView::new(&invited_members);
         imp.list_stack.add_child(&invited_view);
         list_stack_children.insert(Membership::Invite, invited_view.downgrade());
@@ -242,6 +370,375 @@ impl MemberPage {
             "member.verify",
             allowed_actions.contains(UserActions::VERIFY),
         );
+
+        // The menu is not initialized yet when this is called for the first
+        // time.
+        let member = self.imp().member_menu.get().and_then(|menu| menu.member());
+        let (can_make_mod, can_make_admin, can_make_normal) = self
+            .room()
+            .zip(member)
+            .map(|(room, member)| {
+                let power_levels = room.power_levels();
+                let own_user_id = room.session().user().unwrap().user_id();
+
+                if !power_levels.member_is_allowed_to(
+                    &own_user_id,
+                    PowerLevelAction::SendState(StateEventType::RoomPowerLevels),
+                ) {
+                    return (false, false, false);
+                }
+
+                // We can only change our own power level, or the power level of members
+                // below us.
+                let own_power_level = power_levels.user_power_level(&own_user_id);
+                let power_level = member.power_level();
+                if member.user_id() != own_user_id && power_level >= own_power_level {
+                    return (false, false, false);
+                }
+
+                let users_default = PowerLevel::from(power_levels.content().users_default);
+                (
+                    !member.is_mod() && MemberRole::Mod.power_level() <= own_power_level,
+                    !member.is_admin() && MemberRole::Admin.power_level() <= own_power_level,
+                    power_level > users_default,
+                )
+            })
+            .unwrap_or_default();
+
+        self.action_set_enabled("member.make-mod", can_make_mod);
+        self.action_set_enabled("member.make-admin", can_make_admin);
+        self.action_set_enabled("member.make-normal", can_make_normal);
+
+        let member = self.imp().member_menu.get().and_then(|menu| menu.member());
+        let (can_kick, can_ban, can_unban) = self
+            .room()
+            .zip(member)
+            .map(|(room, member)| {
+                let power_levels = room.power_levels();
+                let own_user_id = room.session().user().unwrap().user_id();
+
+                // We can only moderate members below us.
+                if member.user_id() == own_user_id
+                    || member.power_level() >= power_levels.user_power_level(&own_user_id)
+                {
+                    return (false, false, false);
+                }
+
+                let allowed_to_kick =
+                    power_levels.member_is_allowed_to(&own_user_id, PowerLevelAction::Kick);
+                let allowed_to_ban =
+                    power_levels.member_is_allowed_to(&own_user_id, PowerLevelAction::Ban);
+                let membership = member.membership();
+
+                (
+                    allowed_to_kick && matches!(membership, Membership::Join | Membership::Invite),
+                    allowed_to_ban && membership != Membership::Ban,
+                    allowed_to_kick && allowed_to_ban && membership == Membership::Ban,
+                )
+            })
+            .unwrap_or_default();
+
+        self.action_set_enabled("member.kick", can_kick);
+        self.action_set_enabled("member.ban", can_ban);
+        self.action_set_enabled("member.unban", can_unban);
+
+        let member = self.imp().member_menu.get().and_then(|menu| menu.member());
+        let (can_accept_knock, can_deny_knock) = self
+            .room()
+            .zip(member)
+            .filter(|(_, member)| member.membership() == Membership::Knock)
+            .map(|(room, member)| {
+                let power_levels = room.power_levels();
+                let own_user_id = room.session().user().unwrap().user_id();
+
+                let allowed_to_invite =
+                    power_levels.member_is_allowed_to(&own_user_id, PowerLevelAction::Invite);
+                // Denying a request to join is kicking the member, so it is only allowed
+                // for members below us.
+                let allowed_to_kick = member.power_level()
+                    < power_levels.user_power_level(&own_user_id)
+                    && power_levels.member_is_allowed_to(&own_user_id, PowerLevelAction::Kick);
+
+                (allowed_to_invite, allowed_to_kick)
+            })
+            .unwrap_or_default();
+
+        self.action_set_enabled("member.accept-knock", can_accept_knock);
+        self.action_set_enabled("member.deny-knock", can_deny_knock);
+
+        let member = self.imp().member_menu.get().and_then(|menu| menu.member());
+        let is_ignored = self.room().zip(member).and_then(|(room, member)| {
+            let session = room.session();
+            let user_id = member.user_id();
+
+            if session.user().unwrap().user_id() == user_id {
+                return None;
+            }
+
+            Some(session.ignored_users().contains(&user_id))
+        });
+
+        self.action_set_enabled("member.ignore", is_ignored == Some(false));
+        self.action_set_enabled("member.unignore", is_ignored == Some(true));
+    }
+
+    /// Ignore or stop ignoring the given member.
+    fn set_member_ignored(&self, member: Member, ignored: bool) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        self.member_menu().close_popover();
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let session = room.session();
+            let ignored_users = session.ignored_users();
+
+            if ignored {
+                if ignored_users.add(member.user_id()).await.is_err() {
+                    toast!(obj, gettext("Could not ignore the user"));
+                }
+            } else if ignored_users.remove(&member.user_id()).await.is_err() {
+                toast!(obj, gettext("Could not stop ignoring the user"));
+            }
+        }));
+    }
+
+    /// Accept the request of the given member to join the room, by inviting
+    /// them.
+    fn accept_knock(&self, member: Member) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        self.member_menu().close_popover();
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let user = member.upcast::<User>();
+            if room.invite(&[user]).await.is_err() {
+                toast!(obj, gettext("Could not accept the request to join"));
+            }
+        }));
+    }
+
+    /// Deny the request of the given member to join the room, after asking
+    /// for a reason.
+    fn deny_knock(&self, member: Member) {
+        self.moderate_member(member, ModerationAction::DenyKnock);
+    }
+
+    /// Kick, ban or unban the given member, after asking for a reason.
+    fn moderate_member(&self, member: Member, action: ModerationAction) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        self.member_menu().close_popover();
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let Some((reason, remove_messages)) = obj.ask_moderation_reason(&member, action).await else {
+                return;
+            };
+
+            let user_id = member.user_id();
+            let result = match action {
+                ModerationAction::Kick | ModerationAction::DenyKnock => {
+                    room.kick_user(user_id.clone(), reason.clone()).await
+                }
+                ModerationAction::Ban => room.ban_user(user_id.clone(), reason.clone()).await,
+                ModerationAction::Unban => room.unban_user(user_id.clone(), reason.clone()).await,
+            };
+
+            if result.is_err() {
+                let error = match action {
+                    ModerationAction::Kick => gettext("Could not kick the member"),
+                    ModerationAction::Ban => gettext("Could not ban the member"),
+                    ModerationAction::Unban => gettext("Could not unban the member"),
+                    ModerationAction::DenyKnock => gettext("Could not deny the request to join"),
+                };
+                toast!(obj, error);
+                return;
+            }
+
+            if remove_messages {
+                let n = room.redact_loaded_events_of(&user_id, reason);
+                toast!(
+                    obj,
+                    ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}', this
+                        // is a variable name.
+                        "Removing 1 message",
+                        "Removing {n} messages",
+                        n as u32,
+                        &[("n", &n.to_string())],
+                    )
+                );
+            }
+        }));
+    }
+
+    /// Ask the user to confirm the given moderation action on the member.
+    ///
+    /// Returns `None` if the user cancelled, otherwise the reason entered by
+    /// the user, if any, and whether the recent messages of the member should
+    /// be removed.
+    async fn ask_moderation_reason(
+        &self,
+        member: &Member,
+        action: ModerationAction,
+    ) -> Option<(Option<String>, bool)> {
+        let name = member.display_name();
+        let (heading, body, response) = match action {
+            ModerationAction::Kick => (
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "Kick {user}?",
+                    &[("user", &name)],
+                ),
+                gettext("They will be able to join the room again if they are invited or if the room is public."),
+                gettext("_Kick"),
+            ),
+            ModerationAction::Ban => (
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "Ban {user}?",
+                    &[("user", &name)],
+                ),
+                gettext("They will not be able to join the room again until they are unbanned."),
+                gettext("_Ban"),
+            ),
+            ModerationAction::Unban => (
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "Unban {user}?",
+                    &[("user", &name)],
+                ),
+                gettext("They will be able to join the room again."),
+                gettext("_Unban"),
+            ),
+            ModerationAction::DenyKnock => (
+                gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "Deny the Request of {user}?",
+                    &[("user", &name)],
+                ),
+                gettext("They will not be invited to the room but will be able to ask again."),
+                gettext("_Deny"),
+            ),
+        };
+
+        let reason_entry = gtk::Entry::builder()
+            .placeholder_text(gettext("Reason (optional)"))
+            .activates_default(true)
+            .build();
+        let can_redact = member.room().is_some_and(|room| {
+            room.session().user().is_some_and(|user| {
+                room.power_levels()
+                    .member_is_allowed_to(&user.user_id(), PowerLevelAction::Redact)
+            })
+        });
+        let remove_messages_check = gtk::CheckButton::builder()
+            .label(gettext("Remove recent messages"))
+            .tooltip_text(gettext(
+                "Remove the messages of this member that are loaded in the room history",
+            ))
+            .visible(action == ModerationAction::Ban && can_redact)
+            .build();
+
+        let extra_child = gtk::Box::new(gtk::Orientation::Vertical, 12);
+        extra_child.append(&reason_entry);
+        extra_child.append(&remove_messages_check);
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(heading)
+            .body(body)
+            .extra_child(&extra_child)
+            .default_response("confirm")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("confirm", &response)]);
+
+        let appearance = if action == ModerationAction::Unban {
+            adw::ResponseAppearance::Suggested
+        } else {
+            adw::ResponseAppearance::Destructive
+        };
+        dialog.set_response_appearance("confirm", appearance);
+
+        if dialog.choose_future().await != "confirm" {
+            return None;
+        }
+
+        let reason = Some(reason_entry.text().trim().to_owned()).filter(|r| !r.is_empty());
+        let remove_messages =
+            remove_messages_check.is_visible() && remove_messages_check.is_active();
+        Some((reason, remove_messages))
+    }
+
+    /// Change the power level of the given member.
+    ///
+    /// Asks for confirmation if our own user is demoted.
+    fn change_member_power_level(&self, member: Member, power_level: PowerLevel) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        self.member_menu().close_popover();
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let own_user_id = room.session().user().unwrap().user_id();
+            if member.user_id() == own_user_id
+                && power_level < member.power_level()
+                && !obj.confirm_own_demotion(&room).await
+            {
+                return;
+            }
+
+            if room
+                .set_member_power_level(member.user_id(), power_level)
+                .await
+                .is_err()
+            {
+                toast!(obj, gettext("Could not change the role of the member"));
+            }
+        }));
+    }
+
+    /// Ask the user to confirm that they want to demote themselves.
+    ///
+    /// Returns `true` if the user confirmed.
+    async fn confirm_own_demotion(&self, room: &Room) -> bool {
+        let own_user_id = room.session().user().unwrap().user_id();
+
+        let body = if room.power_levels().is_last_admin(&own_user_id) {
+            gettext("You are the last admin of this room. Nobody will be able to get admin rights in this room again.")
+        } else {
+            gettext("You will not be able to undo this change because you are demoting yourself.")
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Demote Yourself?"))
+            .body(body)
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("demote", &gettext("_Demote")),
+        ]);
+        dialog.set_response_appearance("demote", adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future().await == "demote"
+    }
+
+    /// Show the profile of the given member.
+    fn view_member_profile(&self, member: Member) {
+        let dialog = UserProfileDialog::new(self.root().and_downcast_ref(), &member);
+        dialog.present();
     }
 
     fn verify_member(&self, member: Member) {
@@ -274,6 +771,7 @@ impl MemberPage {
 
         if let Some(window) = self.root().and_downcast::<adw::Window>() {
             match state {
+                Membership::Knock => window.set_title(Some(&gettext("Requests to Join"))),
                 Membership::Invite => window.set_title(Some(&gettext("Invited Room Members"))),
                 Membership::Ban => window.set_title(Some(&gettext("Banned Room Members"))),
                 _ => window.set_title(Some(&gettext("Room Members"))),
@@ -2,6 +2,8 @@ mod general_page;
 mod history_viewer;
 mod invite_subpage;
 mod member_page;
+mod permissions_subpage;
+mod settings_subpage;
 
 use std::convert::From;
 
@@ -13,6 +15,8 @@ pub use self::{
     history_viewer::{AudioHistoryViewer, FileHistoryViewer, MediaHistoryViewer},
     invite_subpage::InviteSubpage,
     member_page::MemberPage,
+    permissions_subpage::PermissionsSubpage,
+    settings_subpage::SettingsSubpage,
 };
 use crate::session::model::Room;
 
@@ -23,6 +27,8 @@ pub enum SubpageName {
     MediaHistory,
     FileHistory,
     AudioHistory,
+    Settings,
+    Permissions,
 }
 
 impl glib::variant::StaticVariantType for SubpageName {
@@ -39,6 +45,8 @@ impl glib::variant::FromVariant for SubpageName {
             "media-history" => Some(Self::MediaHistory),
             "file-history" => Some(Self::FileHistory),
             "audio-history" => Some(Self::AudioHistory),
+            "settings" => Some(Self::Settings),
+            "permissions" => Some(Self::Permissions),
             _ => None,
         }
     }
@@ -165,6 +173,8 @@ impl RoomDetails {
             SubpageName::MediaHistory => MediaHistoryViewer::new(room).upcast(),
             SubpageName::FileHistory => FileHistoryViewer::new(room).upcast(),
             SubpageName::AudioHistory => AudioHistoryViewer::new(room).upcast(),
+            SubpageName::Settings => SettingsSubpage::new(room).upcast(),
+            SubpageName::Permissions => PermissionsSubpage::new(room).upcast(),
         });
 
         if is_initial {
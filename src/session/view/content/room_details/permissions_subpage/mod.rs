@@ -0,0 +1,411 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use ruma::{
+    events::{
+        room::power_levels::{PowerLevelAction, RoomPowerLevels, RoomPowerLevelsEventContent},
+        StateEventType, TimelineEventType,
+    },
+    Int,
+};
+
+use crate::{
+    components::SpinnerButton,
+    session::model::{PowerLevel, Room, POWER_LEVEL_MAX, POWER_LEVEL_MIN},
+    spawn, toast,
+};
+
+/// The state event types that are always listed, with their description.
+fn known_event_types() -> Vec<(TimelineEventType, String)> {
+    vec![
+        (TimelineEventType::RoomName, gettext("Change the room name")),
+        (
+            TimelineEventType::RoomTopic,
+            gettext("Change the room topic"),
+        ),
+        (
+            TimelineEventType::RoomAvatar,
+            gettext("Change the room avatar"),
+        ),
+        (
+            TimelineEventType::RoomCanonicalAlias,
+            gettext("Change the main address of the room"),
+        ),
+        (
+            TimelineEventType::RoomJoinRules,
+            gettext("Change who can join the room"),
+        ),
+        (
+            TimelineEventType::RoomHistoryVisibility,
+            gettext("Change who can read the history"),
+        ),
+        (
+            TimelineEventType::RoomEncryption,
+            gettext("Enable encryption"),
+        ),
+        (
+            TimelineEventType::RoomPowerLevels,
+            gettext("Change permissions"),
+        ),
+        (
+            TimelineEventType::RoomServerAcl,
+            gettext("Change server access"),
+        ),
+        (
+            TimelineEventType::RoomTombstone,
+            gettext("Upgrade the room"),
+        ),
+    ]
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{signal::SignalHandlerId, subclass::InitializingObject};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_details/permissions_subpage/mod.ui"
+    )]
+    pub struct PermissionsSubpage {
+        pub room: glib::WeakRef<Room>,
+        #[template_child]
+        pub save_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub users_default_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub events_default_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub state_default_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub invite_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub kick_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub ban_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub redact_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub notify_room_row: TemplateChild<adw::SpinRow>,
+        #[template_child]
+        pub events_group: TemplateChild<adw::PreferencesGroup>,
+        /// The rows for the power levels of event types, with the power level
+        /// they were showing when they were last updated.
+        pub event_rows: RefCell<Vec<(TimelineEventType, adw::SpinRow, PowerLevel)>>,
+        /// Whether the rows are being updated from the power levels of the
+        /// room.
+        pub is_updating: Cell<bool>,
+        pub power_levels_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PermissionsSubpage {
+        const NAME: &'static str = "ContentRoomPermissionsSubpage";
+        type Type = super::PermissionsSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PermissionsSubpage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("room")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "room" => self.obj().set_room(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "room" => self.obj().room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            for row in obj.default_rows() {
+                obj.setup_row(&row);
+            }
+
+            self.save_button
+                .connect_clicked(clone!(@weak obj => move |_| {
+                    obj.save();
+                }));
+
+            obj.update_rows();
+        }
+
+        fn dispose(&self) {
+            if let Some(room) = self.room.upgrade() {
+                if let Some(handler_id) = self.power_levels_handler.take() {
+                    room.power_levels().disconnect(handler_id);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for PermissionsSubpage {}
+    impl NavigationPageImpl for PermissionsSubpage {}
+}
+
+glib::wrapper! {
+    /// Subpage to edit the power levels of a room.
+    pub struct PermissionsSubpage(ObjectSubclass<imp::PermissionsSubpage>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+impl PermissionsSubpage {
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+
+    /// The room to edit the power levels of.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// Set the room to edit the power levels of.
+    fn set_room(&self, room: Option<Room>) {
+        let Some(room) = room else {
+            return;
+        };
+        let imp = self.imp();
+
+        let power_levels_handler = room.power_levels().connect_notify_local(
+            Some("power-levels"),
+            clone!(@weak self as obj => move |_, _| {
+                // Do not discard the unsaved changes.
+                if !obj.has_unsaved_changes() {
+                    obj.update_rows();
+                }
+            }),
+        );
+        imp.power_levels_handler.replace(Some(power_levels_handler));
+
+        imp.room.set(Some(&room));
+    }
+
+    /// The rows for the default power levels and the moderation actions.
+    fn default_rows(&self) -> [adw::SpinRow; 8] {
+        let imp = self.imp();
+
+        [
+            imp.users_default_row.clone(),
+            imp.events_default_row.clone(),
+            imp.state_default_row.clone(),
+            imp.invite_row.clone(),
+            imp.kick_row.clone(),
+            imp.ban_row.clone(),
+            imp.redact_row.clone(),
+            imp.notify_room_row.clone(),
+        ]
+    }
+
+    /// Whether some rows were edited and not saved yet.
+    fn has_unsaved_changes(&self) -> bool {
+        let save_button = &self.imp().save_button;
+        save_button.is_sensitive() || save_button.loading()
+    }
+
+    /// Prepare the given row to edit a power level.
+    fn setup_row(&self, row: &adw::SpinRow) {
+        row.set_adjustment(Some(&gtk::Adjustment::new(
+            0.0,
+            POWER_LEVEL_MIN as f64,
+            POWER_LEVEL_MAX as f64,
+            1.0,
+            10.0,
+            0.0,
+        )));
+        row.connect_notify_local(
+            Some("value"),
+            clone!(@weak self as obj => move |_, _| {
+                if !obj.imp().is_updating.get() {
+                    obj.imp().save_button.set_sensitive(true);
+                }
+            }),
+        );
+    }
+
+    /// Update the rows with the current power levels of the room.
+    fn update_rows(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let imp = self.imp();
+        let content = room.power_levels().content();
+
+        imp.is_updating.set(true);
+
+        imp.users_default_row
+            .set_value(PowerLevel::from(content.users_default) as f64);
+        imp.events_default_row
+            .set_value(PowerLevel::from(content.events_default) as f64);
+        imp.state_default_row
+            .set_value(PowerLevel::from(content.state_default) as f64);
+        imp.invite_row
+            .set_value(PowerLevel::from(content.invite) as f64);
+        imp.kick_row
+            .set_value(PowerLevel::from(content.kick) as f64);
+        imp.ban_row.set_value(PowerLevel::from(content.ban) as f64);
+        imp.redact_row
+            .set_value(PowerLevel::from(content.redact) as f64);
+        imp.notify_room_row
+            .set_value(PowerLevel::from(content.notifications.room) as f64);
+
+        // Rebuild the rows of the event types.
+        for (_, row, _) in imp.event_rows.take() {
+            imp.events_group.remove(&row);
+        }
+
+        let mut event_types = known_event_types();
+        for event_type in content.events.keys() {
+            if !event_types.iter().any(|(known, _)| known == event_type) {
+                event_types.push((event_type.clone(), event_type.to_string()));
+            }
+        }
+
+        let mut event_rows = Vec::with_capacity(event_types.len());
+        for (event_type, title) in event_types {
+            let power_level = content
+                .events
+                .get(&event_type)
+                .copied()
+                .unwrap_or(content.state_default)
+                .into();
+
+            let row = adw::SpinRow::builder().title(title).build();
+            self.setup_row(&row);
+            row.set_value(power_level as f64);
+            imp.events_group.add(&row);
+
+            event_rows.push((event_type, row, power_level));
+        }
+        imp.event_rows.replace(event_rows);
+
+        // Only allow to edit the power levels if our own user is allowed to.
+        let own_user_id = room.session().user().unwrap().user_id();
+        let can_edit = room.power_levels().member_is_allowed_to(
+            &own_user_id,
+            PowerLevelAction::SendState(StateEventType::RoomPowerLevels),
+        );
+        for row in self.default_rows() {
+            row.set_sensitive(can_edit);
+        }
+        for (_, row, _) in imp.event_rows.borrow().iter() {
+            row.set_sensitive(can_edit);
+        }
+        imp.save_button.set_visible(can_edit);
+        imp.save_button.set_sensitive(false);
+
+        imp.is_updating.set(false);
+    }
+
+    /// Send the edited power levels to the homeserver.
+    fn save(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let imp = self.imp();
+
+        let mut content = room.power_levels().content();
+        self.apply_row_values(&mut content);
+
+        imp.save_button.set_loading(true);
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            // Warn the user if they are about to lose the ability to edit the
+            // power levels.
+            let own_user_id = room.session().user().unwrap().user_id();
+            let can_still_edit = RoomPowerLevels::from(content.clone()).user_can_do(
+                &own_user_id,
+                PowerLevelAction::SendState(StateEventType::RoomPowerLevels),
+            );
+            if !can_still_edit && !obj.confirm_lose_permission().await {
+                obj.imp().save_button.set_loading(false);
+                return;
+            }
+
+            let result = room.set_power_levels(content).await;
+            obj.imp().save_button.set_loading(false);
+
+            if result.is_err() {
+                toast!(obj, gettext("Could not change the permissions of the room"));
+            } else {
+                // The changes from the homeserver were ignored while there
+                // were unsaved changes.
+                obj.update_rows();
+            }
+        }));
+    }
+
+    /// Ask the user to confirm that they want to lose the permission to edit
+    /// the power levels.
+    ///
+    /// Returns `true` if the user confirmed.
+    async fn confirm_lose_permission(&self) -> bool {
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Lose Permission?"))
+            .body(gettext("With these changes, you will not be able to change the permissions of this room anymore. You will not be able to undo them."))
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("save", &gettext("_Save Anyway")),
+        ]);
+        dialog.set_response_appearance("save", adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future().await == "save"
+    }
+
+    /// Apply the values of the rows to the given content.
+    fn apply_row_values(&self, content: &mut RoomPowerLevelsEventContent) {
+        let imp = self.imp();
+
+        // Only add the event types whose power level was changed, to avoid
+        // filling the event with the default values.
+        for (event_type, row, previous_power_level) in imp.event_rows.borrow().iter() {
+            let power_level = row.value() as PowerLevel;
+            if power_level != *previous_power_level {
+                content
+                    .events
+                    .insert(event_type.clone(), Int::new_saturating(power_level));
+            }
+        }
+
+        let value = |row: &adw::SpinRow| Int::new_saturating(row.value() as PowerLevel);
+        content.users_default = value(&imp.users_default_row);
+        content.events_default = value(&imp.events_default_row);
+        content.state_default = value(&imp.state_default_row);
+        content.invite = value(&imp.invite_row);
+        content.kick = value(&imp.kick_row);
+        content.ban = value(&imp.ban_row);
+        content.redact = value(&imp.redact_row);
+        content.notifications.room = value(&imp.notify_room_row);
+    }
+}
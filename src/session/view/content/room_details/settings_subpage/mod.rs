@@ -0,0 +1,364 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use ruma::events::{
+    room::{
+        guest_access::GuestAccess,
+        history_visibility::HistoryVisibility,
+        join_rules::{AllowRule, JoinRule, Restricted},
+        power_levels::PowerLevelAction,
+    },
+    StateEventType,
+};
+
+use crate::{session::model::Room, spawn, toast};
+
+/// The history visibilities that can be selected, in the order of the
+/// history visibility row.
+const HISTORY_VISIBILITIES: &[HistoryVisibility] = &[
+    HistoryVisibility::WorldReadable,
+    HistoryVisibility::Shared,
+    HistoryVisibility::Invited,
+    HistoryVisibility::Joined,
+];
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{signal::SignalHandlerId, subclass::InitializingObject};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_details/settings_subpage/mod.ui"
+    )]
+    pub struct SettingsSubpage {
+        pub room: glib::WeakRef<Room>,
+        #[template_child]
+        pub join_rule_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub guest_access_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub history_visibility_row: TemplateChild<adw::ComboRow>,
+        #[template_child]
+        pub encryption_row: TemplateChild<adw::SwitchRow>,
+        /// Whether the rows are being updated from the state of the room.
+        ///
+        /// Used to avoid sending the values back to the homeserver.
+        pub is_updating: Cell<bool>,
+        pub access_handler: RefCell<Option<SignalHandlerId>>,
+        pub encrypted_handler: RefCell<Option<SignalHandlerId>>,
+        pub power_levels_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SettingsSubpage {
+        const NAME: &'static str = "ContentRoomSettingsSubpage";
+        type Type = super::SettingsSubpage;
+        type ParentType = adw::NavigationPage;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SettingsSubpage {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("room")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "room" => self.obj().set_room(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "room" => self.obj().room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            self.join_rule_row
+                .connect_selected_notify(clone!(@weak obj => move |row| {
+                    if !obj.imp().is_updating.get() {
+                        obj.change_join_rule(row.selected());
+                    }
+                }));
+            self.guest_access_row
+                .connect_active_notify(clone!(@weak obj => move |row| {
+                    if !obj.imp().is_updating.get() {
+                        obj.change_guest_access(row.is_active());
+                    }
+                }));
+            self.history_visibility_row
+                .connect_selected_notify(clone!(@weak obj => move |row| {
+                    if !obj.imp().is_updating.get() {
+                        obj.change_history_visibility(row.selected());
+                    }
+                }));
+            self.encryption_row
+                .connect_active_notify(clone!(@weak obj => move |row| {
+                    if !obj.imp().is_updating.get() && row.is_active() {
+                        obj.enable_encryption();
+                    }
+                }));
+
+            obj.update_rows();
+        }
+
+        fn dispose(&self) {
+            if let Some(room) = self.room.upgrade() {
+                if let Some(handler_id) = self.access_handler.take() {
+                    room.disconnect(handler_id);
+                }
+                if let Some(handler_id) = self.encrypted_handler.take() {
+                    room.disconnect(handler_id);
+                }
+                if let Some(handler_id) = self.power_levels_handler.take() {
+                    room.power_levels().disconnect(handler_id);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for SettingsSubpage {}
+    impl NavigationPageImpl for SettingsSubpage {}
+}
+
+glib::wrapper! {
+    /// Subpage to edit the access and encryption settings of a room.
+    pub struct SettingsSubpage(ObjectSubclass<imp::SettingsSubpage>)
+        @extends gtk::Widget, adw::NavigationPage, @implements gtk::Accessible;
+}
+
+impl SettingsSubpage {
+    pub fn new(room: &Room) -> Self {
+        glib::Object::builder().property("room", room).build()
+    }
+
+    /// The room to edit the settings of.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// Set the room to edit the settings of.
+    fn set_room(&self, room: Option<Room>) {
+        let Some(room) = room else {
+            return;
+        };
+        let imp = self.imp();
+
+        let access_handler = room.connect_access_changed(clone!(@weak self as obj => move |_| {
+            obj.update_rows();
+        }));
+        imp.access_handler.replace(Some(access_handler));
+
+        let encrypted_handler = room.connect_notify_local(
+            Some("encrypted"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.update_rows();
+            }),
+        );
+        imp.encrypted_handler.replace(Some(encrypted_handler));
+
+        let power_levels_handler = room.power_levels().connect_notify_local(
+            Some("power-levels"),
+            clone!(@weak self as obj => move |_, _| {
+                obj.update_rows();
+            }),
+        );
+        imp.power_levels_handler.replace(Some(power_levels_handler));
+
+        imp.room.set(Some(&room));
+    }
+
+    /// Whether our own user is allowed to send the given state event in the
+    /// room.
+    fn can_send_state(&self, room: &Room, event_type: StateEventType) -> bool {
+        let user_id = room.session().user().unwrap().user_id();
+        room.power_levels()
+            .member_is_allowed_to(&user_id, PowerLevelAction::SendState(event_type))
+    }
+
+    /// Update the rows with the current state of the room.
+    fn update_rows(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let imp = self.imp();
+
+        imp.is_updating.set(true);
+
+        // The positions must match the order of the join rule row.
+        let position = match room.join_rule() {
+            JoinRule::Restricted(_) => 1,
+            JoinRule::Knock => 2,
+            JoinRule::KnockRestricted(_) => 3,
+            JoinRule::Public => 4,
+            _ => 0,
+        };
+        imp.join_rule_row.set_selected(position);
+        imp.join_rule_row
+            .set_sensitive(self.can_send_state(&room, StateEventType::RoomJoinRules));
+
+        imp.guest_access_row
+            .set_active(room.guest_access() == GuestAccess::CanJoin);
+        imp.guest_access_row
+            .set_sensitive(self.can_send_state(&room, StateEventType::RoomGuestAccess));
+
+        let position = HISTORY_VISIBILITIES
+            .iter()
+            .position(|visibility| *visibility == room.history_visibility())
+            .unwrap_or(1);
+        imp.history_visibility_row.set_selected(position as u32);
+        imp.history_visibility_row
+            .set_sensitive(self.can_send_state(&room, StateEventType::RoomHistoryVisibility));
+
+        // Encryption cannot be disabled.
+        let is_encrypted = room.is_encrypted();
+        imp.encryption_row.set_active(is_encrypted);
+        imp.encryption_row.set_sensitive(
+            !is_encrypted && self.can_send_state(&room, StateEventType::RoomEncryption),
+        );
+
+        imp.is_updating.set(false);
+    }
+
+    /// Change the join rule of the room to the one at the given position.
+    fn change_join_rule(&self, position: u32) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let join_rule = match position {
+            1 | 3 => {
+                let allow = room
+                    .parent_spaces()
+                    .iter()
+                    .map(|space| AllowRule::room_membership(space.room_id().to_owned()))
+                    .collect::<Vec<_>>();
+
+                if allow.is_empty() {
+                    toast!(self, gettext("This room is not part of any space"));
+                    self.update_rows();
+                    return;
+                }
+
+                if position == 1 {
+                    JoinRule::Restricted(Restricted::new(allow))
+                } else {
+                    JoinRule::KnockRestricted(Restricted::new(allow))
+                }
+            }
+            2 => JoinRule::Knock,
+            4 => JoinRule::Public,
+            _ => JoinRule::Invite,
+        };
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if room.set_join_rule(join_rule).await.is_err() {
+                toast!(obj, gettext("Could not change who can join the room"));
+                obj.update_rows();
+            }
+        }));
+    }
+
+    /// Change whether guests can join the room.
+    fn change_guest_access(&self, can_join: bool) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let guest_access = if can_join {
+            GuestAccess::CanJoin
+        } else {
+            GuestAccess::Forbidden
+        };
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if room.set_guest_access(guest_access).await.is_err() {
+                toast!(obj, gettext("Could not change the guest access of the room"));
+                obj.update_rows();
+            }
+        }));
+    }
+
+    /// Change the history visibility of the room to the one at the given
+    /// position.
+    fn change_history_visibility(&self, position: u32) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let Some(history_visibility) = HISTORY_VISIBILITIES.get(position as usize).cloned() else {
+            return;
+        };
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if room.set_history_visibility(history_visibility).await.is_err() {
+                toast!(obj, gettext("Could not change who can read the history of the room"));
+                obj.update_rows();
+            }
+        }));
+    }
+
+    /// Enable encryption in the room, after asking for confirmation.
+    fn enable_encryption(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        self.imp().encryption_row.set_sensitive(false);
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if !obj.confirm_enable_encryption().await {
+                obj.update_rows();
+                return;
+            }
+
+            if room.enable_encryption().await.is_err() {
+                toast!(obj, gettext("Could not enable encryption"));
+            }
+            obj.update_rows();
+        }));
+    }
+
+    /// Ask the user to confirm that they want to enable encryption.
+    ///
+    /// Returns `true` if the user confirmed.
+    async fn confirm_enable_encryption(&self) -> bool {
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Enable Encryption?"))
+            .body(gettext("Encryption cannot be disabled once it is enabled. Bridges and integrations might stop working."))
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .build();
+        dialog.set_transient_for(self.root().and_downcast_ref::<gtk::Window>());
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("enable", &gettext("_Enable")),
+        ]);
+        dialog.set_response_appearance("enable", adw::ResponseAppearance::Destructive);
+
+        dialog.choose_future().await == "enable"
+    }
+}
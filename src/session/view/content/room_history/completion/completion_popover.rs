@@ -14,10 +14,12 @@ use super::CompletionRow;
 use crate::{
     components::Pill,
     prelude::*,
-    session::model::{Member, MemberList, Membership},
+    session::model::{ImagePackItem, Member, MemberList, Membership, Session},
 };
 
 const MAX_MEMBERS: usize = 32;
+/// The minimum length of the search term to complete custom emoji.
+const MIN_EMOTICON_TERM_LEN: usize = 2;
 
 #[derive(Debug, Default)]
 pub struct MemberWatch {
@@ -32,7 +34,7 @@ mod imp {
         collections::HashMap,
     };
 
-    use glib::subclass::InitializingObject;
+    use glib::{subclass::InitializingObject, WeakRef};
     use once_cell::sync::{Lazy, OnceCell};
 
     use super::*;
@@ -54,6 +56,12 @@ mod imp {
         pub selected: Cell<Option<usize>>,
         /// The current autocompleted word.
         pub current_word: RefCell<Option<(gtk::TextIter, gtk::TextIter, String)>>,
+        /// Whether the current word is the shortcode of a custom emoji.
+        pub is_emoticon_search: Cell<bool>,
+        /// The session used to load the images of the custom emoji.
+        pub session: WeakRef<Session>,
+        /// The custom emoji that can be completed.
+        pub emoticons: RefCell<Vec<ImagePackItem>>,
         /// Whether the popover is inhibited for the current word.
         pub inhibit: Cell<bool>,
         /// The buffer to complete with its cursor position signal handler ID.
@@ -218,7 +226,6 @@ mod imp {
                     key_events.connect_key_pressed(clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, key, _, modifier| {
                         if modifier.is_empty() {
                             if obj.is_visible() {
-                                let imp = obj.imp();
                                 if matches!(key, gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::Tab) {
                                     // Activate completion.
                                     obj.activate_selected_row();
@@ -237,9 +244,7 @@ mod imp {
                                     } else {
                                         0
                                     };
-                                    let n_members = imp.filtered_members.n_items() as usize;
-                                    let max = MAX_MEMBERS.min(n_members);
-                                    if new_idx < max {
+                                    if new_idx < obj.count_visible_rows() {
                                         obj.select_row_at_index(Some(new_idx));
                                     }
                                     return glib::Propagation::Stop;
@@ -281,7 +286,8 @@ mod imp {
 }
 
 glib::wrapper! {
-    /// A popover to autocomplete Matrix IDs for its parent `gtk::TextView`.
+    /// A popover to autocomplete Matrix IDs and custom emoji for its parent
+    /// `gtk::TextView`.
     pub struct CompletionPopover(ObjectSubclass<imp::CompletionPopover>)
         @extends gtk::Widget, gtk::Popover;
 }
@@ -447,6 +453,20 @@ impl CompletionPopover {
         &self.imp().filtered_members
     }
 
+    /// The custom emoji that can be completed.
+    pub fn emoticons(&self) -> Vec<ImagePackItem> {
+        self.imp().emoticons.borrow().clone()
+    }
+
+    /// Set the custom emoji that can be completed.
+    ///
+    /// Their images are loaded with the given session.
+    pub fn set_emoticons(&self, session: &Session, emoticons: Vec<ImagePackItem>) {
+        let imp = self.imp();
+        imp.session.set(Some(session));
+        imp.emoticons.replace(emoticons);
+    }
+
     fn current_word(&self) -> Option<(gtk::TextIter, gtk::TextIter, String)> {
         self.imp().current_word.borrow().clone()
     }
@@ -464,14 +484,32 @@ impl CompletionPopover {
     /// If trigger is `true`, the search term will not look for `@` at the start
     /// of the word.
     fn update_completion(&self, trigger: bool) {
-        let search = self.find_search_term(trigger);
+        let imp = self.imp();
+
+        let (search, is_emoticon_search) = match self.find_emoticon_search_term() {
+            Some(search) => (Some(search), true),
+            None => {
+                if imp.is_emoticon_search.get() {
+                    // The word of the previous search is not a member search term.
+                    self.set_current_word(None);
+                }
+                (self.find_search_term(trigger), false)
+            }
+        };
 
         if self.is_inhibited() && search.is_none() {
-            self.imp().inhibit.set(false);
+            imp.inhibit.set(false);
         } else if !self.is_inhibited() {
+            imp.is_emoticon_search.set(is_emoticon_search);
+
             if let Some((start, end, term)) = search {
                 self.set_current_word(Some((start, end, term)));
-                self.search_members();
+
+                if is_emoticon_search {
+                    self.search_emoticons();
+                } else {
+                    self.search_members();
+                }
             } else {
                 self.popdown();
                 self.select_row_at_index(None);
@@ -480,6 +518,57 @@ impl CompletionPopover {
         }
     }
 
+    /// Find the current shortcode of a custom emoji in the underlying buffer.
+    ///
+    /// The shortcode must start with `:` at the start of a word.
+    ///
+    /// Returns the start and end of the shortcode, including the `:`, and the
+    /// term to search for.
+    fn find_emoticon_search_term(&self) -> Option<(gtk::TextIter, gtk::TextIter, String)> {
+        fn is_shortcode_char(c: char) -> bool {
+            c.is_alphanumeric() || matches!(c, '_' | '-' | '+')
+        }
+
+        if self.imp().emoticons.borrow().is_empty() {
+            return None;
+        }
+
+        let buffer = self.view().buffer();
+        let cursor = buffer.iter_at_mark(&buffer.get_insert());
+
+        let mut word_start = cursor;
+        while word_start.backward_cursor_position() {
+            if !is_shortcode_char(word_start.char()) {
+                break;
+            }
+        }
+
+        if word_start.char() != ':' {
+            return None;
+        }
+
+        // The `:` must be at the start of a word.
+        let mut before_start = word_start;
+        if before_start.backward_cursor_position() && !before_start.char().is_whitespace() {
+            return None;
+        }
+
+        let mut word_end = cursor;
+        while is_shortcode_char(word_end.char()) && word_end.forward_cursor_position() {}
+
+        let mut term_start = word_start;
+        term_start.forward_cursor_position();
+        let term = buffer.text(&term_start, &word_end, true);
+
+        if term.chars().count() < MIN_EMOTICON_TERM_LEN
+            || self.in_escaped_markdown(&word_start, &word_end)
+        {
+            return None;
+        }
+
+        Some((word_start, word_end, term.into()))
+    }
+
     /// Find the current search term in the underlying buffer.
     ///
     /// Returns the start and end of the search word and the term to search for.
@@ -733,6 +822,52 @@ impl CompletionPopover {
         }
     }
 
+    fn search_emoticons(&self) {
+        let imp = self.imp();
+        let Some(session) = imp.session.upgrade() else {
+            return;
+        };
+        let Some(term) = self
+            .current_word()
+            .map(|(_, _, term)| lower_lay_string(&term))
+        else {
+            return;
+        };
+
+        let emoticons = imp.emoticons.borrow();
+        let mut matches = emoticons
+            .iter()
+            .filter(|item| lower_lay_string(&item.shortcode).contains(&term))
+            .take(MAX_MEMBERS)
+            .cloned()
+            .collect::<Vec<_>>();
+        drop(emoticons);
+        // Show the emoji starting with the term first.
+        matches.sort_by_key(|item| !lower_lay_string(&item.shortcode).starts_with(&term));
+
+        if matches.is_empty() {
+            self.popdown();
+            self.select_row_at_index(None);
+            return;
+        }
+
+        let mut matches = matches.into_iter();
+        for row in &imp.rows {
+            if let Some(item) = matches.next() {
+                row.set_emoticon_item(item, &session);
+                row.set_visible(true);
+            } else if row.get_visible() {
+                row.set_visible(false);
+            } else {
+                // All remaining rows should be hidden too.
+                break;
+            }
+        }
+
+        self.update_pointing_to();
+        self.popup();
+    }
+
     fn count_visible_rows(&self) -> usize {
         self.imp()
             .rows
@@ -796,7 +931,21 @@ impl CompletionPopover {
     }
 
     fn row_activated(&self, row: &CompletionRow) {
-        if let Some(member) = row.member() {
+        if let Some(item) = row.emoticon_item() {
+            let imp = self.imp();
+
+            if let Some((mut start, mut end, _)) = imp.current_word.take() {
+                let view = self.view();
+                let buffer = view.buffer();
+
+                buffer.delete(&mut start, &mut end);
+                buffer.insert(&mut start, &format!(":{}: ", item.shortcode));
+
+                self.popdown();
+                self.select_row_at_index(None);
+                view.grab_focus();
+            }
+        } else if let Some(member) = row.member() {
             let imp = self.imp();
 
             if let Some((mut start, mut end, _)) = imp.current_word.take() {
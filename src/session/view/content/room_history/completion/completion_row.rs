@@ -1,6 +1,14 @@
-use gtk::{glib, prelude::*, subclass::prelude::*, CompositeTemplate};
+use gtk::{gdk, glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTemplate};
 
-use crate::{components::Avatar, prelude::*, session::model::Member};
+use crate::{
+    components::Avatar,
+    prelude::*,
+    session::model::{ImagePackItem, Member, Session},
+    spawn,
+};
+
+/// The size of the image of a custom emoji in the row.
+const EMOTICON_SIZE: u32 = 40;
 
 mod imp {
     use std::cell::RefCell;
@@ -18,11 +26,15 @@ mod imp {
         #[template_child]
         pub avatar: TemplateChild<Avatar>,
         #[template_child]
+        pub emoticon: TemplateChild<gtk::Image>,
+        #[template_child]
         pub display_name: TemplateChild<gtk::Label>,
         #[template_child]
         pub id: TemplateChild<gtk::Label>,
         /// The room member presented by this row.
         pub member: RefCell<Option<Member>>,
+        /// The custom emoji presented by this row.
+        pub emoticon_item: RefCell<Option<ImagePackItem>>,
     }
 
     #[glib::object_subclass]
@@ -71,7 +83,8 @@ mod imp {
 }
 
 glib::wrapper! {
-    /// A popover to allow completion for a given text buffer.
+    /// A row of the completion popover, presenting a room member or a custom
+    /// emoji.
     pub struct CompletionRow(ObjectSubclass<imp::CompletionRow>)
         @extends gtk::Widget, gtk::ListBoxRow;
 }
@@ -94,6 +107,13 @@ impl CompletionRow {
             return;
         }
 
+        if member.is_some() {
+            imp.emoticon_item.take();
+            imp.emoticon.set_visible(false);
+            imp.emoticon.set_paintable(gdk::Paintable::NONE);
+        }
+        imp.avatar.set_visible(true);
+
         if let Some(member) = &member {
             imp.avatar.set_data(Some(member.avatar_data().to_owned()));
             imp.display_name.set_label(&member.display_name());
@@ -107,6 +127,54 @@ impl CompletionRow {
         imp.member.replace(member);
         self.notify("member");
     }
+
+    /// The custom emoji displayed by this row.
+    pub fn emoticon_item(&self) -> Option<ImagePackItem> {
+        self.imp().emoticon_item.borrow().clone()
+    }
+
+    /// Set the custom emoji displayed by this row.
+    ///
+    /// The image of the emoji is loaded with the given session.
+    pub fn set_emoticon_item(&self, item: ImagePackItem, session: &Session) {
+        let imp = self.imp();
+
+        if imp
+            .emoticon_item
+            .borrow()
+            .as_ref()
+            .is_some_and(|i| i.shortcode == item.shortcode && i.image.url == item.image.url)
+        {
+            return;
+        }
+
+        self.set_member(None);
+        imp.avatar.set_visible(false);
+        imp.emoticon.set_visible(true);
+        imp.emoticon.set_paintable(gdk::Paintable::NONE);
+        imp.display_name.set_label(&format!(":{}:", item.shortcode));
+        imp.id.set_label(item.body());
+
+        spawn!(
+            glib::Priority::LOW,
+            clone!(@weak self as obj, @strong item, @weak session => async move {
+                let paintable = item.load_thumbnail(&session, EMOTICON_SIZE).await;
+
+                // Check that the row still displays the same emoji.
+                let is_same = obj
+                    .imp()
+                    .emoticon_item
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|i| i.image.url == item.image.url);
+                if is_same {
+                    obj.imp().emoticon.set_paintable(paintable.as_ref());
+                }
+            })
+        );
+
+        imp.emoticon_item.replace(Some(item));
+    }
 }
 
 impl Default for CompletionRow {
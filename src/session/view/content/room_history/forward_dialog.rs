@@ -0,0 +1,310 @@
+use adw::subclass::prelude::*;
+use gtk::{gio, glib, glib::clone, prelude::*, CompositeTemplate};
+
+use crate::{
+    components::Avatar,
+    ngettext_f,
+    session::model::{Event, Room, RoomType, Session, User},
+    session_list::SessionList,
+    toast, Window,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{subclass::InitializingObject, WeakRef};
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/forward_dialog.ui"
+    )]
+    pub struct ForwardDialog {
+        /// The list of logged-in sessions.
+        pub session_list: OnceCell<SessionList>,
+        /// The session whose rooms are presented.
+        pub session: WeakRef<Session>,
+        /// The events to forward.
+        pub events: RefCell<Vec<Event>>,
+        /// The rooms that match the search.
+        pub filtered_rooms: OnceCell<gtk::FilterListModel>,
+        /// The rooms to forward the events to.
+        pub selected_rooms: RefCell<Vec<Room>>,
+        #[template_child]
+        pub session_dropdown: TemplateChild<gtk::DropDown>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub room_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub forward_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ForwardDialog {
+        const NAME: &'static str = "ContentForwardDialog";
+        type Type = super::ForwardDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ForwardDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<SessionList>("session-list")
+                        .construct_only()
+                        .build(),
+                    glib::ParamSpecObject::builder::<Session>("session")
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session-list" => {
+                    let _ = self.session_list.set(value.get().unwrap());
+                }
+                "session" => self
+                    .obj()
+                    .set_session(value.get::<Option<Session>>().unwrap().as_ref()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "session-list" => self.obj().session_list().to_value(),
+                "session" => self.obj().session().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let filter =
+                gtk::CustomFilter::new(clone!(@weak obj => @default-return false, move |item| {
+                    obj.room_matches(item.downcast_ref::<Room>().unwrap())
+                }));
+            let filtered_rooms =
+                gtk::FilterListModel::new(gio::ListModel::NONE.cloned(), Some(filter));
+
+            self.room_list.bind_model(
+                Some(&filtered_rooms),
+                clone!(@weak obj => @default-panic, move |item| {
+                    obj.room_row(item.downcast_ref::<Room>().unwrap()).upcast()
+                }),
+            );
+            self.filtered_rooms.set(filtered_rooms).unwrap();
+
+            let session_list = obj.session_list();
+            self.session_dropdown.set_expression(Some(
+                Session::this_expression("user").chain_property::<User>("user-id"),
+            ));
+            self.session_dropdown.set_model(Some(session_list));
+            // Only allow to change the session when there are several.
+            self.session_dropdown
+                .set_visible(session_list.n_items() > 1);
+        }
+    }
+
+    impl WidgetImpl for ForwardDialog {}
+    impl WindowImpl for ForwardDialog {}
+    impl AdwWindowImpl for ForwardDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to choose the rooms to forward events to.
+    pub struct ForwardDialog(ObjectSubclass<imp::ForwardDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl ForwardDialog {
+    pub fn new(window: &Window, session: &Session, events: Vec<Event>) -> Self {
+        let obj = glib::Object::builder::<Self>()
+            .property("transient-for", window)
+            .property("session-list", window.session_list())
+            .build();
+        obj.imp().events.replace(events);
+
+        if let Some(index) = obj.session_list().index(session.session_id()) {
+            obj.imp().session_dropdown.set_selected(index as u32);
+        }
+        obj.set_session(Some(session));
+
+        obj
+    }
+
+    /// The list of logged-in sessions.
+    pub fn session_list(&self) -> &SessionList {
+        self.imp().session_list.get().unwrap()
+    }
+
+    /// The session whose rooms are presented.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// Set the session whose rooms are presented.
+    pub fn set_session(&self, session: Option<&Session>) {
+        let imp = self.imp();
+
+        if self.session().as_ref() == session {
+            return;
+        }
+
+        // The selected rooms belong to the previous session.
+        imp.selected_rooms.take();
+        self.update_forward_button();
+
+        if let Some(filtered_rooms) = imp.filtered_rooms.get() {
+            filtered_rooms.set_model(session.map(|session| session.room_list()));
+        }
+
+        imp.session.set(session);
+        self.notify("session");
+    }
+
+    /// Use the session selected in the drop-down.
+    #[template_callback]
+    fn update_session(&self) {
+        let session = self
+            .imp()
+            .session_dropdown
+            .selected_item()
+            .and_downcast::<Session>();
+
+        if session.is_some() {
+            self.set_session(session.as_ref());
+        }
+    }
+
+    /// Whether the given room should be presented for forwarding.
+    fn room_matches(&self, room: &Room) -> bool {
+        if !room.is_joined() || matches!(room.category(), RoomType::Space | RoomType::Outdated) {
+            return false;
+        }
+
+        let search = self.imp().search_entry.text().to_lowercase();
+        search.is_empty() || room.display_name().to_lowercase().contains(&search)
+    }
+
+    #[template_callback]
+    fn update_filter(&self) {
+        if let Some(filter) = self
+            .imp()
+            .filtered_rooms
+            .get()
+            .and_then(|model| model.filter())
+        {
+            filter.changed(gtk::FilterChange::Different);
+        }
+    }
+
+    /// Whether the given room was selected to forward the events to.
+    fn is_room_selected(&self, room: &Room) -> bool {
+        self.imp().selected_rooms.borrow().contains(room)
+    }
+
+    /// Set whether the given room was selected to forward the events to.
+    fn set_room_selected(&self, room: &Room, selected: bool) {
+        if self.is_room_selected(room) == selected {
+            return;
+        }
+
+        {
+            let mut selected_rooms = self.imp().selected_rooms.borrow_mut();
+            if selected {
+                selected_rooms.push(room.clone());
+            } else {
+                selected_rooms.retain(|r| r != room);
+            }
+        }
+
+        self.update_forward_button();
+    }
+
+    fn update_forward_button(&self) {
+        let imp = self.imp();
+        imp.forward_button
+            .set_sensitive(!imp.selected_rooms.borrow().is_empty());
+    }
+
+    /// Forward the events to the selected rooms.
+    #[template_callback]
+    fn forward(&self) {
+        let imp = self.imp();
+        let rooms = imp.selected_rooms.take();
+        if rooms.is_empty() {
+            return;
+        }
+
+        for room in &rooms {
+            for event in imp.events.borrow().iter() {
+                // The media are sent with the same URI, so they are not uploaded again.
+                if let Some(content) = event.forward_content() {
+                    room.send_room_message_event(content);
+                }
+            }
+        }
+
+        if let Some(window) = self.transient_for() {
+            let n = rooms.len() as u32;
+            toast!(
+                window,
+                ngettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "Forwarded to {n} room",
+                    "Forwarded to {n} rooms",
+                    n,
+                    &[("n", &n.to_string())],
+                )
+            );
+        }
+
+        self.close();
+    }
+
+    /// Create a row presenting the given room.
+    fn room_row(&self, room: &Room) -> adw::ActionRow {
+        let avatar = Avatar::new();
+        avatar.set_size(32);
+        avatar.set_data(Some(room.avatar_data().clone()));
+
+        let check = gtk::CheckButton::builder()
+            .active(self.is_room_selected(room))
+            .valign(gtk::Align::Center)
+            .build();
+        check.connect_toggled(clone!(@weak self as obj, @weak room => move |check| {
+            obj.set_room_selected(&room, check.is_active());
+        }));
+
+        let row = adw::ActionRow::builder()
+            .title(room.display_name())
+            .use_markup(false)
+            .activatable_widget(&check)
+            .build();
+        row.add_prefix(&avatar);
+        row.add_suffix(&check);
+
+        row
+    }
+}
@@ -6,16 +6,22 @@ use once_cell::sync::Lazy;
 use ruma::events::room::{message::MessageType, power_levels::PowerLevelAction};
 use tracing::error;
 
-use super::{DividerRow, MessageRow, RoomHistory, StateRow, TypingRow};
+use super::{
+    DividerRow, ForwardDialog, MessageRow, PendingAttachmentRow, RoomHistory, StateRow, TypingRow,
+};
 use crate::{
     components::{ContextMenuBin, ContextMenuBinExt, ContextMenuBinImpl, ReactionChooser, Spinner},
     prelude::*,
     session::{
-        model::{Event, EventKey, TimelineItem, VirtualItem, VirtualItemKind},
+        model::{
+            Event, EventKey, PendingAttachment, SendState, TimelineItem, VirtualItem,
+            VirtualItemKind,
+        },
         view::EventSourceDialog,
     },
     spawn, spawn_tokio, toast,
     utils::{media::save_to_file, BoundObjectWeakRef},
+    Window,
 };
 
 mod imp {
@@ -260,11 +266,34 @@ impl ItemRow {
                         obj.update_highlight();
                     }),
                 );
-                imp.notify_handlers
-                    .replace(vec![source_notify_handler, is_highlighted_notify_handler]);
+                let send_state_notify_handler = event.connect_notify_local(
+                    Some("send-state"),
+                    clone!(@weak self as obj => move |event, _| {
+                        obj.set_action_group(obj.set_event_actions(Some(event)));
+                    }),
+                );
+                imp.notify_handlers.replace(vec![
+                    source_notify_handler,
+                    is_highlighted_notify_handler,
+                    send_state_notify_handler,
+                ]);
 
                 self.set_event_widget(event.clone());
                 self.set_action_group(self.set_event_actions(Some(event.upcast_ref())));
+            } else if let Some(attachment) = item.downcast_ref::<PendingAttachment>() {
+                self.set_popover(None);
+                self.set_event_actions(None);
+                self.set_action_group(self.set_pending_attachment_actions(attachment));
+
+                let child = if let Some(child) = self.child().and_downcast::<PendingAttachmentRow>()
+                {
+                    child
+                } else {
+                    let child = PendingAttachmentRow::new();
+                    self.set_child(Some(&child));
+                    child
+                };
+                child.set_attachment(Some(attachment.clone()));
             } else if let Some(item) = item.downcast_ref::<VirtualItem>() {
                 self.set_popover(None);
                 self.set_action_group(None);
@@ -413,6 +442,51 @@ impl ItemRow {
         }
     }
 
+    /// Set the actions available on `self` for the given pending attachment.
+    fn set_pending_attachment_actions(
+        &self,
+        attachment: &PendingAttachment,
+    ) -> Option<gio::SimpleActionGroup> {
+        let action_group = gio::SimpleActionGroup::new();
+
+        action_group.add_action_entries([
+            // Try to send the attachment again
+            gio::ActionEntry::builder("retry-send")
+                .activate(
+                    clone!(@weak self as widget, @weak attachment => move |_, _, _| {
+                        if attachment.send_state() != SendState::Failed {
+                            return;
+                        }
+                        let Some(room) = widget.room_history().room() else {
+                            return;
+                        };
+
+                        spawn!(clone!(@weak room, @weak attachment => async move {
+                            room.timeline().send_pending_attachment(&attachment).await;
+                        }));
+                    }),
+                )
+                .build(),
+            // Cancel the sending of the attachment
+            gio::ActionEntry::builder("cancel-send")
+                .activate(
+                    clone!(@weak self as widget, @weak attachment => move |_, _, _| {
+                        let Some(room) = widget.room_history().room() else {
+                            return;
+                        };
+
+                        if !room.timeline().cancel_pending_attachment(&attachment) {
+                            toast!(widget, gettext("Could not cancel the message"));
+                        }
+                    }),
+                )
+                .build(),
+        ]);
+
+        self.insert_action_group("event", Some(&action_group));
+        Some(action_group)
+    }
+
     /// Set the actions available on `self` for `event`.
     ///
     /// Unsets the actions if `event` is `None`.
@@ -440,6 +514,32 @@ impl ItemRow {
             ]);
         }
 
+        if event.send_state() == SendState::Failed {
+            action_group.add_action_entries([
+                // Try to send the event again
+                gio::ActionEntry::builder("retry-send")
+                    .activate(clone!(@weak self as widget, @weak event => move |_, _, _| {
+                        spawn!(clone!(@weak widget, @weak event => async move {
+                            if let Err(error) = event.room().timeline().retry_send(&event).await {
+                                error!("Could not send message again: {error}");
+                                toast!(widget, gettext("Could not send the message"));
+                            }
+                        }));
+                    }))
+                    .build(),
+                // Cancel the sending of the event
+                gio::ActionEntry::builder("cancel-send")
+                    .activate(clone!(@weak self as widget, @weak event => move |_, _, _| {
+                        spawn!(clone!(@weak widget, @weak event => async move {
+                            if !event.room().timeline().cancel_send(&event).await {
+                                toast!(widget, gettext("Could not cancel the message"));
+                            }
+                        }));
+                    }))
+                    .build(),
+            ]);
+        }
+
         if event.event_id().is_some() {
             action_group.add_action_entries([
                 // Create a permalink
@@ -464,7 +564,17 @@ impl ItemRow {
                         })
                     );
                 }))
-                .build()
+                .build(),
+                // Select the event
+                gio::ActionEntry::builder("select")
+                    .activate(clone!(@weak self as widget, @weak event => move |_, _, _| {
+                        let event_id = event.event_id().unwrap();
+                        let _ = widget.activate_action(
+                            "room-history.select-event",
+                            Some(&event_id.as_str().to_variant()),
+                        );
+                    }))
+                    .build(),
             ]);
 
             if let TimelineItemContent::Message(message) = event.content() {
@@ -549,6 +659,27 @@ impl ItemRow {
                                 );
                             }
                         }))
+                    .build(),
+                    // Reply in thread
+                    gio::ActionEntry::builder("open-thread")
+                        .activate(clone!(@weak event, @weak self as widget => move |_, _, _| {
+                            if let Some(root_id) = event.thread_root_id().or_else(|| event.event_id()) {
+                                let _ = widget.activate_action(
+                                    "room-history.open-thread",
+                                    Some(&root_id.as_str().to_variant())
+                                );
+                            }
+                        }))
+                    .build(),
+                    // Forward
+                    gio::ActionEntry::builder("forward")
+                        .activate(clone!(@weak event, @weak self as widget => move |_, _, _| {
+                            let Some(window) = widget.root().and_downcast::<Window>() else {
+                                return;
+                            };
+                            let dialog = ForwardDialog::new(&window, &event.room().session(), vec![event]);
+                            dialog.present();
+                        }))
                     .build()
                 ]);
 
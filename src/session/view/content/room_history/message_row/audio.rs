@@ -10,9 +10,10 @@ use tracing::warn;
 
 use super::{media::MediaState, ContentFormat};
 use crate::{
-    components::{AudioPlayer, Spinner},
+    components::{AudioPlayer, Spinner, Waveform},
     session::model::Session,
     spawn, spawn_tokio,
+    utils::media::audio_waveform,
 };
 
 mod imp {
@@ -37,6 +38,8 @@ mod imp {
         #[template_child]
         pub player: TemplateChild<AudioPlayer>,
         #[template_child]
+        pub waveform: TemplateChild<Waveform>,
+        #[template_child]
         pub state_spinner: TemplateChild<Spinner>,
         #[template_child]
         pub state_error: TemplateChild<gtk::Image>,
@@ -190,6 +193,12 @@ impl MessageAudio {
 
         let compact = matches!(format, ContentFormat::Compact | ContentFormat::Ellipsized);
         self.set_compact(compact);
+
+        let waveform = audio_waveform(&audio);
+        let imp = self.imp();
+        imp.waveform.set_visible(!compact && !waveform.is_empty());
+        imp.waveform.set_samples(waveform);
+
         if compact {
             self.set_state(MediaState::Ready);
             return;
@@ -242,7 +251,9 @@ impl MessageAudio {
             }
         }));
 
-        self.imp().player.set_media_file(Some(media_file));
+        let imp = self.imp();
+        imp.waveform.set_media_file(Some(&media_file));
+        imp.player.set_media_file(Some(media_file));
         self.set_state(MediaState::Ready);
     }
 }
@@ -2,12 +2,12 @@ use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{gdk, glib, glib::clone};
 use matrix_sdk_ui::timeline::{TimelineDetails, TimelineItemContent};
-use ruma::events::room::message::MessageType;
+use ruma::{events::room::message::MessageType, OwnedEventId};
 use tracing::{error, warn};
 
 use super::{
     audio::MessageAudio, file::MessageFile, location::MessageLocation, media::MessageMedia,
-    reply::MessageReply, text::MessageText,
+    poll::MessagePoll, reply::MessageReply, text::MessageText,
 };
 use crate::{
     session::model::{Event, Member, Room},
@@ -154,9 +154,11 @@ impl MessageContent {
                             .get_or_create(related_content.sender().to_owned());
                         let reply = MessageReply::new();
                         reply.set_related_content_sender(sender.upcast_ref());
+                        reply.set_related_event_id(event.reply_to_id());
                         build_content(
                             reply.related_content(),
                             related_content.content().clone(),
+                            event.reply_to_id(),
                             ContentFormat::Compact,
                             sender,
                             &room,
@@ -164,6 +166,7 @@ impl MessageContent {
                         build_content(
                             reply.content(),
                             event.content(),
+                            event.event_id(),
                             ContentFormat::Natural,
                             event.sender(),
                             &room,
@@ -177,7 +180,14 @@ impl MessageContent {
             }
         }
 
-        build_content(self, event.content(), format, event.sender(), &event.room());
+        build_content(
+            self,
+            event.content(),
+            event.event_id(),
+            format,
+            event.sender(),
+            &event.room(),
+        );
     }
 
     /// Get the texture displayed by this widget, if any.
@@ -192,6 +202,7 @@ impl MessageContent {
 fn build_content(
     parent: &impl IsA<adw::Bin>,
     content: TimelineItemContent,
+    event_id: Option<OwnedEventId>,
     format: ContentFormat,
     sender: Member,
     room: &Room,
@@ -353,6 +364,16 @@ fn build_content(
             };
             child.sticker(sticker.content().clone(), &room.session(), format);
         }
+        TimelineItemContent::Poll(poll) => {
+            let child = if let Some(child) = parent.child().and_downcast::<MessagePoll>() {
+                child
+            } else {
+                let child = MessagePoll::new();
+                parent.set_child(Some(&child));
+                child
+            };
+            child.set_poll(poll.results(), event_id, &sender, room, format);
+        }
         TimelineItemContent::UnableToDecrypt(_) => {
             let child = if let Some(child) = parent.child().and_downcast::<MessageText>() {
                 child
@@ -3,12 +3,15 @@ pub mod content;
 mod file;
 mod location;
 mod media;
+mod poll;
 mod reaction;
 mod reaction_list;
 mod reply;
 mod text;
+mod thread_summary;
 
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::{
     gdk, glib,
     glib::{clone, signal::SignalHandlerId},
@@ -18,9 +21,17 @@ use matrix_sdk::ruma::events::room::message::MessageType;
 use tracing::warn;
 
 pub use self::content::ContentFormat;
-use self::{content::MessageContent, media::MessageMedia, reaction_list::MessageReactionList};
+use self::{
+    content::MessageContent, media::MessageMedia, reaction_list::MessageReactionList,
+    thread_summary::MessageThreadSummary,
+};
 use super::ReadReceiptsList;
-use crate::{components::Avatar, prelude::*, session::model::Event, Window};
+use crate::{
+    components::Avatar,
+    prelude::*,
+    session::model::{Event, SendState},
+    Window,
+};
 
 mod imp {
     use std::cell::RefCell;
@@ -46,10 +57,15 @@ mod imp {
         #[template_child]
         pub content: TemplateChild<MessageContent>,
         #[template_child]
+        pub send_state_icon: TemplateChild<gtk::Image>,
+        #[template_child]
         pub reactions: TemplateChild<MessageReactionList>,
         #[template_child]
+        pub thread_summary: TemplateChild<MessageThreadSummary>,
+        #[template_child]
         pub read_receipts: TemplateChild<ReadReceiptsList>,
         pub source_changed_handler: RefCell<Option<SignalHandlerId>>,
+        pub send_state_handler: RefCell<Option<SignalHandlerId>>,
         pub bindings: RefCell<Vec<glib::Binding>>,
         pub event: RefCell<Option<Event>>,
     }
@@ -174,6 +190,9 @@ impl MessageRow {
             if let Some(source_changed_handler) = imp.source_changed_handler.take() {
                 event.disconnect(source_changed_handler);
             }
+            if let Some(handler) = imp.send_state_handler.take() {
+                event.disconnect(handler);
+            }
 
             while let Some(binding) = imp.bindings.borrow_mut().pop() {
                 binding.unbind();
@@ -214,7 +233,17 @@ impl MessageRow {
             )));
         self.update_content(&event);
 
+        imp.send_state_handler
+            .replace(Some(event.connect_notify_local(
+                Some("send-state"),
+                clone!(@weak self as obj => move |event, _| {
+                    obj.update_send_state(event);
+                }),
+            )));
+        self.update_send_state(&event);
+
         imp.reactions.set_reaction_list(event.reactions());
+        imp.thread_summary.set_event(Some(event.clone()));
         imp.read_receipts
             .set_list(&event.room(), event.read_receipts());
         imp.event.replace(Some(event));
@@ -225,6 +254,42 @@ impl MessageRow {
         self.imp().content.update_for_event(event);
     }
 
+    /// Update the indicator of the send state of the event.
+    fn update_send_state(&self, event: &Event) {
+        let imp = self.imp();
+        let send_state = event.send_state();
+
+        match send_state {
+            SendState::Sent => {
+                imp.send_state_icon.set_visible(false);
+            }
+            SendState::Sending => {
+                imp.send_state_icon
+                    .set_icon_name(Some("content-loading-symbolic"));
+                imp.send_state_icon
+                    .set_tooltip_text(Some(&gettext("Sending…")));
+                imp.send_state_icon.remove_css_class("error");
+                imp.send_state_icon.add_css_class("dim-label");
+                imp.send_state_icon.set_visible(true);
+            }
+            SendState::Failed => {
+                imp.send_state_icon
+                    .set_icon_name(Some("dialog-error-symbolic"));
+                imp.send_state_icon
+                    .set_tooltip_text(Some(&gettext("Could not send this message")));
+                imp.send_state_icon.remove_css_class("dim-label");
+                imp.send_state_icon.add_css_class("error");
+                imp.send_state_icon.set_visible(true);
+            }
+        }
+
+        imp.content.set_opacity(if send_state == SendState::Sent {
+            1.0
+        } else {
+            0.6
+        });
+    }
+
     /// Get the texture displayed by this widget, if any.
     pub fn texture(&self) -> Option<gdk::Texture> {
         self.imp().content.texture()
@@ -0,0 +1,317 @@
+use std::collections::HashSet;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use matrix_sdk_ui::timeline::PollResult;
+use ruma::{
+    events::poll::{
+        start::PollKind, unstable_end::UnstablePollEndEventContent,
+        unstable_response::UnstablePollResponseEventContent,
+    },
+    OwnedEventId,
+};
+
+use super::ContentFormat;
+use crate::{
+    ngettext_f,
+    prelude::*,
+    session::model::{Member, Room},
+    spawn,
+};
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{subclass::InitializingObject, WeakRef};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/message_row/poll.ui"
+    )]
+    pub struct MessagePoll {
+        #[template_child]
+        pub question: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub answers: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub footer: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub status: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub end_button: TemplateChild<gtk::Button>,
+        /// The rows of the answers.
+        pub answer_rows: RefCell<Vec<gtk::Widget>>,
+        /// The room of the poll.
+        pub room: WeakRef<Room>,
+        /// The ID of the event that started the poll.
+        pub poll_start_id: RefCell<Option<OwnedEventId>>,
+        /// The IDs of the answers our own user voted for.
+        pub own_votes: RefCell<HashSet<String>>,
+        /// The maximum number of answers that can be selected.
+        pub max_selections: Cell<usize>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessagePoll {
+        const NAME: &'static str = "ContentMessagePoll";
+        type Type = super::MessagePoll;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessagePoll {}
+    impl WidgetImpl for MessagePoll {}
+    impl BinImpl for MessagePoll {}
+}
+
+glib::wrapper! {
+    /// A widget displaying a poll in the timeline, with its results.
+    pub struct MessagePoll(ObjectSubclass<imp::MessagePoll>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl MessagePoll {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Display the given poll.
+    ///
+    /// Voting is only possible if `poll_start_id` is set and the format is
+    /// `ContentFormat::Natural`.
+    pub fn set_poll(
+        &self,
+        poll: PollResult,
+        poll_start_id: Option<OwnedEventId>,
+        sender: &Member,
+        room: &Room,
+        format: ContentFormat,
+    ) {
+        let imp = self.imp();
+        let compact = format != ContentFormat::Natural;
+
+        imp.question.set_label(&poll.question);
+        imp.question
+            .set_ellipsize(if format == ContentFormat::Ellipsized {
+                gtk::pango::EllipsizeMode::End
+            } else {
+                gtk::pango::EllipsizeMode::None
+            });
+        imp.question.set_wrap(format != ContentFormat::Ellipsized);
+
+        for row in imp.answer_rows.take() {
+            imp.answers.remove(&row);
+        }
+        imp.answers.set_visible(!compact);
+        imp.footer.set_visible(!compact);
+        if compact {
+            return;
+        }
+
+        let own_user_id = room.session().user().map(|user| user.user_id().to_string());
+        let is_ended = poll.end_time.is_some();
+        let can_vote = !is_ended && poll_start_id.is_some();
+        let show_results = is_ended || poll.kind == PollKind::Disclosed;
+
+        let own_votes = poll
+            .votes
+            .iter()
+            .filter(|(_, voters)| own_user_id.as_ref().is_some_and(|id| voters.contains(id)))
+            .map(|(answer_id, _)| answer_id.clone())
+            .collect::<HashSet<_>>();
+        let voters_count = poll.votes.values().flatten().collect::<HashSet<_>>().len() as u32;
+        let max_votes = poll.votes.values().map(Vec::len).max().unwrap_or_default();
+
+        let max_selections = poll.max_selections.max(1) as usize;
+        // With a single choice, selecting another answer replaces the vote.
+        let can_select_more = max_selections == 1 || own_votes.len() < max_selections;
+        let mut group: Option<gtk::CheckButton> = None;
+        let mut rows = Vec::with_capacity(poll.answers.len());
+
+        for answer in &poll.answers {
+            let votes = poll.votes.get(&answer.id).map(Vec::len).unwrap_or_default();
+            let is_selected = own_votes.contains(&answer.id);
+
+            let check = gtk::CheckButton::builder()
+                .label(&answer.text)
+                .active(is_selected)
+                .hexpand(true)
+                .sensitive(can_vote && (is_selected || can_select_more))
+                .build();
+            if max_selections == 1 {
+                if let Some(group) = &group {
+                    check.set_group(Some(group));
+                } else {
+                    group = Some(check.clone());
+                }
+            }
+
+            let answer_id = answer.id.clone();
+            check.connect_toggled(clone!(@weak self as obj => move |check| {
+                obj.set_answer_selected(&answer_id, check.is_active());
+            }));
+
+            let header = gtk::Box::builder().spacing(6).build();
+            header.append(&check);
+
+            let row = gtk::Box::builder()
+                .orientation(gtk::Orientation::Vertical)
+                .spacing(6)
+                .build();
+            row.append(&header);
+
+            if show_results {
+                let count = gtk::Label::builder()
+                    .label(ngettext_f(
+                        // Translators: Do NOT translate the content between '{' and '}', this
+                        // is a variable name.
+                        "{n} vote",
+                        "{n} votes",
+                        votes as u32,
+                        &[("n", &votes.to_string())],
+                    ))
+                    .valign(gtk::Align::Center)
+                    .css_classes(["dim-label", "caption"])
+                    .build();
+                header.append(&count);
+
+                let progress = gtk::ProgressBar::new();
+                if voters_count > 0 {
+                    progress.set_fraction(votes as f64 / voters_count as f64);
+                }
+                if is_ended && votes > 0 && votes == max_votes {
+                    progress.add_css_class("success");
+                }
+                row.append(&progress);
+            }
+
+            imp.answers.append(&row);
+            rows.push(row.upcast());
+        }
+
+        let status = if is_ended {
+            ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "Final results based on {n} vote",
+                "Final results based on {n} votes",
+                voters_count,
+                &[("n", &voters_count.to_string())],
+            )
+        } else if show_results {
+            ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "{n} vote cast",
+                "{n} votes cast",
+                voters_count,
+                &[("n", &voters_count.to_string())],
+            )
+        } else {
+            gettext("Results will be visible when the poll is ended")
+        };
+        imp.status.set_label(&status);
+
+        let is_own_poll = own_user_id.is_some_and(|id| sender.user_id().as_str() == id);
+        imp.end_button.set_visible(is_own_poll && can_vote);
+
+        imp.answer_rows.replace(rows);
+        imp.room.set(Some(room));
+        imp.poll_start_id.replace(poll_start_id);
+        imp.own_votes.replace(own_votes);
+        imp.max_selections.set(max_selections);
+    }
+
+    /// Select or unselect the answer with the given ID as our vote.
+    fn set_answer_selected(&self, answer_id: &str, selected: bool) {
+        let imp = self.imp();
+
+        let answers = if imp.max_selections.get() == 1 {
+            // Only one radio button can be active, ignore the one that was unselected.
+            if !selected {
+                return;
+            }
+            vec![answer_id.to_owned()]
+        } else {
+            let mut own_votes = imp.own_votes.borrow().clone();
+            if selected {
+                own_votes.insert(answer_id.to_owned());
+            } else {
+                own_votes.remove(answer_id);
+            }
+            own_votes.into_iter().collect()
+        };
+
+        if answers.iter().collect::<HashSet<_>>() == imp.own_votes.borrow().iter().collect() {
+            return;
+        }
+
+        let Some(room) = imp.room.upgrade() else {
+            return;
+        };
+        let Some(poll_start_id) = imp.poll_start_id.borrow().clone() else {
+            return;
+        };
+
+        imp.own_votes.replace(answers.iter().cloned().collect());
+        room.send_room_message_event(UnstablePollResponseEventContent::new(
+            answers,
+            poll_start_id,
+        ));
+    }
+
+    /// End the poll, after confirmation.
+    #[template_callback]
+    fn end_poll(&self) {
+        let Some(parent_window) = self.root().and_downcast::<gtk::Window>() else {
+            return;
+        };
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("End Poll?"))
+            .body(gettext(
+                "The results will be final and nobody will be able to vote anymore.",
+            ))
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .transient_for(&parent_window)
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("end", &gettext("_End Poll")),
+        ]);
+        dialog.set_response_appearance("end", adw::ResponseAppearance::Destructive);
+
+        spawn!(clone!(@weak self as obj => async move {
+            if dialog.choose_future().await != "end" {
+                return;
+            }
+
+            let imp = obj.imp();
+            let Some(room) = imp.room.upgrade() else {
+                return;
+            };
+            let Some(poll_start_id) = imp.poll_start_id.borrow().clone() else {
+                return;
+            };
+
+            room.send_room_message_event(UnstablePollEndEventContent::new(
+                gettext("The poll has ended."),
+                poll_start_id,
+            ));
+        }));
+    }
+}
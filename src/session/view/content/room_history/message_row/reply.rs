@@ -1,9 +1,12 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gtk::{glib, CompositeTemplate};
+use ruma::OwnedEventId;
 
 use crate::session::model::User;
 
 mod imp {
+    use std::cell::RefCell;
+
     use glib::subclass::InitializingObject;
 
     use super::*;
@@ -19,6 +22,8 @@ mod imp {
         pub related_content: TemplateChild<adw::Bin>,
         #[template_child]
         pub content: TemplateChild<adw::Bin>,
+        /// The ID of the replied-to event.
+        pub related_event_id: RefCell<Option<OwnedEventId>>,
     }
 
     #[glib::object_subclass]
@@ -29,6 +34,7 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -48,6 +54,7 @@ glib::wrapper! {
         @extends gtk::Widget, gtk::Grid, @implements gtk::Accessible;
 }
 
+#[gtk::template_callbacks]
 impl MessageReply {
     pub fn new() -> Self {
         glib::Object::new()
@@ -59,6 +66,24 @@ impl MessageReply {
             .build();
     }
 
+    /// Set the ID of the replied-to event.
+    pub fn set_related_event_id(&self, event_id: Option<OwnedEventId>) {
+        self.imp().related_event_id.replace(event_id);
+    }
+
+    /// Scroll to the replied-to event when the quote is clicked.
+    #[template_callback]
+    fn handle_quote_released(&self) {
+        let Some(event_id) = self.imp().related_event_id.borrow().clone() else {
+            return;
+        };
+
+        let _ = self.activate_action(
+            "room-history.jump-to-event",
+            Some(&event_id.as_str().to_variant()),
+        );
+    }
+
     pub fn related_content(&self) -> &adw::Bin {
         self.imp().related_content.as_ref()
     }
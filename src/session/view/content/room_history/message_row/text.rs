@@ -14,7 +14,10 @@ use crate::{
     components::LabelWithWidgets,
     prelude::*,
     session::model::{Member, Room},
-    utils::{matrix::extract_mentions, EMOJI_REGEX},
+    utils::{
+        matrix::{extract_mentions, MatrixIdUri},
+        EMOJI_REGEX,
+    },
 };
 
 enum WithMentions<'a> {
@@ -217,7 +220,7 @@ impl MessageText {
             let child = if let Some(child) = self.child().and_downcast::<LabelWithWidgets>() {
                 child
             } else {
-                let child = LabelWithWidgets::new();
+                let child = new_label_with_widgets();
                 self.set_child(Some(&child));
                 child
             };
@@ -443,13 +446,41 @@ fn create_widget_for_html_block(
 }
 
 fn new_label() -> gtk::Label {
-    gtk::Label::builder()
+    let label = gtk::Label::builder()
         .wrap(true)
         .wrap_mode(pango::WrapMode::WordChar)
         .xalign(0.0)
         .valign(gtk::Align::Start)
         .css_classes(["line-height"])
-        .build()
+        .build();
+    label.connect_activate_link(|label, uri| activate_link(label, uri));
+    label
+}
+
+fn new_label_with_widgets() -> LabelWithWidgets {
+    let label = LabelWithWidgets::new();
+    label.connect_activate_link(|label, uri| activate_link(label, uri));
+    label
+}
+
+/// Handle the activation of the given link in the given widget.
+///
+/// Matrix URIs are opened in the app, other links are opened with the default
+/// handler.
+fn activate_link(widget: &impl IsA<gtk::Widget>, uri: &str) -> glib::Propagation {
+    if MatrixIdUri::parse(uri).is_err() {
+        return glib::Propagation::Proceed;
+    }
+
+    if widget
+        .activate_action("session.show-matrix-uri", Some(&uri.to_variant()))
+        .is_err()
+    {
+        // The widget is not in a session view.
+        return glib::Propagation::Proceed;
+    }
+
+    glib::Propagation::Stop
 }
 
 fn create_label_for_html(label: &str, room: &Room, ellipsize: bool, cut_text: bool) -> gtk::Widget {
@@ -472,8 +503,10 @@ fn create_label_for_html(label: &str, room: &Room, ellipsize: bool, cut_text: bo
         });
         w.upcast()
     } else {
-        let widgets = widgets.into_iter().map(|(w, _)| w).collect();
-        let w = LabelWithWidgets::with_label_and_widgets(&label, widgets);
+        let widgets = widgets.into_iter().map(|(w, _)| w).collect::<Vec<_>>();
+        let w = new_label_with_widgets();
+        w.set_label(Some(label));
+        w.set_widgets(widgets);
         w.set_use_markup(true);
         w.set_ellipsize(ellipsize);
         w.upcast()
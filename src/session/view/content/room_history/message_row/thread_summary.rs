@@ -0,0 +1,180 @@
+use adw::subclass::prelude::*;
+use gtk::{glib, glib::clone, prelude::*, CompositeTemplate};
+
+use crate::{ngettext_f, session::model::Event};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{signal::SignalHandlerId, subclass::InitializingObject};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/message_row/thread_summary.ui"
+    )]
+    pub struct MessageThreadSummary {
+        /// The root event of the thread.
+        pub event: RefCell<Option<Event>>,
+        pub event_handlers: RefCell<Vec<SignalHandlerId>>,
+        #[template_child]
+        pub count_label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub latest_reply_label: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageThreadSummary {
+        const NAME: &'static str = "ContentMessageThreadSummary";
+        type Type = super::MessageThreadSummary;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+            klass.set_css_name("message-thread-summary");
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageThreadSummary {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Event>("event")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "event" => self.obj().set_event(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "event" => self.obj().event().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(event) = self.event.take() {
+                for handler in self.event_handlers.take() {
+                    event.disconnect(handler);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageThreadSummary {}
+    impl BinImpl for MessageThreadSummary {}
+}
+
+glib::wrapper! {
+    /// A widget displaying the summary of the thread of which an event is the
+    /// root.
+    pub struct MessageThreadSummary(ObjectSubclass<imp::MessageThreadSummary>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl MessageThreadSummary {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The root event of the thread.
+    pub fn event(&self) -> Option<Event> {
+        self.imp().event.borrow().clone()
+    }
+
+    /// Set the root event of the thread.
+    pub fn set_event(&self, event: Option<Event>) {
+        let imp = self.imp();
+
+        if self.event() == event {
+            return;
+        }
+
+        if let Some(event) = imp.event.take() {
+            for handler in imp.event_handlers.take() {
+                event.disconnect(handler);
+            }
+        }
+
+        if let Some(event) = &event {
+            let count_handler = event.connect_notify_local(
+                Some("thread-replies-count"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update();
+                }),
+            );
+            let latest_reply_handler = event.connect_notify_local(
+                Some("thread-latest-reply"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update();
+                }),
+            );
+
+            imp.event_handlers
+                .replace(vec![count_handler, latest_reply_handler]);
+        }
+
+        imp.event.replace(event);
+        self.update();
+        self.notify("event");
+    }
+
+    /// Update this widget for the current state of the thread.
+    fn update(&self) {
+        let imp = self.imp();
+
+        let Some(event) = self.event().filter(|event| event.has_thread_replies()) else {
+            self.set_visible(false);
+            return;
+        };
+
+        let count = event.thread_replies_count();
+        imp.count_label.set_label(&ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this is a
+            // variable name.
+            "1 reply",
+            "{n} replies",
+            count,
+            &[("n", &count.to_string())],
+        ));
+
+        let latest_reply = event.thread_latest_reply().and_then(|reply| {
+            let body = reply.message()?.body().to_owned();
+            Some(format!("{}: {body}", reply.sender().display_name()))
+        });
+        imp.latest_reply_label.set_visible(latest_reply.is_some());
+        imp.latest_reply_label
+            .set_label(latest_reply.as_deref().unwrap_or_default());
+
+        self.set_visible(true);
+    }
+
+    /// Open the thread in the room history.
+    #[template_callback]
+    fn open_thread(&self) {
+        let Some(event_id) = self.event().and_then(|event| event.event_id()) else {
+            return;
+        };
+
+        let _ = self.activate_action(
+            "room-history.open-thread",
+            Some(&event_id.as_str().to_variant()),
+        );
+    }
+}
@@ -1,12 +1,19 @@
 mod attachment_dialog;
 mod completion;
 mod divider_row;
+mod forward_dialog;
 mod item_row;
 mod message_row;
+mod pending_attachment_row;
+mod poll_creation_dialog;
 mod read_receipts_list;
 mod state_row;
+mod sticker_chooser;
+mod sticker_pack_creation_dialog;
+mod thread_view;
 mod typing_row;
 mod verification_info_bar;
+mod voice_recorder;
 
 use std::time::Duration;
 
@@ -21,11 +28,13 @@ use gettextrs::{gettext, pgettext};
 use gtk::{
     gdk, gio,
     glib::{self, clone, FromVariant},
+    pango,
     prelude::*,
     CompositeTemplate,
 };
+use html2pango::html_escape;
 use matrix_sdk::{
-    attachment::{AttachmentInfo, BaseFileInfo, BaseImageInfo},
+    attachment::{AttachmentInfo, BaseAudioInfo, BaseFileInfo, BaseImageInfo},
     ruma::{
         events::{
             room::message::{EmoteMessageEventContent, FormattedBody, MessageType},
@@ -35,7 +44,7 @@ use matrix_sdk::{
     },
 };
 use ruma::{
-    api::client::receipt::create_receipt::v3::ReceiptType,
+    api::{client::receipt::create_receipt::v3::ReceiptType, Direction},
     events::{
         receipt::ReceiptThread,
         room::{
@@ -47,7 +56,7 @@ use ruma::{
         },
         AnyMessageLikeEventContent,
     },
-    OwnedEventId,
+    MilliSecondsSinceUnixEpoch, OwnedEventId, UInt,
 };
 use sourceview::prelude::*;
 use tracing::{debug, error, warn};
@@ -56,25 +65,43 @@ use self::{
     attachment_dialog::AttachmentDialog,
     completion::CompletionPopover,
     divider_row::DividerRow,
+    forward_dialog::ForwardDialog,
     item_row::ItemRow,
     message_row::{content::MessageContent, MessageRow},
+    pending_attachment_row::PendingAttachmentRow,
+    poll_creation_dialog::PollCreationDialog,
     read_receipts_list::ReadReceiptsList,
     state_row::StateRow,
+    sticker_chooser::StickerChooser,
+    sticker_pack_creation_dialog::StickerPackCreationDialog,
+    thread_view::ThreadView,
     typing_row::TypingRow,
     verification_info_bar::VerificationInfoBar,
+    voice_recorder::{VoiceMessage, VoiceRecorder},
 };
 use super::{room_details, RoomDetails};
 use crate::{
     components::{
         CustomEntry, DragOverlay, LabelWithWidgets, Pill, ReactionChooser, RoomTitle, Spinner,
+        Waveform,
     },
-    gettext_f,
+    gettext_f, ngettext_f,
     prelude::*,
-    session::model::{Event, EventKey, MemberList, Room, RoomType, Timeline, TimelineState},
+    session::{
+        model::{
+            ComposerDraft, DraftChunk, DraftRelation, Event, EventKey, ImagePack, ImagePackItem,
+            Member, MemberList, Membership, Room, RoomType, SearchResult, Timeline,
+            TimelineContext, TimelineItem, TimelineState, VirtualItem, VirtualItemKind,
+        },
+        view::UserProfileDialog,
+    },
     spawn, spawn_tokio, toast,
     utils::{
-        matrix::extract_mentions,
-        media::{filename_for_mime, get_audio_info, get_image_info, get_video_info, load_file},
+        matrix::{extract_mentions, parse_pill},
+        media::{
+            filename_for_mime, get_audio_info, get_image_info, get_video_info, load_file,
+            save_to_file,
+        },
         template_callbacks::TemplateCallbacks,
     },
     Window,
@@ -84,6 +111,10 @@ use crate::{
 const SCROLL_TIMEOUT: Duration = Duration::from_millis(500);
 /// The time to wait before considering that messages on a screen where read.
 const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// The time to wait after a change in the composer before saving the draft.
+const DRAFT_TIMEOUT: Duration = Duration::from_secs(1);
+/// The minimum duration of a voice message.
+const MIN_VOICE_MESSAGE_DURATION: Duration = Duration::from_secs(1);
 
 #[derive(Debug, Default, Hash, Eq, PartialEq, Clone, Copy, glib::Enum)]
 #[repr(i32)]
@@ -113,6 +144,9 @@ mod imp {
         pub room: RefCell<Option<Room>>,
         pub room_members: RefCell<Option<MemberList>>,
         pub room_handlers: RefCell<Vec<SignalHandlerId>>,
+        /// The signal handler for the updates of the crypto identities of the
+        /// session of the room.
+        pub identities_handler: RefCell<Option<SignalHandlerId>>,
         pub timeline_handlers: RefCell<Vec<SignalHandlerId>>,
         pub md_enabled: Cell<bool>,
         pub is_auto_scrolling: Cell<bool>,
@@ -121,6 +155,10 @@ mod imp {
         pub item_reaction_chooser: ReactionChooser,
         pub completion: CompletionPopover,
         #[template_child]
+        pub split_view: TemplateChild<adw::OverlaySplitView>,
+        #[template_child]
+        pub thread_view: TemplateChild<ThreadView>,
+        #[template_child]
         pub room_title: TemplateChild<RoomTitle>,
         #[template_child]
         pub room_menu: TemplateChild<gtk::MenuButton>,
@@ -144,6 +182,12 @@ mod imp {
         pub stack: TemplateChild<gtk::Stack>,
         #[template_child]
         pub tombstoned_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub identity_changed_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub unread_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub latest_banner: TemplateChild<adw::Banner>,
         pub is_loading: Cell<bool>,
         #[template_child]
         pub drag_overlay: TemplateChild<DragOverlay>,
@@ -155,10 +199,47 @@ mod imp {
         pub related_event: RefCell<Option<Event>>,
         pub scroll_timeout: RefCell<Option<glib::SourceId>>,
         pub read_timeout: RefCell<Option<glib::SourceId>>,
+        pub draft_timeout: RefCell<Option<glib::SourceId>>,
+        /// The relation of the draft that could not be restored yet, because
+        /// the related event is not loaded.
+        pub pending_draft_relation: RefCell<Option<DraftRelation>>,
+        /// The ID of the event that was fully read when the room was opened.
+        ///
+        /// The fully-read marker moves while the room is presented, so it is
+        /// used to jump to the first unread message.
+        pub first_unread_anchor: RefCell<Option<OwnedEventId>>,
         /// The GtkSelectionModel used in the listview.
-        // TODO: use gtk::MultiSelection to allow selection
-        pub selection_model: OnceCell<gtk::NoSelection>,
+        pub selection_model: OnceCell<gtk::MultiSelection>,
+        /// Whether several events can be selected to act on them.
+        pub selection_mode: Cell<bool>,
+        #[template_child]
+        pub composer: TemplateChild<gtk::Widget>,
+        #[template_child]
+        pub selection_bar: TemplateChild<gtk::ActionBar>,
+        #[template_child]
+        pub selection_label: TemplateChild<gtk::Label>,
         pub room_expr_watches: RefCell<HashMap<&'static str, gtk::ExpressionWatch>>,
+        #[template_child]
+        pub composer_stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub voice_record_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub recording_waveform: TemplateChild<Waveform>,
+        #[template_child]
+        pub recording_duration: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub voice_preview_play_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub preview_waveform: TemplateChild<Waveform>,
+        #[template_child]
+        pub preview_duration: TemplateChild<gtk::Label>,
+        pub voice_recorder: VoiceRecorder,
+        /// The voice message that was recorded and is waiting to be sent.
+        pub voice_message: RefCell<Option<VoiceMessage>>,
+        /// The media file to play the recorded voice message.
+        pub voice_media_file: RefCell<Option<gtk::MediaFile>>,
+        /// Whether the recording button is currently pressed.
+        pub voice_record_pressed: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -170,6 +251,7 @@ mod imp {
         fn class_init(klass: &mut Self::Class) {
             CustomEntry::static_type();
             ItemRow::static_type();
+            StickerChooser::static_type();
             VerificationInfoBar::static_type();
             Timeline::static_type();
             Self::bind_template(klass);
@@ -205,11 +287,33 @@ mod imp {
             klass.install_action("room-history.invite-members", None, move |widget, _, _| {
                 widget.open_room_details(Some(room_details::SubpageName::Invite));
             });
+            klass.install_action("room-history.search-messages", None, move |widget, _, _| {
+                widget.open_message_search();
+            });
 
             klass.install_action("room-history.scroll-down", None, move |widget, _, _| {
                 widget.scroll_down();
             });
 
+            klass.install_action(
+                "room-history.jump-to-event",
+                Some("s"),
+                move |widget, _, v| {
+                    if let Some(event_id) = v
+                        .and_then(String::from_variant)
+                        .and_then(|s| EventId::parse(s).ok())
+                    {
+                        widget.jump_to_event(EventKey::EventId(event_id));
+                    }
+                },
+            );
+
+            klass.install_action("room-history.jump-to-date", None, move |widget, _, _| {
+                spawn!(clone!(@weak widget => async move {
+                    widget.jump_to_date().await;
+                }));
+            });
+
             klass.install_action("room-history.select-file", None, move |widget, _, _| {
                 spawn!(clone!(@weak widget => async move {
                     widget.select_file().await;
@@ -241,6 +345,10 @@ mod imp {
                 }));
             });
 
+            klass.install_action("room-history.create-poll", None, move |widget, _, _| {
+                widget.create_poll();
+            });
+
             klass.install_property_action("room-history.markdown", "markdown-enabled");
 
             klass.install_action(
@@ -278,6 +386,104 @@ mod imp {
                     }
                 }
             });
+
+            klass.install_action(
+                "room-history.open-thread",
+                Some("s"),
+                move |widget, _, v| {
+                    if let Some(event_id) = v
+                        .and_then(String::from_variant)
+                        .and_then(|s| EventId::parse(s).ok())
+                    {
+                        if let Some(event) = widget.room().and_then(|room| {
+                            room.timeline().event_by_key(&EventKey::EventId(event_id))
+                        }) {
+                            widget.open_thread(event);
+                        }
+                    }
+                },
+            );
+
+            klass.install_action("room-history.close-thread", None, move |widget, _, _| {
+                widget.close_thread();
+            });
+
+            klass.install_action(
+                "room-history.select-event",
+                Some("s"),
+                move |widget, _, v| {
+                    if let Some(event_id) = v
+                        .and_then(String::from_variant)
+                        .and_then(|s| EventId::parse(s).ok())
+                    {
+                        widget.select_event(&EventKey::EventId(event_id));
+                    }
+                },
+            );
+
+            klass.install_action("room-history.exit-selection", None, move |widget, _, _| {
+                widget.set_selection_mode(false);
+            });
+
+            klass.install_action(
+                "room-history.copy-selection-text",
+                None,
+                move |widget, _, _| {
+                    widget.copy_selection(false);
+                },
+            );
+
+            klass.install_action(
+                "room-history.copy-selection-markdown",
+                None,
+                move |widget, _, _| {
+                    widget.copy_selection(true);
+                },
+            );
+
+            klass.install_action(
+                "room-history.forward-selection",
+                None,
+                move |widget, _, _| {
+                    widget.forward_selection();
+                },
+            );
+
+            klass.install_action(
+                "room-history.save-selection-media",
+                None,
+                move |widget, _, _| {
+                    spawn!(clone!(@weak widget => async move {
+                        widget.save_selection_media().await;
+                    }));
+                },
+            );
+
+            klass.install_action(
+                "room-history.create-sticker-pack",
+                None,
+                move |widget, _, _| {
+                    widget.create_sticker_pack();
+                },
+            );
+
+            klass.install_action(
+                "room-history.discard-voice-message",
+                None,
+                move |widget, _, _| {
+                    widget.discard_voice_message();
+                },
+            );
+
+            klass.install_action(
+                "room-history.remove-selection",
+                None,
+                move |widget, _, _| {
+                    spawn!(clone!(@weak widget => async move {
+                        widget.remove_selection().await;
+                    }));
+                },
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -302,6 +508,9 @@ mod imp {
                     glib::ParamSpecBoolean::builder("sticky")
                         .explicit_notify()
                         .build(),
+                    glib::ParamSpecBoolean::builder("selection-mode")
+                        .explicit_notify()
+                        .build(),
                     glib::ParamSpecEnum::builder::<RelatedEventType>("related-event-type")
                         .read_only()
                         .build(),
@@ -321,6 +530,7 @@ mod imp {
                 "room" => obj.set_room(value.get().unwrap()),
                 "markdown-enabled" => obj.set_markdown_enabled(value.get().unwrap()),
                 "sticky" => obj.set_sticky(value.get().unwrap()),
+                "selection-mode" => obj.set_selection_mode(value.get().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -333,6 +543,7 @@ mod imp {
                 "empty" => obj.is_empty().to_value(),
                 "markdown-enabled" => obj.markdown_enabled().to_value(),
                 "sticky" => obj.sticky().to_value(),
+                "selection-mode" => obj.selection_mode().to_value(),
                 "related-event-type" => obj.related_event_type().to_value(),
                 "related-event" => obj.related_event().to_value(),
                 _ => unimplemented!(),
@@ -342,21 +553,45 @@ mod imp {
         fn constructed(&self) {
             let obj = self.obj();
 
+            // The events in the context of a focused event are not `TimelineItem`s, so
+            // the rows are created when the items are bound.
             let factory = gtk::SignalListItemFactory::new();
-            factory.connect_setup(clone!(@weak obj => move |_, item| {
-                let item = match item.downcast_ref::<gtk::ListItem>() {
-                    Some(item) => item,
-                    None => {
-                        error!("List item factory did not receive a list item: {item:?}");
-                        return;
-                    }
+            factory.connect_setup(move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    error!("List item factory did not receive a list item: {item:?}");
+                    return;
                 };
-                let row = ItemRow::new(&obj);
-                item.set_child(Some(&row));
-                item.bind_property("item", &row, "item").build();
+
                 item.set_activatable(false);
+                // The list view replaces the selection on click, so we handle
+                // clicks ourselves to toggle events in selection mode.
                 item.set_selectable(false);
+            });
+            factory.connect_bind(clone!(@weak obj => move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+
+                if let Some(timeline_item) = item.item().and_downcast::<TimelineItem>() {
+                    let row = item.child().and_downcast::<ItemRow>().unwrap_or_else(|| {
+                        let row = obj.create_item_row(item);
+                        item.set_child(Some(&row));
+                        row
+                    });
+                    row.set_property("item", timeline_item);
+                } else if let Some(event) = item.item().and_downcast::<SearchResult>() {
+                    item.set_child(Some(&remote_event_row(&event)));
+                }
             }));
+            factory.connect_unbind(move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+
+                if let Some(row) = item.child().and_downcast::<ItemRow>() {
+                    row.set_property("item", None::<TimelineItem>);
+                }
+            });
             self.listview.set_factory(Some(&factory));
 
             // Needed to use the natural height of GtkPictures
@@ -364,6 +599,11 @@ mod imp {
                 .set_vscroll_policy(gtk::ScrollablePolicy::Natural);
 
             self.listview.set_model(Some(obj.selection_model()));
+            obj.selection_model()
+                .connect_selection_changed(clone!(@weak obj => move |_, _, _| {
+                    obj.update_selection_actions();
+                }));
+            obj.update_selection_actions();
 
             obj.set_sticky(true);
             let adj = self.listview.vadjustment().unwrap();
@@ -373,7 +613,10 @@ mod imp {
 
                 obj.trigger_read_receipts_update();
 
-                let is_at_bottom = adj.value() + adj.page_size() == adj.upper();
+                // The bottom of the context of a focused event is not the end of the
+                // timeline.
+                let is_at_bottom =
+                    adj.value() + adj.page_size() == adj.upper() && obj.context().is_none();
                 if imp.is_auto_scrolling.get() {
                     if is_at_bottom {
                         imp.is_auto_scrolling.set(false);
@@ -458,6 +701,7 @@ mod imp {
                let is_empty = start_iter == end_iter;
                obj.action_set_enabled("room-history.send-text-message", !is_empty);
                obj.send_typing_notification(!is_empty);
+               obj.queue_save_draft();
             }));
             crate::utils::sourceview::setup_style_scheme(&buffer);
 
@@ -476,15 +720,23 @@ mod imp {
                 .build();
 
             self.completion.set_parent(&*self.message_entry);
+            self.thread_view.set_room_history(Some(&*obj));
 
             obj.setup_drop_target();
+            obj.setup_voice_recorder();
 
             self.parent_constructed();
         }
 
         fn dispose(&self) {
+            // Don't lose the latest changes if the draft was not saved yet.
+            self.obj().save_draft();
             self.completion.unparent();
 
+            if let Some(voice_message) = self.voice_message.take() {
+                voice_message.delete();
+            }
+
             for (_, expr_watch) in self.room_expr_watches.take() {
                 expr_watch.unwatch();
             }
@@ -519,15 +771,30 @@ impl RoomHistory {
                 room.disconnect(handler);
             }
 
+            if let Some(handler) = imp.identities_handler.take() {
+                room.session().crypto_identities().disconnect(handler);
+            }
+
             for handler in imp.timeline_handlers.take() {
                 room.timeline().disconnect(handler);
             }
 
+            // Go back to the live timeline when the room is opened again.
+            room.timeline().set_context(None);
+
             for (_, expr_watch) in imp.room_expr_watches.take() {
                 expr_watch.unwatch();
             }
 
+            self.save_draft();
+            self.set_selection_mode(false);
             self.clear_related_event();
+            imp.pending_draft_relation.take();
+            imp.message_entry.buffer().set_text("");
+            self.close_thread();
+            imp.voice_recorder.cancel();
+            self.discard_voice_message();
+            imp.latest_banner.set_revealed(false);
         }
 
         if let Some(source_id) = imp.scroll_timeout.take() {
@@ -568,13 +835,28 @@ impl RoomHistory {
                 }),
             );
 
+            let encrypted_handler = room.connect_notify_local(
+                Some("encrypted"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_identity_changed_banner();
+                }),
+            );
+
             imp.room_handlers.replace(vec![
                 category_handler,
                 tombstoned_handler,
                 successor_handler,
                 successor_room_handler,
+                encrypted_handler,
             ]);
 
+            let identities_handler = room.session().crypto_identities().connect_user_updated(
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_identity_changed_banner();
+                }),
+            );
+            imp.identities_handler.replace(Some(identities_handler));
+
             let empty_handler = timeline.connect_notify_local(
                 Some("empty"),
                 clone!(@weak self as obj => move |_, _| {
@@ -591,11 +873,21 @@ impl RoomHistory {
                     if timeline.state() == TimelineState::Ready {
                         obj.start_loading();
                     }
+
+                    // The event related to the draft might have been loaded.
+                    obj.restore_draft_relation();
+                }),
+            );
+
+            let context_handler = timeline.connect_notify_local(
+                Some("context"),
+                clone!(@weak self as obj => move |_, _| {
+                    obj.update_model();
                 }),
             );
 
             imp.timeline_handlers
-                .replace(vec![empty_handler, state_handler]);
+                .replace(vec![empty_handler, state_handler, context_handler]);
 
             timeline.remove_empty_typing_row();
             self.trigger_read_receipts_update();
@@ -615,11 +907,14 @@ impl RoomHistory {
         imp.is_loading.set(false);
         imp.message_entry.grab_focus();
         imp.room.replace(room);
+        self.load_draft();
         self.update_view();
         self.start_loading();
         self.update_room_state();
         self.update_completion();
         self.update_tombstoned_banner();
+        self.update_identity_changed_banner();
+        self.update_unread_banner();
         self.notify("room");
         self.notify("empty");
     }
@@ -690,6 +985,7 @@ impl RoomHistory {
 
         self.imp().related_event.replace(event);
         self.notify("related-event");
+        self.queue_save_draft();
     }
 
     pub fn clear_related_event(&self) {
@@ -702,10 +998,309 @@ impl RoomHistory {
         self.set_related_event_type(RelatedEventType::default());
     }
 
-    fn selection_model(&self) -> &gtk::NoSelection {
+    fn selection_model(&self) -> &gtk::MultiSelection {
         self.imp()
             .selection_model
-            .get_or_init(|| gtk::NoSelection::new(gio::ListModel::NONE.cloned()))
+            .get_or_init(|| gtk::MultiSelection::new(gio::ListModel::NONE.cloned()))
+    }
+
+    /// Whether several events can be selected to act on them.
+    pub fn selection_mode(&self) -> bool {
+        self.imp().selection_mode.get()
+    }
+
+    /// Set whether several events can be selected to act on them.
+    pub fn set_selection_mode(&self, selection_mode: bool) {
+        let imp = self.imp();
+
+        if self.selection_mode() == selection_mode {
+            return;
+        }
+
+        if !selection_mode {
+            self.selection_model().unselect_all();
+        }
+
+        imp.composer.set_visible(!selection_mode);
+        imp.selection_bar.set_visible(selection_mode);
+
+        imp.selection_mode.set(selection_mode);
+        self.notify("selection-mode");
+    }
+
+    /// Enter selection mode and add the event with the given key to the
+    /// selection.
+    fn select_event(&self, key: &EventKey) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let items = room.timeline().items();
+
+        let position = items
+            .iter::<glib::Object>()
+            .filter_map(Result::ok)
+            .position(|item| {
+                item.downcast_ref::<Event>()
+                    .is_some_and(|e| e.key() == *key)
+            });
+        let Some(position) = position else {
+            return;
+        };
+
+        self.set_selection_mode(true);
+        self.selection_model().select_item(position as u32, false);
+    }
+
+    /// Create a row for the given list item presenting a `TimelineItem`.
+    fn create_item_row(&self, item: &gtk::ListItem) -> ItemRow {
+        let row = ItemRow::new(self);
+
+        let gesture = gtk::GestureClick::new();
+        gesture.connect_released(
+            clone!(@weak self as obj, @weak item => move |gesture, _, _, _| {
+                if !obj.selection_mode() {
+                    return;
+                }
+
+                gesture.set_state(gtk::EventSequenceState::Claimed);
+                obj.toggle_selected(item.position());
+            }),
+        );
+        row.add_controller(gesture);
+
+        row
+    }
+
+    /// Toggle whether the event at the given position is selected.
+    fn toggle_selected(&self, position: u32) {
+        let selection_model = self.selection_model();
+
+        // Only events can be selected.
+        if selection_model
+            .item(position)
+            .and_downcast::<Event>()
+            .is_none()
+        {
+            return;
+        }
+
+        if selection_model.is_selected(position) {
+            selection_model.unselect_item(position);
+        } else {
+            selection_model.select_item(position, false);
+        }
+    }
+
+    /// The events that are currently selected, in chronological order.
+    fn selected_events(&self) -> Vec<Event> {
+        let selection_model = self.selection_model();
+        let selection = selection_model.selection();
+
+        (0..selection.size())
+            .filter_map(|i| {
+                selection_model
+                    .item(selection.nth(i as u32))
+                    .and_downcast::<Event>()
+            })
+            .collect()
+    }
+
+    /// Whether our own user can remove the given event.
+    fn can_remove_event(&self, event: &Event) -> bool {
+        if event.event_id().is_none() || !event.is_message() {
+            return false;
+        }
+
+        let room = event.room();
+        let Some(own_user_id) = room.session().user().map(|user| user.user_id()) else {
+            return false;
+        };
+
+        event.sender_id() == own_user_id
+            || room
+                .power_levels()
+                .member_is_allowed_to(&own_user_id, PowerLevelAction::Redact)
+    }
+
+    /// Update the label and the actions for the current selection.
+    fn update_selection_actions(&self) {
+        let events = self.selected_events();
+        let count = events.len() as u32;
+
+        self.imp().selection_label.set_label(&ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this
+            // is a variable name.
+            "{n} message selected",
+            "{n} messages selected",
+            count,
+            &[("n", &count.to_string())],
+        ));
+
+        self.action_set_enabled(
+            "room-history.copy-selection-text",
+            events.iter().any(|e| e.message().is_some()),
+        );
+        self.action_set_enabled(
+            "room-history.copy-selection-markdown",
+            events.iter().any(|e| e.message().is_some()),
+        );
+        self.action_set_enabled(
+            "room-history.forward-selection",
+            events.iter().any(|e| e.forward_content().is_some()),
+        );
+        self.action_set_enabled(
+            "room-history.save-selection-media",
+            events.iter().any(|e| e.has_media_content()),
+        );
+        self.action_set_enabled(
+            "room-history.create-sticker-pack",
+            events.iter().any(|e| e.pack_image().is_some()),
+        );
+        self.action_set_enabled(
+            "room-history.remove-selection",
+            events.iter().any(|e| self.can_remove_event(e)),
+        );
+    }
+
+    /// Copy the selected messages to the clipboard as quotes, in plain text or
+    /// in Markdown.
+    fn copy_selection(&self, markdown: bool) {
+        let quotes = self
+            .selected_events()
+            .iter()
+            .filter_map(|event| {
+                let message = event.message()?;
+                let sender = event.sender().display_name();
+                let time = event.time();
+
+                let header = if markdown {
+                    format!("**{sender}** ({time})")
+                } else {
+                    format!("{sender} ({time})")
+                };
+                let quote = message
+                    .body()
+                    .lines()
+                    .map(|line| format!("> {line}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                Some(format!("{header}\n{quote}"))
+            })
+            .collect::<Vec<_>>();
+
+        if quotes.is_empty() {
+            return;
+        }
+
+        self.clipboard().set_text(&quotes.join("\n\n"));
+        toast!(self, gettext("Messages copied to clipboard"));
+        self.set_selection_mode(false);
+    }
+
+    /// Forward the selected messages to other rooms.
+    fn forward_selection(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+
+        let dialog = ForwardDialog::new(&window, &room.session(), self.selected_events());
+        dialog.present();
+        self.set_selection_mode(false);
+    }
+
+    /// Save the media of the selected messages to files.
+    async fn save_selection_media(&self) {
+        let events = self
+            .selected_events()
+            .into_iter()
+            .filter(Event::has_media_content)
+            .collect::<Vec<_>>();
+        self.set_selection_mode(false);
+
+        for event in events {
+            let (filename, data) = match event.get_media_content().await {
+                Ok(res) => res,
+                Err(error) => {
+                    error!("Could not get event file: {error}");
+                    toast!(self, error.to_user_facing());
+                    continue;
+                }
+            };
+
+            save_to_file(self, data, filename).await;
+        }
+    }
+
+    /// Open a dialog to create a sticker pack from the selected images.
+    fn create_sticker_pack(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let images = self
+            .selected_events()
+            .iter()
+            .filter_map(Event::pack_image)
+            .collect::<Vec<_>>();
+        if images.is_empty() {
+            return;
+        }
+
+        let dialog = StickerPackCreationDialog::new(self.parent_window().as_ref(), &room, images);
+        dialog.present();
+        self.set_selection_mode(false);
+    }
+
+    /// Remove the selected messages that our own user is allowed to remove.
+    async fn remove_selection(&self) {
+        let events = self
+            .selected_events()
+            .into_iter()
+            .filter(|event| self.can_remove_event(event))
+            .collect::<Vec<_>>();
+        let count = events.len() as u32;
+        if count == 0 {
+            return;
+        }
+
+        let dialog = adw::MessageDialog::builder()
+            .heading(ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "Remove {n} Message?",
+                "Remove {n} Messages?",
+                count,
+                &[("n", &count.to_string())],
+            ))
+            .body(gettext(
+                "The messages will be removed for everyone in the room. This cannot be undone.",
+            ))
+            .default_response("cancel")
+            .close_response("cancel")
+            .modal(true)
+            .transient_for(&self.parent_window().unwrap())
+            .build();
+        dialog.add_responses(&[
+            ("cancel", &gettext("_Cancel")),
+            ("remove", &gettext("_Remove")),
+        ]);
+        dialog.set_response_appearance("remove", adw::ResponseAppearance::Destructive);
+
+        if dialog.choose_future().await != "remove" {
+            return;
+        }
+
+        for event in events {
+            if let Some(event_id) = event.event_id() {
+                event.room().redact(event_id, None);
+            }
+        }
+
+        self.set_selection_mode(false);
     }
 
     pub fn set_reply_to(&self, event: Event) {
@@ -763,15 +1358,7 @@ impl RoomHistory {
         };
 
         let imp = self.imp();
-        imp.related_event_header.set_widgets::<gtk::Widget>(vec![]);
-        imp.related_event_header
-            // Translators: In this string, 'Edit' is a noun.
-            .set_label(Some(pgettext("room-history", "Edit")));
-
-        imp.related_event_content.set_visible(false);
-
-        self.set_related_event_type(RelatedEventType::Edit);
-        self.set_related_event(Some(event));
+        self.set_edit_header(event);
 
         let view = &*imp.message_entry;
         let buffer = view.buffer();
@@ -805,20 +1392,155 @@ impl RoomHistory {
         imp.message_entry.grab_focus();
     }
 
-    /// Get an iterator over chunks of the message entry's text between the
-    /// given start and end, split by mentions.
-    fn split_buffer_mentions(&self, start: gtk::TextIter, end: gtk::TextIter) -> SplitMentions {
-        SplitMentions { iter: start, end }
-    }
-
-    pub fn send_text_message(&self) {
+    /// Show that the given event is being edited, without changing the
+    /// content of the composer.
+    fn set_edit_header(&self, event: Event) {
         let imp = self.imp();
-        let buffer = imp.message_entry.buffer();
-        let (start_iter, end_iter) = buffer.bounds();
-        let body_len = buffer.text(&start_iter, &end_iter, true).len();
-
-        let is_markdown = imp.md_enabled.get();
+        imp.related_event_header.set_widgets::<gtk::Widget>(vec![]);
+        imp.related_event_header
+            // Translators: In this string, 'Edit' is a noun.
+            .set_label(Some(pgettext("room-history", "Edit")));
+
+        imp.related_event_content.set_visible(false);
+
+        self.set_related_event_type(RelatedEventType::Edit);
+        self.set_related_event(Some(event));
+    }
+
+    /// Save the draft of the current room after a delay.
+    fn queue_save_draft(&self) {
+        let imp = self.imp();
+
+        if let Some(source_id) = imp.draft_timeout.take() {
+            source_id.remove();
+        }
+
+        imp.draft_timeout.replace(Some(glib::timeout_add_local_once(
+            DRAFT_TIMEOUT,
+            clone!(@weak self as obj => move || {
+                obj.imp().draft_timeout.take();
+                obj.save_draft();
+            }),
+        )));
+    }
+
+    /// Save the content of the composer as the draft of the current room.
+    fn save_draft(&self) {
+        let imp = self.imp();
+
+        if let Some(source_id) = imp.draft_timeout.take() {
+            source_id.remove();
+        }
+
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let buffer = imp.message_entry.buffer();
+        let (start_iter, end_iter) = buffer.bounds();
+        let chunks = self
+            .split_buffer_mentions(start_iter, end_iter)
+            .map(|chunk| match chunk {
+                MentionChunk::Text(text) => DraftChunk::Text(text),
+                MentionChunk::Mention { name, uri } => DraftChunk::Mention { name, uri },
+            })
+            .collect();
+
+        let relation = self
+            .related_event()
+            .and_then(|event| event.event_id())
+            .and_then(|event_id| match self.related_event_type() {
+                RelatedEventType::None => None,
+                RelatedEventType::Reply => Some(DraftRelation::Reply(event_id)),
+                RelatedEventType::Edit => Some(DraftRelation::Edit(event_id)),
+            })
+            .or_else(|| imp.pending_draft_relation.borrow().clone());
+
+        room.set_draft(Some(ComposerDraft { chunks, relation }));
+    }
+
+    /// Restore the draft of the current room in the composer.
+    fn load_draft(&self) {
+        let imp = self.imp();
+        let Some(room) = self.room() else {
+            return;
+        };
+        let Some(draft) = room.draft() else {
+            return;
+        };
+
+        let session = room.session();
+        let view = &*imp.message_entry;
+        let buffer = view.buffer();
+        let mut iter = buffer.end_iter();
+
+        for chunk in draft.chunks {
+            match chunk {
+                DraftChunk::Text(text) => buffer.insert(&mut iter, &text),
+                DraftChunk::Mention { name, uri } => {
+                    if let Some(pill) = parse_pill(&uri, &room, &session) {
+                        let anchor = buffer.create_child_anchor(&mut iter);
+                        view.add_child_at_anchor(&pill, &anchor);
+                    } else {
+                        buffer.insert(&mut iter, &name);
+                    }
+                }
+            }
+        }
+
+        imp.pending_draft_relation.replace(draft.relation);
+        self.restore_draft_relation();
+    }
+
+    /// Restore the related event of the draft, if it is loaded.
+    fn restore_draft_relation(&self) {
+        let imp = self.imp();
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let (event_id, is_reply) = match &*imp.pending_draft_relation.borrow() {
+            Some(DraftRelation::Reply(event_id)) => (event_id.clone(), true),
+            Some(DraftRelation::Edit(event_id)) => (event_id.clone(), false),
+            None => return,
+        };
+
+        let Some(event) = room.timeline().event_by_key(&EventKey::EventId(event_id)) else {
+            return;
+        };
+        imp.pending_draft_relation.take();
+
+        if is_reply {
+            self.set_reply_to(event);
+        } else {
+            self.set_edit_header(event);
+        }
+    }
+
+    /// Get an iterator over chunks of the message entry's text between the
+    /// given start and end, split by mentions.
+    fn split_buffer_mentions(&self, start: gtk::TextIter, end: gtk::TextIter) -> SplitMentions {
+        SplitMentions { iter: start, end }
+    }
+
+    pub fn send_text_message(&self) {
+        let imp = self.imp();
+
+        if let Some(voice_message) = imp.voice_message.take() {
+            spawn!(clone!(@weak self as obj => async move {
+                obj.send_voice_message(voice_message).await;
+            }));
+            return;
+        }
+
+        let buffer = imp.message_entry.buffer();
+        let (start_iter, end_iter) = buffer.bounds();
+        let body_len = buffer.text(&start_iter, &end_iter, true).len();
+
+        let is_markdown = imp.md_enabled.get();
+        let emoticons = imp.completion.emoticons();
         let mut has_mentions = false;
+        let mut has_emoticons = false;
         let mut plain_body = String::with_capacity(body_len);
         // formatted_body is Markdown if is_markdown is true, and HTML if false.
         let mut formatted_body = String::with_capacity(body_len);
@@ -827,7 +1549,13 @@ impl RoomHistory {
             match chunk {
                 MentionChunk::Text(text) => {
                     plain_body.push_str(&text);
-                    formatted_body.push_str(&text);
+
+                    if let Some(html) = replace_emoticons(&text, &emoticons) {
+                        has_emoticons = true;
+                        formatted_body.push_str(&html);
+                    } else {
+                        formatted_body.push_str(&text);
+                    }
                 }
                 MentionChunk::Mention { name, uri } => {
                     has_mentions = true;
@@ -849,7 +1577,7 @@ impl RoomHistory {
 
         let html_body = if is_markdown {
             FormattedBody::markdown(formatted_body).map(|b| b.body)
-        } else if has_mentions {
+        } else if has_mentions || has_emoticons {
             // Already formatted with HTML
             Some(formatted_body)
         } else {
@@ -923,6 +1651,23 @@ impl RoomHistory {
         self.clear_related_event();
     }
 
+    /// Open the thread with the given root event in the side panel.
+    pub fn open_thread(&self, root: Event) {
+        let imp = self.imp();
+
+        imp.thread_view.set_root(Some(root));
+        imp.split_view.set_show_sidebar(true);
+        imp.thread_view.grab_entry_focus();
+    }
+
+    /// Close the thread currently displayed in the side panel, if any.
+    pub fn close_thread(&self) {
+        let imp = self.imp();
+
+        imp.split_view.set_show_sidebar(false);
+        imp.thread_view.set_root(None);
+    }
+
     /// Leave the room.
     pub async fn leave(&self) {
         let Some(room) = self.room() else {
@@ -1001,6 +1746,20 @@ impl RoomHistory {
         }
     }
 
+    /// Opens the dialog to search messages in the room.
+    fn open_message_search(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let Some(window) = self.root().and_downcast::<Window>() else {
+            return;
+        };
+
+        window
+            .session_view()
+            .show_message_search_dialog(Some(&room));
+    }
+
     fn update_room_state(&self) {
         let imp = self.imp();
 
@@ -1063,6 +1822,11 @@ impl RoomHistory {
             return;
         }
 
+        if let Some(context) = self.context() {
+            self.load_context(&context);
+            return;
+        }
+
         if !self.need_messages() {
             return;
         }
@@ -1105,15 +1869,32 @@ impl RoomHistory {
         }
 
         imp.scroll_btn_revealer.set_reveal_child(!sticky);
+        if sticky {
+            imp.latest_banner.set_revealed(false);
+        }
 
         imp.sticky.set(sticky);
         self.notify("sticky");
+
+        if sticky && imp.unread_banner.is_revealed() {
+            // The user caught up with the newest messages, resume updating
+            // the read marker.
+            imp.unread_banner.set_revealed(false);
+            self.trigger_read_receipts_update();
+        }
     }
 
     /// Scroll to the newest message in the timeline
+    ///
+    /// If the context of an event is presented, the live timeline is
+    /// presented again.
     pub fn scroll_down(&self) {
         let imp = self.imp();
 
+        if let Some(room) = self.room() {
+            room.timeline().set_context(None);
+        }
+
         imp.is_auto_scrolling.set(true);
 
         imp.scrolled_window
@@ -1125,7 +1906,7 @@ impl RoomHistory {
         let imp = self.imp();
         let adj = imp.listview.vadjustment().unwrap();
         let is_at_bottom = adj.value() + adj.page_size() == adj.upper();
-        self.set_sticky(is_at_bottom);
+        self.set_sticky(is_at_bottom && self.context().is_none());
     }
 
     fn try_again(&self) {
@@ -1136,6 +1917,16 @@ impl RoomHistory {
         self.imp().message_entry.emit_insert_emoji();
     }
 
+    /// Open a dialog to create a poll in the current room.
+    fn create_poll(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let dialog = PollCreationDialog::new(self.parent_window().as_ref(), &room);
+        dialog.present();
+    }
+
     async fn send_location(&self) -> ashpd::Result<()> {
         let Some(room) = self.room() else {
             return Ok(());
@@ -1295,6 +2086,203 @@ impl RoomHistory {
         }
     }
 
+    /// Set up the recording of voice messages.
+    fn setup_voice_recorder(&self) {
+        let imp = self.imp();
+
+        // Record while the button is pressed.
+        let gesture = gtk::GestureClick::new();
+        gesture.connect_pressed(clone!(@weak self as obj => move |gesture, _, _, _| {
+            gesture.set_state(gtk::EventSequenceState::Claimed);
+            obj.imp().voice_record_pressed.set(true);
+            obj.start_voice_recording();
+        }));
+        gesture.connect_released(clone!(@weak self as obj => move |_, _, _, _| {
+            obj.finish_voice_recording();
+        }));
+        gesture.connect_cancel(clone!(@weak self as obj => move |_, _| {
+            obj.finish_voice_recording();
+        }));
+        imp.voice_record_button.add_controller(gesture);
+
+        imp.voice_recorder.connect_notify_local(
+            Some("duration"),
+            clone!(@weak self as obj => move |recorder, _| {
+                let imp = obj.imp();
+                imp.recording_waveform.set_samples(recorder.samples());
+                imp.recording_duration.set_label(&format_duration(recorder.duration()));
+            }),
+        );
+        imp.voice_recorder.connect_notify_local(
+            Some("recording"),
+            clone!(@weak self as obj => move |recorder, _| {
+                let imp = obj.imp();
+                if recorder.is_recording() {
+                    imp.voice_record_button.add_css_class("destructive-action");
+                    imp.composer_stack.set_visible_child_name("recording");
+                } else {
+                    imp.voice_record_button.remove_css_class("destructive-action");
+                }
+            }),
+        );
+    }
+
+    /// Start or stop recording a voice message.
+    ///
+    /// This is used when the record button is activated with the keyboard.
+    #[template_callback]
+    fn toggle_voice_recording(&self) {
+        if self.imp().voice_recorder.is_recording() {
+            self.stop_voice_recording();
+        } else {
+            self.start_voice_recording();
+        }
+    }
+
+    /// Start recording a voice message.
+    fn start_voice_recording(&self) {
+        let imp = self.imp();
+
+        if imp.voice_recorder.is_recording() {
+            return;
+        }
+        self.discard_voice_message();
+
+        if let Err(error) = imp.voice_recorder.start() {
+            error!("Could not start recording voice message: {error}");
+            toast!(self, gettext("Could not start recording"));
+        }
+    }
+
+    /// Stop recording a voice message, if the record button was pressed.
+    fn finish_voice_recording(&self) {
+        if self.imp().voice_record_pressed.replace(false) {
+            self.stop_voice_recording();
+        }
+    }
+
+    /// Stop recording a voice message and show its preview.
+    fn stop_voice_recording(&self) {
+        spawn!(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+
+            let Some(voice_message) = imp.voice_recorder.stop().await else {
+                imp.composer_stack.set_visible_child_name("entry");
+                return;
+            };
+
+            if voice_message.duration < MIN_VOICE_MESSAGE_DURATION {
+                voice_message.delete();
+                imp.composer_stack.set_visible_child_name("entry");
+                toast!(obj, gettext("Hold the button to record a voice message"));
+                return;
+            }
+
+            obj.set_voice_message(voice_message);
+        }));
+    }
+
+    /// Set the voice message waiting to be sent, and show its preview.
+    fn set_voice_message(&self, voice_message: VoiceMessage) {
+        let imp = self.imp();
+
+        let media_file = gtk::MediaFile::for_file(&voice_message.file);
+        media_file.connect_playing_notify(clone!(@weak self as obj => move |media_file| {
+            let imp = obj.imp();
+            if media_file.is_playing() {
+                imp.voice_preview_play_button.set_icon_name("media-playback-pause-symbolic");
+                imp.voice_preview_play_button.set_tooltip_text(Some(&gettext("Pause")));
+            } else {
+                imp.voice_preview_play_button.set_icon_name("media-playback-start-symbolic");
+                imp.voice_preview_play_button.set_tooltip_text(Some(&gettext("Play")));
+            }
+        }));
+
+        imp.preview_waveform
+            .set_samples(voice_message.samples.clone());
+        imp.preview_waveform.set_media_file(Some(&media_file));
+        imp.preview_duration
+            .set_label(&format_duration(voice_message.duration));
+        imp.composer_stack.set_visible_child_name("preview");
+        self.action_set_enabled("room-history.send-text-message", true);
+
+        imp.voice_media_file.replace(Some(media_file));
+        imp.voice_message.replace(Some(voice_message));
+    }
+
+    /// Play or pause the preview of the recorded voice message.
+    #[template_callback]
+    fn toggle_voice_preview(&self) {
+        if let Some(media_file) = &*self.imp().voice_media_file.borrow() {
+            if media_file.is_playing() {
+                media_file.pause();
+            } else {
+                if media_file.is_ended() {
+                    media_file.seek(0);
+                }
+                media_file.play();
+            }
+        }
+    }
+
+    /// Discard the recorded voice message, if any, and go back to the message
+    /// entry.
+    fn discard_voice_message(&self) {
+        let imp = self.imp();
+
+        if let Some(media_file) = imp.voice_media_file.take() {
+            media_file.clear();
+        }
+        imp.preview_waveform.set_media_file(None);
+
+        if let Some(voice_message) = imp.voice_message.take() {
+            voice_message.delete();
+        }
+
+        imp.composer_stack.set_visible_child_name("entry");
+
+        let buffer = imp.message_entry.buffer();
+        let (start_iter, end_iter) = buffer.bounds();
+        self.action_set_enabled("room-history.send-text-message", start_iter != end_iter);
+    }
+
+    /// Send the given voice message to the current room.
+    async fn send_voice_message(&self, voice_message: VoiceMessage) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let result = voice_message.file.load_contents_future().await;
+        voice_message.delete();
+        self.discard_voice_message();
+
+        let bytes = match result {
+            Ok((bytes, _)) => bytes,
+            Err(error) => {
+                error!("Could not read voice message file: {error}");
+                toast!(self, gettext("Could not send voice message"));
+                return;
+            }
+        };
+
+        let info = AttachmentInfo::Voice {
+            audio_info: BaseAudioInfo {
+                duration: Some(voice_message.duration),
+                size: Some((bytes.len() as u32).into()),
+            },
+            waveform: Some(voice_message.waveform()),
+        };
+        // Translators: This is the name of the file of a voice message.
+        let filename = format!("{}.ogg", gettext("Voice message"));
+
+        room.send_attachment(
+            bytes.to_vec(),
+            "audio/ogg".parse().unwrap(),
+            &filename,
+            info,
+        );
+    }
+
     fn setup_drop_target(&self) {
         let imp = self.imp();
 
@@ -1398,7 +2386,22 @@ impl RoomHistory {
         completion.set_user_id(Some(room.session().user().unwrap().user_id().to_string()));
         // We should have a strong reference to the list so we can use
         // `get_or_create_members()`.
-        completion.set_members(Some(&room_members))
+        completion.set_members(Some(&room_members));
+
+        // Load the custom emoji of the room.
+        completion.set_emoticons(&room.session(), Vec::new());
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let emoticons = ImagePack::load_for_room(&room)
+                .await
+                .into_iter()
+                .flat_map(|pack| pack.emoticons)
+                .collect();
+
+            // The room might have changed while we were loading.
+            if obj.room().as_ref() == Some(&room) {
+                obj.imp().completion.set_emoticons(&room.session(), emoticons);
+            }
+        }));
     }
 
     // Copy the selection in the message entry to the clipboard while replacing
@@ -1419,25 +2422,250 @@ impl RoomHistory {
     #[template_callback]
     fn handle_related_event_click(&self, n_pressed: i32) {
         if n_pressed == 1 {
-            if let Some(related_event) = &*self.imp().related_event.borrow() {
-                self.scroll_to_event(&related_event.key());
+            let key = self
+                .imp()
+                .related_event
+                .borrow()
+                .as_ref()
+                .map(|event| event.key());
+            if let Some(key) = key {
+                self.jump_to_event(key);
             }
         }
     }
 
-    fn scroll_to_event(&self, key: &EventKey) {
-        let room = match self.room() {
-            Some(room) => room,
-            None => return,
+    /// Scroll to the item at the given position in the timeline.
+    fn scroll_to_position(&self, pos: u32) {
+        let _ = self
+            .imp()
+            .listview
+            .activate_action("list.scroll-to-item", Some(&pos.to_variant()));
+    }
+
+    /// Scroll to the event with the given key.
+    ///
+    /// If the event is not loaded in the live timeline, the context of the
+    /// event is presented instead. If the event is in a thread, the thread is
+    /// opened instead.
+    ///
+    /// A banner is shown to go back to the latest messages.
+    pub fn jump_to_event(&self, key: EventKey) {
+        spawn!(clone!(@weak self as obj => async move {
+            obj.jump_to_event_inner(key).await;
+        }));
+    }
+
+    async fn jump_to_event_inner(&self, key: EventKey) {
+        let Some(room) = self.room() else {
+            return;
         };
+        let timeline = room.timeline();
 
-        if let Some(pos) = room.timeline().find_event_position(key) {
-            let pos = pos as u32;
-            let _ = self
-                .imp()
-                .listview
-                .activate_action("list.scroll-to-item", Some(&pos.to_variant()));
+        // The event might be in the batch that is being loaded.
+        timeline.wait_for_loading().await;
+
+        if self.room().as_ref() != Some(&room) {
+            // Another room was opened in the meantime.
+            return;
+        }
+
+        if self.scroll_to_live_event(&key) {
+            return;
+        }
+
+        if let Some(root) = timeline
+            .event_by_key(&key)
+            .and_then(|event| event.thread_root_id())
+            .and_then(|root_id| timeline.event_by_key(&EventKey::EventId(root_id)))
+        {
+            self.open_thread(root);
+            return;
+        }
+
+        let EventKey::EventId(event_id) = key else {
+            toast!(
+                self,
+                gettext("Could not find the message in the room history")
+            );
+            return;
+        };
+
+        if let Err(error) = self.show_event_context(&room, event_id.clone()).await {
+            error!("Could not load context of event {event_id}: {error}");
+            toast!(
+                self,
+                gettext("Could not find the message in the room history")
+            );
+        }
+    }
+
+    /// Scroll to the event with the given key if it is presented in the live
+    /// timeline.
+    ///
+    /// Returns `false` if the event is not presented in the live timeline.
+    fn scroll_to_live_event(&self, key: &EventKey) -> bool {
+        let Some(room) = self.room() else {
+            return false;
+        };
+        let timeline = room.timeline();
+
+        let Some(pos) = timeline.find_event_position(key) else {
+            return false;
+        };
+
+        timeline.set_context(None);
+        self.scroll_to_position(pos as u32);
+        self.show_latest_banner();
+        true
+    }
+
+    /// Fetch the context of the event with the given ID, present it instead
+    /// of the live timeline and scroll to the event.
+    async fn show_event_context(
+        &self,
+        room: &Room,
+        event_id: OwnedEventId,
+    ) -> Result<(), matrix_sdk::Error> {
+        let timeline = room.timeline();
+        let context = timeline.fetch_context(event_id).await?;
+
+        if self.room().as_ref() != Some(room) {
+            // Another room was opened in the meantime.
+            return Ok(());
         }
+
+        timeline.set_context(Some(context.clone()));
+        self.scroll_to_position(context.focus_position());
+        Ok(())
+    }
+
+    /// Ask the user for a date and scroll to the first message sent on that
+    /// day.
+    async fn jump_to_date(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let calendar = gtk::Calendar::new();
+        let dialog = adw::MessageDialog::builder()
+            .heading(gettext("Jump to Date"))
+            .body(gettext("Choose the day of the messages to show."))
+            .extra_child(&calendar)
+            .default_response("jump")
+            .close_response("cancel")
+            .modal(true)
+            .transient_for(&self.parent_window().unwrap())
+            .build();
+        dialog.add_responses(&[("cancel", &gettext("_Cancel")), ("jump", &gettext("_Jump"))]);
+        dialog.set_response_appearance("jump", adw::ResponseAppearance::Suggested);
+
+        if dialog.choose_future().await != "jump" {
+            return;
+        }
+
+        // The date of the calendar is at midnight in the local time zone.
+        let secs = calendar.date().to_unix().max(0) as u64;
+        let timestamp = MilliSecondsSinceUnixEpoch(UInt::new_saturating(secs * 1000));
+        let timeline = room.timeline();
+
+        let event_id = match timeline.fetch_event_id_for_date(timestamp).await {
+            Ok(event_id) => event_id,
+            Err(error) => {
+                error!("Could not find event for date: {error}");
+                toast!(
+                    self,
+                    gettext("Could not find messages sent after this date")
+                );
+                return;
+            }
+        };
+
+        if self.room().as_ref() != Some(&room) {
+            // Another room was opened in the meantime.
+            return;
+        }
+
+        if self.scroll_to_live_event(&EventKey::EventId(event_id.clone())) {
+            return;
+        }
+
+        if let Err(error) = self.show_event_context(&room, event_id.clone()).await {
+            error!("Could not load context of event {event_id}: {error}");
+            toast!(self, gettext("Could not load messages sent at this date"));
+        }
+    }
+
+    /// Show the banner to go back to the latest messages, after jumping to an
+    /// older message.
+    fn show_latest_banner(&self) {
+        if !self.sticky() {
+            self.imp().latest_banner.set_revealed(true);
+        }
+    }
+
+    /// Go back to the latest messages.
+    #[template_callback]
+    fn jump_to_latest(&self) {
+        self.imp().latest_banner.set_revealed(false);
+        self.scroll_down();
+    }
+
+    /// The context of the focused event in the current room, if any.
+    fn context(&self) -> Option<TimelineContext> {
+        self.room().and_then(|room| room.timeline().context())
+    }
+
+    /// Present the context of the focused event in the current room, or the
+    /// live timeline if there is none.
+    fn update_model(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let imp = self.imp();
+        let timeline = room.timeline();
+
+        self.set_selection_mode(false);
+
+        if let Some(context) = timeline.context() {
+            // The bottom of the context is not the end of the timeline.
+            imp.is_auto_scrolling.set(false);
+            self.set_sticky(false);
+
+            self.selection_model().set_model(Some(context.items()));
+            imp.latest_banner.set_revealed(true);
+        } else {
+            self.selection_model().set_model(Some(timeline.items()));
+            imp.latest_banner.set_revealed(false);
+        }
+    }
+
+    /// Load more events in the context of the focused event, if the user gets
+    /// close to one of its edges.
+    fn load_context(&self, context: &TimelineContext) {
+        let imp = self.imp();
+        let adj = imp.listview.vadjustment().unwrap();
+
+        let is_near_top = adj.value() < adj.page_size() * 2.0;
+        let is_near_bottom = adj.upper() - adj.value() - adj.page_size() < adj.page_size() * 2.0;
+
+        let direction = if is_near_top && context.can_load(Direction::Backward) {
+            Direction::Backward
+        } else if is_near_bottom && context.can_load(Direction::Forward) {
+            Direction::Forward
+        } else {
+            return;
+        };
+
+        imp.is_loading.set(true);
+
+        let obj_weak = self.downgrade();
+        spawn!(clone!(@weak context => async move {
+            context.load(direction).await;
+
+            if let Some(obj) = obj_weak.upgrade() {
+                obj.imp().is_loading.set(false);
+            }
+        }));
     }
 
     fn send_typing_notification(&self, typing: bool) {
@@ -1490,6 +2718,8 @@ impl RoomHistory {
             }),
         )));
 
+        self.hide_unread_banner_if_marker_visible();
+
         let last_event_id = self.last_visible_event_id();
 
         if let Some(event_id) = last_event_id {
@@ -1500,10 +2730,22 @@ impl RoomHistory {
     }
 
     /// Update the read marker.
+    ///
+    /// It is not moved while the banner to jump to the first unread message
+    /// is shown, so the user doesn't lose track of the unread messages, unless
+    /// the user is reading at the bottom of the timeline.
     fn update_read_marker(&self) {
         let imp = self.imp();
         imp.read_timeout.take();
 
+        if imp.unread_banner.is_revealed() {
+            if !self.sticky() {
+                return;
+            }
+
+            imp.unread_banner.set_revealed(false);
+        }
+
         let last_event_id = self.last_visible_event_id();
 
         if let Some(event_id) = last_event_id {
@@ -1517,22 +2759,9 @@ impl RoomHistory {
     fn last_visible_event_id(&self) -> Option<OwnedEventId> {
         let listview = &*self.imp().listview;
         let mut child = listview.last_child();
-        // The visible part of the listview spans between 0 and max.
-        let max = listview.height() as f64;
 
         while let Some(item) = child {
-            // Vertical position of the top of the item.
-            let (_, top_pos) = item.translate_coordinates(listview, 0.0, 0.0).unwrap();
-            // Vertical position of the bottom of the item.
-            let (_, bottom_pos) = item
-                .translate_coordinates(listview, 0.0, item.height() as f64)
-                .unwrap();
-
-            let top_in_view = top_pos > 0.0 && top_pos <= max;
-            let bottom_in_view = bottom_pos > 0.0 && bottom_pos <= max;
-            // If a message is too big and takes more space than the current view.
-            let content_in_view = top_pos <= max && bottom_pos > 0.0;
-            if top_in_view || bottom_in_view || content_in_view {
+            if self.is_in_view(&item) {
                 if let Some(event_id) = item
                     .first_child()
                     .and_downcast::<ItemRow>()
@@ -1550,6 +2779,26 @@ impl RoomHistory {
         None
     }
 
+    /// Whether the given child of the list view is visible.
+    fn is_in_view(&self, item: &gtk::Widget) -> bool {
+        let listview = &*self.imp().listview;
+        // The visible part of the listview spans between 0 and max.
+        let max = listview.height() as f64;
+
+        // Vertical position of the top of the item.
+        let (_, top_pos) = item.translate_coordinates(listview, 0.0, 0.0).unwrap();
+        // Vertical position of the bottom of the item.
+        let (_, bottom_pos) = item
+            .translate_coordinates(listview, 0.0, item.height() as f64)
+            .unwrap();
+
+        let top_in_view = top_pos > 0.0 && top_pos <= max;
+        let bottom_in_view = bottom_pos > 0.0 && bottom_pos <= max;
+        // If a message is too big and takes more space than the current view.
+        let content_in_view = top_pos <= max && bottom_pos > 0.0;
+        top_in_view || bottom_in_view || content_in_view
+    }
+
     /// Send the given receipt.
     async fn send_receipt(&self, receipt_type: ReceiptType, event_id: OwnedEventId) {
         let Some(room) = self.room() else {
@@ -1597,6 +2846,164 @@ impl RoomHistory {
         banner.set_revealed(true);
     }
 
+    /// The joined members of the room whose identity changed since it was
+    /// verified.
+    fn identity_changed_members(&self) -> Vec<Member> {
+        let (Some(room), Some(members)) = (self.room(), self.room_members()) else {
+            return Vec::new();
+        };
+
+        if !room.is_encrypted() {
+            return Vec::new();
+        }
+
+        room.session()
+            .crypto_identities()
+            .changed_identities()
+            .into_iter()
+            .filter(|user_id| members.get_membership(user_id) == Membership::Join)
+            .map(|user_id| members.get_or_create(user_id))
+            .collect()
+    }
+
+    /// Update the banner warning about identity changes of the members of the
+    /// current room.
+    fn update_identity_changed_banner(&self) {
+        let banner = &self.imp().identity_changed_banner;
+        let members = self.identity_changed_members();
+
+        match members.as_slice() {
+            [] => {
+                banner.set_revealed(false);
+                return;
+            }
+            [member] => {
+                banner.set_title(&gettext_f(
+                    // Translators: Do NOT translate the content between '{' and '}', this
+                    // is a variable name.
+                    "The identity of {user} changed since it was verified",
+                    &[("user", &member.display_name())],
+                ));
+            }
+            _ => {
+                banner.set_title(&gettext(
+                    "The identity of several members changed since they were verified",
+                ));
+            }
+        }
+
+        banner.set_revealed(true);
+    }
+
+    /// Update the banner to jump to the first unread message.
+    ///
+    /// It is only shown if the room had unread messages when it was opened.
+    fn update_unread_banner(&self) {
+        let imp = self.imp();
+        imp.unread_banner.set_revealed(false);
+        imp.first_unread_anchor.take();
+
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        if room.is_read() {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let fully_read_event_id = room.timeline().fetch_fully_read_event_id().await;
+
+            // The room might have changed while we were fetching the marker.
+            if obj.room().as_ref() != Some(&room) {
+                return;
+            }
+
+            let imp = obj.imp();
+            imp.unread_banner.set_revealed(fully_read_event_id.is_some());
+            imp.first_unread_anchor.replace(fully_read_event_id);
+        }));
+    }
+
+    /// Scroll to the first unread message.
+    #[template_callback]
+    fn jump_to_first_unread(&self) {
+        self.imp().unread_banner.set_revealed(false);
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.jump_to_first_unread_inner().await;
+        }));
+    }
+
+    async fn jump_to_first_unread_inner(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let timeline = room.timeline();
+
+        let Some(event_id) = self.imp().first_unread_anchor.borrow().clone() else {
+            return;
+        };
+
+        if self.scroll_to_live_event(&EventKey::EventId(event_id.clone())) {
+            return;
+        }
+
+        // The read marker might be presented even if the event it is attached
+        // to is not.
+        if let Some(pos) = timeline.find_read_marker_position() {
+            timeline.set_context(None);
+            self.scroll_to_position(pos as u32);
+            self.show_latest_banner();
+            return;
+        }
+
+        if let Err(error) = self.show_event_context(&room, event_id.clone()).await {
+            error!("Could not load context of event {event_id}: {error}");
+            toast!(self, gettext("Could not load the unread messages"));
+        }
+    }
+
+    /// Hide the banner to jump to the first unread message if the read marker
+    /// is visible.
+    fn hide_unread_banner_if_marker_visible(&self) {
+        let imp = self.imp();
+
+        if !imp.unread_banner.is_revealed() {
+            return;
+        }
+
+        let listview = &*imp.listview;
+        let mut child = listview.first_child();
+
+        while let Some(item) = child {
+            let is_read_marker = item
+                .first_child()
+                .and_downcast::<ItemRow>()
+                .and_then(|row| row.item())
+                .and_downcast::<VirtualItem>()
+                .is_some_and(|item| item.kind() == VirtualItemKind::NewMessages);
+
+            if is_read_marker && self.is_in_view(&item) {
+                imp.unread_banner.set_revealed(false);
+                return;
+            }
+
+            child = item.next_sibling();
+        }
+    }
+
+    /// View the profile of a member whose identity changed.
+    #[template_callback]
+    fn view_identity_changed_member(&self) {
+        let Some(member) = self.identity_changed_members().into_iter().next() else {
+            return;
+        };
+
+        let dialog = UserProfileDialog::new(self.root().and_downcast_ref(), &member);
+        dialog.present();
+    }
+
     /// Join or view the room's successor, if possible.
     #[template_callback]
     fn join_or_view_successor(&self) {
@@ -1628,6 +3035,49 @@ impl RoomHistory {
     }
 }
 
+/// Replace the shortcodes of the given custom emoji in the given text with
+/// their HTML representation.
+///
+/// Returns `None` if the text doesn't contain any known shortcode.
+fn replace_emoticons(text: &str, emoticons: &[ImagePackItem]) -> Option<String> {
+    if emoticons.is_empty() {
+        return None;
+    }
+
+    let mut html = String::with_capacity(text.len());
+    let mut has_emoticons = false;
+    let mut rest = text;
+
+    while let Some(start) = rest.find(':') {
+        let after_start = &rest[start + 1..];
+        let Some(len) = after_start.find(':') else {
+            break;
+        };
+
+        let shortcode = &after_start[..len];
+        if let Some(item) = emoticons.iter().find(|item| item.shortcode == shortcode) {
+            has_emoticons = true;
+            html.push_str(&rest[..start]);
+
+            // The shortcode comes from the image pack and can contain any
+            // character.
+            let shortcode = html_escape(shortcode);
+            html.push_str(&format!(
+                "<img data-mx-emoticon src=\"{}\" alt=\":{shortcode}:\" title=\":{shortcode}:\" height=\"32\">",
+                html_escape(item.image.url.as_str())
+            ));
+            rest = &after_start[len + 1..];
+        } else {
+            // The closing `:` might be the opening one of a shortcode.
+            html.push_str(&rest[..start + 1 + len]);
+            rest = &after_start[len..];
+        }
+    }
+
+    html.push_str(rest);
+    has_emoticons.then_some(html)
+}
+
 enum MentionChunk {
     Text(String),
     Mention { name: String, uri: String },
@@ -1703,3 +3153,46 @@ impl Iterator for SplitMentions {
         }
     }
 }
+
+/// Format the given duration as `MM:SS`.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let mins = secs / 60;
+    let secs = secs % 60;
+    format!("{mins:02}:{secs:02}")
+}
+
+/// Create a row presenting the given event that is not loaded in the timeline.
+fn remote_event_row(event: &SearchResult) -> gtk::Widget {
+    let sender_name = event
+        .sender()
+        .map(|sender| sender.display_name())
+        .unwrap_or_default();
+
+    let sender = gtk::Label::builder()
+        .label(sender_name)
+        .halign(gtk::Align::Start)
+        .ellipsize(pango::EllipsizeMode::End)
+        .css_classes(["bold"])
+        .build();
+    let body = gtk::Label::builder()
+        .label(event.body())
+        .halign(gtk::Align::Start)
+        .xalign(0.0)
+        .wrap(true)
+        .wrap_mode(pango::WrapMode::WordChar)
+        .selectable(true)
+        .build();
+
+    let row = gtk::Box::builder()
+        .orientation(gtk::Orientation::Vertical)
+        .spacing(6)
+        .margin_top(6)
+        .margin_bottom(6)
+        .margin_start(12)
+        .margin_end(12)
+        .build();
+    row.append(&sender);
+    row.append(&body);
+    row.upcast()
+}
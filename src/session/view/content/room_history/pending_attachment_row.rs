@@ -0,0 +1,124 @@
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    glib::{clone, signal::SignalHandlerId},
+    prelude::*,
+    CompositeTemplate,
+};
+
+use crate::session::model::{PendingAttachment, SendState};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/pending_attachment_row.ui"
+    )]
+    pub struct PendingAttachmentRow {
+        #[template_child]
+        pub body: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub retry_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub cancel_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub send_state_icon: TemplateChild<gtk::Image>,
+        /// The presented attachment.
+        pub attachment: RefCell<Option<PendingAttachment>>,
+        pub send_state_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PendingAttachmentRow {
+        const NAME: &'static str = "ContentPendingAttachmentRow";
+        type Type = super::PendingAttachmentRow;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PendingAttachmentRow {
+        fn dispose(&self) {
+            self.obj().set_attachment(None);
+        }
+    }
+
+    impl WidgetImpl for PendingAttachmentRow {}
+    impl BinImpl for PendingAttachmentRow {}
+}
+
+glib::wrapper! {
+    /// A row presenting an attachment that was not sent yet.
+    pub struct PendingAttachmentRow(ObjectSubclass<imp::PendingAttachmentRow>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+impl PendingAttachmentRow {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Set the attachment presented by this row.
+    pub fn set_attachment(&self, attachment: Option<PendingAttachment>) {
+        let imp = self.imp();
+
+        if let Some(attachment) = imp.attachment.take() {
+            if let Some(handler) = imp.send_state_handler.take() {
+                attachment.disconnect(handler);
+            }
+        }
+
+        if let Some(attachment) = &attachment {
+            imp.body.set_label(attachment.body());
+
+            let send_state_handler = attachment.connect_notify_local(
+                Some("send-state"),
+                clone!(@weak self as obj => move |attachment, _| {
+                    obj.update_send_state(attachment);
+                }),
+            );
+            imp.send_state_handler.replace(Some(send_state_handler));
+
+            self.update_send_state(attachment);
+        }
+
+        imp.attachment.replace(attachment);
+    }
+
+    /// Update the indicator of the send state of the attachment.
+    fn update_send_state(&self, attachment: &PendingAttachment) {
+        let imp = self.imp();
+        let is_failed = attachment.send_state() == SendState::Failed;
+
+        if is_failed {
+            imp.send_state_icon
+                .set_icon_name(Some("dialog-error-symbolic"));
+            imp.send_state_icon
+                .set_tooltip_text(Some(&gettext("Could not send this file")));
+            imp.send_state_icon.remove_css_class("dim-label");
+            imp.send_state_icon.add_css_class("error");
+        } else {
+            imp.send_state_icon
+                .set_icon_name(Some("content-loading-symbolic"));
+            imp.send_state_icon
+                .set_tooltip_text(Some(&gettext("Sending…")));
+            imp.send_state_icon.remove_css_class("error");
+            imp.send_state_icon.add_css_class("dim-label");
+        }
+
+        imp.retry_button.set_visible(is_failed);
+        imp.cancel_button.set_visible(is_failed);
+    }
+}
@@ -0,0 +1,267 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use ruma::{
+    events::poll::{
+        start::PollKind,
+        unstable_start::{
+            NewUnstablePollStartEventContent, UnstablePollAnswer, UnstablePollAnswers,
+            UnstablePollStartContentBlock, UnstablePollStartEventContent,
+        },
+    },
+    UInt,
+};
+use tracing::error;
+
+use crate::{gettext_f, session::model::Room};
+
+/// The minimum number of answers of a poll.
+const MIN_ANSWERS: usize = 2;
+/// The maximum number of answers of a poll.
+const MAX_ANSWERS: usize = 20;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/poll_creation_dialog.ui"
+    )]
+    pub struct PollCreationDialog {
+        /// The room where the poll will be sent.
+        pub room: OnceCell<Room>,
+        /// The rows to enter the answers, with their button to remove them.
+        pub answer_rows: RefCell<Vec<(adw::EntryRow, gtk::Button)>>,
+        #[template_child]
+        pub question_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub answers_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub add_answer_button: TemplateChild<gtk::Button>,
+        #[template_child]
+        pub multiple_choice_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub hide_results_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub create_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for PollCreationDialog {
+        const NAME: &'static str = "ContentPollCreationDialog";
+        type Type = super::PollCreationDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for PollCreationDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("room")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "room" => {
+                    let _ = self.room.set(value.get().unwrap());
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "room" => self.obj().room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            for _ in 0..MIN_ANSWERS {
+                obj.append_answer_row();
+            }
+        }
+    }
+
+    impl WidgetImpl for PollCreationDialog {}
+    impl WindowImpl for PollCreationDialog {}
+    impl AdwWindowImpl for PollCreationDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to create a poll in a room.
+    pub struct PollCreationDialog(ObjectSubclass<imp::PollCreationDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl PollCreationDialog {
+    pub fn new(parent_window: Option<&gtk::Window>, room: &Room) -> Self {
+        glib::Object::builder()
+            .property("transient-for", parent_window)
+            .property("room", room)
+            .build()
+    }
+
+    /// The room where the poll will be sent.
+    pub fn room(&self) -> &Room {
+        self.imp().room.get().unwrap()
+    }
+
+    /// Add a row to enter a new answer and focus it.
+    #[template_callback]
+    fn add_answer(&self) {
+        self.append_answer_row().grab_focus();
+    }
+
+    /// Append a row to enter a new answer.
+    fn append_answer_row(&self) -> adw::EntryRow {
+        let imp = self.imp();
+
+        let row = adw::EntryRow::new();
+        row.connect_changed(clone!(@weak self as obj => move |_| {
+            obj.update_create_button();
+        }));
+
+        let remove_button = gtk::Button::builder()
+            .icon_name("user-trash-symbolic")
+            .tooltip_text(gettext("Remove Option"))
+            .valign(gtk::Align::Center)
+            .css_classes(["flat"])
+            .build();
+        remove_button.connect_clicked(clone!(@weak self as obj, @weak row => move |_| {
+            obj.remove_answer(&row);
+        }));
+        row.add_suffix(&remove_button);
+
+        imp.answers_group.add(&row);
+        imp.answer_rows
+            .borrow_mut()
+            .push((row.clone(), remove_button));
+
+        self.update_answer_rows();
+        row
+    }
+
+    /// Remove the given answer row.
+    fn remove_answer(&self, row: &adw::EntryRow) {
+        let imp = self.imp();
+
+        imp.answers_group.remove(row);
+        imp.answer_rows.borrow_mut().retain(|(r, _)| r != row);
+
+        self.update_answer_rows();
+    }
+
+    /// Update the rows of the answers after one was added or removed.
+    fn update_answer_rows(&self) {
+        let imp = self.imp();
+        let rows = imp.answer_rows.borrow();
+        let can_remove = rows.len() > MIN_ANSWERS;
+
+        for (i, (row, remove_button)) in rows.iter().enumerate() {
+            row.set_title(&gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "Option {n}",
+                &[("n", &(i + 1).to_string())],
+            ));
+            remove_button.set_visible(can_remove);
+        }
+
+        imp.add_answer_button
+            .set_sensitive(rows.len() < MAX_ANSWERS);
+        drop(rows);
+
+        self.update_create_button();
+    }
+
+    /// The question of the poll, if it is not empty.
+    fn question(&self) -> Option<String> {
+        let question = self.imp().question_entry.text();
+        let question = question.trim();
+        (!question.is_empty()).then(|| question.to_owned())
+    }
+
+    /// The non-empty answers of the poll.
+    fn answers(&self) -> Vec<String> {
+        self.imp()
+            .answer_rows
+            .borrow()
+            .iter()
+            .map(|(row, _)| row.text().trim().to_owned())
+            .filter(|answer| !answer.is_empty())
+            .collect()
+    }
+
+    #[template_callback]
+    fn update_create_button(&self) {
+        let can_create = self.question().is_some() && self.answers().len() >= MIN_ANSWERS;
+        self.imp().create_button.set_sensitive(can_create);
+    }
+
+    /// Send the poll to the room.
+    #[template_callback]
+    fn create(&self) {
+        let imp = self.imp();
+        let Some(question) = self.question() else {
+            return;
+        };
+        let answers = self.answers();
+
+        let mut fallback = question.clone();
+        for (i, answer) in answers.iter().enumerate() {
+            fallback.push_str(&format!("\n{}. {answer}", i + 1));
+        }
+
+        let answers_count = answers.len();
+        let answers = match UnstablePollAnswers::try_from(
+            answers
+                .into_iter()
+                .enumerate()
+                .map(|(i, answer)| UnstablePollAnswer::new(format!("answer-{i}"), answer))
+                .collect::<Vec<_>>(),
+        ) {
+            Ok(answers) => answers,
+            Err(error) => {
+                error!("Could not create poll answers: {error}");
+                return;
+            }
+        };
+
+        let mut poll_start = UnstablePollStartContentBlock::new(question, answers);
+        if imp.hide_results_row.is_active() {
+            poll_start.kind = PollKind::Undisclosed;
+        }
+        if imp.multiple_choice_row.is_active() {
+            poll_start.max_selections = UInt::from(answers_count as u32);
+        }
+
+        let content = NewUnstablePollStartEventContent::plain_text(fallback, poll_start);
+        self.room()
+            .send_room_message_event(UnstablePollStartEventContent::New(content));
+
+        self.close();
+    }
+}
@@ -0,0 +1,192 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{
+    glib,
+    glib::{clone, closure},
+    CompositeTemplate,
+};
+use ruma::events::room::power_levels::PowerLevelAction;
+
+use crate::{
+    session::model::{Member, Membership, User},
+    spawn, toast,
+    utils::and_expr,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/state_row/knock.ui"
+    )]
+    pub struct StateKnock {
+        #[template_child]
+        pub label: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub accept_button: TemplateChild<gtk::Button>,
+        /// The member who requested to be invited.
+        pub member: RefCell<Option<Member>>,
+        pub accept_watch: RefCell<Option<gtk::ExpressionWatch>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StateKnock {
+        const NAME: &'static str = "ContentStateKnock";
+        type Type = super::StateKnock;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StateKnock {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Member>("member")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "member" => obj.set_member(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "member" => obj.member().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            if let Some(watch) = self.accept_watch.take() {
+                watch.unwatch();
+            }
+        }
+    }
+
+    impl WidgetImpl for StateKnock {}
+    impl BinImpl for StateKnock {}
+}
+
+glib::wrapper! {
+    /// A state event of a user requesting to be invited to a room.
+    ///
+    /// It has a button to accept the request if our own user is allowed to.
+    pub struct StateKnock(ObjectSubclass<imp::StateKnock>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl StateKnock {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Set the text of this event.
+    pub fn set_label(&self, label: &str) {
+        self.imp().label.set_text(label);
+    }
+
+    /// The member who requested to be invited.
+    pub fn member(&self) -> Option<Member> {
+        self.imp().member.borrow().clone()
+    }
+
+    /// Set the member who requested to be invited.
+    pub fn set_member(&self, member: Option<Member>) {
+        let imp = self.imp();
+
+        if self.member() == member {
+            return;
+        }
+
+        if let Some(watch) = imp.accept_watch.take() {
+            watch.unwatch();
+        }
+
+        if let Some(member) = &member {
+            // The request can only be accepted if it is still pending, like the
+            // `member.accept-knock` action of the members page.
+            let is_knocking = member
+                .property_expression("membership")
+                .chain_closure::<bool>(closure!(
+                    |_: Option<glib::Object>, membership: Membership| {
+                        membership == Membership::Knock
+                    }
+                ));
+            let can_invite = member
+                .room()
+                .map(|room| room.own_user_is_allowed_to_expr(PowerLevelAction::Invite));
+
+            if let Some(can_invite) = can_invite {
+                let watch = and_expr(can_invite, is_knocking).bind(
+                    &*imp.accept_button,
+                    "visible",
+                    gtk::Widget::NONE,
+                );
+                imp.accept_watch.replace(Some(watch));
+            } else {
+                imp.accept_button.set_visible(false);
+            }
+        } else {
+            imp.accept_button.set_visible(false);
+        }
+
+        imp.member.replace(member);
+        self.notify("member");
+    }
+
+    /// Accept the request of the member, by inviting them.
+    #[template_callback]
+    fn accept(&self) {
+        let Some(member) = self.member() else {
+            return;
+        };
+        let Some(room) = member.room() else {
+            return;
+        };
+
+        let button = &*self.imp().accept_button;
+        button.set_sensitive(false);
+
+        spawn!(
+            clone!(@weak self as obj, @weak button, @weak room => async move {
+                let user = member.upcast::<User>();
+                if room.invite(&[user]).await.is_err() {
+                    toast!(obj, gettext("Could not accept the request to join"));
+                }
+
+                button.set_sensitive(true);
+            })
+        );
+    }
+}
+
+impl Default for StateKnock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,4 +1,5 @@
 mod creation;
+mod knock;
 mod tombstone;
 
 use adw::{prelude::*, subclass::prelude::*};
@@ -14,9 +15,13 @@ use ruma::{
 };
 use tracing::warn;
 
-use self::{creation::StateCreation, tombstone::StateTombstone};
+use self::{creation::StateCreation, knock::StateKnock, tombstone::StateTombstone};
 use super::ReadReceiptsList;
-use crate::{gettext_f, prelude::*, session::model::Event};
+use crate::{
+    gettext_f,
+    prelude::*,
+    session::model::{Event, Room},
+};
 
 mod imp {
     use std::cell::RefCell;
@@ -108,9 +113,12 @@ impl StateRow {
 
     pub fn set_event(&self, event: Event) {
         match event.content() {
-            TimelineItemContent::MembershipChange(membership_change) => {
-                self.update_with_membership_change(&membership_change, &event.sender_id())
-            }
+            TimelineItemContent::MembershipChange(membership_change) => self
+                .update_with_membership_change(
+                    &membership_change,
+                    &event.sender_id(),
+                    &event.room(),
+                ),
             TimelineItemContent::ProfileChange(profile_change) => {
                 self.update_with_profile_change(&profile_change, &event.sender().display_name())
             }
@@ -182,6 +190,7 @@ impl StateRow {
         &self,
         membership_change: &RoomMembershipChange,
         sender: &UserId,
+        room: &Room,
     ) {
         let display_name = match membership_change.content() {
             FullStateEventContent::Original { content, .. } => content
@@ -232,6 +241,8 @@ impl StateRow {
                 }
             };
 
+        let is_knock = matches!(supported_membership_change, MembershipChange::Knocked);
+
         let message = match supported_membership_change {
             MembershipChange::Joined => {
                 // Translators: Do NOT translate the content between '{' and '}', this
@@ -291,16 +302,12 @@ impl StateRow {
                 "The invitation for {user} has been revoked.",
                 &[("user", &display_name)],
             ),
-            MembershipChange::Knocked =>
-            // TODO: Add button to invite the user.
-            {
-                gettext_f(
-                    // Translators: Do NOT translate the content between '{' and '}', this
-                    // is a variable name.
-                    "{user} requested to be invited to this room.",
-                    &[("user", &display_name)],
-                )
-            }
+            MembershipChange::Knocked => gettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this
+                // is a variable name.
+                "{user} requested to be invited to this room.",
+                &[("user", &display_name)],
+            ),
             MembershipChange::KnockRetracted => gettext_f(
                 // Translators: Do NOT translate the content between
                 // '{' and '}', this is a variable name.
@@ -323,7 +330,24 @@ impl StateRow {
         };
 
         let content = self.content();
-        if let Some(child) = content.child().and_downcast::<gtk::Label>() {
+        if is_knock {
+            let child = content
+                .child()
+                .and_downcast::<StateKnock>()
+                .unwrap_or_else(|| {
+                    let child = StateKnock::new();
+                    content.set_child(Some(&child));
+                    child
+                });
+            child.set_label(&message);
+
+            // We should have a strong reference to the list in the RoomHistory so we can
+            // use `get_or_create_members()`.
+            let member = room
+                .get_or_create_members()
+                .get_or_create(membership_change.user_id().to_owned());
+            child.set_member(Some(member));
+        } else if let Some(child) = content.child().and_downcast::<gtk::Label>() {
             child.set_text(&message);
         } else {
             content.set_child(Some(&text(message)));
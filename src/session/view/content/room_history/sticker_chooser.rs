@@ -0,0 +1,237 @@
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, prelude::*, CompositeTemplate};
+use ruma::events::{room::ImageInfo, sticker::StickerEventContent};
+
+use crate::{
+    session::model::{ImagePack, ImagePackItem, Room},
+    spawn,
+};
+
+/// The size of the thumbnails of the stickers.
+const STICKER_SIZE: u32 = 80;
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{subclass::InitializingObject, WeakRef};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/sticker_chooser.ui"
+    )]
+    pub struct StickerChooser {
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub packs_box: TemplateChild<gtk::Box>,
+        /// The room where the stickers are sent.
+        pub room: WeakRef<Room>,
+        /// The widgets presenting the packs.
+        pub pack_widgets: RefCell<Vec<gtk::Widget>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StickerChooser {
+        const NAME: &'static str = "ContentStickerChooser";
+        type Type = super::StickerChooser;
+        type ParentType = gtk::Popover;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StickerChooser {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("room")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "room" => self.obj().set_room(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "room" => self.obj().room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            // Reload the packs every time, since they might have changed.
+            self.obj().connect_show(|obj| {
+                obj.load_packs();
+            });
+        }
+    }
+
+    impl WidgetImpl for StickerChooser {}
+    impl PopoverImpl for StickerChooser {}
+}
+
+glib::wrapper! {
+    /// A popover to choose a sticker to send, from the image packs available
+    /// in a room.
+    pub struct StickerChooser(ObjectSubclass<imp::StickerChooser>)
+        @extends gtk::Widget, gtk::Popover, @implements gtk::Accessible;
+}
+
+impl StickerChooser {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The room where the stickers are sent.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// Set the room where the stickers are sent.
+    pub fn set_room(&self, room: Option<&Room>) {
+        if self.room().as_ref() == room {
+            return;
+        }
+
+        self.imp().room.set(room);
+        self.notify("room");
+    }
+
+    /// Load the image packs of the current room.
+    fn load_packs(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        self.imp().stack.set_visible_child_name("loading");
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            let packs = ImagePack::load_for_room(&room).await;
+
+            // The room might have changed while we were loading.
+            if obj.room().as_ref() != Some(&room) {
+                return;
+            }
+
+            obj.set_packs(packs);
+        }));
+    }
+
+    /// Present the stickers of the given packs.
+    fn set_packs(&self, packs: Vec<ImagePack>) {
+        let imp = self.imp();
+
+        for widget in imp.pack_widgets.take() {
+            imp.packs_box.remove(&widget);
+        }
+
+        let mut pack_widgets = Vec::new();
+        for pack in packs.into_iter().filter(|pack| !pack.stickers.is_empty()) {
+            let display_name = if pack.display_name.is_empty() {
+                gettext("Personal Pack")
+            } else {
+                pack.display_name
+            };
+            let title = gtk::Label::builder()
+                .label(display_name)
+                .xalign(0.0)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .css_classes(["heading"])
+                .build();
+
+            let flow_box = gtk::FlowBox::builder()
+                .selection_mode(gtk::SelectionMode::None)
+                .homogeneous(true)
+                .min_children_per_line(3)
+                .max_children_per_line(4)
+                .build();
+            for item in pack.stickers {
+                flow_box.append(&self.sticker_button(item));
+            }
+
+            imp.packs_box.append(&title);
+            imp.packs_box.append(&flow_box);
+            pack_widgets.push(title.upcast());
+            pack_widgets.push(flow_box.upcast());
+        }
+
+        let page = if pack_widgets.is_empty() {
+            "empty"
+        } else {
+            "stickers"
+        };
+        imp.stack.set_visible_child_name(page);
+        imp.pack_widgets.replace(pack_widgets);
+    }
+
+    /// Create a button to send the given sticker.
+    fn sticker_button(&self, item: ImagePackItem) -> gtk::Button {
+        let picture = gtk::Picture::builder()
+            .width_request(STICKER_SIZE as i32)
+            .height_request(STICKER_SIZE as i32)
+            .content_fit(gtk::ContentFit::Contain)
+            .alternative_text(item.body())
+            .build();
+
+        let button = gtk::Button::builder()
+            .child(&picture)
+            .tooltip_text(item.body())
+            .css_classes(["flat"])
+            .build();
+
+        if let Some(room) = self.room() {
+            spawn!(
+                glib::Priority::LOW,
+                clone!(@weak picture, @strong item => async move {
+                    let paintable = item.load_thumbnail(&room.session(), STICKER_SIZE).await;
+                    picture.set_paintable(paintable.as_ref());
+                })
+            );
+        }
+
+        button.connect_clicked(clone!(@weak self as obj => move |_| {
+            obj.send_sticker(&item);
+        }));
+
+        button
+    }
+
+    /// Send the given sticker to the current room.
+    fn send_sticker(&self, item: &ImagePackItem) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        let info = item.image.info.clone().unwrap_or_else(ImageInfo::new);
+        room.send_room_message_event(StickerEventContent::new(
+            item.body().to_owned(),
+            info,
+            item.image.url.clone(),
+        ));
+        self.popdown();
+    }
+}
+
+impl Default for StickerChooser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
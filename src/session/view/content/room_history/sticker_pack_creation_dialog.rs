@@ -0,0 +1,325 @@
+use std::collections::BTreeMap;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use matrix_sdk::deserialized_responses::SyncOrStrippedState;
+use ruma::events::{room::power_levels::PowerLevelAction, StateEventType, SyncStateEvent};
+use tracing::error;
+
+use crate::{
+    ngettext_f,
+    session::model::{
+        PackImage, PackInfo, PackUsage, Room, RoomImagePackEventContent, UserImagePackEventContent,
+    },
+    spawn, spawn_tokio, toast,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/content/room_history/sticker_pack_creation_dialog.ui"
+    )]
+    pub struct StickerPackCreationDialog {
+        /// The room where the images come from.
+        pub room: OnceCell<Room>,
+        /// The images to add to the pack.
+        pub images: RefCell<Vec<PackImage>>,
+        #[template_child]
+        pub name_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub images_group: TemplateChild<adw::PreferencesGroup>,
+        #[template_child]
+        pub share_row: TemplateChild<adw::SwitchRow>,
+        #[template_child]
+        pub create_button: TemplateChild<gtk::Button>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for StickerPackCreationDialog {
+        const NAME: &'static str = "ContentStickerPackCreationDialog";
+        type Type = super::StickerPackCreationDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for StickerPackCreationDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("room")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "room" => {
+                    let _ = self.room.set(value.get().unwrap());
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "room" => self.obj().room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let room = self.obj().room();
+
+            // Only users allowed to change the state of the room can add a pack to it.
+            let can_share = room.session().user().is_some_and(|user| {
+                room.power_levels().member_is_allowed_to(
+                    &user.user_id(),
+                    PowerLevelAction::SendState(StateEventType::from("im.ponies.room_emotes")),
+                )
+            });
+            self.share_row.set_sensitive(can_share);
+        }
+    }
+
+    impl WidgetImpl for StickerPackCreationDialog {}
+    impl WindowImpl for StickerPackCreationDialog {}
+    impl AdwWindowImpl for StickerPackCreationDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to create a sticker pack from images of a room.
+    pub struct StickerPackCreationDialog(ObjectSubclass<imp::StickerPackCreationDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl StickerPackCreationDialog {
+    pub fn new(parent_window: Option<&gtk::Window>, room: &Room, images: Vec<PackImage>) -> Self {
+        let obj = glib::Object::builder::<Self>()
+            .property("transient-for", parent_window)
+            .property("room", room)
+            .build();
+        obj.set_images(images);
+        obj
+    }
+
+    /// The room where the images come from.
+    pub fn room(&self) -> &Room {
+        self.imp().room.get().unwrap()
+    }
+
+    /// Set the images to add to the pack.
+    fn set_images(&self, images: Vec<PackImage>) {
+        let imp = self.imp();
+        let n = images.len() as u32;
+
+        imp.images_group.set_description(Some(&ngettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this
+            // is a variable name.
+            "{n} image will be added to the pack.",
+            "{n} images will be added to the pack.",
+            n,
+            &[("n", &n.to_string())],
+        )));
+        imp.images.replace(images);
+    }
+
+    /// The name of the pack, if it is not empty.
+    fn pack_name(&self) -> Option<String> {
+        let name = self.imp().name_entry.text();
+        let name = name.trim();
+        (!name.is_empty()).then(|| name.to_owned())
+    }
+
+    #[template_callback]
+    fn update_create_button(&self) {
+        let imp = self.imp();
+        let can_create = self.pack_name().is_some() || !imp.share_row.is_active();
+        imp.create_button.set_sensitive(can_create);
+    }
+
+    /// Create the pack.
+    #[template_callback]
+    fn create(&self) {
+        let imp = self.imp();
+        imp.create_button.set_sensitive(false);
+
+        spawn!(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+            let pack_name = obj.pack_name();
+            let images = imp.images.borrow().clone();
+
+            let result = if imp.share_row.is_active() {
+                obj.add_to_room_pack(pack_name.unwrap_or_default(), images).await
+            } else {
+                obj.add_to_user_pack(pack_name, images).await
+            };
+
+            let Some(window) = obj.transient_for() else {
+                return;
+            };
+            match result {
+                Ok(()) => {
+                    toast!(window, gettext("Sticker pack saved"));
+                    obj.close();
+                }
+                Err(error) => {
+                    error!("Could not save sticker pack: {error}");
+                    toast!(window, gettext("Could not save sticker pack"));
+                    imp.create_button.set_sensitive(true);
+                }
+            }
+        }));
+    }
+
+    /// Add the given images to the pack of the user.
+    ///
+    /// The name of the pack is only changed if it doesn't have one.
+    async fn add_to_user_pack(
+        &self,
+        pack_name: Option<String>,
+        images: Vec<PackImage>,
+    ) -> Result<(), matrix_sdk::Error> {
+        let client = self.room().session().client();
+
+        let handle = spawn_tokio!(async move {
+            let account = client.account();
+            let mut content = account
+                .account_data::<UserImagePackEventContent>()
+                .await?
+                .map(|raw| raw.deserialize())
+                .transpose()?
+                .unwrap_or_default();
+
+            add_images(&mut content.images, images);
+            let pack = content.pack.get_or_insert_with(Default::default);
+            if pack.display_name.is_none() {
+                pack.display_name = pack_name;
+            }
+
+            account.set_account_data(content).await?;
+            Ok(())
+        });
+
+        handle.await.unwrap()
+    }
+
+    /// Add the given images to the pack of the room with the given name.
+    ///
+    /// If the room already has a pack with this name, the images are added to
+    /// it.
+    async fn add_to_room_pack(
+        &self,
+        pack_name: String,
+        images: Vec<PackImage>,
+    ) -> Result<(), matrix_sdk::Error> {
+        let matrix_room = self.room().matrix_room();
+        let state_key = pack_state_key(&pack_name);
+
+        let handle = spawn_tokio!(async move {
+            let event = matrix_room
+                .get_state_event_static_for_key::<RoomImagePackEventContent, _>(&state_key)
+                .await?
+                .map(|raw| raw.deserialize())
+                .transpose()?;
+            let mut content = match event {
+                Some(SyncOrStrippedState::Sync(SyncStateEvent::Original(event))) => event.content,
+                _ => RoomImagePackEventContent {
+                    images: Default::default(),
+                    pack: Some(PackInfo {
+                        display_name: Some(pack_name),
+                        usage: vec![PackUsage::Sticker],
+                        ..Default::default()
+                    }),
+                },
+            };
+
+            add_images(&mut content.images, images);
+
+            matrix_room
+                .send_state_event_for_key(&state_key, content)
+                .await?;
+            Ok(())
+        });
+
+        handle.await.unwrap()
+    }
+}
+
+/// Add the given images to the images of a pack.
+///
+/// The shortcodes are generated from the description of the images, and images
+/// that are already in the pack are ignored.
+fn add_images(pack_images: &mut BTreeMap<String, PackImage>, images: Vec<PackImage>) {
+    for image in images {
+        if pack_images.values().any(|i| i.url == image.url) {
+            continue;
+        }
+
+        let base = shortcode_from_body(image.body.as_deref().unwrap_or_default());
+        let mut shortcode = base.clone();
+        let mut i = 1;
+        while pack_images.contains_key(&shortcode) {
+            i += 1;
+            shortcode = format!("{base}_{i}");
+        }
+
+        pack_images.insert(shortcode, image);
+    }
+}
+
+/// Generate a shortcode from the description of an image.
+///
+/// It is the description without its file extension.
+fn shortcode_from_body(body: &str) -> String {
+    let name = body.rsplit_once('.').map(|(name, _)| name).unwrap_or(body);
+    slugify(name, "sticker")
+}
+
+/// Generate the state key of the pack with the given name.
+fn pack_state_key(pack_name: &str) -> String {
+    slugify(pack_name, "pack")
+}
+
+/// Convert the given string to lowercase and replace characters other than
+/// alphanumerics with `_`.
+///
+/// Returns `fallback` if the result is empty.
+fn slugify(s: &str, fallback: &str) -> String {
+    let slug = s
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_lowercase().next().unwrap_or(c)
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    let slug = slug.trim_matches('_');
+
+    if slug.is_empty() {
+        fallback.to_owned()
+    } else {
+        slug.to_owned()
+    }
+}
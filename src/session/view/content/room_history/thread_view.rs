@@ -0,0 +1,334 @@
+use adw::subclass::prelude::*;
+use gtk::{
+    gdk,
+    glib::{self, clone},
+    prelude::*,
+    CompositeTemplate,
+};
+use ruma::events::{
+    relation::Thread as ThreadRelation,
+    room::message::{Relation, RoomMessageEventContent},
+};
+use sourceview::prelude::*;
+use tracing::error;
+
+use super::{remote_event_row, ItemRow, RoomHistory};
+use crate::{
+    components::CustomEntry,
+    session::model::{Event, SearchResult, Thread, TimelineItem},
+    spawn,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/content/room_history/thread_view.ui")]
+    pub struct ThreadView {
+        /// The room history containing this thread view.
+        pub room_history: glib::WeakRef<RoomHistory>,
+        /// The root event of the thread.
+        pub root: RefCell<Option<Event>>,
+        /// The thread that is displayed.
+        pub thread: RefCell<Option<Thread>>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub listview: TemplateChild<gtk::ListView>,
+        #[template_child]
+        pub message_entry: TemplateChild<sourceview::View>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for ThreadView {
+        const NAME: &'static str = "ContentThreadView";
+        type Type = super::ThreadView;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            CustomEntry::static_type();
+            Self::bind_template(klass);
+            klass.set_accessible_role(gtk::AccessibleRole::Group);
+
+            klass.install_action("thread-view.send-message", None, move |widget, _, _| {
+                widget.send_message();
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for ThreadView {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<RoomHistory>("room-history")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecObject::builder::<Event>("root")
+                        .explicit_notify()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "room-history" => obj.set_room_history(value.get().ok().as_ref()),
+                "root" => obj.set_root(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "room-history" => obj.room_history().to_value(),
+                "root" => obj.root().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            // The replies that are not loaded in the timeline are not `TimelineItem`s, so
+            // the rows are created when the items are bound.
+            let factory = gtk::SignalListItemFactory::new();
+            factory.connect_setup(move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    error!("List item factory did not receive a list item: {item:?}");
+                    return;
+                };
+
+                item.set_activatable(false);
+                item.set_selectable(false);
+            });
+            factory.connect_bind(clone!(@weak obj => move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+                let Some(room_history) = obj.room_history() else {
+                    return;
+                };
+
+                if let Some(timeline_item) = item.item().and_downcast::<TimelineItem>() {
+                    let row = item.child().and_downcast::<ItemRow>().unwrap_or_else(|| {
+                        let row = ItemRow::new(&room_history);
+                        item.set_child(Some(&row));
+                        row
+                    });
+                    row.set_property("item", timeline_item);
+                } else if let Some(reply) = item.item().and_downcast::<SearchResult>() {
+                    item.set_child(Some(&remote_event_row(&reply)));
+                }
+            }));
+            factory.connect_unbind(move |_, item| {
+                let Some(item) = item.downcast_ref::<gtk::ListItem>() else {
+                    return;
+                };
+
+                if let Some(row) = item.child().and_downcast::<ItemRow>() {
+                    row.set_property("item", None::<TimelineItem>);
+                }
+            });
+            self.listview.set_factory(Some(&factory));
+
+            // Load older replies when the top of the thread is reached.
+            self.scrolled_window
+                .connect_edge_reached(clone!(@weak obj => move |_, position| {
+                    if position == gtk::PositionType::Top {
+                        obj.load();
+                    }
+                }));
+
+            // Needed to use the natural height of GtkPictures
+            self.listview
+                .set_vscroll_policy(gtk::ScrollablePolicy::Natural);
+
+            let key_events = gtk::EventControllerKey::new();
+            key_events.connect_key_pressed(
+                clone!(@weak obj => @default-return glib::Propagation::Proceed, move |_, key, _, modifier| {
+                    if modifier.is_empty() && (key == gdk::Key::Return || key == gdk::Key::KP_Enter) {
+                        obj.activate_action("thread-view.send-message", None).unwrap();
+                        glib::Propagation::Stop
+                    } else {
+                        glib::Propagation::Proceed
+                    }
+                }),
+            );
+            self.message_entry.add_controller(key_events);
+
+            let buffer = self
+                .message_entry
+                .buffer()
+                .downcast::<sourceview::Buffer>()
+                .unwrap();
+
+            buffer.connect_text_notify(clone!(@weak obj => move |buffer| {
+                let (start_iter, end_iter) = buffer.bounds();
+                obj.action_set_enabled("thread-view.send-message", start_iter != end_iter);
+            }));
+            crate::utils::sourceview::setup_style_scheme(&buffer);
+
+            let md_lang = sourceview::LanguageManager::default().language("markdown");
+            buffer.set_language(md_lang.as_ref());
+
+            obj.action_set_enabled("thread-view.send-message", false);
+        }
+    }
+
+    impl WidgetImpl for ThreadView {}
+    impl BinImpl for ThreadView {}
+}
+
+glib::wrapper! {
+    /// A view displaying the root and the replies of a thread, with a composer
+    /// to reply in the thread.
+    pub struct ThreadView(ObjectSubclass<imp::ThreadView>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+impl ThreadView {
+    /// The room history containing this thread view.
+    pub fn room_history(&self) -> Option<RoomHistory> {
+        self.imp().room_history.upgrade()
+    }
+
+    /// Set the room history containing this thread view.
+    pub fn set_room_history(&self, room_history: Option<&RoomHistory>) {
+        if self.room_history().as_ref() == room_history {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(room_history) = room_history {
+            let buffer = imp.message_entry.buffer();
+            room_history
+                .bind_property("markdown-enabled", &buffer, "highlight-syntax")
+                .sync_create()
+                .build();
+        }
+
+        imp.room_history.set(room_history);
+        self.notify("room-history");
+    }
+
+    /// The root event of the thread.
+    pub fn root(&self) -> Option<Event> {
+        self.imp().root.borrow().clone()
+    }
+
+    /// Set the root event of the thread.
+    pub fn set_root(&self, root: Option<Event>) {
+        if self.root() == root {
+            return;
+        }
+
+        let imp = self.imp();
+
+        let thread = root.as_ref().and_then(|root| {
+            let root_id = root.event_id()?;
+            Some(root.room().timeline().thread(root_id))
+        });
+        let model = thread.as_ref().map(|thread| thread.items().clone());
+        imp.listview
+            .set_model(Some(&gtk::NoSelection::new(model.clone())));
+        imp.message_entry.buffer().set_text("");
+
+        if let Some(model) = model {
+            model.connect_items_changed(
+                clone!(@weak self as obj => move |model, position, _, added| {
+                    // Only follow the new replies, not the older ones that were loaded.
+                    if position + added == model.n_items() {
+                        obj.scroll_down();
+                    }
+                }),
+            );
+        }
+
+        imp.thread.replace(thread);
+        imp.root.replace(root);
+        self.scroll_down();
+        self.load();
+        self.notify("root");
+    }
+
+    /// Load older replies of the thread.
+    fn load(&self) {
+        let Some(thread) = self.imp().thread.borrow().clone() else {
+            return;
+        };
+
+        spawn!(async move {
+            thread.load().await;
+        });
+    }
+
+    /// Scroll to the latest reply in the thread.
+    fn scroll_down(&self) {
+        self.imp()
+            .scrolled_window
+            .emit_scroll_child(gtk::ScrollType::End, false);
+    }
+
+    /// Give the focus to the message entry.
+    pub fn grab_entry_focus(&self) {
+        self.imp().message_entry.grab_focus();
+    }
+
+    /// Send the content of the message entry as a reply in the thread.
+    fn send_message(&self) {
+        let Some(root) = self.root() else {
+            return;
+        };
+        let Some(root_id) = root.event_id() else {
+            return;
+        };
+
+        let buffer = self.imp().message_entry.buffer();
+        let (start_iter, end_iter) = buffer.bounds();
+        let body = buffer.text(&start_iter, &end_iter, true).to_string();
+
+        if body.trim().is_empty() {
+            return;
+        }
+
+        let is_markdown = self
+            .room_history()
+            .is_some_and(|room_history| room_history.markdown_enabled());
+        let mut content = if is_markdown {
+            RoomMessageEventContent::text_markdown(body)
+        } else {
+            RoomMessageEventContent::text_plain(body)
+        };
+
+        // Clients that don't support threads should display it as a reply to the
+        // latest event in the thread.
+        let latest_event_id = root
+            .thread_latest_reply()
+            .and_then(|event| event.event_id())
+            .unwrap_or_else(|| root_id.clone());
+        content.relates_to = Some(Relation::Thread(ThreadRelation::plain(
+            root_id,
+            latest_event_id,
+        )));
+
+        root.room().send_room_message_event(content);
+        buffer.set_text("");
+    }
+}
@@ -0,0 +1,297 @@
+use std::time::Duration;
+
+use gst::{bus::BusWatchGuard, prelude::*};
+use gtk::{gio, glib, glib::clone, subclass::prelude::*};
+use tracing::{error, warn};
+
+/// The interval between two measures of the level of the audio.
+const LEVEL_INTERVAL: Duration = Duration::from_millis(100);
+/// The maximum number of values in the waveform of a voice message.
+const MAX_WAVEFORM_LEN: usize = 100;
+/// The maximum amplitude of a value in the waveform of a voice message.
+const MAX_WAVEFORM_AMPLITUDE: f32 = 1024.0;
+
+/// A recorded voice message.
+#[derive(Debug, Clone)]
+pub struct VoiceMessage {
+    /// The Ogg/Opus file of the recording.
+    pub file: gio::File,
+    /// The duration of the recording.
+    pub duration: Duration,
+    /// The amplitudes of the recording, between `0.0` and `1.0`.
+    pub samples: Vec<f32>,
+}
+
+impl VoiceMessage {
+    /// The waveform of the recording, as defined in MSC3246.
+    pub fn waveform(&self) -> Vec<u16> {
+        let len = self.samples.len().min(MAX_WAVEFORM_LEN);
+        if len == 0 {
+            return Vec::new();
+        }
+
+        (0..len)
+            .map(|i| {
+                let start = i * self.samples.len() / len;
+                let end = (i + 1) * self.samples.len() / len;
+                let chunk = &self.samples[start..end.max(start + 1)];
+                let average = chunk.iter().sum::<f32>() / chunk.len() as f32;
+                (average * MAX_WAVEFORM_AMPLITUDE).round() as u16
+            })
+            .collect()
+    }
+
+    /// Delete the file of the recording.
+    pub fn delete(&self) {
+        if let Err(error) = self.file.delete(gio::Cancellable::NONE) {
+            warn!("Could not delete voice message file: {error}");
+        }
+    }
+}
+
+mod imp {
+    use std::cell::RefCell;
+
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct VoiceRecorder {
+        /// The pipeline of the current recording.
+        pub pipeline: RefCell<Option<(gst::Pipeline, BusWatchGuard)>>,
+        /// The file where the current recording is saved.
+        pub file: RefCell<Option<gio::File>>,
+        /// The amplitudes of the current recording.
+        pub samples: RefCell<Vec<f32>>,
+        /// The sender to notify that the recording was finalized.
+        pub eos_sender: RefCell<Option<futures_channel::oneshot::Sender<()>>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for VoiceRecorder {
+        const NAME: &'static str = "ContentVoiceRecorder";
+        type Type = super::VoiceRecorder;
+    }
+
+    impl ObjectImpl for VoiceRecorder {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecBoolean::builder("recording")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecUInt64::builder("duration")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "recording" => obj.is_recording().to_value(),
+                "duration" => (obj.duration().as_millis() as u64).to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            self.obj().cancel();
+        }
+    }
+}
+
+glib::wrapper! {
+    /// An API to record voice messages from the default audio source.
+    ///
+    /// The recording is encoded to Ogg/Opus.
+    pub struct VoiceRecorder(ObjectSubclass<imp::VoiceRecorder>);
+}
+
+impl VoiceRecorder {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Whether a recording is in progress.
+    pub fn is_recording(&self) -> bool {
+        self.imp().pipeline.borrow().is_some()
+    }
+
+    /// The duration of the current recording.
+    pub fn duration(&self) -> Duration {
+        LEVEL_INTERVAL * self.imp().samples.borrow().len() as u32
+    }
+
+    /// The amplitudes of the current recording, between `0.0` and `1.0`.
+    pub fn samples(&self) -> Vec<f32> {
+        self.imp().samples.borrow().clone()
+    }
+
+    /// Start a new recording.
+    pub fn start(&self) -> Result<(), glib::Error> {
+        if self.is_recording() {
+            return Ok(());
+        }
+        let imp = self.imp();
+
+        let (file, _) = gio::File::new_tmp(Some("fractal-voice-XXXXXX.ogg"))?;
+        let Some(path) = file.path() else {
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::NotSupported,
+                "Temporary file has no path",
+            ));
+        };
+
+        let pipeline = gst::parse_launch(&format!(
+            "autoaudiosrc ! audioconvert ! level interval={} post-messages=true ! audioresample ! opusenc ! oggmux ! filesink name=sink",
+            LEVEL_INTERVAL.as_nanos()
+        ))?
+        .downcast::<gst::Pipeline>()
+        .unwrap();
+        pipeline
+            .by_name("sink")
+            .unwrap()
+            .set_property("location", path.to_string_lossy().as_ref());
+
+        let bus = pipeline.bus().unwrap();
+        let bus_guard = bus
+            .add_watch_local(
+                clone!(@weak self as obj => @default-return glib::ControlFlow::Break, move |_, message| {
+                    obj.handle_message(message);
+                    glib::ControlFlow::Continue
+                }),
+            )
+            .expect("Failed to add bus watch");
+
+        if let Err(error) = pipeline.set_state(gst::State::Playing) {
+            error!("Could not start recording: {error}");
+            let _ = pipeline.set_state(gst::State::Null);
+            return Err(glib::Error::new(
+                gio::IOErrorEnum::Failed,
+                "Could not start recording",
+            ));
+        }
+
+        imp.samples.borrow_mut().clear();
+        imp.file.replace(Some(file));
+        imp.pipeline.replace(Some((pipeline, bus_guard)));
+
+        self.notify("recording");
+        self.notify("duration");
+        Ok(())
+    }
+
+    /// Handle the given message of the bus of the pipeline.
+    fn handle_message(&self, message: &gst::Message) {
+        let imp = self.imp();
+
+        match message.view() {
+            gst::MessageView::Element(element) => {
+                let Some(structure) = element.structure() else {
+                    return;
+                };
+                if structure.name() != "level" {
+                    return;
+                }
+
+                // The RMS of each channel, in dB.
+                let Ok(rms) = structure.get::<glib::ValueArray>("rms") else {
+                    return;
+                };
+                let db = rms
+                    .iter()
+                    .filter_map(|value| value.get::<f64>().ok())
+                    .fold(f64::NEG_INFINITY, f64::max);
+                let amplitude = 10f64.powf(db / 20.0).clamp(0.0, 1.0) as f32;
+
+                imp.samples.borrow_mut().push(amplitude);
+                self.notify("duration");
+            }
+            gst::MessageView::Eos(_) => {
+                if let Some(sender) = imp.eos_sender.take() {
+                    let _ = sender.send(());
+                }
+            }
+            gst::MessageView::Error(error) => {
+                error!(
+                    "Error from {:?}: {} ({:?})",
+                    error.src().map(|s| s.path_string()),
+                    error.error(),
+                    error.debug()
+                );
+
+                // The recording cannot be used, drop it. This also drops the
+                // EOS sender so `stop()` doesn't wait forever.
+                self.cancel();
+            }
+            _ => {}
+        }
+    }
+
+    /// Stop the current recording.
+    ///
+    /// Returns the recorded voice message, if any.
+    pub async fn stop(&self) -> Option<VoiceMessage> {
+        let imp = self.imp();
+
+        let pipeline = imp
+            .pipeline
+            .borrow()
+            .as_ref()
+            .map(|(pipeline, _)| pipeline.clone())?;
+
+        // Wait for the end of the stream to make sure the file is finalized.
+        let (sender, receiver) = futures_channel::oneshot::channel();
+        imp.eos_sender.replace(Some(sender));
+        pipeline.send_event(gst::event::Eos::new());
+        if receiver.await.is_err() {
+            // The recording was cancelled or the pipeline failed.
+            return None;
+        }
+
+        let _ = pipeline.set_state(gst::State::Null);
+        imp.pipeline.take();
+        self.notify("recording");
+
+        let file = imp.file.take()?;
+        let samples = imp.samples.take();
+
+        Some(VoiceMessage {
+            file,
+            duration: LEVEL_INTERVAL * samples.len() as u32,
+            samples,
+        })
+    }
+
+    /// Cancel the current recording, if any.
+    pub fn cancel(&self) {
+        let imp = self.imp();
+
+        let Some((pipeline, _)) = imp.pipeline.take() else {
+            return;
+        };
+        let _ = pipeline.set_state(gst::State::Null);
+        imp.eos_sender.take();
+        imp.samples.take();
+
+        if let Some(file) = imp.file.take() {
+            if let Err(error) = file.delete(gio::Cancellable::NONE) {
+                warn!("Could not delete voice message file: {error}");
+            }
+        }
+
+        self.notify("recording");
+    }
+}
+
+impl Default for VoiceRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
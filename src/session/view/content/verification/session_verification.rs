@@ -7,7 +7,9 @@ use super::IdentityVerificationWidget;
 use crate::{
     components::{AuthDialog, AuthError, SpinnerButton},
     login::Login,
-    session::model::{IdentityVerification, Session, VerificationState},
+    session::model::{
+        IdentityVerification, RecoverError, RecoveryState, Session, VerificationState,
+    },
     spawn, spawn_tokio, toast, Window,
 };
 
@@ -50,8 +52,23 @@ mod imp {
         pub bootstrap_restart_button: TemplateChild<gtk::Button>,
         #[template_child]
         pub verification_widget: TemplateChild<IdentityVerificationWidget>,
+        #[template_child]
+        pub recovery_passphrase_entry: TemplateChild<adw::PasswordEntryRow>,
+        #[template_child]
+        pub recovery_passphrase_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub recovery_key_entry: TemplateChild<adw::EntryRow>,
+        #[template_child]
+        pub recovery_key_button: TemplateChild<SpinnerButton>,
         pub state_handler: RefCell<Option<SignalHandlerId>>,
+        pub recovery_state_handler: RefCell<Option<SignalHandlerId>>,
         pub bootstrap_can_restart: Cell<bool>,
+        /// The mode of the bootstrap page, if it was shown.
+        pub(super) bootstrap_mode: Cell<Option<BootstrapMode>>,
+        /// Whether the last verification request failed.
+        ///
+        /// Used to only warn the user once when the request keeps failing.
+        pub verification_failed: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -62,6 +79,12 @@ mod imp {
 
         fn class_init(klass: &mut Self::Class) {
             Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+
+            klass.install_action("session-verification.start", None, move |obj, _, _| {
+                obj.imp().verification_failed.set(false);
+                obj.start();
+            });
 
             klass.install_action(
                 "session-verification.show-recovery",
@@ -71,6 +94,24 @@ mod imp {
                 },
             );
 
+            klass.install_action(
+                "session-verification.show-recovery-passphrase",
+                None,
+                move |obj, _, _| {
+                    obj.imp()
+                        .main_stack
+                        .set_visible_child_name("recovery-passphrase");
+                },
+            );
+
+            klass.install_action(
+                "session-verification.show-recovery-key",
+                None,
+                move |obj, _, _| {
+                    obj.imp().main_stack.set_visible_child_name("recovery-key");
+                },
+            );
+
             klass.install_action(
                 "session-verification.reset-identity",
                 None,
@@ -126,7 +167,16 @@ mod imp {
             self.parent_constructed();
             let obj = self.obj();
 
-            obj.action_set_enabled("session-verification.show-recovery", false);
+            let recovery_state_handler = obj.session().key_backup().connect_notify_local(
+                Some("recovery-state"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_recovery_action();
+                    obj.update_bootstrap();
+                }),
+            );
+            self.recovery_state_handler
+                .replace(Some(recovery_state_handler));
+            obj.update_recovery_action();
 
             self.bootstrap_setup_button
                 .connect_clicked(clone!(@weak obj => move |button| {
@@ -141,9 +191,17 @@ mod imp {
         }
 
         fn dispose(&self) {
-            if let Some(request) = self.obj().request() {
+            let obj = self.obj();
+
+            if let Some(request) = obj.request() {
                 request.cancel(true);
             }
+
+            if let Some(handler) = self.recovery_state_handler.take() {
+                if let Some(session) = self.session.upgrade() {
+                    session.key_backup().disconnect(handler);
+                }
+            }
         }
     }
 
@@ -156,6 +214,7 @@ glib::wrapper! {
         @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
 }
 
+#[gtk::template_callbacks]
 impl SessionVerification {
     pub fn new(login: &Login, session: &Session) -> Self {
         glib::Object::builder()
@@ -235,28 +294,139 @@ impl SessionVerification {
         let imp = self.imp();
 
         if request.is_finished() && request.state() != VerificationState::Completed {
+            if request.state() == VerificationState::Error && !imp.verification_failed.replace(true)
+            {
+                toast!(self, gettext("The verification failed. Try again."));
+            }
+
+            // Start over, the user can choose to reset the encryption identity
+            // from there if needed.
             self.start();
             return;
         }
 
         match request.state() {
-            // FIXME: we bootstrap on all errors
-            VerificationState::Error => {
-                self.show_bootstrap(BootstrapMode::Reset);
-            }
             VerificationState::RequestSend => {
                 imp.main_stack.set_visible_child_name("wait-for-device");
             }
             _ => {
+                // The other device answered, so the request did not fail.
+                imp.verification_failed.set(false);
                 imp.main_stack.set_visible_child(&*imp.verification_widget);
             }
         }
     }
 
+    /// Whether this session can be verified with a recovery key or
+    /// passphrase.
+    fn can_recover(&self) -> bool {
+        matches!(
+            self.session().key_backup().recovery_state(),
+            RecoveryState::Enabled | RecoveryState::Incomplete
+        )
+    }
+
+    fn update_recovery_action(&self) {
+        self.action_set_enabled("session-verification.show-recovery", self.can_recover());
+    }
+
+    /// Replace the bootstrap page if recovery became available after it was
+    /// shown because no other devices could be used.
+    fn update_bootstrap(&self) {
+        let imp = self.imp();
+
+        if imp.main_stack.visible_child_name().as_deref() == Some("bootstrap")
+            && matches!(imp.bootstrap_mode.get(), Some(BootstrapMode::NoDevices))
+            && self.can_recover()
+        {
+            self.show_recovery();
+        }
+    }
+
     fn show_recovery(&self) {
-        // TODO: stop the request
+        let imp = self.imp();
 
-        self.imp().main_stack.set_visible_child_name("recovery");
+        self.set_request(None);
+
+        imp.recovery_passphrase_entry.set_text("");
+        imp.recovery_key_entry.set_text("");
+        imp.main_stack.set_visible_child_name("recovery");
+    }
+
+    #[template_callback]
+    fn update_recovery_buttons(&self) {
+        let imp = self.imp();
+
+        imp.recovery_passphrase_button
+            .set_sensitive(!imp.recovery_passphrase_entry.text().is_empty());
+        imp.recovery_key_button
+            .set_sensitive(!imp.recovery_key_entry.text().trim().is_empty());
+    }
+
+    #[template_callback]
+    fn recover_with_passphrase(&self) {
+        let imp = self.imp();
+        let passphrase = imp.recovery_passphrase_entry.text().to_string();
+
+        if passphrase.is_empty() || imp.recovery_passphrase_button.loading() {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+            obj.recover(
+                passphrase,
+                imp.recovery_passphrase_entry.upcast_ref(),
+                &imp.recovery_passphrase_button,
+            )
+            .await;
+        }));
+    }
+
+    #[template_callback]
+    fn recover_with_key(&self) {
+        let imp = self.imp();
+        let recovery_key = imp.recovery_key_entry.text().trim().to_owned();
+
+        if recovery_key.is_empty() || imp.recovery_key_button.loading() {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj => async move {
+            let imp = obj.imp();
+            obj.recover(
+                recovery_key,
+                imp.recovery_key_entry.upcast_ref(),
+                &imp.recovery_key_button,
+            )
+            .await;
+        }));
+    }
+
+    /// Verify this session by recovering the secrets of the account with the
+    /// given recovery key or passphrase.
+    async fn recover(&self, secret: String, entry: &gtk::Widget, button: &SpinnerButton) {
+        entry.set_sensitive(false);
+        button.set_loading(true);
+
+        match self.session().key_backup().recover(secret).await {
+            Ok(()) => {
+                self.login().unwrap().show_completed();
+            }
+            Err(error) => {
+                error!("Failed to verify this session with recovery: {error}");
+
+                let message = if let RecoverError::Recovery(_) = error {
+                    gettext("Could not verify this session. Check the recovery key or passphrase.")
+                } else {
+                    gettext("Could not verify this session")
+                };
+                toast!(self, message);
+            }
+        }
+
+        entry.set_sensitive(true);
+        button.set_loading(false);
     }
 
     fn show_bootstrap(&self, mode: BootstrapMode) {
@@ -293,6 +463,7 @@ impl SessionVerification {
             }
         }
 
+        imp.bootstrap_mode.set(Some(mode));
         imp.main_stack.set_visible_child_name("bootstrap");
     }
 
@@ -353,7 +524,12 @@ impl SessionVerification {
 
         if !can_verify_with_devices {
             debug!("No other device is cross-signed, don’t request verification");
-            self.show_bootstrap(BootstrapMode::NoDevices);
+
+            if self.can_recover() {
+                self.show_recovery();
+            } else {
+                self.show_bootstrap(BootstrapMode::NoDevices);
+            }
             return;
         }
 
@@ -1,16 +1,10 @@
 use gtk::{glib, prelude::*, subclass::prelude::*};
-use matrix_sdk::ruma::{
-    api::client::room::create_room,
-    assign,
-    events::{room::encryption::RoomEncryptionEventContent, InitialStateEvent},
-    MxcUri, UserId,
-};
+use matrix_sdk::ruma::{MxcUri, UserId};
 use tracing::{debug, error};
 
 use crate::{
     prelude::*,
     session::model::{Room, Session, User},
-    spawn_tokio,
 };
 
 mod imp {
@@ -105,8 +99,6 @@ impl DmUser {
     /// If A DM chat exists already no new room is created and the existing one
     /// is returned.
     pub async fn start_chat(&self) -> Result<Room, ()> {
-        let session = self.session();
-        let client = session.client();
         let other_user = self.user_id();
 
         if let Some(room) = self.dm_room() {
@@ -127,15 +119,8 @@ impl DmUser {
             return Ok(room);
         }
 
-        let handle = spawn_tokio!(async move { create_dm(client, other_user).await });
-
-        match handle.await.unwrap() {
-            Ok(matrix_room) => {
-                let room = session
-                    .room_list()
-                    .get_wait(matrix_room.room_id())
-                    .await
-                    .expect("The newly created room was not found");
+        match self.upcast_ref::<User>().create_direct_chat().await {
+            Ok(room) => {
                 self.set_dm_room(Some(&room));
                 Ok(room)
             }
@@ -146,20 +131,3 @@ impl DmUser {
         }
     }
 }
-
-async fn create_dm(
-    client: matrix_sdk::Client,
-    other_user: ruma::OwnedUserId,
-) -> Result<matrix_sdk::Room, matrix_sdk::Error> {
-    let request = assign!(create_room::v3::Request::new(),
-    {
-        is_direct: true,
-        invite: vec![other_user],
-        preset: Some(create_room::v3::RoomPreset::TrustedPrivateChat),
-        initial_state: vec![
-           InitialStateEvent::new(RoomEncryptionEventContent::with_recommended_defaults()).to_raw_any(),
-        ],
-    });
-
-    client.create_room(request).await
-}
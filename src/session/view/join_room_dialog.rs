@@ -1,12 +1,10 @@
 use adw::{prelude::*, subclass::prelude::*};
 use gettextrs::gettext;
 use gtk::{gdk, glib, glib::clone, CompositeTemplate};
-use ruma::{
-    matrix_uri::MatrixId, MatrixToUri, MatrixUri, OwnedRoomOrAliasId, OwnedServerName,
-    RoomOrAliasId,
-};
+use ruma::{OwnedRoomOrAliasId, OwnedServerName, RoomOrAliasId};
 
-use crate::{session::model::Session, spawn, toast, Window};
+use super::KnockDialog;
+use crate::{session::model::Session, spawn, toast, utils::matrix::MatrixIdUri, Window};
 
 mod imp {
     use glib::{object::WeakRef, subclass::InitializingObject};
@@ -79,8 +77,10 @@ mod imp {
 
     impl MessageDialogImpl for JoinRoomDialog {
         fn response(&self, response: &str) {
-            if response == "join" {
-                self.obj().join_room();
+            match response {
+                "join" => self.obj().join_room(),
+                "knock" => self.obj().knock_room(),
+                _ => {}
             }
 
             self.parent_response(response)
@@ -120,16 +120,23 @@ impl JoinRoomDialog {
         self.notify("session");
     }
 
+    /// Set the room to join, as a room ID, room alias or permalink.
+    pub fn set_room_identifier(&self, identifier: &str) {
+        self.imp().entry.set_text(identifier);
+    }
+
     /// Handle when the entry text changed.
     #[template_callback]
     fn entry_changed(&self, entry: &gtk::Entry) {
         let Some(session) = self.session() else {
             self.set_response_enabled("join", false);
+            self.set_response_enabled("knock", false);
             return;
         };
 
         let Some((room_id, _)) = parse_room(&entry.text()) else {
             self.set_response_enabled("join", false);
+            self.set_response_enabled("knock", false);
             return;
         };
 
@@ -141,8 +148,10 @@ impl JoinRoomDialog {
             .is_some()
         {
             self.set_response_label("join", &gettext("_View"));
+            self.set_response_enabled("knock", false);
         } else {
             self.set_response_label("join", &gettext("_Join"));
+            self.set_response_enabled("knock", true);
         }
     }
 
@@ -172,29 +181,36 @@ impl JoinRoomDialog {
             }));
         }
     }
+
+    /// Request an invite to the room that was entered, if it is valid.
+    fn knock_room(&self) {
+        let Some((room_id, via)) = parse_room(&self.imp().entry.text()) else {
+            return;
+        };
+
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let room_name = room_id.to_string();
+        let dialog = KnockDialog::new(
+            self.transient_for().as_ref(),
+            &session.room_list(),
+            room_id,
+            via,
+            &room_name,
+        );
+        dialog.present();
+    }
 }
 
 fn parse_room(room: &str) -> Option<(OwnedRoomOrAliasId, Vec<OwnedServerName>)> {
-    MatrixUri::parse(room)
+    MatrixIdUri::parse(room)
         .ok()
-        .and_then(|uri| match uri.id() {
-            MatrixId::Room(room_id) => Some((room_id.clone().into(), uri.via().to_owned())),
-            MatrixId::RoomAlias(room_alias) => {
-                Some((room_alias.clone().into(), uri.via().to_owned()))
-            }
+        .and_then(|uri| match uri {
+            MatrixIdUri::Room(room_uri) => Some((room_uri.id, room_uri.via)),
             _ => None,
         })
-        .or_else(|| {
-            MatrixToUri::parse(room)
-                .ok()
-                .and_then(|uri| match uri.id() {
-                    MatrixId::Room(room_id) => Some((room_id.clone().into(), uri.via().to_owned())),
-                    MatrixId::RoomAlias(room_alias) => {
-                        Some((room_alias.clone().into(), uri.via().to_owned()))
-                    }
-                    _ => None,
-                })
-        })
         .or_else(|| {
             RoomOrAliasId::parse(room)
                 .ok()
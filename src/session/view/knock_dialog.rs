@@ -0,0 +1,120 @@
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, CompositeTemplate};
+use ruma::{OwnedRoomOrAliasId, OwnedServerName};
+
+use crate::{gettext_f, session::model::RoomList, spawn, toast};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{object::WeakRef, subclass::InitializingObject};
+    use once_cell::unsync::OnceCell;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/knock_dialog.ui")]
+    pub struct KnockDialog {
+        /// The list of rooms of the current session.
+        pub room_list: WeakRef<RoomList>,
+        /// The identifier of the room to knock on.
+        pub identifier: OnceCell<OwnedRoomOrAliasId>,
+        /// The servers to try to knock through.
+        pub via: RefCell<Vec<OwnedServerName>>,
+        #[template_child]
+        pub reason_entry: TemplateChild<gtk::Entry>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for KnockDialog {
+        const NAME: &'static str = "KnockDialog";
+        type Type = super::KnockDialog;
+        type ParentType = adw::MessageDialog;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for KnockDialog {}
+    impl WidgetImpl for KnockDialog {}
+    impl WindowImpl for KnockDialog {}
+
+    impl MessageDialogImpl for KnockDialog {
+        fn response(&self, response: &str) {
+            if response == "knock" {
+                self.obj().knock();
+            }
+
+            self.parent_response(response)
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Dialog to knock on a room, to request an invite to it.
+    pub struct KnockDialog(ObjectSubclass<imp::KnockDialog>)
+        @extends gtk::Widget, gtk::Window, adw::MessageDialog, @implements gtk::Accessible;
+}
+
+impl KnockDialog {
+    /// Create a dialog to knock on the room with the given identifier.
+    ///
+    /// `room_name` is the name of the room presented to the user.
+    pub fn new(
+        parent_window: Option<&impl IsA<gtk::Window>>,
+        room_list: &RoomList,
+        identifier: OwnedRoomOrAliasId,
+        via: Vec<OwnedServerName>,
+        room_name: &str,
+    ) -> Self {
+        let obj = glib::Object::builder::<Self>()
+            .property("transient-for", parent_window)
+            .build();
+        let imp = obj.imp();
+
+        imp.room_list.set(Some(room_list));
+        imp.identifier.set(identifier).unwrap();
+        imp.via.replace(via);
+
+        obj.set_body(&gettext_f(
+            // Translators: Do NOT translate the content between '{' and '}', this is a
+            // variable name.
+            "Ask the moderators of {room} to invite you. You can add a message for them.",
+            &[("room", room_name)],
+        ));
+
+        obj
+    }
+
+    /// Send the request to join the room.
+    fn knock(&self) {
+        let imp = self.imp();
+        let Some(room_list) = imp.room_list.upgrade() else {
+            return;
+        };
+
+        let identifier = imp.identifier.get().unwrap().clone();
+        let via = imp.via.take();
+        let reason = Some(imp.reason_entry.text().trim().to_owned()).filter(|r| !r.is_empty());
+
+        // Toasts cannot be shown in this dialog since it is closed after the response.
+        let parent_window = self.transient_for();
+
+        spawn!(clone!(@weak room_list => async move {
+            let message = match room_list.knock_by_id_or_alias(identifier, reason, via).await {
+                Ok(()) => gettext("Request sent"),
+                Err(error) => error,
+            };
+
+            if let Some(parent_window) = parent_window {
+                toast!(parent_window, message);
+            }
+        }));
+    }
+}
@@ -0,0 +1,249 @@
+mod result_row;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gtk::{gdk, glib, glib::clone, CompositeTemplate};
+use tracing::error;
+
+use self::result_row::MessageSearchResultRow;
+use crate::{
+    components::Spinner,
+    session::model::{MessageSearch, MessageSearchState, Room, SearchResult, Session},
+    Window,
+};
+
+mod imp {
+    use glib::{object::WeakRef, subclass::InitializingObject};
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/message_search_dialog/mod.ui")]
+    pub struct MessageSearchDialog {
+        /// The current session.
+        pub session: WeakRef<Session>,
+        /// The room that was open when the dialog was opened, if any.
+        pub room: WeakRef<Room>,
+        /// The search model.
+        pub search: OnceCell<MessageSearch>,
+        #[template_child]
+        pub search_entry: TemplateChild<gtk::SearchEntry>,
+        #[template_child]
+        pub scope_box: TemplateChild<gtk::Box>,
+        #[template_child]
+        pub room_scope_button: TemplateChild<gtk::ToggleButton>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub initial_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub spinner: TemplateChild<Spinner>,
+        #[template_child]
+        pub empty_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub error_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub listview: TemplateChild<gtk::ListView>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageSearchDialog {
+        const NAME: &'static str = "MessageSearchDialog";
+        type Type = super::MessageSearchDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            MessageSearchResultRow::static_type();
+            Self::bind_template(klass);
+
+            klass.add_binding(
+                gdk::Key::Escape,
+                gdk::ModifierType::empty(),
+                |obj, _| {
+                    obj.close();
+                    true
+                },
+                None,
+            );
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageSearchDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Session>("session")
+                        .construct_only()
+                        .build(),
+                    glib::ParamSpecObject::builder::<Room>("room")
+                        .construct_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "session" => self.session.set(value.get().ok().as_ref()),
+                "room" => self.room.set(value.get().ok().flatten().as_ref()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "session" => obj.session().to_value(),
+                "room" => obj.room().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let Some(session) = obj.session() else {
+                error!("Cannot search messages without a session");
+                return;
+            };
+
+            let search = MessageSearch::new(&session);
+            search.set_room(obj.room());
+            search.connect_notify_local(
+                Some("state"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_visible_child();
+                }),
+            );
+            search.connect_items_changed(clone!(@weak obj => move |_, _, _, _| {
+                obj.update_visible_child();
+            }));
+            self.listview
+                .set_model(Some(&gtk::NoSelection::new(Some(search.clone()))));
+            self.search.set(search).unwrap();
+
+            // Searching in the current room is only possible if there is one.
+            self.scope_box.set_visible(obj.room().is_some());
+            self.room_scope_button.set_active(obj.room().is_some());
+            self.room_scope_button
+                .connect_toggled(clone!(@weak obj => move |button| {
+                    let room = button.is_active().then(|| obj.room()).flatten();
+                    obj.search().set_room(room);
+                }));
+
+            self.search_entry
+                .connect_search_changed(clone!(@weak obj => move |entry| {
+                    obj.search().search(entry.text().into());
+                }));
+
+            let adj = self.scrolled_window.vadjustment();
+            adj.connect_value_changed(clone!(@weak obj => move |adj| {
+                if adj.upper() - adj.value() < adj.page_size() * 2.0 {
+                    obj.search().load_more();
+                }
+            }));
+
+            self.listview
+                .connect_activate(clone!(@weak obj => move |listview, pos| {
+                    let Some(result) = listview
+                        .model()
+                        .and_then(|model| model.item(pos))
+                        .and_downcast::<SearchResult>()
+                    else {
+                        return;
+                    };
+
+                    obj.show_result(&result);
+                }));
+
+            obj.update_visible_child();
+        }
+    }
+
+    impl WidgetImpl for MessageSearchDialog {}
+    impl WindowImpl for MessageSearchDialog {}
+    impl AdwWindowImpl for MessageSearchDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to search for messages in a room or in all the joined rooms.
+    pub struct MessageSearchDialog(ObjectSubclass<imp::MessageSearchDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, @implements gtk::Accessible;
+}
+
+impl MessageSearchDialog {
+    /// Construct a new `MessageSearchDialog` for the given session.
+    ///
+    /// If `room` is set, the search is restricted to this room by default.
+    pub fn new(
+        parent_window: Option<&impl IsA<gtk::Window>>,
+        session: &Session,
+        room: Option<&Room>,
+    ) -> Self {
+        glib::Object::builder()
+            .property("transient-for", parent_window)
+            .property("session", session)
+            .property("room", room)
+            .build()
+    }
+
+    /// The current session.
+    pub fn session(&self) -> Option<Session> {
+        self.imp().session.upgrade()
+    }
+
+    /// The room that was open when the dialog was opened, if any.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.upgrade()
+    }
+
+    /// The search model.
+    fn search(&self) -> &MessageSearch {
+        self.imp().search.get().unwrap()
+    }
+
+    /// Update the visible child according to the state of the search.
+    fn update_visible_child(&self) {
+        let imp = self.imp();
+        let Some(search) = imp.search.get() else {
+            return;
+        };
+
+        if search.n_items() > 0 {
+            imp.stack.set_visible_child(&*imp.scrolled_window);
+            return;
+        }
+
+        match search.state() {
+            MessageSearchState::Initial => imp.stack.set_visible_child(&*imp.initial_page),
+            MessageSearchState::Loading | MessageSearchState::Ready => {
+                imp.stack.set_visible_child(&*imp.spinner)
+            }
+            MessageSearchState::Error => imp.stack.set_visible_child(&*imp.error_page),
+            MessageSearchState::Complete => imp.stack.set_visible_child(&*imp.empty_page),
+        }
+    }
+
+    /// Jump to the given result in the room history.
+    fn show_result(&self, result: &SearchResult) {
+        let Some(room) = result.room() else {
+            return;
+        };
+        let Some(window) = self.transient_for().and_downcast::<Window>() else {
+            return;
+        };
+
+        window.session_view().show_event(&room, result.event_key());
+        self.close();
+    }
+}
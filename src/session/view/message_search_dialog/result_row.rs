@@ -0,0 +1,117 @@
+use adw::subclass::prelude::*;
+use gtk::{glib, prelude::*, CompositeTemplate};
+
+use crate::{components::Avatar, prelude::*, session::model::SearchResult};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::subclass::InitializingObject;
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/message_search_dialog/result_row.ui")]
+    pub struct MessageSearchResultRow {
+        /// The search result displayed by this row.
+        pub result: RefCell<Option<SearchResult>>,
+        #[template_child]
+        pub sender: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub date: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub body: TemplateChild<gtk::Label>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for MessageSearchResultRow {
+        const NAME: &'static str = "MessageSearchResultRow";
+        type Type = super::MessageSearchResultRow;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Avatar::static_type();
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for MessageSearchResultRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<SearchResult>("result")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "result" => self.obj().set_result(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "result" => self.obj().result().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl WidgetImpl for MessageSearchResultRow {}
+    impl BinImpl for MessageSearchResultRow {}
+}
+
+glib::wrapper! {
+    /// A row presenting a message matching a search.
+    pub struct MessageSearchResultRow(ObjectSubclass<imp::MessageSearchResultRow>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+impl MessageSearchResultRow {
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// The search result displayed by this row.
+    pub fn result(&self) -> Option<SearchResult> {
+        self.imp().result.borrow().clone()
+    }
+
+    /// Set the search result displayed by this row.
+    pub fn set_result(&self, result: Option<SearchResult>) {
+        if self.result() == result {
+            return;
+        }
+
+        let imp = self.imp();
+
+        if let Some(result) = &result {
+            let sender_name = result
+                .sender()
+                .map(|sender| sender.display_name())
+                .unwrap_or_default();
+            imp.sender.set_label(&sender_name);
+            imp.date
+                .set_label(&result.timestamp().format("%x").unwrap_or_default());
+            imp.body.set_label(result.body());
+        }
+
+        imp.result.replace(result);
+        self.notify("result");
+    }
+}
+
+impl Default for MessageSearchResultRow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
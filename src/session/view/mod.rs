@@ -3,10 +3,14 @@ mod content;
 mod create_dm_dialog;
 mod event_source_dialog;
 mod join_room_dialog;
+mod knock_dialog;
 mod media_viewer;
+mod message_search_dialog;
 mod room_creation;
 mod session_view;
 mod sidebar;
+mod space_hierarchy_dialog;
+mod user_profile_dialog;
 
 pub use self::{
     account_settings::AccountSettings, content::verification::SessionVerification,
@@ -14,6 +18,7 @@ pub use self::{
 };
 use self::{
     content::Content, create_dm_dialog::CreateDmDialog, event_source_dialog::EventSourceDialog,
-    join_room_dialog::JoinRoomDialog, media_viewer::MediaViewer, room_creation::RoomCreation,
-    sidebar::Sidebar,
+    join_room_dialog::JoinRoomDialog, knock_dialog::KnockDialog, media_viewer::MediaViewer,
+    message_search_dialog::MessageSearchDialog, room_creation::RoomCreation, sidebar::Sidebar,
+    space_hierarchy_dialog::SpaceHierarchyDialog, user_profile_dialog::UserProfileDialog,
 };
@@ -1,4 +1,5 @@
 use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
 use gtk::{
     self, gdk, glib,
     glib::{clone, signal::SignalHandlerId},
@@ -7,10 +8,16 @@ use gtk::{
 use ruma::RoomId;
 use tracing::{error, warn};
 
-use super::{Content, CreateDmDialog, JoinRoomDialog, MediaViewer, RoomCreation, Sidebar};
+use super::{
+    Content, CreateDmDialog, JoinRoomDialog, MediaViewer, MessageSearchDialog, RoomCreation,
+    Sidebar, UserProfileDialog,
+};
 use crate::{
-    session::model::{Event, Room, Selection, Session, SidebarListModel},
-    spawn, toast, Window,
+    prelude::*,
+    session::model::{Event, EventKey, Room, Selection, Session, SidebarListModel, User},
+    spawn, toast,
+    utils::matrix::{MatrixIdUri, MatrixRoomIdUri},
+    Window,
 };
 
 mod imp {
@@ -63,6 +70,17 @@ mod imp {
                 }
             });
 
+            klass.install_action(
+                "session.show-matrix-uri",
+                Some("s"),
+                move |obj, _, parameter| match MatrixIdUri::parse(
+                    &parameter.unwrap().get::<String>().unwrap(),
+                ) {
+                    Ok(uri) => obj.show_matrix_uri(uri),
+                    Err(error) => error!("Cannot show invalid Matrix URI: {error}"),
+                },
+            );
+
             klass.install_action("session.logout", None, move |obj, _, _| {
                 if let Some(session) = obj.session() {
                     spawn!(clone!(@weak obj, @weak session => async move {
@@ -108,6 +126,23 @@ mod imp {
                 "session.toggle-room-search",
                 None,
             );
+
+            klass.install_action("session.search-messages", None, move |obj, _, _| {
+                obj.show_message_search_dialog(None);
+            });
+
+            klass.install_action("session.mark-all-read", None, move |obj, _, _| {
+                spawn!(clone!(@weak obj => async move {
+                    obj.mark_all_rooms_as_read().await;
+                }));
+            });
+
+            klass.add_binding_action(
+                gdk::Key::F,
+                gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::SHIFT_MASK,
+                "session.search-messages",
+                None,
+            );
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -300,6 +335,104 @@ impl SessionView {
         dialog.present();
     }
 
+    /// Show the dialog to search messages.
+    ///
+    /// If `room` is set, the search is restricted to this room by default.
+    pub fn show_message_search_dialog(&self, room: Option<&Room>) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let dialog = MessageSearchDialog::new(self.parent_window().as_ref(), &session, room);
+        dialog.present();
+    }
+
+    /// Mark all the messages of all the joined rooms as read.
+    async fn mark_all_rooms_as_read(&self) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        let rooms = session
+            .room_list()
+            .iter::<Room>()
+            .map_while(Result::ok)
+            .filter(|room| room.is_joined() && !room.is_read())
+            .collect::<Vec<_>>();
+
+        let mut has_error = false;
+        for room in rooms {
+            if let Err(error) = room.timeline().mark_as_read().await {
+                error!("Could not mark room {} as read: {error}", room.room_id());
+                has_error = true;
+            }
+        }
+
+        if has_error {
+            toast!(self, gettext("Failed to mark some rooms as read"));
+        }
+    }
+
+    /// Show the event with the given key in the history of the given room.
+    pub fn show_event(&self, room: &Room, key: EventKey) {
+        self.select_room(Some(room.clone()));
+        self.imp().content.jump_to_event(key);
+    }
+
+    /// Show the given Matrix URI in this session.
+    ///
+    /// Rooms that are not joined yet are offered to be joined.
+    pub fn show_matrix_uri(&self, uri: MatrixIdUri) {
+        let Some(session) = self.session() else {
+            return;
+        };
+
+        match uri {
+            MatrixIdUri::Room(room_uri) => {
+                spawn!(clone!(@weak self as obj, @weak session => async move {
+                    if let Some(room) = session.room_list().resolve_joined_room(&room_uri.id).await {
+                        obj.select_room(Some(room));
+                    } else {
+                        obj.show_join_room_dialog_for_uri(&session, &room_uri);
+                    }
+                }));
+            }
+            MatrixIdUri::User(user_id) => {
+                let user = User::new(&session, &user_id);
+                user.load_profile();
+
+                let parent_window = self.parent_window();
+                let dialog = UserProfileDialog::new(
+                    parent_window
+                        .as_ref()
+                        .map(|w| w.upcast_ref::<gtk::Window>()),
+                    &user,
+                );
+                dialog.present();
+            }
+            MatrixIdUri::Event(event_uri) => {
+                spawn!(clone!(@weak self as obj, @weak session => async move {
+                    if let Some(room) = session
+                        .room_list()
+                        .resolve_joined_room(&event_uri.room_uri.id)
+                        .await
+                    {
+                        obj.show_event(&room, EventKey::EventId(event_uri.event_id));
+                    } else {
+                        obj.show_join_room_dialog_for_uri(&session, &event_uri.room_uri);
+                    }
+                }));
+            }
+        }
+    }
+
+    /// Show the dialog to join the room with the given URI.
+    fn show_join_room_dialog_for_uri(&self, session: &Session, room_uri: &MatrixRoomIdUri) {
+        let dialog = JoinRoomDialog::new(self.parent_window().as_ref(), session);
+        dialog.set_room_identifier(&room_uri.matrix_to_uri().to_string());
+        dialog.present();
+    }
+
     pub fn handle_paste_action(&self) {
         self.imp().content.handle_paste_action();
     }
@@ -5,13 +5,15 @@ mod row;
 mod verification_row;
 
 use adw::{prelude::*, subclass::prelude::*};
-use gtk::{gio, glib, glib::clone, CompositeTemplate};
+use gtk::{gio, glib, glib::clone, pango, CompositeTemplate};
+use ruma::RoomId;
 use tracing::error;
 
 use self::{
     category_row::CategoryRow, entry_row::EntryRow, room_row::RoomRow, row::Row,
     verification_row::VerificationRow,
 };
+use super::SpaceHierarchyDialog;
 use crate::{
     components::Avatar,
     prelude::*,
@@ -47,6 +49,12 @@ mod imp {
         pub room_row_menu: TemplateChild<gio::MenuModel>,
         #[template_child]
         pub offline_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub space_switcher_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub space_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub space_bar: TemplateChild<gtk::Revealer>,
         pub room_row_popover: OnceCell<gtk::PopoverMenu>,
         pub user: RefCell<Option<User>>,
         /// The type of the source that activated drop mode.
@@ -71,6 +79,25 @@ mod imp {
             Avatar::static_type();
             Self::bind_template(klass);
             klass.set_css_name("sidebar");
+
+            klass.install_action("sidebar.set-space", Some("s"), move |obj, _, v| {
+                if let Some(room_id) = v
+                    .and_then(String::from_variant)
+                    .and_then(|s| RoomId::parse(s).ok())
+                {
+                    obj.set_space_by_id(&room_id);
+                }
+            });
+
+            klass.install_action("sidebar.show-all-rooms", None, move |obj, _, _| {
+                if let Some(list_model) = obj.list_model() {
+                    list_model.item_list().set_space(None);
+                }
+            });
+
+            klass.install_action("sidebar.browse-space", None, move |obj, _, _| {
+                obj.show_space_hierarchy_dialog();
+            });
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -263,9 +290,31 @@ impl Sidebar {
                     .sync_create()
                     .bidirectional()
                     .build(),
+                list_model
+                    .item_list()
+                    .spaces()
+                    .bind_property("empty", &*imp.space_switcher_button, "visible")
+                    .sync_create()
+                    .invert_boolean()
+                    .build(),
+                list_model
+                    .item_list()
+                    .bind_property("space", &*imp.space_bar, "reveal-child")
+                    .sync_create()
+                    .transform_to(|_, space: Option<Room>| Some(space.is_some()))
+                    .build(),
             ];
 
             imp.bindings.replace(bindings);
+
+            imp.space_list
+                .bind_model(Some(list_model.item_list().spaces()), |item| {
+                    let space = item.downcast_ref::<Room>().unwrap();
+                    create_space_row(space).upcast()
+                });
+        } else {
+            imp.space_list
+                .bind_model(None::<&gio::ListModel>, |_| unreachable!());
         }
 
         imp.listview
@@ -358,4 +407,54 @@ impl Sidebar {
     fn parent_window(&self) -> Option<Window> {
         self.root().and_downcast()
     }
+
+    /// Only show the rooms of the space with the given ID.
+    fn set_space_by_id(&self, room_id: &RoomId) {
+        let Some(list_model) = self.list_model() else {
+            return;
+        };
+        let item_list = list_model.item_list();
+
+        if let Some(space) = item_list.room_list().get(room_id) {
+            item_list.set_space(Some(space));
+        }
+
+        self.imp().space_switcher_button.popdown();
+    }
+
+    /// Show the dialog to browse the hierarchy of the current space.
+    fn show_space_hierarchy_dialog(&self) {
+        let Some(space) = self.list_model().and_then(|m| m.item_list().space()) else {
+            return;
+        };
+
+        let dialog = SpaceHierarchyDialog::new(self.parent_window().as_ref(), &space);
+        dialog.present();
+    }
+}
+
+/// Create a row to select the given space in the space switcher.
+fn create_space_row(space: &Room) -> gtk::ListBoxRow {
+    let avatar = Avatar::new();
+    avatar.set_size(24);
+    avatar.set_data(Some(space.avatar_data().clone()));
+
+    let label = gtk::Label::builder()
+        .xalign(0.0)
+        .ellipsize(pango::EllipsizeMode::End)
+        .build();
+    space
+        .bind_property("display-name", &label, "label")
+        .sync_create()
+        .build();
+
+    let child = gtk::Box::new(gtk::Orientation::Horizontal, 12);
+    child.append(&avatar);
+    child.append(&label);
+
+    gtk::ListBoxRow::builder()
+        .child(&child)
+        .action_name("sidebar.set-space")
+        .action_target(&space.room_id().as_str().to_variant())
+        .build()
 }
@@ -5,7 +5,7 @@ use gtk::{gdk, glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTem
 use super::Row;
 use crate::{
     components::{ContextMenuBin, ContextMenuBinExt, ContextMenuBinImpl},
-    session::model::{HighlightFlags, Room, RoomType},
+    session::model::{HighlightFlags, NotificationsRoomSetting, Room, RoomType},
     spawn, toast,
 };
 
@@ -23,6 +23,7 @@ mod imp {
         pub room: RefCell<Option<Room>>,
         pub binding: RefCell<Option<glib::Binding>>,
         pub signal_handler: RefCell<Option<SignalHandlerId>>,
+        pub notifications_setting_handler: RefCell<Option<SignalHandlerId>>,
         #[template_child]
         pub display_name: TemplateChild<gtk::Label>,
         #[template_child]
@@ -52,6 +53,12 @@ mod imp {
                 }));
             });
 
+            klass.install_action("room-row.mark-as-read", None, move |obj, _, _| {
+                spawn!(clone!(@weak obj => async move {
+                    obj.mark_as_read().await;
+                }));
+            });
+
             klass.install_action("room-row.set-favorite", None, move |obj, _, _| {
                 spawn!(clone!(@weak obj => async move {
                     obj.set_category(RoomType::Favorite).await
@@ -88,6 +95,9 @@ mod imp {
                     obj.forget().await
                 }));
             });
+
+            klass
+                .install_property_action("room-row.notifications-setting", "notifications-setting");
         }
 
         fn instance_init(obj: &InitializingObject<Self>) {
@@ -98,9 +108,14 @@ mod imp {
     impl ObjectImpl for RoomRow {
         fn properties() -> &'static [glib::ParamSpec] {
             static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
-                vec![glib::ParamSpecObject::builder::<Room>("room")
-                    .explicit_notify()
-                    .build()]
+                vec![
+                    glib::ParamSpecObject::builder::<Room>("room")
+                        .explicit_notify()
+                        .build(),
+                    glib::ParamSpecString::builder("notifications-setting")
+                        .explicit_notify()
+                        .build(),
+                ]
             });
 
             PROPERTIES.as_ref()
@@ -109,6 +124,9 @@ mod imp {
         fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
             match pspec.name() {
                 "room" => self.obj().set_room(value.get().unwrap()),
+                "notifications-setting" => self
+                    .obj()
+                    .sync_notifications_setting(value.get::<Option<&str>>().unwrap()),
                 _ => unimplemented!(),
             }
         }
@@ -116,6 +134,7 @@ mod imp {
         fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
             match pspec.name() {
                 "room" => self.obj().room().to_value(),
+                "notifications-setting" => self.obj().notifications_setting().to_value(),
                 _ => unimplemented!(),
             }
         }
@@ -147,6 +166,9 @@ mod imp {
                 if let Some(id) = self.signal_handler.take() {
                     room.disconnect(id);
                 }
+                if let Some(id) = self.notifications_setting_handler.take() {
+                    room.disconnect(id);
+                }
             }
         }
     }
@@ -197,6 +219,9 @@ impl RoomRow {
             if let Some(id) = imp.signal_handler.take() {
                 room.disconnect(id);
             }
+            if let Some(id) = imp.notifications_setting_handler.take() {
+                room.disconnect(id);
+            }
             if let Some(binding) = imp.binding.take() {
                 binding.unbind();
             }
@@ -221,6 +246,13 @@ impl RoomRow {
                         obj.update_highlight();
                 }),
             )));
+            imp.notifications_setting_handler
+                .replace(Some(room.connect_notify_local(
+                    Some("notifications-setting"),
+                    clone!(@weak self as obj => move |_, _| {
+                        obj.notify("notifications-setting");
+                    }),
+                )));
 
             if room.category() == RoomType::Left {
                 imp.display_name.add_css_class("dim-label");
@@ -231,6 +263,41 @@ impl RoomRow {
         self.update_highlight();
         self.update_actions();
         self.notify("room");
+        self.notify("notifications-setting");
+    }
+
+    /// The notifications setting of the room, as a string.
+    pub fn notifications_setting(&self) -> &'static str {
+        self.room()
+            .map(|room| room.notifications_setting().as_str())
+            .unwrap_or_default()
+    }
+
+    /// Change the notifications setting of the room on the homeserver.
+    fn sync_notifications_setting(&self, setting: Option<&str>) {
+        let Some(room) = self.room() else {
+            return;
+        };
+        let Some(setting) = setting.and_then(|s| NotificationsRoomSetting::try_from(s).ok()) else {
+            return;
+        };
+
+        if room.notifications_setting() == setting {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj, @weak room => async move {
+            if room.sync_notifications_setting(setting).await.is_err() {
+                toast!(
+                    obj,
+                    gettext(
+                        // Translators: Do NOT translate the content between '{' and '}', this is a variable name.
+                        "Failed to change the notifications setting of {room}.",
+                    ),
+                    @room,
+                );
+            }
+        }));
     }
 
     fn update_highlight(&self) {
@@ -262,6 +329,7 @@ impl RoomRow {
                 RoomType::Invited => {
                     self.action_set_enabled("room-row.accept-invite", true);
                     self.action_set_enabled("room-row.reject-invite", true);
+                    self.action_set_enabled("room-row.mark-as-read", false);
                     self.action_set_enabled("room-row.set-favorite", false);
                     self.action_set_enabled("room-row.set-normal", false);
                     self.action_set_enabled("room-row.set-lowpriority", false);
@@ -269,11 +337,13 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", false);
                     self.action_set_enabled("room-row.forget", false);
                     self.action_set_enabled("room-row.set-direct", false);
+                    self.action_set_enabled("room-row.notifications-setting", false);
                     return;
                 }
                 RoomType::Favorite => {
                     self.action_set_enabled("room-row.accept-invite", false);
                     self.action_set_enabled("room-row.reject-invite", false);
+                    self.action_set_enabled("room-row.mark-as-read", true);
                     self.action_set_enabled("room-row.set-favorite", false);
                     self.action_set_enabled("room-row.set-normal", true);
                     self.action_set_enabled("room-row.set-lowpriority", true);
@@ -281,11 +351,13 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", false);
                     self.action_set_enabled("room-row.forget", false);
                     self.action_set_enabled("room-row.set-direct", true);
+                    self.action_set_enabled("room-row.notifications-setting", true);
                     return;
                 }
                 RoomType::Normal => {
                     self.action_set_enabled("room-row.accept-invite", false);
                     self.action_set_enabled("room-row.reject-invite", false);
+                    self.action_set_enabled("room-row.mark-as-read", true);
                     self.action_set_enabled("room-row.set-favorite", true);
                     self.action_set_enabled("room-row.set-normal", false);
                     self.action_set_enabled("room-row.set-lowpriority", true);
@@ -293,11 +365,13 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", false);
                     self.action_set_enabled("room-row.forget", false);
                     self.action_set_enabled("room-row.set-direct", true);
+                    self.action_set_enabled("room-row.notifications-setting", true);
                     return;
                 }
                 RoomType::LowPriority => {
                     self.action_set_enabled("room-row.accept-invite", false);
                     self.action_set_enabled("room-row.reject-invite", false);
+                    self.action_set_enabled("room-row.mark-as-read", true);
                     self.action_set_enabled("room-row.set-favorite", true);
                     self.action_set_enabled("room-row.set-normal", true);
                     self.action_set_enabled("room-row.set-lowpriority", false);
@@ -305,11 +379,13 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", false);
                     self.action_set_enabled("room-row.forget", false);
                     self.action_set_enabled("room-row.set-direct", true);
+                    self.action_set_enabled("room-row.notifications-setting", true);
                     return;
                 }
                 RoomType::Left => {
                     self.action_set_enabled("room-row.accept-invite", false);
                     self.action_set_enabled("room-row.reject-invite", false);
+                    self.action_set_enabled("room-row.mark-as-read", false);
                     self.action_set_enabled("room-row.set-favorite", false);
                     self.action_set_enabled("room-row.set-normal", false);
                     self.action_set_enabled("room-row.set-lowpriority", false);
@@ -317,6 +393,7 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", true);
                     self.action_set_enabled("room-row.forget", true);
                     self.action_set_enabled("room-row.set-direct", false);
+                    self.action_set_enabled("room-row.notifications-setting", false);
                     return;
                 }
                 RoomType::Outdated => {}
@@ -324,6 +401,7 @@ impl RoomRow {
                 RoomType::Direct => {
                     self.action_set_enabled("room-row.accept-invite", false);
                     self.action_set_enabled("room-row.reject-invite", false);
+                    self.action_set_enabled("room-row.mark-as-read", true);
                     self.action_set_enabled("room-row.set-favorite", true);
                     self.action_set_enabled("room-row.set-normal", true);
                     self.action_set_enabled("room-row.set-lowpriority", true);
@@ -331,6 +409,7 @@ impl RoomRow {
                     self.action_set_enabled("room-row.join", false);
                     self.action_set_enabled("room-row.forget", false);
                     self.action_set_enabled("room-row.set-direct", false);
+                    self.action_set_enabled("room-row.notifications-setting", true);
                     return;
                 }
             }
@@ -338,6 +417,7 @@ impl RoomRow {
 
         self.action_set_enabled("room-row.accept-invite", false);
         self.action_set_enabled("room-row.reject-invite", false);
+        self.action_set_enabled("room-row.mark-as-read", false);
         self.action_set_enabled("room-row.set-favorite", false);
         self.action_set_enabled("room-row.set-normal", false);
         self.action_set_enabled("room-row.set-lowpriority", false);
@@ -345,6 +425,7 @@ impl RoomRow {
         self.action_set_enabled("room-row.join", false);
         self.action_set_enabled("room-row.forget", false);
         self.action_set_enabled("room-row.set-direct", false);
+        self.action_set_enabled("room-row.notifications-setting", false);
     }
 
     fn drag_prepare(&self, drag: &gtk::DragSource, x: f64, y: f64) -> Option<gdk::ContentProvider> {
@@ -369,6 +450,22 @@ impl RoomRow {
         row.remove_css_class("drag");
     }
 
+    /// Mark all the messages of the room as read.
+    async fn mark_as_read(&self) {
+        let Some(room) = self.room() else {
+            return;
+        };
+
+        if room.timeline().mark_as_read().await.is_err() {
+            toast!(
+                self,
+                // Translators: Do NOT translate the content between '{' and '}', this is a variable name.
+                gettext("Failed to mark {room} as read."),
+                @room,
+            );
+        }
+    }
+
     async fn set_room_as_normal_or_direct(&self) {
         let Some(room) = self.room() else {
             return;
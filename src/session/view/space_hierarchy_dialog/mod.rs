@@ -0,0 +1,420 @@
+mod space_child;
+mod space_child_row;
+
+use adw::{prelude::*, subclass::prelude::*};
+use gettextrs::gettext;
+use gtk::{gdk, gio, glib, glib::clone, CompositeTemplate};
+use ruma::{
+    api::client::space::get_hierarchy,
+    assign,
+    events::{room::power_levels::PowerLevelAction, StateEventType},
+    OwnedRoomId, RoomId,
+};
+use tracing::error;
+
+use self::{space_child::SpaceChild, space_child_row::SpaceChildRow};
+use crate::{components::Spinner, session::model::Room, spawn, spawn_tokio, toast};
+
+/// The loading state of the hierarchy of a space.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HierarchyState {
+    /// More rooms can be loaded.
+    #[default]
+    Ready,
+    /// Rooms are being loaded.
+    Loading,
+    /// An error occurred while loading the rooms.
+    Error,
+    /// All the rooms were loaded.
+    Complete,
+}
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::{object::WeakRef, signal::SignalHandlerId, subclass::InitializingObject};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/space_hierarchy_dialog/mod.ui")]
+    pub struct SpaceHierarchyDialog {
+        /// The space to browse.
+        pub space: WeakRef<Room>,
+        /// The rooms in the hierarchy of the space.
+        pub list: gio::ListStore,
+        /// The token to load the next batch of rooms.
+        pub next_batch: RefCell<Option<String>>,
+        /// The number of the current load of the hierarchy.
+        ///
+        /// Used to ignore responses to outdated requests.
+        pub generation: Cell<u32>,
+        pub state: Cell<HierarchyState>,
+        pub children_handler: RefCell<Option<SignalHandlerId>>,
+        #[template_child]
+        pub add_button: TemplateChild<gtk::MenuButton>,
+        #[template_child]
+        pub add_popover: TemplateChild<gtk::Popover>,
+        #[template_child]
+        pub add_room_list: TemplateChild<gtk::ListBox>,
+        #[template_child]
+        pub stack: TemplateChild<gtk::Stack>,
+        #[template_child]
+        pub spinner: TemplateChild<Spinner>,
+        #[template_child]
+        pub empty_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub error_page: TemplateChild<adw::StatusPage>,
+        #[template_child]
+        pub scrolled_window: TemplateChild<gtk::ScrolledWindow>,
+        #[template_child]
+        pub listview: TemplateChild<gtk::ListView>,
+    }
+
+    impl Default for SpaceHierarchyDialog {
+        fn default() -> Self {
+            Self {
+                space: Default::default(),
+                list: gio::ListStore::new::<SpaceChild>(),
+                next_batch: Default::default(),
+                generation: Default::default(),
+                state: Default::default(),
+                children_handler: Default::default(),
+                add_button: Default::default(),
+                add_popover: Default::default(),
+                add_room_list: Default::default(),
+                stack: Default::default(),
+                spinner: Default::default(),
+                empty_page: Default::default(),
+                error_page: Default::default(),
+                scrolled_window: Default::default(),
+                listview: Default::default(),
+            }
+        }
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceHierarchyDialog {
+        const NAME: &'static str = "SpaceHierarchyDialog";
+        type Type = super::SpaceHierarchyDialog;
+        type ParentType = adw::Window;
+
+        fn class_init(klass: &mut Self::Class) {
+            SpaceChildRow::static_type();
+            Self::bind_template(klass);
+
+            klass.add_binding(
+                gdk::Key::Escape,
+                gdk::ModifierType::empty(),
+                |obj, _| {
+                    obj.close();
+                    true
+                },
+                None,
+            );
+
+            klass.install_action("space-hierarchy.add-child", Some("s"), |obj, _, v| {
+                if let Some(room_id) = v
+                    .and_then(String::from_variant)
+                    .and_then(|s| RoomId::parse(s).ok())
+                {
+                    obj.add_child(room_id);
+                }
+            });
+
+            klass.install_action("space-hierarchy.remove-child", Some("s"), |obj, _, v| {
+                if let Some(room_id) = v
+                    .and_then(String::from_variant)
+                    .and_then(|s| RoomId::parse(s).ok())
+                {
+                    obj.remove_child(room_id);
+                }
+            });
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SpaceHierarchyDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<Room>("space")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "space" => self.space.set(value.get().ok().as_ref()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "space" => self.obj().space().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+
+            let Some(space) = obj.space() else {
+                error!("Cannot browse the hierarchy without a space");
+                return;
+            };
+
+            space
+                .bind_property("display-name", &*obj, "title")
+                .sync_create()
+                .build();
+
+            // Only show the button to add rooms if the user is allowed to.
+            space
+                .own_user_is_allowed_to_expr(PowerLevelAction::SendState(
+                    StateEventType::SpaceChild,
+                ))
+                .bind(&*self.add_button, "visible", gtk::Widget::NONE);
+
+            self.add_popover.connect_show(clone!(@weak obj => move |_| {
+                obj.update_add_room_list();
+            }));
+
+            let children_handler =
+                space.connect_space_children_changed(clone!(@weak obj => move |_| {
+                    obj.reload();
+                }));
+            self.children_handler.replace(Some(children_handler));
+
+            self.list
+                .connect_items_changed(clone!(@weak obj => move |_, _, _, _| {
+                    obj.update_visible_child();
+                }));
+            self.listview
+                .set_model(Some(&gtk::NoSelection::new(Some(self.list.clone()))));
+
+            let adj = self.scrolled_window.vadjustment();
+            adj.connect_value_changed(clone!(@weak obj => move |adj| {
+                if adj.upper() - adj.value() < adj.page_size() * 2.0 {
+                    obj.load_more();
+                }
+            }));
+
+            obj.reload();
+        }
+
+        fn dispose(&self) {
+            if let Some(space) = self.space.upgrade() {
+                if let Some(handler_id) = self.children_handler.take() {
+                    space.disconnect(handler_id);
+                }
+            }
+        }
+    }
+
+    impl WidgetImpl for SpaceHierarchyDialog {}
+    impl WindowImpl for SpaceHierarchyDialog {}
+    impl AdwWindowImpl for SpaceHierarchyDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to browse the rooms in a space and to edit its children.
+    pub struct SpaceHierarchyDialog(ObjectSubclass<imp::SpaceHierarchyDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, @implements gtk::Accessible;
+}
+
+impl SpaceHierarchyDialog {
+    pub fn new(parent_window: Option<&impl IsA<gtk::Window>>, space: &Room) -> Self {
+        glib::Object::builder()
+            .property("transient-for", parent_window)
+            .property("space", space)
+            .build()
+    }
+
+    /// The space to browse.
+    pub fn space(&self) -> Option<Room> {
+        self.imp().space.upgrade()
+    }
+
+    /// The loading state of the hierarchy.
+    fn state(&self) -> HierarchyState {
+        self.imp().state.get()
+    }
+
+    /// Set the loading state of the hierarchy.
+    fn set_state(&self, state: HierarchyState) {
+        self.imp().state.set(state);
+        self.update_visible_child();
+    }
+
+    /// Update the visible child according to the loading state.
+    fn update_visible_child(&self) {
+        let imp = self.imp();
+
+        if imp.list.n_items() > 0 {
+            imp.stack.set_visible_child(&*imp.scrolled_window);
+            return;
+        }
+
+        match self.state() {
+            HierarchyState::Ready | HierarchyState::Loading => {
+                imp.stack.set_visible_child(&*imp.spinner)
+            }
+            HierarchyState::Error => imp.stack.set_visible_child(&*imp.error_page),
+            HierarchyState::Complete => imp.stack.set_visible_child(&*imp.empty_page),
+        }
+    }
+
+    /// Load the hierarchy of the space from the start.
+    fn reload(&self) {
+        let imp = self.imp();
+
+        imp.generation.set(imp.generation.get().wrapping_add(1));
+        imp.next_batch.take();
+        imp.list.remove_all();
+
+        self.set_state(HierarchyState::Ready);
+        self.load_more();
+    }
+
+    /// Load the next batch of rooms in the hierarchy.
+    fn load_more(&self) {
+        if self.state() != HierarchyState::Ready {
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.load_more_inner().await;
+        }));
+    }
+
+    async fn load_more_inner(&self) {
+        let Some(space) = self.space() else {
+            return;
+        };
+        let imp = self.imp();
+
+        self.set_state(HierarchyState::Loading);
+
+        let generation = imp.generation.get();
+        let space_id = space.room_id().to_owned();
+        let request = assign!(get_hierarchy::v1::Request::new(space_id.clone()), {
+            from: imp.next_batch.borrow().clone(),
+        });
+
+        let client = space.session().client();
+        let handle = spawn_tokio!(async move { client.send(request, None).await });
+        let result = handle.await.unwrap();
+
+        if imp.generation.get() != generation {
+            // The hierarchy was reloaded in the meantime.
+            return;
+        }
+
+        match result {
+            Ok(response) => {
+                let session = space.session();
+                let room_list = session.room_list();
+                let own_user_id = session.user().unwrap().user_id();
+                let can_edit = space.power_levels().member_is_allowed_to(
+                    &own_user_id,
+                    PowerLevelAction::SendState(StateEventType::SpaceChild),
+                );
+                let direct_children = space.space_children();
+
+                let children = response
+                    .rooms
+                    .into_iter()
+                    // The space itself is part of the response.
+                    .filter(|chunk| chunk.room_id != space_id)
+                    .map(|chunk| {
+                        let can_remove = can_edit && direct_children.contains(&chunk.room_id);
+                        SpaceChild::new(room_list, chunk, can_remove)
+                    })
+                    .collect::<Vec<_>>();
+                imp.list.extend_from_slice(&children);
+
+                let has_more = response.next_batch.is_some();
+                imp.next_batch.replace(response.next_batch);
+
+                if has_more {
+                    self.set_state(HierarchyState::Ready);
+                } else {
+                    self.set_state(HierarchyState::Complete);
+                }
+            }
+            Err(error) => {
+                error!("Failed to load the hierarchy of the space: {error}");
+                self.set_state(HierarchyState::Error);
+            }
+        }
+    }
+
+    /// Update the list of rooms that can be added to the space.
+    fn update_add_room_list(&self) {
+        let Some(space) = self.space() else {
+            return;
+        };
+        let add_room_list = &self.imp().add_room_list;
+
+        while let Some(child) = add_room_list.first_child() {
+            add_room_list.remove(&child);
+        }
+
+        let children = space.space_children();
+        let mut rooms = space
+            .session()
+            .room_list()
+            .iter::<Room>()
+            .filter_map(Result::ok)
+            .filter(|room| room.is_joined() && room != &space && !children.contains(room.room_id()))
+            .collect::<Vec<_>>();
+        rooms.sort_by_key(|room| room.display_name().to_lowercase());
+
+        for room in rooms {
+            let row = adw::ActionRow::builder()
+                .title(room.display_name())
+                .use_markup(false)
+                .activatable(true)
+                .action_name("space-hierarchy.add-child")
+                .action_target(&room.room_id().as_str().to_variant())
+                .build();
+            add_room_list.append(&row);
+        }
+    }
+
+    /// Add the room with the given ID to the children of the space.
+    fn add_child(&self, room_id: OwnedRoomId) {
+        let Some(space) = self.space() else {
+            return;
+        };
+        self.imp().add_popover.popdown();
+
+        spawn!(clone!(@weak self as obj, @weak space => async move {
+            if space.add_space_child(room_id).await.is_err() {
+                toast!(obj, gettext("Could not add the room to the space"));
+            }
+        }));
+    }
+
+    /// Remove the room with the given ID from the children of the space.
+    fn remove_child(&self, room_id: OwnedRoomId) {
+        let Some(space) = self.space() else {
+            return;
+        };
+
+        spawn!(clone!(@weak self as obj, @weak space => async move {
+            if space.remove_space_child(room_id).await.is_err() {
+                toast!(obj, gettext("Could not remove the room from the space"));
+            }
+        }));
+    }
+}
@@ -0,0 +1,178 @@
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*};
+use ruma::api::client::space::SpaceHierarchyRoomsChunk;
+
+use crate::session::model::{AvatarData, AvatarImage, AvatarUriSource, Room, RoomList};
+
+mod imp {
+    use std::cell::{Cell, RefCell};
+
+    use glib::signal::SignalHandlerId;
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default)]
+    pub struct SpaceChild {
+        /// The list of rooms in this session.
+        pub room_list: OnceCell<RoomList>,
+        /// The data of the room from the space hierarchy.
+        pub chunk: OnceCell<SpaceHierarchyRoomsChunk>,
+        pub avatar_data: OnceCell<AvatarData>,
+        /// The `Room` object for this room, if the user is a member of it.
+        pub room: RefCell<Option<Room>>,
+        /// Whether the user asked to join this room.
+        pub is_pending: Cell<bool>,
+        /// Whether this room can be removed from the space.
+        pub can_remove: Cell<bool>,
+        pub room_handler: RefCell<Option<SignalHandlerId>>,
+        pub pending_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceChild {
+        const NAME: &'static str = "SpaceHierarchyChild";
+        type Type = super::SpaceChild;
+    }
+
+    impl ObjectImpl for SpaceChild {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<Room>("room")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecBoolean::builder("pending")
+                        .read_only()
+                        .build(),
+                    glib::ParamSpecObject::builder::<AvatarData>("avatar-data")
+                        .read_only()
+                        .build(),
+                ]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "room" => obj.room().to_value(),
+                "pending" => obj.is_pending().to_value(),
+                "avatar-data" => obj.avatar_data().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn dispose(&self) {
+            let Some(room_list) = self.room_list.get() else {
+                return;
+            };
+
+            if let Some(handler_id) = self.room_handler.take() {
+                room_list.disconnect(handler_id);
+            }
+            if let Some(handler_id) = self.pending_handler.take() {
+                room_list.disconnect(handler_id);
+            }
+        }
+    }
+}
+
+glib::wrapper! {
+    /// A room in the hierarchy of a space.
+    pub struct SpaceChild(ObjectSubclass<imp::SpaceChild>);
+}
+
+impl SpaceChild {
+    pub fn new(room_list: &RoomList, chunk: SpaceHierarchyRoomsChunk, can_remove: bool) -> Self {
+        let obj = glib::Object::new::<Self>();
+        let imp = obj.imp();
+
+        let avatar_data = AvatarData::new(AvatarImage::new(
+            &room_list.session(),
+            None,
+            AvatarUriSource::Room,
+        ));
+        avatar_data.set_display_name(chunk.name.clone());
+        avatar_data.image().set_uri(chunk.avatar_url.clone());
+        imp.avatar_data.set(avatar_data).unwrap();
+
+        imp.can_remove.set(can_remove);
+        imp.is_pending
+            .set(room_list.is_pending_room((*chunk.room_id).into()));
+        imp.room.replace(room_list.get(&chunk.room_id));
+
+        let room_id = chunk.room_id.clone();
+        let room_handler = room_list.connect_items_changed(
+            clone!(@weak obj, @strong room_id => move |room_list, _, _, _| {
+                obj.set_room(room_list.get(&room_id));
+            }),
+        );
+        imp.room_handler.replace(Some(room_handler));
+
+        let pending_handler =
+            room_list.connect_pending_rooms_changed(clone!(@weak obj => move |room_list| {
+                obj.set_pending(room_list.is_pending_room((*room_id).into()));
+            }));
+        imp.pending_handler.replace(Some(pending_handler));
+
+        imp.room_list.set(room_list.clone()).unwrap();
+        imp.chunk.set(chunk).unwrap();
+
+        obj
+    }
+
+    /// The list of rooms in this session.
+    pub fn room_list(&self) -> &RoomList {
+        self.imp().room_list.get().unwrap()
+    }
+
+    /// The data of the room from the space hierarchy.
+    pub fn chunk(&self) -> &SpaceHierarchyRoomsChunk {
+        self.imp().chunk.get().unwrap()
+    }
+
+    /// The [`AvatarData`] of this room.
+    pub fn avatar_data(&self) -> &AvatarData {
+        self.imp().avatar_data.get().unwrap()
+    }
+
+    /// The `Room` object for this room, if the user is a member of it.
+    pub fn room(&self) -> Option<Room> {
+        self.imp().room.borrow().clone()
+    }
+
+    /// Set the `Room` object for this room.
+    fn set_room(&self, room: Option<Room>) {
+        if self.room() == room {
+            return;
+        }
+
+        self.imp().room.replace(room);
+        self.notify("room");
+    }
+
+    /// Whether the user asked to join this room.
+    pub fn is_pending(&self) -> bool {
+        self.imp().is_pending.get()
+    }
+
+    /// Set whether the user asked to join this room.
+    fn set_pending(&self, is_pending: bool) {
+        if self.is_pending() == is_pending {
+            return;
+        }
+
+        self.imp().is_pending.set(is_pending);
+        self.notify("pending");
+    }
+
+    /// Whether this room can be removed from the space.
+    ///
+    /// This is only possible for the direct children of the space, if our
+    /// own user is allowed to edit them.
+    pub fn can_remove(&self) -> bool {
+        self.imp().can_remove.get()
+    }
+}
@@ -0,0 +1,226 @@
+use adw::subclass::prelude::BinImpl;
+use gettextrs::gettext;
+use gtk::{glib, glib::clone, prelude::*, subclass::prelude::*, CompositeTemplate};
+
+use super::SpaceChild;
+use crate::{
+    components::{Avatar, SpinnerButton},
+    ngettext_f, spawn, toast, Window,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{signal::SignalHandlerId, subclass::InitializingObject};
+    use once_cell::sync::Lazy;
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(
+        resource = "/org/gnome/Fractal/ui/session/view/space_hierarchy_dialog/space_child_row.ui"
+    )]
+    pub struct SpaceChildRow {
+        pub space_child: RefCell<Option<SpaceChild>>,
+        #[template_child]
+        pub avatar: TemplateChild<Avatar>,
+        #[template_child]
+        pub display_name: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub description: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub members_count: TemplateChild<gtk::Label>,
+        #[template_child]
+        pub button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub remove_button: TemplateChild<gtk::Button>,
+        pub pending_handler: RefCell<Option<SignalHandlerId>>,
+        pub room_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for SpaceChildRow {
+        const NAME: &'static str = "SpaceHierarchyChildRow";
+        type Type = super::SpaceChildRow;
+        type ParentType = adw::Bin;
+
+        fn class_init(klass: &mut Self::Class) {
+            Self::bind_template(klass);
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for SpaceChildRow {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<SpaceChild>("space-child")
+                    .explicit_notify()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "space-child" => self.obj().set_space_child(value.get().unwrap()),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "space-child" => self.obj().space_child().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.button
+                .connect_clicked(clone!(@weak self as imp => move |_| {
+                    imp.obj().join_or_view();
+                }));
+        }
+
+        fn dispose(&self) {
+            self.obj().disconnect_space_child();
+        }
+    }
+
+    impl WidgetImpl for SpaceChildRow {}
+    impl BinImpl for SpaceChildRow {}
+}
+
+glib::wrapper! {
+    /// A row presenting a room in the hierarchy of a space.
+    pub struct SpaceChildRow(ObjectSubclass<imp::SpaceChildRow>)
+        @extends gtk::Widget, adw::Bin, @implements gtk::Accessible;
+}
+
+impl SpaceChildRow {
+    /// The room presented by this row.
+    pub fn space_child(&self) -> Option<SpaceChild> {
+        self.imp().space_child.borrow().clone()
+    }
+
+    /// Set the room presented by this row.
+    pub fn set_space_child(&self, space_child: Option<SpaceChild>) {
+        if self.space_child() == space_child {
+            return;
+        }
+        let imp = self.imp();
+
+        self.disconnect_space_child();
+
+        if let Some(space_child) = &space_child {
+            let chunk = space_child.chunk();
+
+            let display_name = chunk
+                .name
+                .clone()
+                .or_else(|| chunk.canonical_alias.as_ref().map(ToString::to_string))
+                .unwrap_or_else(|| chunk.room_id.to_string());
+            imp.display_name.set_text(&display_name);
+
+            imp.description
+                .set_text(chunk.topic.as_deref().unwrap_or_default());
+            imp.description.set_visible(chunk.topic.is_some());
+
+            let n = u32::try_from(chunk.num_joined_members).unwrap_or(u32::MAX);
+            imp.members_count.set_text(&ngettext_f(
+                // Translators: Do NOT translate the content between '{' and '}', this is a
+                // variable name.
+                "1 member",
+                "{n} members",
+                n,
+                &[("n", &n.to_string())],
+            ));
+
+            imp.remove_button.set_visible(space_child.can_remove());
+            imp.remove_button
+                .set_action_target_value(Some(&chunk.room_id.as_str().to_variant()));
+
+            let pending_handler = space_child.connect_notify_local(
+                Some("pending"),
+                clone!(@weak self as obj => move |space_child, _| {
+                    obj.update_button(space_child);
+                }),
+            );
+            imp.pending_handler.replace(Some(pending_handler));
+
+            let room_handler = space_child.connect_notify_local(
+                Some("room"),
+                clone!(@weak self as obj => move |space_child, _| {
+                    obj.update_button(space_child);
+                }),
+            );
+            imp.room_handler.replace(Some(room_handler));
+
+            self.update_button(space_child);
+        }
+
+        imp.avatar.set_data(
+            space_child
+                .as_ref()
+                .map(|child| child.avatar_data().clone()),
+        );
+        imp.space_child.replace(space_child);
+        self.notify("space-child");
+    }
+
+    /// Disconnect the handlers from the current room.
+    fn disconnect_space_child(&self) {
+        let imp = self.imp();
+
+        if let Some(space_child) = self.space_child() {
+            if let Some(handler_id) = imp.pending_handler.take() {
+                space_child.disconnect(handler_id);
+            }
+            if let Some(handler_id) = imp.room_handler.take() {
+                space_child.disconnect(handler_id);
+            }
+        }
+    }
+
+    fn update_button(&self, space_child: &SpaceChild) {
+        let button = &self.imp().button;
+        if space_child.room().is_some() {
+            button.set_label(&gettext("View"));
+        } else {
+            button.set_label(&gettext("Join"));
+        }
+
+        button.set_loading(space_child.is_pending());
+    }
+
+    /// Join or view the room.
+    pub fn join_or_view(&self) {
+        let Some(space_child) = self.space_child() else {
+            return;
+        };
+        let room_list = space_child.room_list();
+
+        if let Some(room) = space_child.room() {
+            let Some(dialog) = self.root().and_downcast::<gtk::Window>() else {
+                return;
+            };
+            if let Some(window) = dialog.transient_for().and_downcast::<Window>() {
+                let session = room_list.session();
+                window.show_room(session.session_id(), room.room_id());
+                dialog.close();
+            }
+        } else {
+            let room_id = space_child.chunk().room_id.clone();
+
+            spawn!(clone!(@weak self as obj, @weak room_list => async move {
+                if let Err(error) = room_list.join_by_id_or_alias(room_id.into(), vec![]).await {
+                    toast!(obj, error);
+                }
+            }));
+        }
+    }
+}
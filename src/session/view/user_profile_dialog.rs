@@ -0,0 +1,454 @@
+use adw::subclass::prelude::*;
+use gettextrs::gettext;
+use gtk::{gio, glib, glib::clone, prelude::*, CompositeTemplate};
+use matrix_sdk::encryption::{identities::Device as CryptoDevice, LocalTrust};
+use ruma::{DeviceId, OwnedDeviceId};
+use tracing::error;
+
+use crate::{
+    components::{Avatar, SpinnerButton, ToastableWindow},
+    prelude::*,
+    session::model::{User, VerificationState},
+    spawn, spawn_tokio, toast, Application,
+};
+
+mod imp {
+    use std::cell::RefCell;
+
+    use glib::{subclass::InitializingObject, SignalHandlerId};
+    use once_cell::{sync::Lazy, unsync::OnceCell};
+
+    use super::*;
+
+    #[derive(Debug, Default, CompositeTemplate)]
+    #[template(resource = "/org/gnome/Fractal/ui/session/view/user_profile_dialog.ui")]
+    pub struct UserProfileDialog {
+        /// The user displayed by this dialog.
+        pub user: OnceCell<User>,
+        #[template_child]
+        pub identity_changed_banner: TemplateChild<adw::Banner>,
+        #[template_child]
+        pub direct_chat_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub ignore_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub identity_row: TemplateChild<adw::ActionRow>,
+        #[template_child]
+        pub verify_button: TemplateChild<SpinnerButton>,
+        #[template_child]
+        pub devices_group: TemplateChild<adw::PreferencesGroup>,
+        /// The rows of the devices of the user.
+        pub device_rows: RefCell<Vec<adw::ActionRow>>,
+        /// The signal handlers connected to the user.
+        pub user_handlers: RefCell<Vec<SignalHandlerId>>,
+        /// The signal handler for the updates of the crypto identities.
+        pub identities_handler: RefCell<Option<SignalHandlerId>>,
+        /// The signal handler for the changes of the ignored users.
+        pub ignored_users_handler: RefCell<Option<SignalHandlerId>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for UserProfileDialog {
+        const NAME: &'static str = "UserProfileDialog";
+        type Type = super::UserProfileDialog;
+        type ParentType = ToastableWindow;
+
+        fn class_init(klass: &mut Self::Class) {
+            Avatar::static_type();
+            SpinnerButton::static_type();
+            Self::bind_template(klass);
+            Self::Type::bind_template_callbacks(klass);
+
+            klass.install_action(
+                "user-profile.trust-device",
+                Some("s"),
+                move |obj, _, param| {
+                    obj.set_device_trust_for_param(param, LocalTrust::Verified);
+                },
+            );
+
+            klass.install_action(
+                "user-profile.block-device",
+                Some("s"),
+                move |obj, _, param| {
+                    obj.set_device_trust_for_param(param, LocalTrust::BlackListed);
+                },
+            );
+
+            klass.install_action(
+                "user-profile.reset-device-trust",
+                Some("s"),
+                move |obj, _, param| {
+                    obj.set_device_trust_for_param(param, LocalTrust::Unset);
+                },
+            );
+        }
+
+        fn instance_init(obj: &InitializingObject<Self>) {
+            obj.init_template();
+        }
+    }
+
+    impl ObjectImpl for UserProfileDialog {
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: Lazy<Vec<glib::ParamSpec>> = Lazy::new(|| {
+                vec![glib::ParamSpecObject::builder::<User>("user")
+                    .construct_only()
+                    .build()]
+            });
+
+            PROPERTIES.as_ref()
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "user" => {
+                    let _ = self.user.set(value.get().unwrap());
+                }
+                _ => unimplemented!(),
+            }
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "user" => self.obj().user().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn constructed(&self) {
+            self.parent_constructed();
+            let obj = self.obj();
+            let user = obj.user();
+
+            let verified_handler = user.connect_notify_local(
+                Some("verified"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_identity();
+                }),
+            );
+            let identity_changed_handler = user.connect_notify_local(
+                Some("identity-changed"),
+                clone!(@weak obj => move |_, _| {
+                    obj.update_identity();
+                }),
+            );
+            self.user_handlers
+                .replace(vec![verified_handler, identity_changed_handler]);
+
+            let user_id = user.user_id();
+            let identities_handler = user.session().crypto_identities().connect_user_updated(
+                clone!(@weak obj => move |_, updated_user_id| {
+                    if updated_user_id == &*user_id {
+                        obj.load_devices();
+                    }
+                }),
+            );
+            self.identities_handler.replace(Some(identities_handler));
+
+            let ignored_users_handler =
+                user.session()
+                    .ignored_users()
+                    .connect_changed(clone!(@weak obj => move |_| {
+                        obj.update_ignored();
+                    }));
+            self.ignored_users_handler
+                .replace(Some(ignored_users_handler));
+
+            self.direct_chat_button.set_visible(!obj.is_own_user());
+            self.ignore_button.set_visible(!obj.is_own_user());
+            obj.update_identity();
+            obj.update_ignored();
+            obj.load_devices();
+        }
+
+        fn dispose(&self) {
+            let Some(user) = self.user.get() else {
+                return;
+            };
+
+            for handler in self.user_handlers.take() {
+                user.disconnect(handler);
+            }
+
+            if let Some(handler) = self.identities_handler.take() {
+                user.session().crypto_identities().disconnect(handler);
+            }
+
+            if let Some(handler) = self.ignored_users_handler.take() {
+                user.session().ignored_users().disconnect(handler);
+            }
+        }
+    }
+
+    impl WidgetImpl for UserProfileDialog {}
+    impl WindowImpl for UserProfileDialog {}
+    impl AdwWindowImpl for UserProfileDialog {}
+    impl ToastableWindowImpl for UserProfileDialog {}
+}
+
+glib::wrapper! {
+    /// Dialog to view the profile of a user and to manage the trust of their
+    /// encryption identity and sessions.
+    pub struct UserProfileDialog(ObjectSubclass<imp::UserProfileDialog>)
+        @extends gtk::Widget, gtk::Window, adw::Window, ToastableWindow, @implements gtk::Accessible;
+}
+
+#[gtk::template_callbacks]
+impl UserProfileDialog {
+    pub fn new(parent_window: Option<&gtk::Window>, user: &impl IsA<User>) -> Self {
+        glib::Object::builder()
+            .property("transient-for", parent_window)
+            .property("user", user.upcast_ref())
+            .build()
+    }
+
+    /// The user displayed by this dialog.
+    pub fn user(&self) -> &User {
+        self.imp().user.get().unwrap()
+    }
+
+    /// Whether the user is the user of the current session.
+    fn is_own_user(&self) -> bool {
+        let user = self.user();
+        user.session()
+            .user()
+            .is_some_and(|own_user| own_user.user_id() == user.user_id())
+    }
+
+    /// Update the state of the identity of the user.
+    fn update_identity(&self) {
+        let imp = self.imp();
+        let user = self.user();
+
+        if user.is_verified() {
+            imp.identity_row.set_subtitle(&gettext("Verified"));
+            imp.verify_button.set_visible(false);
+        } else {
+            imp.identity_row.set_subtitle(&gettext("Not verified"));
+            imp.verify_button.set_visible(!self.is_own_user());
+        }
+
+        imp.identity_changed_banner
+            .set_revealed(user.identity_changed());
+    }
+
+    /// Open the direct chat with the user, creating it if needed.
+    #[template_callback]
+    fn open_direct_chat(&self) {
+        let button = &self.imp().direct_chat_button;
+        if button.loading() {
+            return;
+        }
+        button.set_loading(true);
+
+        spawn!(clone!(@weak self as obj => async move {
+            let user = obj.user();
+            let result = match user.direct_chat() {
+                Some(room) => Ok(room),
+                None => user.create_direct_chat().await,
+            };
+
+            match result {
+                Ok(room) => {
+                    let window = Application::default().main_window();
+                    window.session_view().select_room(Some(room));
+                    window.present();
+                    obj.close();
+                }
+                Err(error) => {
+                    error!("Could not create direct chat: {error}");
+                    toast!(obj, gettext("Could not create a direct chat"));
+                    obj.imp().direct_chat_button.set_loading(false);
+                }
+            }
+        }));
+    }
+
+    /// Whether the user is ignored.
+    fn is_ignored(&self) -> bool {
+        let user = self.user();
+        user.session().ignored_users().contains(&user.user_id())
+    }
+
+    /// Update the button to ignore the user.
+    fn update_ignored(&self) {
+        let button = &self.imp().ignore_button;
+
+        if self.is_ignored() {
+            button.set_label(&gettext("Stop Ignoring"));
+            button.remove_css_class("destructive-action");
+        } else {
+            button.set_label(&gettext("Ignore"));
+            button.add_css_class("destructive-action");
+        }
+    }
+
+    /// Ignore the user or stop ignoring them.
+    #[template_callback]
+    fn toggle_ignored(&self) {
+        let button = &self.imp().ignore_button;
+        if button.loading() {
+            return;
+        }
+        button.set_loading(true);
+
+        spawn!(clone!(@weak self as obj => async move {
+            let user = obj.user();
+            let session = user.session();
+            let ignored_users = session.ignored_users();
+
+            if obj.is_ignored() {
+                if ignored_users.remove(&user.user_id()).await.is_err() {
+                    toast!(obj, gettext("Could not stop ignoring the user"));
+                }
+            } else if ignored_users.add(user.user_id()).await.is_err() {
+                toast!(obj, gettext("Could not ignore the user"));
+            }
+
+            obj.imp().ignore_button.set_loading(false);
+        }));
+    }
+
+    /// Start the verification of the user.
+    #[template_callback]
+    fn verify_user(&self) {
+        let button = &self.imp().verify_button;
+        if button.loading() {
+            return;
+        }
+        button.set_loading(true);
+
+        spawn!(clone!(@weak self as obj => async move {
+            let request = obj.user().verify_identity().await;
+
+            if request.state() == VerificationState::Error {
+                toast!(obj, gettext("Could not start the verification of this user"));
+            } else {
+                toast!(obj, gettext("Verification request sent"));
+            }
+
+            obj.imp().verify_button.set_loading(false);
+        }));
+    }
+
+    /// Stop warning about the identity change of the user.
+    #[template_callback]
+    fn dismiss_identity_change(&self) {
+        let user = self.user();
+        user.session()
+            .crypto_identities()
+            .dismiss_identity_change(&user.user_id());
+    }
+
+    /// Load the devices of the user.
+    fn load_devices(&self) {
+        spawn!(clone!(@weak self as obj => async move {
+            let devices = obj.user().crypto_devices().await;
+            obj.update_devices(devices);
+        }));
+    }
+
+    /// Replace the rows of the devices with the given devices.
+    fn update_devices(&self, devices: Vec<CryptoDevice>) {
+        let imp = self.imp();
+
+        for row in imp.device_rows.take() {
+            imp.devices_group.remove(&row);
+        }
+
+        let mut rows = Vec::with_capacity(devices.len());
+        for device in devices {
+            let row = device_row(&device);
+            imp.devices_group.add(&row);
+            rows.push(row);
+        }
+
+        imp.devices_group.set_visible(!rows.is_empty());
+        imp.device_rows.replace(rows);
+    }
+
+    fn set_device_trust_for_param(&self, param: Option<&glib::Variant>, trust: LocalTrust) {
+        let Some(device_id) = param.and_then(|variant| variant.get::<String>()) else {
+            return;
+        };
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.set_device_trust(device_id.into(), trust).await;
+        }));
+    }
+
+    /// Set the local trust of the device with the given ID.
+    async fn set_device_trust(&self, device_id: OwnedDeviceId, trust: LocalTrust) {
+        let user = self.user();
+        let encryption = user.session().client().encryption();
+        let user_id = user.user_id();
+
+        let handle = spawn_tokio!(async move {
+            let Some(device) = encryption.get_device(&user_id, &device_id).await? else {
+                return Ok(());
+            };
+            device.set_local_trust(trust).await
+        });
+
+        match handle.await.unwrap() {
+            Ok(()) => self.load_devices(),
+            Err(error) => {
+                error!("Could not change the trust of the device: {error}");
+                toast!(self, gettext("Could not change the trust of the session"));
+            }
+        }
+    }
+}
+
+/// Create a row for the given device.
+fn device_row(device: &CryptoDevice) -> adw::ActionRow {
+    let device_id: &DeviceId = device.device_id();
+    let title = device
+        .display_name()
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| device_id.to_string());
+
+    let (status, icon_name) = if device.is_blacklisted() {
+        (gettext("Blocked"), "action-unavailable-symbolic")
+    } else if device.is_verified() {
+        (gettext("Verified"), "emblem-ok-symbolic")
+    } else {
+        (gettext("Not verified"), "dialog-warning-symbolic")
+    };
+
+    let row = adw::ActionRow::builder()
+        .title(glib::markup_escape_text(&title))
+        .subtitle(glib::markup_escape_text(&format!("{device_id} · {status}")))
+        .build();
+    row.add_prefix(&gtk::Image::from_icon_name(icon_name));
+
+    let target = device_id.as_str().to_variant();
+    let menu = gio::Menu::new();
+    if device.is_locally_trusted() || device.is_blacklisted() {
+        let item = gio::MenuItem::new(Some(&gettext("Reset Trust")), None);
+        item.set_action_and_target_value(Some("user-profile.reset-device-trust"), Some(&target));
+        menu.append_item(&item);
+    }
+    if !device.is_verified() && !device.is_blacklisted() {
+        let item = gio::MenuItem::new(Some(&gettext("Trust")), None);
+        item.set_action_and_target_value(Some("user-profile.trust-device"), Some(&target));
+        menu.append_item(&item);
+    }
+    if !device.is_blacklisted() {
+        let item = gio::MenuItem::new(Some(&gettext("Block")), None);
+        item.set_action_and_target_value(Some("user-profile.block-device"), Some(&target));
+        menu.append_item(&item);
+    }
+
+    let button = gtk::MenuButton::builder()
+        .icon_name("view-more-symbolic")
+        .tooltip_text(gettext("Session Options"))
+        .valign(gtk::Align::Center)
+        .menu_model(&menu)
+        .build();
+    button.add_css_class("flat");
+    row.add_suffix(&button);
+
+    row
+}
@@ -9,7 +9,8 @@ use matrix_sdk::{config::RequestConfig, Client, ClientBuildError};
 use ruma::{
     events::{room::message::MessageType, AnyMessageLikeEventContent, AnySyncTimelineEvent},
     matrix_uri::MatrixId,
-    MatrixToUri, MatrixUri,
+    MatrixToUri, MatrixUri, OwnedEventId, OwnedRoomOrAliasId, OwnedServerName, OwnedUserId,
+    RoomAliasId, RoomId,
 };
 use thiserror::Error;
 
@@ -146,6 +147,10 @@ pub fn get_event_body(event: &AnySyncTimelineEvent, sender_name: &str) -> Option
             "{user} sent a sticker.",
             &[("user", sender_name)],
         )),
+        AnyMessageLikeEventContent::UnstablePollStart(_) => Some(gettext_f(
+            "{user} started a poll.",
+            &[("user", sender_name)],
+        )),
         _ => None,
     }
 }
@@ -352,42 +357,147 @@ pub fn extract_mentions(s: &str, room: &Room) -> (String, Vec<(Pill, String)>) {
 }
 
 /// Try to parse the given string to a Matrix URI and generate a pill for it.
-fn parse_pill(s: &str, room: &Room, session: &Session) -> Option<Pill> {
+pub fn parse_pill(s: &str, room: &Room, session: &Session) -> Option<Pill> {
     let uri = html_escape::decode_html_entities(s);
 
-    let id = if let Ok(mx_uri) = MatrixUri::parse(&uri) {
-        mx_uri.id().to_owned()
-    } else if let Ok(mx_to_uri) = MatrixToUri::parse(&uri) {
-        mx_to_uri.id().to_owned()
-    } else {
-        return None;
-    };
-
-    match id {
-        MatrixId::Room(room_id) => session
+    match MatrixIdUri::parse(&uri).ok()? {
+        MatrixIdUri::Room(room_uri) => session
             .room_list()
-            .get(&room_id)
+            .get_by_identifier((&*room_uri.id).into())
             .map(|room| Pill::for_room(&room)),
-        MatrixId::RoomAlias(room_alias) => {
-            // TODO: Handle non-canonical aliases.
-            session
-                .client()
-                .rooms()
-                .iter()
-                .find_map(|matrix_room| {
-                    matrix_room
-                        .canonical_alias()
-                        .filter(|alias| alias == &room_alias)
-                        .and_then(|_| session.room_list().get(matrix_room.room_id()))
-                })
-                .map(|room| Pill::for_room(&room))
-        }
-        MatrixId::User(user_id) => {
+        MatrixIdUri::User(user_id) => {
             // We should have a strong reference to the list wherever we show a user pill so
             // we can use `get_or_create_members()`.
             let user = room.get_or_create_members().get_or_create(user_id).upcast();
             Some(Pill::for_user(&user))
         }
-        _ => None,
+        MatrixIdUri::Event(_) => None,
+    }
+}
+
+/// A URI for a Matrix ID.
+///
+/// It can be parsed from a `matrix:` URI or a `https://matrix.to` URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixIdUri {
+    /// A room.
+    Room(MatrixRoomIdUri),
+    /// A user.
+    User(OwnedUserId),
+    /// An event.
+    Event(MatrixEventIdUri),
+}
+
+impl MatrixIdUri {
+    /// Constructs a `MatrixIdUri` from the given ID and servers list.
+    fn try_from_parts(
+        id: MatrixId,
+        via: &[OwnedServerName],
+    ) -> Result<Self, MatrixIdUriParseError> {
+        let uri = match id {
+            MatrixId::Room(room_id) => Self::Room(MatrixRoomIdUri {
+                id: room_id.into(),
+                via: via.to_owned(),
+            }),
+            MatrixId::RoomAlias(room_alias) => Self::Room(MatrixRoomIdUri {
+                id: room_alias.into(),
+                via: via.to_owned(),
+            }),
+            MatrixId::User(user_id) => Self::User(user_id),
+            MatrixId::Event(room_id, event_id) => Self::Event(MatrixEventIdUri {
+                event_id,
+                room_uri: MatrixRoomIdUri {
+                    id: room_id,
+                    via: via.to_owned(),
+                },
+            }),
+            _ => return Err(MatrixIdUriParseError::UnsupportedId(id)),
+        };
+
+        Ok(uri)
+    }
+
+    /// Try parsing a `&str` into a `MatrixIdUri`.
+    pub fn parse(s: &str) -> Result<Self, MatrixIdUriParseError> {
+        if let Ok(uri) = MatrixToUri::parse(s) {
+            return uri.try_into();
+        }
+
+        MatrixUri::parse(s)?.try_into()
+    }
+}
+
+impl TryFrom<&MatrixUri> for MatrixIdUri {
+    type Error = MatrixIdUriParseError;
+
+    fn try_from(uri: &MatrixUri) -> Result<Self, Self::Error> {
+        // The action is ignored, we let the user choose what to do with the ID.
+        Self::try_from_parts(uri.id().clone(), uri.via())
+    }
+}
+
+impl TryFrom<MatrixUri> for MatrixIdUri {
+    type Error = MatrixIdUriParseError;
+
+    fn try_from(uri: MatrixUri) -> Result<Self, Self::Error> {
+        Self::try_from(&uri)
+    }
+}
+
+impl TryFrom<&MatrixToUri> for MatrixIdUri {
+    type Error = MatrixIdUriParseError;
+
+    fn try_from(uri: &MatrixToUri) -> Result<Self, Self::Error> {
+        Self::try_from_parts(uri.id().clone(), uri.via())
+    }
+}
+
+impl TryFrom<MatrixToUri> for MatrixIdUri {
+    type Error = MatrixIdUriParseError;
+
+    fn try_from(uri: MatrixToUri) -> Result<Self, Self::Error> {
+        Self::try_from(&uri)
     }
 }
+
+/// A URI for a Matrix room ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixRoomIdUri {
+    /// The room ID or alias.
+    pub id: OwnedRoomOrAliasId,
+    /// Matrix servers usable to route a `RoomId`.
+    pub via: Vec<OwnedServerName>,
+}
+
+impl MatrixRoomIdUri {
+    /// Convert this URI to a `https://matrix.to` URI.
+    pub fn matrix_to_uri(&self) -> MatrixToUri {
+        if self.id.is_room_id() {
+            let room_id = <&RoomId>::try_from(self.id.as_str()).unwrap();
+            room_id.matrix_to_uri_via(self.via.clone())
+        } else {
+            let room_alias = <&RoomAliasId>::try_from(self.id.as_str()).unwrap();
+            room_alias.matrix_to_uri()
+        }
+    }
+}
+
+/// A URI for a Matrix event ID.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatrixEventIdUri {
+    /// The event ID.
+    pub event_id: OwnedEventId,
+    /// The event's room ID URI.
+    pub room_uri: MatrixRoomIdUri,
+}
+
+/// Errors encountered when parsing a Matrix ID URI.
+#[derive(Debug, Clone, Error)]
+pub enum MatrixIdUriParseError {
+    /// Not a valid Matrix URI.
+    #[error(transparent)]
+    InvalidUri(#[from] ruma::IdParseError),
+    /// Unsupported Matrix ID.
+    #[error("unsupported Matrix ID: {0:?}")]
+    UnsupportedId(MatrixId),
+}
@@ -4,7 +4,10 @@ use std::{cell::Cell, str::FromStr, sync::Mutex};
 
 use gettextrs::gettext;
 use gtk::{gio, glib, prelude::*};
-use matrix_sdk::attachment::{BaseAudioInfo, BaseImageInfo, BaseVideoInfo};
+use matrix_sdk::{
+    attachment::{BaseAudioInfo, BaseImageInfo, BaseVideoInfo},
+    ruma::events::room::message::{AudioMessageEventContent, UnstableAmplitude},
+};
 use mime::Mime;
 use tracing::{debug, error};
 
@@ -185,6 +188,25 @@ pub async fn get_audio_info(file: &gio::File) -> BaseAudioInfo {
     info
 }
 
+/// Get the waveform of the given audio message, if it has one.
+///
+/// The amplitudes are between `0.0` and `1.0`.
+pub fn audio_waveform(audio: &AudioMessageEventContent) -> Vec<f32> {
+    let max = u64::from(UnstableAmplitude::MAX) as f32;
+
+    audio
+        .audio
+        .as_ref()
+        .map(|details| {
+            details
+                .waveform
+                .iter()
+                .map(|amplitude| u64::from(amplitude.get()) as f32 / max)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
 /// Save the given data to a file with the given filename.
 pub async fn save_to_file(obj: &impl IsA<gtk::Widget>, data: Vec<u8>, filename: String) {
     let dialog = gtk::FileDialog::builder()
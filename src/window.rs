@@ -1,4 +1,4 @@
-use std::cell::Cell;
+use std::cell::{Cell, RefCell};
 
 use adw::subclass::prelude::AdwApplicationWindowImpl;
 use gettextrs::gettext;
@@ -7,6 +7,7 @@ use ruma::RoomId;
 use tracing::{error, info, warn};
 
 use crate::{
+    account_chooser_dialog::AccountChooserDialog,
     account_switcher::AccountSwitcher,
     components::Spinner,
     error_page::ErrorPage,
@@ -19,7 +20,9 @@ use crate::{
         view::{AccountSettings, SessionView},
     },
     session_list::SessionList,
-    spawn, spawn_tokio, toast, Application, APP_ID, PROFILE,
+    spawn, spawn_tokio, toast,
+    utils::matrix::MatrixIdUri,
+    Application, APP_ID, PROFILE,
 };
 
 mod imp {
@@ -59,6 +62,8 @@ mod imp {
         pub session_selection: gtk::SingleSelection,
         pub account_switcher: AccountSwitcher,
         pub waiting_sessions: Cell<usize>,
+        /// The Matrix URI to show once a session is visible.
+        pub pending_matrix_uri: RefCell<Option<MatrixIdUri>>,
     }
 
     #[glib::object_subclass]
@@ -455,6 +460,10 @@ impl Window {
     pub fn switch_to_session_page(&self) {
         let imp = self.imp();
         imp.main_stack.set_visible_child(&imp.session.get());
+
+        if let Some(uri) = imp.pending_matrix_uri.take() {
+            self.show_matrix_uri(uri);
+        }
     }
 
     pub fn switch_to_login_page(&self) {
@@ -514,6 +523,42 @@ impl Window {
         }
     }
 
+    /// Show the given Matrix URI.
+    ///
+    /// If several sessions are logged in, the user is asked which one to use.
+    /// If no session is visible yet, the URI is shown once one is.
+    pub fn show_matrix_uri(&self, uri: MatrixIdUri) {
+        let imp = self.imp();
+
+        if imp.main_stack.visible_child() != Some(imp.session.get().upcast()) {
+            imp.pending_matrix_uri.replace(Some(uri));
+            return;
+        }
+
+        spawn!(clone!(@weak self as obj => async move {
+            obj.show_matrix_uri_inner(uri).await;
+        }));
+    }
+
+    async fn show_matrix_uri_inner(&self, uri: MatrixIdUri) {
+        let session_list = self.session_list();
+
+        let session_id = if session_list.n_items() > 1 {
+            let dialog = AccountChooserDialog::new(Some(self), session_list);
+            dialog.choose_account().await
+        } else {
+            self.current_session_id()
+        };
+        let Some(session_id) = session_id else {
+            return;
+        };
+
+        if self.set_current_session_by_id(&session_id) {
+            self.imp().session.show_matrix_uri(uri);
+            self.present();
+        }
+    }
+
     pub fn save_current_visible_session(&self) -> Result<(), glib::BoolError> {
         let settings = Application::default().settings();
 